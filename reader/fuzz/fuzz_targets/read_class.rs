@@ -0,0 +1,39 @@
+//! Checks that reading arbitrary bytes as a class never panics. Run it with
+//! `cargo +nightly fuzz run read_class`, from the `reader` directory. Inputs that used to
+//! crash should be added to `tests/resources/malformed`, so that they are checked by the
+//! regression tests.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rjvm_reader::{
+    class_file_view::ClassFileView, class_reader::read_buffer, disassembler::disassemble,
+    instruction::Instruction,
+};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(class) = read_buffer(data) {
+        for code in class.methods.iter().filter_map(|method| method.code.as_ref()) {
+            let _ = Instruction::parse_instructions(&code.code);
+        }
+        let _ = disassemble(&class);
+    }
+    if let Ok(view) = ClassFileView::parse(data) {
+        let _ = (view.name(), view.superclass());
+        view.interfaces().for_each(drop);
+        view.constants().class_names().for_each(drop);
+        view.attributes().for_each(drop);
+        for field in view.fields() {
+            let _ = (field.name(), field.descriptor());
+            field.attributes().for_each(drop);
+        }
+        for method in view.methods() {
+            let _ = (method.name(), method.descriptor());
+            method.attributes().for_each(drop);
+            if let Ok(Some(code)) = method.code() {
+                let _ = (code.instructions(), code.exception_table());
+                code.attributes().for_each(drop);
+            }
+        }
+    }
+});
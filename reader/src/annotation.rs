@@ -0,0 +1,107 @@
+use std::{fmt, fmt::Formatter};
+
+use itertools::Itertools;
+
+/// An annotation applied to a class, field, method or method parameter
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Annotation {
+    /// The field descriptor of the annotation type, for example `Ljava/lang/Deprecated;`
+    pub type_descriptor: String,
+    /// Whether the annotation is retained at runtime, i.e. it comes from a
+    /// `RuntimeVisible*Annotations` attribute rather than a `RuntimeInvisible*Annotations` one
+    pub runtime_visible: bool,
+    pub elements: Vec<AnnotationElement>,
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", class_name_of(&self.type_descriptor))?;
+        if !self.elements.is_empty() {
+            write!(f, "({})", self.elements.iter().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Annotation {
+    /// Returns the value of the element with the given name, if it was specified
+    pub fn element(&self, name: &str) -> Option<&ElementValue> {
+        self.elements
+            .iter()
+            .find(|element| element.name == name)
+            .map(|element| &element.value)
+    }
+}
+
+/// A pair name = value of an annotation
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnnotationElement {
+    pub name: String,
+    pub value: ElementValue,
+}
+
+impl fmt::Display for AnnotationElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.name, self.value)
+    }
+}
+
+/// Possible values of an annotation element
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ElementValue {
+    Byte(i8),
+    Char(u16),
+    Double(f64),
+    Float(f32),
+    Int(i32),
+    Long(i64),
+    Short(i16),
+    Boolean(bool),
+    String(String),
+    /// An enum constant, given the field descriptor of the enum type and the constant name
+    Enum {
+        type_descriptor: String,
+        const_name: String,
+    },
+    /// A class literal, given its return descriptor (which can be `V` for `void.class`)
+    Class(String),
+    Annotation(Box<Annotation>),
+    Array(Vec<ElementValue>),
+}
+
+impl fmt::Display for ElementValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ElementValue::Byte(value) => write!(f, "{value}"),
+            ElementValue::Char(value) => match char::from_u32(*value as u32) {
+                Some(c) => write!(f, "{c:?}"),
+                None => write!(f, "'\\u{value:04x}'"),
+            },
+            ElementValue::Double(value) => write!(f, "{value}"),
+            ElementValue::Float(value) => write!(f, "{value}"),
+            ElementValue::Int(value) => write!(f, "{value}"),
+            ElementValue::Long(value) => write!(f, "{value}"),
+            ElementValue::Short(value) => write!(f, "{value}"),
+            ElementValue::Boolean(value) => write!(f, "{value}"),
+            ElementValue::String(value) => write!(f, "{value:?}"),
+            ElementValue::Enum {
+                type_descriptor,
+                const_name,
+            } => write!(f, "{}.{const_name}", class_name_of(type_descriptor)),
+            ElementValue::Class(descriptor) => write!(f, "{}.class", class_name_of(descriptor)),
+            ElementValue::Annotation(annotation) => write!(f, "{annotation}"),
+            ElementValue::Array(values) => write!(f, "{{{}}}", values.iter().join(", ")),
+        }
+    }
+}
+
+/// Strips the leading `L` and trailing `;` of an object type descriptor
+fn class_name_of(type_descriptor: &str) -> &str {
+    type_descriptor
+        .strip_prefix('L')
+        .and_then(|name| name.strip_suffix(';'))
+        .unwrap_or(type_descriptor)
+}
@@ -2,6 +2,7 @@ use std::{fmt, fmt::Formatter};
 
 /// An attribute in the class file, which can belong to a class, field, method, or code block.
 #[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attribute {
     pub name: String,
     pub bytes: Vec<u8>,
@@ -12,3 +13,73 @@ impl fmt::Display for Attribute {
         write!(f, "{} (data = {} bytes)", self.name, self.bytes.len())
     }
 }
+
+/// Attributes of a class that are parsed into the fields of [crate::class_file::ClassFile]
+pub(crate) const CLASS_ATTRIBUTES: &[&str] = &[
+    "SourceFile",
+    "Deprecated",
+    "BootstrapMethods",
+    "InnerClasses",
+    "EnclosingMethod",
+    "Signature",
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+    "Module",
+    "ModulePackages",
+    "ModuleMainClass",
+    "NestHost",
+    "NestMembers",
+    "Record",
+    "PermittedSubclasses",
+];
+
+/// Attributes of a field that are parsed into the fields of [crate::class_file_field::ClassFileField]
+pub(crate) const FIELD_ATTRIBUTES: &[&str] = &[
+    "ConstantValue",
+    "Deprecated",
+    "Signature",
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+];
+
+/// Attributes of a method that are parsed into the fields of
+/// [crate::class_file_method::ClassFileMethod]
+pub(crate) const METHOD_ATTRIBUTES: &[&str] = &[
+    "Code",
+    "Exceptions",
+    "Deprecated",
+    "Signature",
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleParameterAnnotations",
+    "RuntimeInvisibleParameterAnnotations",
+    "AnnotationDefault",
+    "MethodParameters",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+];
+
+/// Attributes of a code block that are parsed into the fields of
+/// [crate::class_file_method::ClassFileMethodCode]
+pub(crate) const CODE_ATTRIBUTES: &[&str] = &[
+    "LineNumberTable",
+    "LocalVariableTable",
+    "LocalVariableTypeTable",
+    "StackMapTable",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+];
+
+/// Attributes of a record component that are parsed into the fields of
+/// [crate::record_component::RecordComponent]
+pub(crate) const RECORD_COMPONENT_ATTRIBUTES: &[&str] = &[
+    "Signature",
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+];
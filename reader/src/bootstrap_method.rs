@@ -0,0 +1,18 @@
+use std::fmt;
+
+/// An entry of the `BootstrapMethods` attribute of a class, which specifies how to link
+/// the call sites of an `invokedynamic` instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BootstrapMethod {
+    /// Index of the method handle constant that refers to the bootstrap method
+    pub method_ref: u16,
+    /// Indexes of the constants passed as static arguments to the bootstrap method
+    pub arguments: Vec<u16>,
+}
+
+impl fmt::Display for BootstrapMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} arguments: {:?}", self.method_ref, self.arguments)
+    }
+}
@@ -5,6 +5,9 @@ use thiserror::Error;
 pub struct Buffer<'a> {
     buffer: &'a [u8],
     position: usize,
+    /// The offset of the data in the enclosing file, so that positions can be reported
+    /// relative to it
+    base_offset: usize,
 }
 
 /// Errors related to reading from a [Buffer]
@@ -21,14 +24,25 @@ type Result<T> = std::result::Result<T, BufferError>;
 
 impl<'a> Buffer<'a> {
     pub fn new(data: &'a [u8]) -> Self {
+        Self::new_at(data, 0)
+    }
+
+    /// Creates a buffer for data that starts at the given offset of an enclosing file
+    pub fn new_at(data: &'a [u8], base_offset: usize) -> Self {
         Buffer {
             buffer: data,
             position: 0,
+            base_offset,
         }
     }
 
+    /// The offset of the next byte to be read, relative to the enclosing file
+    pub fn position(&self) -> usize {
+        self.base_offset + self.position
+    }
+
     fn advance(&mut self, size: usize) -> Result<&'a [u8]> {
-        if self.position + size > self.buffer.len() {
+        if size > self.remaining() {
             Err(BufferError::UnexpectedEndOfData)
         } else {
             let slice = &self.buffer[self.position..self.position + size];
@@ -37,6 +51,11 @@ impl<'a> Buffer<'a> {
         }
     }
 
+    /// The number of bytes that can still be read
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.position
+    }
+
     pub fn read_u8(&mut self) -> Result<u8> {
         self.advance(std::mem::size_of::<u8>())
             .map(|bytes| u8::from_be_bytes(bytes.try_into().unwrap()))
@@ -103,4 +122,16 @@ mod tests {
 
         assert!(buffer.read_u32().is_err());
     }
+
+    #[test]
+    fn position_is_relative_to_the_base_offset() {
+        let data = vec![0x00, 0x01, 0x02];
+        let mut buffer = Buffer::new_at(&data, 10);
+
+        assert_eq!(10, buffer.position());
+        buffer.read_u16().unwrap();
+        assert_eq!(12, buffer.position());
+        assert!(buffer.read_u16().is_err());
+        assert_eq!(12, buffer.position());
+    }
 }
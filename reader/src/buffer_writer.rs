@@ -0,0 +1,99 @@
+use cesu8::to_java_cesu8;
+
+use crate::class_writer_error::{ClassWriterError, Result};
+
+/// A buffer writer, the counterpart of [crate::buffer::Buffer], used to marshall data
+/// into a byte array in the big endian format used by class files
+#[derive(Default)]
+pub struct BufferWriter {
+    buffer: Vec<u8>,
+}
+
+impl BufferWriter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.buffer.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Writes a string in the modified utf8 format, prefixed by its length in bytes
+    pub fn write_utf8(&mut self, value: &str) -> Result<()> {
+        let bytes = to_java_cesu8(value);
+        let len = u16::try_from(bytes.len()).map_err(|_| {
+            ClassWriterError::LimitExceeded(format!(
+                "string constant too long: {} bytes",
+                bytes.len()
+            ))
+        })?;
+        self.write_u16(len);
+        self.write_bytes(&bytes);
+        Ok(())
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Writes the length of a table as an u16, failing if it is too long
+    pub fn write_len(&mut self, len: usize, what: &str) -> Result<()> {
+        let len = u16::try_from(len)
+            .map_err(|_| ClassWriterError::LimitExceeded(format!("too many {what}: {len}")))?;
+        self.write_u16(len);
+        Ok(())
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buffer::Buffer, buffer_writer::BufferWriter};
+
+    #[test]
+    fn written_data_can_be_read_back() {
+        let mut writer = BufferWriter::new();
+        writer.write_u8(0x42);
+        writer.write_u16(0x1234);
+        writer.write_i64(-2);
+        writer.write_utf8("h\u{0}llo").unwrap();
+        let bytes = writer.into_bytes();
+
+        let mut buffer = Buffer::new(&bytes);
+        assert_eq!(0x42, buffer.read_u8().unwrap());
+        assert_eq!(0x1234, buffer.read_u16().unwrap());
+        assert_eq!(-2, buffer.read_i64().unwrap());
+        // The nul character is encoded in two bytes in the modified utf8 format
+        let len = buffer.read_u16().unwrap();
+        assert_eq!(6, len);
+        assert_eq!("h\u{0}llo", buffer.read_utf8(len as usize).unwrap());
+        assert!(!buffer.has_more_data());
+    }
+}
@@ -9,6 +9,8 @@ bitflags! {
         const SYNTHETIC = 0x1000;
         const ANNOTATION = 0x2000;
         const ENUM = 0x4000;
+        /// The class file is the `module-info.class` of a module
+        const MODULE = 0x8000;
     }
 }
 
@@ -17,3 +19,6 @@ impl Default for ClassAccessFlags {
         ClassAccessFlags::empty()
     }
 }
+
+#[cfg(feature = "serde")]
+crate::flags_serde::impl_serde_for_flags!(ClassAccessFlags);
@@ -1,15 +1,21 @@
 use std::fmt;
 
 use crate::{
+    annotation::Annotation, attribute::Attribute, bootstrap_method::BootstrapMethod,
     class_access_flags::ClassAccessFlags, class_file_field::ClassFileField,
     class_file_method::ClassFileMethod, class_file_version::ClassFileVersion,
-    constant_pool::ConstantPool,
+    constant_pool::ConstantPool, custom_attribute::CustomAttribute,
+    enclosing_method::EnclosingMethod, inner_class::InnerClass, module::Module,
+    record_component::RecordComponent, signature::ClassSignature, type_annotation::TypeAnnotation,
 };
 
 /// Represents the content of a .class file.
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassFile {
     pub version: ClassFileVersion,
+    /// Usually zero; 65535 marks classes that use the preview features of their version
+    pub minor_version: u16,
     pub constants: ConstantPool,
     pub flags: ClassAccessFlags,
     pub name: String,
@@ -19,6 +25,35 @@ pub struct ClassFile {
     pub methods: Vec<ClassFileMethod>,
     pub deprecated: bool,
     pub source_file: Option<String>,
+    pub bootstrap_methods: Vec<BootstrapMethod>,
+    pub inner_classes: Vec<InnerClass>,
+    pub enclosing_method: Option<EnclosingMethod>,
+    /// Generic signature, present only if the class has type parameters or extends
+    /// or implements parameterized types
+    pub signature: Option<ClassSignature>,
+    pub annotations: Vec<Annotation>,
+    /// Annotations of the type parameters and of the supertypes of the class
+    pub type_annotations: Vec<TypeAnnotation>,
+    /// Present only in the `module-info.class` of a module
+    pub module: Option<Module>,
+    /// The packages of a module, exported or not. Note that javac does not generate
+    /// this attribute, but the jar tool does.
+    pub module_packages: Vec<String>,
+    /// The main class of a module, if it was packaged with one
+    pub module_main_class: Option<String>,
+    /// The top-level class of the nest to which a nested class belongs
+    pub nest_host: Option<String>,
+    /// The classes nested in a top-level class
+    pub nest_members: Vec<String>,
+    /// The components of a record class. Note that a record can have no components.
+    pub record_components: Option<Vec<RecordComponent>>,
+    /// The classes allowed to extend or implement a sealed class or interface
+    pub permitted_subclasses: Vec<String>,
+    /// Values of the attributes parsed by the parsers given to the class reader
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub custom_attributes: Vec<CustomAttribute>,
+    /// Generic attributes of the class that are not parsed into the other fields
+    pub attributes: Vec<Attribute>,
 }
 
 impl fmt::Display for ClassFile {
@@ -28,6 +63,9 @@ impl fmt::Display for ClassFile {
             write!(f, "(extends {}) ", superclass)?;
         }
         writeln!(f, "version: {}", self.version)?;
+        if let Some(signature) = &self.signature {
+            writeln!(f, "signature: {signature}")?;
+        }
         write!(f, "{}", self.constants)?;
         writeln!(
             f,
@@ -35,6 +73,42 @@ impl fmt::Display for ClassFile {
             self.flags, self.deprecated
         )?;
         writeln!(f, "interfaces: {:?}", self.interfaces)?;
+        for annotation in self.annotations.iter() {
+            writeln!(f, "annotation: {annotation}")?;
+        }
+        for type_annotation in self.type_annotations.iter() {
+            writeln!(f, "type annotation: {type_annotation}")?;
+        }
+        for custom_attribute in self.custom_attributes.iter() {
+            writeln!(f, "custom attribute: {custom_attribute}")?;
+        }
+        if let Some(enclosing_method) = &self.enclosing_method {
+            writeln!(f, "enclosing method: {enclosing_method}")?;
+        }
+        if let Some(nest_host) = &self.nest_host {
+            writeln!(f, "nest host: {nest_host}")?;
+        }
+        if !self.nest_members.is_empty() {
+            writeln!(f, "nest members: {:?}", self.nest_members)?;
+        }
+        if !self.permitted_subclasses.is_empty() {
+            writeln!(f, "permitted subclasses: {:?}", self.permitted_subclasses)?;
+        }
+        if let Some(module) = &self.module {
+            writeln!(f, "module: {module}")?;
+        }
+        if !self.module_packages.is_empty() {
+            writeln!(f, "module packages: {:?}", self.module_packages)?;
+        }
+        if let Some(module_main_class) = &self.module_main_class {
+            writeln!(f, "module main class: {module_main_class}")?;
+        }
+        if let Some(record_components) = &self.record_components {
+            writeln!(f, "record components:")?;
+            for record_component in record_components.iter() {
+                writeln!(f, "  - {record_component}")?;
+            }
+        }
         writeln!(f, "fields:")?;
         for field in self.fields.iter() {
             writeln!(f, "  - {field}")?;
@@ -43,6 +117,18 @@ impl fmt::Display for ClassFile {
         for method in self.methods.iter() {
             writeln!(f, "  - {method}")?;
         }
+        if !self.inner_classes.is_empty() {
+            writeln!(f, "inner classes:")?;
+            for inner_class in self.inner_classes.iter() {
+                writeln!(f, "  - {inner_class}")?;
+            }
+        }
+        if !self.bootstrap_methods.is_empty() {
+            writeln!(f, "bootstrap methods:")?;
+            for (index, bootstrap_method) in self.bootstrap_methods.iter().enumerate() {
+                writeln!(f, "  {index}: {bootstrap_method}")?;
+            }
+        }
         Ok(())
     }
 }
@@ -1,9 +1,14 @@
 use std::{fmt, fmt::Formatter};
 
-use crate::{field_flags::FieldFlags, field_type::FieldType};
+use crate::{
+    annotation::Annotation, attribute::Attribute, custom_attribute::CustomAttribute,
+    field_flags::FieldFlags, field_type::FieldType, signature::FieldSignature,
+    type_annotation::TypeAnnotation,
+};
 
 /// Models a field in a class
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassFileField {
     pub flags: FieldFlags,
     pub name: String,
@@ -11,6 +16,16 @@ pub struct ClassFileField {
     /// Fields which model a constant (final) will have an attribute specifying the value
     pub constant_value: Option<FieldConstantValue>,
     pub deprecated: bool,
+    /// Generic signature, present only if the type of the field is generic
+    pub signature: Option<FieldSignature>,
+    pub annotations: Vec<Annotation>,
+    /// Annotations of the type of the field
+    pub type_annotations: Vec<TypeAnnotation>,
+    /// Values of the attributes parsed by the parsers given to the class reader
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub custom_attributes: Vec<CustomAttribute>,
+    /// Generic attributes of the field that are not parsed into the other fields
+    pub attributes: Vec<Attribute>,
 }
 
 impl fmt::Display for ClassFileField {
@@ -23,12 +38,26 @@ impl fmt::Display for ClassFileField {
             self.type_descriptor,
             self.constant_value,
             if self.deprecated { " (deprecated)" } else { "" }
-        )
+        )?;
+        if let Some(signature) = &self.signature {
+            write!(f, " signature: {signature}")?;
+        }
+        for annotation in self.annotations.iter() {
+            write!(f, " {annotation}")?;
+        }
+        for type_annotation in self.type_annotations.iter() {
+            write!(f, " {type_annotation}")?;
+        }
+        for custom_attribute in self.custom_attributes.iter() {
+            write!(f, " {custom_attribute}")?;
+        }
+        Ok(())
     }
 }
 
 /// Possible constant values of a field
 #[derive(Debug, PartialEq, strum_macros::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FieldConstantValue {
     Int(i32),
     Float(f32),
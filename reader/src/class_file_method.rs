@@ -1,17 +1,25 @@
 use std::{fmt, fmt::Formatter};
 
 use crate::{
+    annotation::{Annotation, ElementValue},
     attribute::Attribute,
+    custom_attribute::CustomAttribute,
     exception_table::ExceptionTable,
     field_type::{BaseType, FieldType},
     instruction::Instruction,
     line_number_table::LineNumberTable,
+    local_variable_table::LocalVariableTable,
     method_descriptor::MethodDescriptor,
     method_flags::MethodFlags,
+    method_parameter::MethodParameter,
+    signature::MethodSignature,
+    stack_map_table::StackMapFrame,
+    type_annotation::TypeAnnotation,
 };
 
 /// Models a method in a class
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassFileMethod {
     pub flags: MethodFlags,
     pub name: String,
@@ -26,6 +34,23 @@ pub struct ClassFileMethod {
     pub deprecated: bool,
     /// List of exceptions in the `throws` clause of the method
     pub thrown_exceptions: Vec<String>,
+    /// Generic signature, present only if the method is generic or its types are
+    pub signature: Option<MethodSignature>,
+    pub annotations: Vec<Annotation>,
+    /// Annotations of each parameter. Note that javac might not count synthetic parameters,
+    /// so this can be shorter than the parameters in the descriptor.
+    pub parameter_annotations: Vec<Vec<Annotation>>,
+    /// Default value of an element of an annotation type
+    pub annotation_default: Option<ElementValue>,
+    /// Names and flags of the parameters. Empty unless the class was compiled with
+    /// `-parameters`, or if the method has no parameters.
+    pub parameters: Vec<MethodParameter>,
+    /// Annotations of the types in the signature of the method, i.e. the type parameters,
+    /// the return type, the receiver, the parameters and the thrown exceptions
+    pub type_annotations: Vec<TypeAnnotation>,
+    /// Values of the attributes parsed by the parsers given to the class reader
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub custom_attributes: Vec<CustomAttribute>,
 }
 
 impl fmt::Display for ClassFileMethod {
@@ -39,6 +64,29 @@ impl fmt::Display for ClassFileMethod {
             if self.deprecated { " (deprecated)" } else { "" },
             self.thrown_exceptions,
         )?;
+        if let Some(signature) = &self.signature {
+            writeln!(f, "  signature: {signature}")?;
+        }
+        for annotation in self.annotations.iter() {
+            writeln!(f, "  annotation: {annotation}")?;
+        }
+        for (index, annotations) in self.parameter_annotations.iter().enumerate() {
+            for annotation in annotations.iter() {
+                writeln!(f, "  parameter {index} annotation: {annotation}")?;
+            }
+        }
+        if let Some(default) = &self.annotation_default {
+            writeln!(f, "  default: {default}")?;
+        }
+        for (index, parameter) in self.parameters.iter().enumerate() {
+            writeln!(f, "  parameter {index}: {parameter}")?;
+        }
+        for type_annotation in self.type_annotations.iter() {
+            writeln!(f, "  type annotation: {type_annotation}")?;
+        }
+        for custom_attribute in self.custom_attributes.iter() {
+            writeln!(f, "  custom attribute: {custom_attribute}")?;
+        }
         if let Some(code) = &self.code {
             writeln!(f, "  code: {code}")?;
         }
@@ -75,6 +123,7 @@ impl ClassFileMethod {
 
 /// Code of a given method
 #[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassFileMethodCode {
     /// Maximum depth of the stack at any time
     pub max_stack: u16,
@@ -84,6 +133,16 @@ pub struct ClassFileMethodCode {
     pub code: Vec<u8>,
     pub exception_table: ExceptionTable,
     pub line_number_table: Option<LineNumberTable>,
+    pub local_variable_table: Option<LocalVariableTable>,
+    /// Frames of the `StackMapTable` attribute. Empty if the code has no branches, or if
+    /// the class predates Java 6.
+    pub stack_map_table: Vec<StackMapFrame>,
+    /// Annotations of the types used in the code, for example in local variables,
+    /// casts or `new` expressions
+    pub type_annotations: Vec<TypeAnnotation>,
+    /// Values of the attributes parsed by the parsers given to the class reader
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub custom_attributes: Vec<CustomAttribute>,
 
     /// Generic unmapped attributes of the code
     // TODO: replace with some proper struct
@@ -94,8 +153,8 @@ impl fmt::Display for ClassFileMethodCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         writeln!(
             f,
-            "max_stack = {}, max_locals = {}, exception_table = {:?}, line_number_table: {:?}, attributes = {:?}, instructions:",
-            self.max_stack, self.max_locals, self.exception_table, self.line_number_table, self.attributes,
+            "max_stack = {}, max_locals = {}, exception_table = {:?}, line_number_table: {:?}, local_variable_table: {:?}, attributes = {:?}, instructions:",
+            self.max_stack, self.max_locals, self.exception_table, self.line_number_table, self.local_variable_table, self.attributes,
         )?;
 
         let instructions = Instruction::parse_instructions(&self.code);
@@ -106,6 +165,21 @@ impl fmt::Display for ClassFileMethodCode {
         } else {
             writeln!(f, "    unparsable code: {:?}", self.code)?;
         }
+        if !self.stack_map_table.is_empty() {
+            writeln!(f, "  stack map frames:")?;
+            for frame in self.stack_map_table.iter() {
+                writeln!(f, "    {frame}")?;
+            }
+        }
+        if !self.type_annotations.is_empty() {
+            writeln!(f, "  type annotations:")?;
+            for type_annotation in self.type_annotations.iter() {
+                writeln!(f, "    {type_annotation}")?;
+            }
+        }
+        for custom_attribute in self.custom_attributes.iter() {
+            writeln!(f, "  custom attribute: {custom_attribute}")?;
+        }
         Ok(())
     }
 }
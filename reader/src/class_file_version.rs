@@ -1,7 +1,8 @@
 use crate::class_reader_error::{ClassReaderError, Result};
 
 /// Versions of the JVM class file format.
-#[derive(Debug, PartialEq, Default, strum_macros::Display)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default, strum_macros::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(dead_code)]
 pub enum ClassFileVersion {
     Jdk1_1,
@@ -58,6 +59,34 @@ impl ClassFileVersion {
             _ => Err(ClassReaderError::UnsupportedVersion(major, minor)),
         }
     }
+
+    /// Returns the major version stored in the class file, i.e. the inverse of [ClassFileVersion::from]
+    pub fn major(&self) -> u16 {
+        match self {
+            ClassFileVersion::Jdk1_1 => 45,
+            ClassFileVersion::Jdk1_2 => 46,
+            ClassFileVersion::Jdk1_3 => 47,
+            ClassFileVersion::Jdk1_4 => 48,
+            ClassFileVersion::Jdk1_5 => 49,
+            ClassFileVersion::Jdk6 => 50,
+            ClassFileVersion::Jdk7 => 51,
+            ClassFileVersion::Jdk8 => 52,
+            ClassFileVersion::Jdk9 => 53,
+            ClassFileVersion::Jdk10 => 54,
+            ClassFileVersion::Jdk11 => 55,
+            ClassFileVersion::Jdk12 => 56,
+            ClassFileVersion::Jdk13 => 57,
+            ClassFileVersion::Jdk14 => 58,
+            ClassFileVersion::Jdk15 => 59,
+            ClassFileVersion::Jdk16 => 60,
+            ClassFileVersion::Jdk17 => 61,
+            ClassFileVersion::Jdk18 => 62,
+            ClassFileVersion::Jdk19 => 63,
+            ClassFileVersion::Jdk20 => 64,
+            ClassFileVersion::Jdk21 => 65,
+            ClassFileVersion::Jdk22 => 66,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -72,6 +101,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn major_version_is_the_inverse_of_parsing() {
+        for major in 45..=66 {
+            assert_eq!(major, ClassFileVersion::from(major, 0).unwrap().major());
+        }
+    }
+
     #[test]
     fn can_parse_future_versions() {
         assert_eq!(
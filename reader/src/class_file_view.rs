@@ -0,0 +1,484 @@
+use std::borrow::Cow;
+
+use cesu8::from_java_cesu8;
+
+use crate::{
+    buffer::{Buffer, BufferError},
+    class_access_flags::ClassAccessFlags,
+    class_file::ClassFile,
+    class_file_version::ClassFileVersion,
+    class_reader::{read_buffer, read_table},
+    class_reader_error::{ClassReaderError, Result},
+    constant_pool::InvalidConstantPoolIndexError,
+    exception_table::{ExceptionTable, ExceptionTableEntry},
+    field_flags::FieldFlags,
+    instruction::Instruction,
+    method_flags::MethodFlags,
+    program_counter::ProgramCounter,
+    type_conversion::ToUsizeSafe,
+};
+
+/// A view of a class file that borrows from the bytes it was read from, meant to scan many
+/// classes quickly, for example to index their dependencies. Only the structure of the class
+/// is checked upfront: strings are decoded from the constant pool when accessed, without
+/// copying them unless they contain characters that need to be converted from modified UTF-8,
+/// and the code of methods is parsed on demand.
+///
+/// Use [crate::class_reader::read_buffer], or [ClassFileView::to_class_file], to get the
+/// complete model of the class.
+pub struct ClassFileView<'data> {
+    data: &'data [u8],
+    version: ClassFileVersion,
+    minor_version: u16,
+    constants: ConstantPoolView<'data>,
+    flags: ClassAccessFlags,
+    this_class: u16,
+    super_class: u16,
+    /// The indexes of the interfaces in the constant pool, as big endian u16
+    interfaces: &'data [u8],
+    fields: Vec<Member<'data, FieldFlags>>,
+    methods: Vec<Member<'data, MethodFlags>>,
+    attributes: AttributeTable<'data>,
+}
+
+impl<'data> ClassFileView<'data> {
+    /// Checks the structure of the given class file and creates a view over it
+    pub fn parse(data: &'data [u8]) -> Result<Self> {
+        let mut buf = Buffer::new(data);
+
+        read_structure(&mut buf, "magic", |buf| match buf.read_u32()? {
+            0xCAFEBABE => Ok(()),
+            _ => Err(ClassReaderError::invalid_class_data(
+                "invalid magic number".to_owned(),
+            )),
+        })?;
+        let (version, minor_version) = read_structure(&mut buf, "version", Self::read_version)?;
+        let constants = ConstantPoolView::read(&mut buf, data)?;
+        let flags = read_structure(&mut buf, "access_flags", |buf| {
+            read_flags(buf, ClassAccessFlags::from_bits, "class")
+        })?;
+        let this_class = read_structure(&mut buf, "this_class", |buf| Ok(buf.read_u16()?))?;
+        let super_class = read_structure(&mut buf, "super_class", |buf| Ok(buf.read_u16()?))?;
+        let interfaces_count = read_count(&mut buf, "interfaces_count")?;
+        let interfaces = read_structure(&mut buf, "interfaces", |buf| {
+            Ok(buf.read_bytes(2 * interfaces_count)?)
+        })?;
+        let fields_count = read_count(&mut buf, "fields_count")?;
+        let fields = read_table(&mut buf, "fields", fields_count, |buf| {
+            Member::read(buf, data, FieldFlags::from_bits, "field")
+        })?;
+        let methods_count = read_count(&mut buf, "methods_count")?;
+        let methods = read_table(&mut buf, "methods", methods_count, |buf| {
+            Member::read(buf, data, MethodFlags::from_bits, "method")
+        })?;
+        let attributes = AttributeTable::read(&mut buf, data)?;
+
+        Ok(Self {
+            data,
+            version,
+            minor_version,
+            constants,
+            flags,
+            this_class,
+            super_class,
+            interfaces,
+            fields,
+            methods,
+            attributes,
+        })
+    }
+
+    fn read_version(buf: &mut Buffer) -> Result<(ClassFileVersion, u16)> {
+        let minor_version = buf.read_u16()?;
+        let major_version = buf.read_u16()?;
+        let version = ClassFileVersion::from(major_version, minor_version)?;
+        Ok((version, minor_version))
+    }
+
+    /// Reads the complete, owned model of the class
+    pub fn to_class_file(&self) -> Result<ClassFile> {
+        read_buffer(self.data)
+    }
+
+    pub fn version(&self) -> &ClassFileVersion {
+        &self.version
+    }
+
+    pub fn minor_version(&self) -> u16 {
+        self.minor_version
+    }
+
+    pub fn constants(&self) -> &ConstantPoolView<'data> {
+        &self.constants
+    }
+
+    pub fn flags(&self) -> ClassAccessFlags {
+        self.flags
+    }
+
+    pub fn name(&self) -> Result<Cow<'data, str>> {
+        self.constants.text_of(self.this_class)
+    }
+
+    pub fn superclass(&self) -> Result<Option<Cow<'data, str>>> {
+        if self.super_class == 0 {
+            Ok(None)
+        } else {
+            self.constants.text_of(self.super_class).map(Some)
+        }
+    }
+
+    pub fn interfaces(&self) -> impl ExactSizeIterator<Item = Result<Cow<'data, str>>> + '_ {
+        self.interfaces.chunks_exact(2).map(|index| {
+            self.constants
+                .text_of(u16::from_be_bytes([index[0], index[1]]))
+        })
+    }
+
+    pub fn fields(&self) -> impl ExactSizeIterator<Item = FieldView<'_, 'data>> {
+        self.fields.iter().map(|member| MemberView {
+            member,
+            constants: &self.constants,
+        })
+    }
+
+    pub fn methods(&self) -> impl ExactSizeIterator<Item = MethodView<'_, 'data>> {
+        self.methods.iter().map(|member| MemberView {
+            member,
+            constants: &self.constants,
+        })
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = Result<AttributeView<'data>>> + '_ {
+        self.attributes.iter(&self.constants)
+    }
+}
+
+/// The constant pool of a [ClassFileView], whose entries are decoded when accessed
+pub struct ConstantPoolView<'data> {
+    data: &'data [u8],
+    /// The tag of the entry with each index, and the offset of its content in the data.
+    /// Missing for index 0 and for the second slot of long and double constants.
+    entries: Vec<Option<(u8, usize)>>,
+}
+
+impl<'data> ConstantPoolView<'data> {
+    fn read(buf: &mut Buffer<'data>, data: &'data [u8]) -> Result<Self> {
+        let constants_count =
+            read_structure(buf, "constant_pool_count", |buf| match buf.read_u16()? {
+                0 => Err(ClassReaderError::invalid_class_data(
+                    "invalid constant pool count: 0".to_string(),
+                )),
+                count => Ok(count),
+            })?;
+
+        let mut entries = Vec::with_capacity(constants_count.into_usize_safe());
+        entries.push(None);
+        while entries.len() < constants_count.into_usize_safe() {
+            let index = entries.len();
+            let (tag, offset) =
+                read_structure(buf, &format!("constant_pool[{index}]"), Self::skip_constant)?;
+            entries.push(Some((tag, offset)));
+            if tag == 5 || tag == 6 {
+                entries.push(None); // long and double constants take up two slots in the pool
+            }
+        }
+        Ok(Self { data, entries })
+    }
+
+    /// Skips over a constant, returning its tag and the offset of its content
+    fn skip_constant(buf: &mut Buffer) -> Result<(u8, usize)> {
+        let tag = buf.read_u8()?;
+        let offset = buf.position();
+        let len = match tag {
+            1 => buf.read_u16()?.into_usize_safe(),
+            7 | 8 | 16 | 19 | 20 => 2,
+            15 => 3,
+            3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => 4,
+            5 | 6 => 8,
+            _ => {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                    "Unknown constant type: 0x{tag:X}"
+                )))
+            }
+        };
+        buf.read_bytes(len)?;
+        Ok((tag, offset))
+    }
+
+    /// Returns the number of slots used in the pool, counting the two slots of long and
+    /// double constants
+    pub fn len(&self) -> usize {
+        self.entries.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the tag of the entry with the given index, and a buffer over its content
+    fn entry(&self, index: u16) -> Result<(u8, Buffer<'data>)> {
+        match self.entries.get(index.into_usize_safe()) {
+            Some(Some((tag, offset))) => Ok((*tag, Buffer::new_at(&self.data[*offset..], *offset))),
+            _ => Err(InvalidConstantPoolIndexError { index }.into()),
+        }
+    }
+
+    /// Decodes the `Utf8` constant with the given index
+    pub fn utf8(&self, index: u16) -> Result<Cow<'data, str>> {
+        let (tag, mut buf) = self.entry(index)?;
+        if tag != 1 {
+            return Err(ClassReaderError::invalid_class_data(format!(
+                "constant {index} is not a Utf8 entry"
+            )));
+        }
+        let len = buf.read_u16()?;
+        let bytes = buf.read_bytes(len.into_usize_safe())?;
+        from_java_cesu8(bytes).map_err(|_| BufferError::InvalidCesu8String.into())
+    }
+
+    /// Returns the text of a constant that refers to a name, i.e. a `Utf8`, `Class`,
+    /// `String`, `MethodType`, `Module` or `Package` entry
+    pub fn text_of(&self, index: u16) -> Result<Cow<'data, str>> {
+        let (tag, mut buf) = self.entry(index)?;
+        match tag {
+            1 => self.utf8(index),
+            7 | 8 | 16 | 19 | 20 => self.utf8(buf.read_u16()?),
+            _ => Err(ClassReaderError::invalid_class_data(format!(
+                "constant {index} does not refer to a name"
+            ))),
+        }
+    }
+
+    /// Returns the names of all the classes referred to by the constant pool, i.e. the
+    /// class itself, its superclass and interfaces, and those used by its code
+    pub fn class_names(&self) -> impl Iterator<Item = Result<Cow<'data, str>>> + '_ {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| matches!(entry, Some((7, _))))
+            .map(|(index, _)| self.text_of(index as u16))
+    }
+}
+
+/// A field or method of a [ClassFileView], whose flags have the given type
+struct Member<'data, F> {
+    flags: F,
+    name_index: u16,
+    descriptor_index: u16,
+    attributes: AttributeTable<'data>,
+}
+
+impl<'data, F> Member<'data, F> {
+    fn read(
+        buf: &mut Buffer<'data>,
+        data: &'data [u8],
+        from_bits: impl FnOnce(u16) -> Option<F>,
+        kind: &str,
+    ) -> Result<Self> {
+        let flags = read_flags(buf, from_bits, kind)?;
+        let name_index = buf.read_u16()?;
+        let descriptor_index = buf.read_u16()?;
+        let attributes = AttributeTable::read(buf, data)?;
+        Ok(Self {
+            flags,
+            name_index,
+            descriptor_index,
+            attributes,
+        })
+    }
+}
+
+/// A field or a method of a [ClassFileView]
+pub struct MemberView<'view, 'data, F> {
+    member: &'view Member<'data, F>,
+    constants: &'view ConstantPoolView<'data>,
+}
+
+pub type FieldView<'view, 'data> = MemberView<'view, 'data, FieldFlags>;
+pub type MethodView<'view, 'data> = MemberView<'view, 'data, MethodFlags>;
+
+impl<'view, 'data, F: Copy> MemberView<'view, 'data, F> {
+    pub fn flags(&self) -> F {
+        self.member.flags
+    }
+
+    pub fn name(&self) -> Result<Cow<'data, str>> {
+        self.constants.utf8(self.member.name_index)
+    }
+
+    /// The type descriptor in the internal JVM form, i.e. something like `(I)V`
+    pub fn descriptor(&self) -> Result<Cow<'data, str>> {
+        self.constants.utf8(self.member.descriptor_index)
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = Result<AttributeView<'data>>> + 'view {
+        self.member.attributes.iter(self.constants)
+    }
+}
+
+impl<'view, 'data> MethodView<'view, 'data> {
+    /// Parses the `Code` attribute of the method. Returns `None` for abstract and
+    /// native methods.
+    pub fn code(&self) -> Result<Option<CodeView<'view, 'data>>> {
+        for attribute in self.attributes() {
+            let attribute = attribute?;
+            if attribute.name == "Code" {
+                return CodeView::parse(&attribute, self.constants).map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The code of a method of a [ClassFileView]
+pub struct CodeView<'view, 'data> {
+    /// Maximum depth of the stack at any time
+    pub max_stack: u16,
+    /// Number of local variables used by the method
+    pub max_locals: u16,
+    /// Raw bytecode
+    pub code: &'data [u8],
+    /// The entries of the exception table, each 8 bytes long
+    exception_table: &'data [u8],
+    attributes: AttributeTable<'data>,
+    constants: &'view ConstantPoolView<'data>,
+}
+
+impl<'view, 'data> CodeView<'view, 'data> {
+    fn parse(
+        attribute: &AttributeView<'data>,
+        constants: &'view ConstantPoolView<'data>,
+    ) -> Result<Self> {
+        let mut buf = Buffer::new_at(attribute.bytes, attribute.offset);
+        read_structure(&mut buf, "Code", |buf| {
+            let max_stack = buf.read_u16()?;
+            let max_locals = buf.read_u16()?;
+            let code_length = buf.read_u32()?.into_usize_safe();
+            let code = buf.read_bytes(code_length)?;
+            let exception_table_length = buf.read_u16()?.into_usize_safe();
+            let exception_table = buf.read_bytes(8 * exception_table_length)?;
+            let attributes = AttributeTable::read(buf, constants.data)?;
+            Ok(Self {
+                max_stack,
+                max_locals,
+                code,
+                exception_table,
+                attributes,
+                constants,
+            })
+        })
+    }
+
+    pub fn instructions(&self) -> Result<Vec<(usize, Instruction)>> {
+        Instruction::parse_instructions(self.code)
+    }
+
+    pub fn exception_table(&self) -> Result<ExceptionTable> {
+        let entries = self
+            .exception_table
+            .chunks_exact(8)
+            .map(|entry| {
+                let read_u16 = |index: usize| u16::from_be_bytes([entry[index], entry[index + 1]]);
+                let catch_class = match read_u16(6) {
+                    0 => None,
+                    index => Some(self.constants.text_of(index)?.into_owned()),
+                };
+                Ok(ExceptionTableEntry {
+                    range: ProgramCounter(read_u16(0))..ProgramCounter(read_u16(2)),
+                    handler_pc: ProgramCounter(read_u16(4)),
+                    catch_class,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ExceptionTable::new(entries))
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = Result<AttributeView<'data>>> + 'view {
+        self.attributes.iter(self.constants)
+    }
+}
+
+/// An attribute of a [ClassFileView], with its content still to be parsed
+#[derive(Debug)]
+pub struct AttributeView<'data> {
+    pub name: Cow<'data, str>,
+    pub bytes: &'data [u8],
+    /// The offset of the content of the attribute in the class file
+    offset: usize,
+}
+
+/// A table of attributes, whose structure has been checked when creating the view
+#[derive(Clone, Copy)]
+struct AttributeTable<'data> {
+    /// The attributes, starting right after their count
+    data: &'data [u8],
+    offset: usize,
+    count: u16,
+}
+
+impl<'data> AttributeTable<'data> {
+    /// Reads a table of attributes from the buffer, given the data of the whole class file
+    fn read(buf: &mut Buffer<'data>, data: &'data [u8]) -> Result<Self> {
+        let count = read_structure(buf, "attributes_count", |buf| Ok(buf.read_u16()?))?;
+        let offset = buf.position();
+        read_table(buf, "attributes", count.into_usize_safe(), |buf| {
+            buf.read_u16()?;
+            let len = buf.read_u32()?;
+            buf.read_bytes(len.into_usize_safe())?;
+            Ok(())
+        })?;
+        Ok(Self {
+            data: &data[offset..buf.position()],
+            offset,
+            count,
+        })
+    }
+
+    fn iter<'view>(
+        &self,
+        constants: &'view ConstantPoolView<'data>,
+    ) -> impl Iterator<Item = Result<AttributeView<'data>>> + 'view
+    where
+        'data: 'view,
+    {
+        let mut buf = Buffer::new_at(self.data, self.offset);
+        (0..self.count).map(move |_| {
+            let name_index = buf.read_u16()?;
+            let len = buf.read_u32()?;
+            let offset = buf.position();
+            let bytes = buf.read_bytes(len.into_usize_safe())?;
+            Ok(AttributeView {
+                name: constants.utf8(name_index)?,
+                bytes,
+                offset,
+            })
+        })
+    }
+}
+
+/// Reads a structure, attributing any error to it at the offset where it starts
+fn read_structure<'a, T>(
+    buf: &mut Buffer<'a>,
+    structure: &str,
+    read: impl FnOnce(&mut Buffer<'a>) -> Result<T>,
+) -> Result<T> {
+    let offset = buf.position();
+    read(buf).map_err(|err| err.located(offset, structure))
+}
+
+/// Reads the number of entries of a table
+fn read_count(buf: &mut Buffer, structure: &str) -> Result<usize> {
+    read_structure(buf, structure, |buf| Ok(buf.read_u16()?.into_usize_safe()))
+}
+
+fn read_flags<F>(
+    buf: &mut Buffer,
+    from_bits: impl FnOnce(u16) -> Option<F>,
+    kind: &str,
+) -> Result<F> {
+    let bits = buf.read_u16()?;
+    from_bits(bits).ok_or_else(|| {
+        ClassReaderError::invalid_class_data(format!("invalid {kind} flags: {bits:#0x}"))
+    })
+}
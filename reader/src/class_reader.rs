@@ -1,50 +1,101 @@
+use std::ops::Range;
+
 use log::warn;
 use result::prelude::*;
 
 use crate::{
-    attribute::Attribute,
+    annotation::{Annotation, AnnotationElement, ElementValue},
+    attribute::{Attribute, CLASS_ATTRIBUTES, FIELD_ATTRIBUTES, RECORD_COMPONENT_ATTRIBUTES},
+    bootstrap_method::BootstrapMethod,
     class_access_flags::ClassAccessFlags,
     class_file::ClassFile,
     class_file_field::{ClassFileField, FieldConstantValue},
     class_file_method::{ClassFileMethod, ClassFileMethodCode},
     class_file_version::ClassFileVersion,
     class_reader_error::{ClassReaderError, Result},
-    constant_pool::{ConstantPool, ConstantPoolEntry},
+    constant_pool::{ConstantPool, ConstantPoolEntry, MethodHandleKind},
+    custom_attribute::{AttributeParsers, CustomAttribute},
+    enclosing_method::EnclosingMethod,
     exception_table::{ExceptionTable, ExceptionTableEntry},
     field_flags::FieldFlags,
     field_type::FieldType,
+    inner_class::InnerClass,
+    inner_class_flags::InnerClassFlags,
     line_number::LineNumber,
     line_number_table::{LineNumberTable, LineNumberTableEntry},
+    local_variable_table::{LocalVariableTable, LocalVariableTableEntry},
     method_descriptor::MethodDescriptor,
     method_flags::MethodFlags,
+    method_parameter::MethodParameter,
+    method_parameter_flags::MethodParameterFlags,
+    module::{Module, ModulePackage, ModuleProvides, ModuleRequires},
+    module_flags::{ModuleFlags, ModulePackageFlags, ModuleRequiresFlags},
     program_counter::ProgramCounter,
+    record_component::RecordComponent,
+    signature::{ClassSignature, MethodSignature, TypeSignature},
+    stack_map_table::{StackMapFrame, StackMapFrameKind, VerificationType},
+    type_annotation::{LocalVariableTarget, TypeAnnotation, TypeAnnotationTarget, TypePathElement},
 };
 use crate::{buffer::Buffer, type_conversion::ToUsizeSafe};
 
-/// A reader of a byte array representing a class. Supports only a subset of Java 7 class format,
-/// in particular it does not support generics.
+/// Bounds the nesting of annotation element values, i.e. arrays and annotations, so that
+/// malformed attributes cannot overflow the stack. Real code nests them a few levels at most.
+const MAX_ELEMENT_VALUE_DEPTH: usize = 64;
+
+/// An entry of either the `LocalVariableTable` or `LocalVariableTypeTable` attribute, whose
+/// descriptor is respectively a type descriptor or a signature
+struct RawLocalVariableTableEntry<T> {
+    range: Range<ProgramCounter>,
+    name: String,
+    descriptor: T,
+    index: u16,
+}
+
+/// An attribute as read from the class file, before being parsed into the model
+struct RawAttribute {
+    attribute: Attribute,
+    /// The offset of the content of the attribute in the class file
+    offset: usize,
+}
+
+impl RawAttribute {
+    fn name(&self) -> &str {
+        &self.attribute.name
+    }
+
+    /// Parses the content of the attribute, attributing any error to it
+    fn parse<T>(&self, parse: impl FnOnce(&mut Buffer) -> Result<T>) -> Result<T> {
+        let mut buf = Buffer::new_at(&self.attribute.bytes, self.offset);
+        parse(&mut buf).map_err(|err| err.located(self.offset, self.name()))
+    }
+}
+
+/// A reader of a byte array representing a class. Supports only a subset of Java 7 class format.
 struct ClassFileReader<'a> {
     buffer: Buffer<'a>,
     /// The class being read, created empty and updated in place
     class_file: ClassFile,
+    attribute_parsers: &'a AttributeParsers,
 }
 
 /// Reference: https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-4.html
 impl<'a> ClassFileReader<'a> {
-    fn new(data: &[u8]) -> ClassFileReader {
+    fn new(data: &'a [u8], attribute_parsers: &'a AttributeParsers) -> ClassFileReader<'a> {
         ClassFileReader {
             buffer: Buffer::new(data),
             class_file: Default::default(),
+            attribute_parsers,
         }
     }
 
     fn read(mut self) -> Result<ClassFile> {
-        self.check_magic_number()?;
-        self.read_version()?;
+        self.read_structure("magic", Self::check_magic_number)?;
+        self.read_structure("version", Self::read_version)?;
         self.read_constants()?;
-        self.read_access_flags()?;
-        self.class_file.name = self.read_class_reference()?;
-        self.class_file.superclass = self.read_class_reference_optional()?;
+        self.read_structure("access_flags", Self::read_access_flags)?;
+        self.class_file.name = self.read_structure("this_class", Self::read_class_reference)?;
+        self.class_file.superclass =
+            self.read_structure("super_class", Self::read_class_reference_optional)?;
         self.read_interfaces()?;
         self.read_fields()?;
         self.read_methods()?;
@@ -53,6 +104,21 @@ impl<'a> ClassFileReader<'a> {
         Ok(self.class_file)
     }
 
+    /// Reads a structure, attributing any error to it at the offset where it starts
+    fn read_structure<T>(
+        &mut self,
+        structure: impl Into<String>,
+        read: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let offset = self.buffer.position();
+        read(self).map_err(|err| err.located(offset, structure))
+    }
+
+    /// Reads the number of entries of a table
+    fn read_count(&mut self, structure: &str) -> Result<u16> {
+        self.read_structure(structure, |reader| Ok(reader.buffer.read_u16()?))
+    }
+
     fn check_magic_number(&mut self) -> Result<()> {
         match self.buffer.read_u32() {
             Ok(0xCAFEBABE) => Ok(()),
@@ -68,48 +134,155 @@ impl<'a> ClassFileReader<'a> {
         let major_version = self.buffer.read_u16()?;
 
         self.class_file.version = ClassFileVersion::from(major_version, minor_version)?;
+        self.class_file.minor_version = minor_version;
         Ok(())
     }
 
     fn read_constants(&mut self) -> Result<()> {
-        let constants_count = self.buffer.read_u16()? - 1;
+        // The count is one more than the number of slots, since index 0 is not used
+        let constants_count = self.read_structure("constant_pool_count", |reader| {
+            reader.buffer.read_u16()?.checked_sub(1).ok_or_else(|| {
+                ClassReaderError::invalid_class_data("invalid constant pool count: 0".to_string())
+            })
+        })?;
         let mut i = 0;
         while i < constants_count {
-            let tag = self.buffer.read_u8()?;
-            let constant = match tag {
-                1 => self.read_utf8_constant()?,
-                3 => self.read_int_constant()?,
-                4 => self.read_float_constant()?,
-                5 => {
-                    i += 1; // long constants takes up two slots in the pool
-                    self.read_long_constant()?
-                }
-                6 => {
-                    i += 1; // double constants takes up two slots in the pool
-                    self.read_double_constant()?
-                }
-                7 => self.read_class_reference_constant()?,
-                8 => self.read_string_reference_constant()?,
-                9 => self.read_field_reference_constant()?,
-                10 => self.read_method_reference_constant()?,
-                11 => self.read_interface_method_reference_constant()?,
-                12 => self.read_name_and_type_constant()?,
-                // For newer versions of java, there are more constant types
-                _ => {
-                    warn!("invalid entry in constant pool at index {} tag {}", i, tag);
-                    return Err(ClassReaderError::invalid_class_data(format!(
-                        "Unknown constant type: 0x{tag:X}"
-                    )));
-                }
-            };
+            let constant = self.read_structure(format!("constant_pool[{}]", i + 1), |reader| {
+                reader.read_constant(i + 1)
+            })?;
+            if matches!(
+                constant,
+                ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_)
+            ) {
+                i += 1; // long and double constants take up two slots in the pool
+            }
             self.class_file.constants.add(constant);
 
             i += 1;
         }
 
+        self.check_constant_references()
+    }
+
+    /// Constants can refer to the ones that follow them, so we check that every reference
+    /// points to a constant of the right kind once the whole pool has been read. This also
+    /// ensures that references cannot form cycles, since they always end in a `Utf8`.
+    fn check_constant_references(&self) -> Result<()> {
+        let constants = &self.class_file.constants;
+        let check = |index: u16,
+                     referenced: u16,
+                     expected: &str,
+                     is_valid: fn(&ConstantPoolEntry) -> bool| {
+            if constants.get(referenced).is_ok_and(is_valid) {
+                Ok(())
+            } else {
+                Err(ClassReaderError::invalid_class_data(format!(
+                    "constant {index} should refer to a {expected}, but found constant {referenced}"
+                )))
+            }
+        };
+        let utf8 = |entry: &ConstantPoolEntry| matches!(entry, ConstantPoolEntry::Utf8(_));
+        let class =
+            |entry: &ConstantPoolEntry| matches!(entry, ConstantPoolEntry::ClassReference(_));
+        let name_and_type = |entry: &ConstantPoolEntry| {
+            matches!(entry, ConstantPoolEntry::NameAndTypeDescriptor(_, _))
+        };
+
+        for (index, entry) in constants.iter() {
+            match entry {
+                ConstantPoolEntry::ClassReference(name)
+                | ConstantPoolEntry::StringReference(name)
+                | ConstantPoolEntry::MethodType(name)
+                | ConstantPoolEntry::Module(name)
+                | ConstantPoolEntry::Package(name) => check(index, *name, "Utf8", utf8)?,
+                ConstantPoolEntry::FieldReference(class_index, name_and_type_index)
+                | ConstantPoolEntry::MethodReference(class_index, name_and_type_index)
+                | ConstantPoolEntry::InterfaceMethodReference(class_index, name_and_type_index) => {
+                    check(index, *class_index, "Class", class)?;
+                    check(index, *name_and_type_index, "NameAndType", name_and_type)?;
+                }
+                ConstantPoolEntry::NameAndTypeDescriptor(name, type_descriptor) => {
+                    check(index, *name, "Utf8", utf8)?;
+                    check(index, *type_descriptor, "Utf8", utf8)?;
+                }
+                ConstantPoolEntry::Dynamic(_, name_and_type_index)
+                | ConstantPoolEntry::InvokeDynamic(_, name_and_type_index) => {
+                    check(index, *name_and_type_index, "NameAndType", name_and_type)?
+                }
+                ConstantPoolEntry::MethodHandle(kind, reference) => match kind {
+                    MethodHandleKind::GetField
+                    | MethodHandleKind::GetStatic
+                    | MethodHandleKind::PutField
+                    | MethodHandleKind::PutStatic => {
+                        check(index, *reference, "Fieldref", |entry| {
+                            matches!(entry, ConstantPoolEntry::FieldReference(_, _))
+                        })?
+                    }
+                    MethodHandleKind::InvokeVirtual | MethodHandleKind::NewInvokeSpecial => {
+                        check(index, *reference, "Methodref", |entry| {
+                            matches!(entry, ConstantPoolEntry::MethodReference(_, _))
+                        })?
+                    }
+                    MethodHandleKind::InvokeStatic | MethodHandleKind::InvokeSpecial => check(
+                        index,
+                        *reference,
+                        "Methodref or InterfaceMethodref",
+                        |entry| {
+                            matches!(
+                                entry,
+                                ConstantPoolEntry::MethodReference(_, _)
+                                    | ConstantPoolEntry::InterfaceMethodReference(_, _)
+                            )
+                        },
+                    )?,
+                    MethodHandleKind::InvokeInterface => {
+                        check(index, *reference, "InterfaceMethodref", |entry| {
+                            matches!(entry, ConstantPoolEntry::InterfaceMethodReference(_, _))
+                        })?
+                    }
+                },
+                ConstantPoolEntry::Utf8(_)
+                | ConstantPoolEntry::Integer(_)
+                | ConstantPoolEntry::Float(_)
+                | ConstantPoolEntry::Long(_)
+                | ConstantPoolEntry::Double(_) => {}
+            }
+        }
         Ok(())
     }
 
+    fn read_constant(&mut self, index: u16) -> Result<ConstantPoolEntry> {
+        let tag = self.buffer.read_u8()?;
+        match tag {
+            1 => self.read_utf8_constant(),
+            3 => self.read_int_constant(),
+            4 => self.read_float_constant(),
+            5 => self.read_long_constant(),
+            6 => self.read_double_constant(),
+            7 => self.read_class_reference_constant(),
+            8 => self.read_string_reference_constant(),
+            9 => self.read_field_reference_constant(),
+            10 => self.read_method_reference_constant(),
+            11 => self.read_interface_method_reference_constant(),
+            12 => self.read_name_and_type_constant(),
+            15 => self.read_method_handle_constant(),
+            16 => self.read_method_type_constant(),
+            17 => self.read_dynamic_constant(),
+            18 => self.read_invoke_dynamic_constant(),
+            19 => self.read_module_constant(),
+            20 => self.read_package_constant(),
+            _ => {
+                warn!(
+                    "invalid entry in constant pool at index {} tag {}",
+                    index, tag
+                );
+                Err(ClassReaderError::invalid_class_data(format!(
+                    "Unknown constant type: 0x{tag:X}"
+                )))
+            }
+        }
+    }
+
     fn read_utf8_constant(&mut self) -> Result<ConstantPoolEntry> {
         let len = self.buffer.read_u16()?;
         self.buffer
@@ -192,6 +365,48 @@ impl<'a> ClassFileReader<'a> {
         ))
     }
 
+    fn read_method_handle_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let reference_kind = MethodHandleKind::from(self.buffer.read_u8()?)?;
+        let reference_index = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::MethodHandle(
+            reference_kind,
+            reference_index,
+        ))
+    }
+
+    fn read_method_type_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let descriptor_index = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::MethodType(descriptor_index))
+    }
+
+    fn read_dynamic_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let bootstrap_method_attr_index = self.buffer.read_u16()?;
+        let name_and_type = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::Dynamic(
+            bootstrap_method_attr_index,
+            name_and_type,
+        ))
+    }
+
+    fn read_invoke_dynamic_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let bootstrap_method_attr_index = self.buffer.read_u16()?;
+        let name_and_type = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::InvokeDynamic(
+            bootstrap_method_attr_index,
+            name_and_type,
+        ))
+    }
+
+    fn read_module_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let name_index = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::Module(name_index))
+    }
+
+    fn read_package_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let name_index = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::Package(name_index))
+    }
+
     fn read_access_flags(&mut self) -> Result<()> {
         let num = self.buffer.read_u16()?;
         match ClassAccessFlags::from_bits(num) {
@@ -223,30 +438,47 @@ impl<'a> ClassFileReader<'a> {
         Self::read_string_reference_from(&self.class_file.constants, index)
     }
 
+    fn read_string_reference_optional(&self, index: u16) -> Result<Option<String>> {
+        if index == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(self.read_string_reference(index)?))
+        }
+    }
+
     fn read_string_reference_from(constants_pool: &ConstantPool, index: u16) -> Result<String> {
         constants_pool.text_of(index).map_err(|err| err.into())
     }
 
     fn read_interfaces(&mut self) -> Result<()> {
-        let interfaces_count = self.buffer.read_u16()?;
+        let interfaces_count = self.read_count("interfaces_count")?;
         self.class_file.interfaces = (0..interfaces_count)
-            .map(|_| self.read_class_reference())
+            .map(|index| {
+                self.read_structure(format!("interfaces[{index}]"), Self::read_class_reference)
+            })
             .collect::<Result<Vec<String>>>()?;
         Ok(())
     }
 
     fn read_fields(&mut self) -> Result<()> {
-        let fields_count = self.buffer.read_u16()?;
+        let fields_count = self.read_count("fields_count")?;
         self.class_file.fields = (0..fields_count)
-            .map(|_| self.read_field())
+            .map(|index| {
+                let offset = self.buffer.position();
+                let mut structure = format!("fields[{index}]");
+                self.read_field(&mut structure)
+                    .map_err(|err| err.located(offset, structure))
+            })
             .collect::<Result<Vec<ClassFileField>>>()?;
         Ok(())
     }
 
-    fn read_field(&mut self) -> Result<ClassFileField> {
+    /// The structure describing the field in errors is completed with its name, once read
+    fn read_field(&mut self, structure: &mut String) -> Result<ClassFileField> {
         let flags = self.read_field_flags()?;
         let name_constant_index = self.buffer.read_u16()?;
         let name = self.read_string_reference(name_constant_index)?;
+        *structure = format!("{structure} {name:?}");
         let type_constant_index = self.buffer.read_u16()?;
         let type_descriptor_raw = self.read_string_reference(type_constant_index)?;
         let type_descriptor = FieldType::parse(&type_descriptor_raw)?;
@@ -254,6 +486,12 @@ impl<'a> ClassFileReader<'a> {
         let raw_attributes = self.read_raw_attributes()?;
         let constant_value = self.extract_constant_value(&raw_attributes)?;
         let deprecated = self.search_deprecated_attribute(&raw_attributes);
+        let signature =
+            self.search_signature_attribute(&raw_attributes, TypeSignature::parse_field_signature)?;
+        let annotations = self.extract_annotations(&raw_attributes)?;
+        let type_annotations = self.extract_type_annotations(&raw_attributes)?;
+        let custom_attributes = self.extract_custom_attributes(&raw_attributes)?;
+        let attributes = Self::unmapped_attributes(raw_attributes, FIELD_ATTRIBUTES);
 
         Ok(ClassFileField {
             flags,
@@ -261,6 +499,11 @@ impl<'a> ClassFileReader<'a> {
             type_descriptor,
             constant_value,
             deprecated,
+            signature,
+            annotations,
+            type_annotations,
+            custom_attributes,
+            attributes,
         })
     }
 
@@ -276,19 +519,19 @@ impl<'a> ClassFileReader<'a> {
 
     fn extract_constant_value(
         &self,
-        raw_attributes: &[Attribute],
+        raw_attributes: &[RawAttribute],
     ) -> Result<Option<FieldConstantValue>> {
         raw_attributes
             .iter()
-            .filter(|attr| attr.name == "ConstantValue")
+            .filter(|attr| attr.name() == "ConstantValue")
             .map(|attr| {
-                if attr.bytes.len() != std::mem::size_of::<u16>() {
-                    Err(ClassReaderError::invalid_class_data(
-                        "invalid attribute of type ConstantValue".to_string(),
-                    ))
-                } else {
-                    let attribute_bytes: &[u8] = &attr.bytes;
-                    let constant_index = u16::from_be_bytes(attribute_bytes.try_into().unwrap());
+                attr.parse(|buf| {
+                    if attr.attribute.bytes.len() != std::mem::size_of::<u16>() {
+                        return Err(ClassReaderError::invalid_class_data(
+                            "invalid attribute of type ConstantValue".to_string(),
+                        ));
+                    }
+                    let constant_index = buf.read_u16()?;
                     self.class_file
                         .constants
                         .get(constant_index)
@@ -306,28 +549,367 @@ impl<'a> ClassFileReader<'a> {
                                 "invalid type for ConstantValue: {v:?}"
                             ))),
                         })
-                }
+                })
             })
             .next()
             .invert()
     }
 
-    fn search_deprecated_attribute(&self, raw_attributes: &[Attribute]) -> bool {
-        raw_attributes.iter().any(|attr| attr.name == "Deprecated")
+    /// Reads the signature of a class, field or method, and parses it with the given function
+    fn search_signature_attribute<T>(
+        &self,
+        raw_attributes: &[RawAttribute],
+        parse_signature: impl FnOnce(&str) -> Result<T>,
+    ) -> Result<Option<T>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == "Signature")
+            .map(|attr| {
+                attr.parse(|buf| {
+                    let signature = self.read_string_reference(buf.read_u16()?)?;
+                    parse_signature(&signature)
+                })
+            })
+            .invert()
+    }
+
+    /// Extracts both the runtime visible and invisible annotations
+    fn extract_annotations(&self, raw_attributes: &[RawAttribute]) -> Result<Vec<Annotation>> {
+        let mut annotations = Vec::new();
+        for attr in raw_attributes.iter() {
+            let runtime_visible = match attr.name() {
+                "RuntimeVisibleAnnotations" => true,
+                "RuntimeInvisibleAnnotations" => false,
+                _ => continue,
+            };
+            annotations.append(&mut attr.parse(|buf| self.read_annotations(buf, runtime_visible))?);
+        }
+        Ok(annotations)
+    }
+
+    /// Extracts both the runtime visible and invisible annotations of each parameter
+    fn extract_parameter_annotations(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Vec<Vec<Annotation>>> {
+        let mut parameter_annotations: Vec<Vec<Annotation>> = Vec::new();
+        for attr in raw_attributes.iter() {
+            let runtime_visible = match attr.name() {
+                "RuntimeVisibleParameterAnnotations" => true,
+                "RuntimeInvisibleParameterAnnotations" => false,
+                _ => continue,
+            };
+            let annotations_of_parameters = attr.parse(|buf| {
+                let num_parameters = buf.read_u8()?.into_usize_safe();
+                read_table(buf, "parameter_annotations", num_parameters, |buf| {
+                    self.read_annotations(buf, runtime_visible)
+                })
+            })?;
+            if parameter_annotations.len() < annotations_of_parameters.len() {
+                parameter_annotations.resize(annotations_of_parameters.len(), Vec::new());
+            }
+            for (annotations, mut new_annotations) in parameter_annotations
+                .iter_mut()
+                .zip(annotations_of_parameters)
+            {
+                annotations.append(&mut new_annotations);
+            }
+        }
+        Ok(parameter_annotations)
+    }
+
+    fn search_annotation_default(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Option<ElementValue>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == "AnnotationDefault")
+            .map(|attr| {
+                // Annotations nested in the default value are always retained at runtime,
+                // since the attribute is visible via reflection
+                attr.parse(|buf| self.read_element_value(buf, true, 0))
+            })
+            .invert()
+    }
+
+    fn read_annotations(&self, buf: &mut Buffer, runtime_visible: bool) -> Result<Vec<Annotation>> {
+        let num_annotations = buf.read_u16()?.into_usize_safe();
+        read_table(buf, "annotations", num_annotations, |buf| {
+            self.read_annotation(buf, runtime_visible, 0)
+        })
+    }
+
+    /// The depth is the number of element values that contain the annotation
+    fn read_annotation(
+        &self,
+        buf: &mut Buffer,
+        runtime_visible: bool,
+        depth: usize,
+    ) -> Result<Annotation> {
+        let type_descriptor = self.read_string_reference(buf.read_u16()?)?;
+        let num_elements = buf.read_u16()?.into_usize_safe();
+        let elements = read_table(buf, "element_value_pairs", num_elements, |buf| {
+            let name = self.read_string_reference(buf.read_u16()?)?;
+            let value = self.read_element_value(buf, runtime_visible, depth)?;
+            Ok(AnnotationElement { name, value })
+        })?;
+        Ok(Annotation {
+            type_descriptor,
+            runtime_visible,
+            elements,
+        })
+    }
+
+    /// The depth is the number of element values that contain this one
+    fn read_element_value(
+        &self,
+        buf: &mut Buffer,
+        runtime_visible: bool,
+        depth: usize,
+    ) -> Result<ElementValue> {
+        if depth == MAX_ELEMENT_VALUE_DEPTH {
+            return Err(ClassReaderError::invalid_class_data(
+                "annotation element values are nested too deeply".to_string(),
+            ));
+        }
+        let tag = buf.read_u8()?;
+        Ok(match tag {
+            b'B' => ElementValue::Byte(self.read_int_element_value(buf)? as i8),
+            b'C' => ElementValue::Char(self.read_int_element_value(buf)? as u16),
+            b'I' => ElementValue::Int(self.read_int_element_value(buf)?),
+            b'S' => ElementValue::Short(self.read_int_element_value(buf)? as i16),
+            b'Z' => ElementValue::Boolean(self.read_int_element_value(buf)? != 0),
+            b'D' | b'F' | b'J' => {
+                let constant_index = buf.read_u16()?;
+                match (tag, self.class_file.constants.get(constant_index)?) {
+                    (b'D', ConstantPoolEntry::Double(value)) => ElementValue::Double(*value),
+                    (b'F', ConstantPoolEntry::Float(value)) => ElementValue::Float(*value),
+                    (b'J', ConstantPoolEntry::Long(value)) => ElementValue::Long(*value),
+                    _ => return Err(Self::invalid_element_value_constant(constant_index)),
+                }
+            }
+            b's' => ElementValue::String(self.read_string_reference(buf.read_u16()?)?),
+            b'e' => {
+                let type_descriptor = self.read_string_reference(buf.read_u16()?)?;
+                let const_name = self.read_string_reference(buf.read_u16()?)?;
+                ElementValue::Enum {
+                    type_descriptor,
+                    const_name,
+                }
+            }
+            b'c' => ElementValue::Class(self.read_string_reference(buf.read_u16()?)?),
+            b'@' => ElementValue::Annotation(Box::new(self.read_annotation(
+                buf,
+                runtime_visible,
+                depth + 1,
+            )?)),
+            b'[' => {
+                let num_values = buf.read_u16()?.into_usize_safe();
+                ElementValue::Array(read_table(buf, "values", num_values, |buf| {
+                    self.read_element_value(buf, runtime_visible, depth + 1)
+                })?)
+            }
+            _ => {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                    "invalid annotation element value tag: {tag:#0x}"
+                )))
+            }
+        })
+    }
+
+    /// Elements of type byte, char, int, short and boolean are all stored as integer constants
+    fn read_int_element_value(&self, buf: &mut Buffer) -> Result<i32> {
+        let constant_index = buf.read_u16()?;
+        match self.class_file.constants.get(constant_index)? {
+            ConstantPoolEntry::Integer(value) => Ok(*value),
+            _ => Err(Self::invalid_element_value_constant(constant_index)),
+        }
+    }
+
+    fn invalid_element_value_constant(constant_index: u16) -> ClassReaderError {
+        ClassReaderError::invalid_class_data(format!(
+            "invalid constant for annotation element value: {constant_index}"
+        ))
+    }
+
+    /// Extracts both the runtime visible and invisible type annotations
+    fn extract_type_annotations(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Vec<TypeAnnotation>> {
+        let mut type_annotations = Vec::new();
+        for attr in raw_attributes.iter() {
+            let runtime_visible = match attr.name() {
+                "RuntimeVisibleTypeAnnotations" => true,
+                "RuntimeInvisibleTypeAnnotations" => false,
+                _ => continue,
+            };
+            type_annotations.append(&mut attr.parse(|buf| {
+                let num_annotations = buf.read_u16()?.into_usize_safe();
+                read_table(buf, "annotations", num_annotations, |buf| {
+                    self.read_type_annotation(buf, runtime_visible)
+                })
+            })?);
+        }
+        Ok(type_annotations)
+    }
+
+    fn read_type_annotation(
+        &self,
+        buf: &mut Buffer,
+        runtime_visible: bool,
+    ) -> Result<TypeAnnotation> {
+        let target = Self::read_type_annotation_target(buf)?;
+        let type_path = Self::read_type_path(buf)?;
+        let annotation = self.read_annotation(buf, runtime_visible, 0)?;
+        Ok(TypeAnnotation {
+            target,
+            type_path,
+            annotation,
+        })
+    }
+
+    /// Reads the target_type and the target_info that depends on it
+    fn read_type_annotation_target(buf: &mut Buffer) -> Result<TypeAnnotationTarget> {
+        let target_type = buf.read_u8()?;
+        Ok(match target_type {
+            0x00 => TypeAnnotationTarget::ClassTypeParameter {
+                type_parameter_index: buf.read_u8()?,
+            },
+            0x01 => TypeAnnotationTarget::MethodTypeParameter {
+                type_parameter_index: buf.read_u8()?,
+            },
+            0x10 => {
+                // The superclass is identified by the index 65535
+                let supertype_index = buf.read_u16()?;
+                TypeAnnotationTarget::ClassExtends {
+                    interface_index: (supertype_index != u16::MAX).then_some(supertype_index),
+                }
+            }
+            0x11 => TypeAnnotationTarget::ClassTypeParameterBound {
+                type_parameter_index: buf.read_u8()?,
+                bound_index: buf.read_u8()?,
+            },
+            0x12 => TypeAnnotationTarget::MethodTypeParameterBound {
+                type_parameter_index: buf.read_u8()?,
+                bound_index: buf.read_u8()?,
+            },
+            0x13 => TypeAnnotationTarget::Field,
+            0x14 => TypeAnnotationTarget::MethodReturn,
+            0x15 => TypeAnnotationTarget::MethodReceiver,
+            0x16 => TypeAnnotationTarget::MethodFormalParameter {
+                formal_parameter_index: buf.read_u8()?,
+            },
+            0x17 => TypeAnnotationTarget::Throws {
+                throws_type_index: buf.read_u16()?,
+            },
+            0x40 => TypeAnnotationTarget::LocalVariable(Self::read_local_variable_targets(buf)?),
+            0x41 => TypeAnnotationTarget::ResourceVariable(Self::read_local_variable_targets(buf)?),
+            0x42 => TypeAnnotationTarget::ExceptionParameter {
+                exception_table_index: buf.read_u16()?,
+            },
+            0x43 => TypeAnnotationTarget::InstanceOf {
+                offset: ProgramCounter(buf.read_u16()?),
+            },
+            0x44 => TypeAnnotationTarget::New {
+                offset: ProgramCounter(buf.read_u16()?),
+            },
+            0x45 => TypeAnnotationTarget::ConstructorReference {
+                offset: ProgramCounter(buf.read_u16()?),
+            },
+            0x46 => TypeAnnotationTarget::MethodReference {
+                offset: ProgramCounter(buf.read_u16()?),
+            },
+            0x47 => TypeAnnotationTarget::Cast {
+                offset: ProgramCounter(buf.read_u16()?),
+                type_argument_index: buf.read_u8()?,
+            },
+            0x48 => TypeAnnotationTarget::ConstructorInvocationTypeArgument {
+                offset: ProgramCounter(buf.read_u16()?),
+                type_argument_index: buf.read_u8()?,
+            },
+            0x49 => TypeAnnotationTarget::MethodInvocationTypeArgument {
+                offset: ProgramCounter(buf.read_u16()?),
+                type_argument_index: buf.read_u8()?,
+            },
+            0x4A => TypeAnnotationTarget::ConstructorReferenceTypeArgument {
+                offset: ProgramCounter(buf.read_u16()?),
+                type_argument_index: buf.read_u8()?,
+            },
+            0x4B => TypeAnnotationTarget::MethodReferenceTypeArgument {
+                offset: ProgramCounter(buf.read_u16()?),
+                type_argument_index: buf.read_u8()?,
+            },
+            _ => {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                    "invalid type annotation target type: {target_type:#0x}"
+                )))
+            }
+        })
+    }
+
+    fn read_local_variable_targets(buf: &mut Buffer) -> Result<Vec<LocalVariableTarget>> {
+        let table_length = buf.read_u16()?.into_usize_safe();
+        read_table(buf, "table", table_length, |buf| {
+            let start_pc = buf.read_u16()?;
+            let length = buf.read_u16()?;
+            let end_pc = start_pc.checked_add(length).ok_or_else(|| {
+                ClassReaderError::invalid_class_data(format!(
+                    "invalid local variable range: {start_pc} + {length}"
+                ))
+            })?;
+            let index = buf.read_u16()?;
+            Ok(LocalVariableTarget {
+                range: ProgramCounter(start_pc)..ProgramCounter(end_pc),
+                index,
+            })
+        })
+    }
+
+    fn read_type_path(buf: &mut Buffer) -> Result<Vec<TypePathElement>> {
+        let path_length = buf.read_u8()?.into_usize_safe();
+        read_table(buf, "path", path_length, |buf| {
+            let type_path_kind = buf.read_u8()?;
+            let type_argument_index = buf.read_u8()?;
+            match (type_path_kind, type_argument_index) {
+                (0, 0) => Ok(TypePathElement::Array),
+                (1, 0) => Ok(TypePathElement::InnerType),
+                (2, 0) => Ok(TypePathElement::Wildcard),
+                (3, index) => Ok(TypePathElement::TypeArgument(index)),
+                _ => Err(ClassReaderError::invalid_class_data(format!(
+                    "invalid type path element: kind {type_path_kind}, \
+                    type argument index {type_argument_index}"
+                ))),
+            }
+        })
+    }
+
+    fn search_deprecated_attribute(&self, raw_attributes: &[RawAttribute]) -> bool {
+        raw_attributes
+            .iter()
+            .any(|attr| attr.name() == "Deprecated")
     }
 
     fn read_methods(&mut self) -> Result<()> {
-        let methods_count = self.buffer.read_u16()?;
+        let methods_count = self.read_count("methods_count")?;
         self.class_file.methods = (0..methods_count)
-            .map(|_| self.read_method())
+            .map(|index| {
+                let offset = self.buffer.position();
+                let mut structure = format!("methods[{index}]");
+                self.read_method(&mut structure)
+                    .map_err(|err| err.located(offset, structure))
+            })
             .collect::<Result<Vec<ClassFileMethod>>>()?;
         Ok(())
     }
 
-    fn read_method(&mut self) -> Result<ClassFileMethod> {
+    /// The structure describing the method in errors is completed with its name, once read
+    fn read_method(&mut self, structure: &mut String) -> Result<ClassFileMethod> {
         let flags = self.read_method_flags()?;
         let name_constant_index = self.buffer.read_u16()?;
         let name = self.read_string_reference(name_constant_index)?;
+        *structure = format!("{structure} {name:?}");
         let type_constant_index = self.buffer.read_u16()?;
         let type_descriptor = self.read_string_reference(type_constant_index)?;
         let parsed_type_descriptor = MethodDescriptor::parse(&type_descriptor)?;
@@ -339,16 +921,30 @@ impl<'a> ClassFileReader<'a> {
         };
         let deprecated = self.search_deprecated_attribute(&raw_attributes);
         let thrown_exceptions = self.extract_thrown_exceptions(&raw_attributes)?;
+        let signature = self.search_signature_attribute(&raw_attributes, MethodSignature::parse)?;
+        let annotations = self.extract_annotations(&raw_attributes)?;
+        let parameter_annotations = self.extract_parameter_annotations(&raw_attributes)?;
+        let annotation_default = self.search_annotation_default(&raw_attributes)?;
+        let parameters = self.extract_method_parameters(&raw_attributes)?;
+        let type_annotations = self.extract_type_annotations(&raw_attributes)?;
+        let custom_attributes = self.extract_custom_attributes(&raw_attributes)?;
 
         Ok(ClassFileMethod {
             flags,
             name,
             type_descriptor,
             parsed_type_descriptor,
-            attributes: raw_attributes,
+            attributes: Self::all_attributes(raw_attributes),
             code,
             deprecated,
             thrown_exceptions,
+            signature,
+            annotations,
+            parameter_annotations,
+            annotation_default,
+            parameters,
+            type_annotations,
+            custom_attributes,
         })
     }
 
@@ -364,30 +960,39 @@ impl<'a> ClassFileReader<'a> {
 
     fn extract_code(
         &self,
-        raw_attributes: &[Attribute],
+        raw_attributes: &[RawAttribute],
         name: &str,
     ) -> Result<ClassFileMethodCode> {
         raw_attributes
             .iter()
-            .filter(|attr| attr.name == "Code")
+            .filter(|attr| attr.name() == "Code")
             .map(|attr| {
-                let mut buf = Buffer::new(&attr.bytes);
-                let max_stack = buf.read_u16()?;
-                let max_locals = buf.read_u16()?;
-                let code_length = buf.read_u32()?.into_usize_safe();
-                let code = Vec::from(buf.read_bytes(code_length)?);
-                let exception_table = self.read_exception_table(&mut buf)?;
-                let attributes =
-                    Self::read_raw_attributes_from(&self.class_file.constants, &mut buf)?;
-                let line_number_table = self.extract_line_number_table(&attributes)?;
-
-                Result::<ClassFileMethodCode>::Ok(ClassFileMethodCode {
-                    max_stack,
-                    max_locals,
-                    code,
-                    exception_table,
-                    line_number_table,
-                    attributes,
+                attr.parse(|buf| {
+                    let max_stack = buf.read_u16()?;
+                    let max_locals = buf.read_u16()?;
+                    let code_length = buf.read_u32()?.into_usize_safe();
+                    let code = Vec::from(buf.read_bytes(code_length)?);
+                    let exception_table = self.read_exception_table(buf)?;
+                    let attributes =
+                        Self::read_raw_attributes_from(&self.class_file.constants, buf)?;
+                    let line_number_table = self.extract_line_number_table(&attributes)?;
+                    let local_variable_table = self.extract_local_variable_table(&attributes)?;
+                    let stack_map_table = self.extract_stack_map_table(&attributes)?;
+                    let type_annotations = self.extract_type_annotations(&attributes)?;
+                    let custom_attributes = self.extract_custom_attributes(&attributes)?;
+
+                    Ok(ClassFileMethodCode {
+                        max_stack,
+                        max_locals,
+                        code,
+                        exception_table,
+                        line_number_table,
+                        local_variable_table,
+                        stack_map_table,
+                        type_annotations,
+                        custom_attributes,
+                        attributes: Self::all_attributes(attributes),
+                    })
                 })
             })
             .next()
@@ -401,64 +1006,291 @@ impl<'a> ClassFileReader<'a> {
 
     fn read_exception_table(&self, buf: &mut Buffer) -> Result<ExceptionTable> {
         let exception_table_length = buf.read_u16()?.into_usize_safe();
-        let mut entries: Vec<ExceptionTableEntry> = Vec::with_capacity(exception_table_length / 8);
-        for _ in 0..exception_table_length {
+        let entries = read_table(buf, "exception_table", exception_table_length, |buf| {
             let start_pc = buf.read_u16()?;
             let end_pc = buf.read_u16()?;
             let handler_pc = buf.read_u16()?;
+            let catch_type_offset = buf.position();
             let catch_class_constant = buf.read_u16()?;
             let catch_class = if catch_class_constant == 0 {
                 None
             } else {
-                Some(self.read_string_reference(catch_class_constant)?)
+                Some(
+                    self.read_string_reference(catch_class_constant)
+                        .map_err(|err| err.located(catch_type_offset, "catch_type"))?,
+                )
             };
-            entries.push(ExceptionTableEntry {
+            Ok(ExceptionTableEntry {
                 range: ProgramCounter(start_pc)..ProgramCounter(end_pc),
                 handler_pc: ProgramCounter(handler_pc),
                 catch_class,
             })
-        }
+        })?;
         Ok(ExceptionTable::new(entries))
     }
 
+    fn extract_stack_map_table(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Vec<StackMapFrame>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == "StackMapTable")
+            .map(|attr| {
+                attr.parse(|buf| {
+                    let num_entries = buf.read_u16()?.into_usize_safe();
+                    let mut frames: Vec<StackMapFrame> = Vec::with_capacity(num_entries);
+                    for index in 0..num_entries {
+                        let offset = buf.position();
+                        let frame = self
+                            .read_stack_map_frame(buf, frames.last())
+                            .map_err(|err| err.located(offset, format!("entries[{index}]")))?;
+                        frames.push(frame);
+                    }
+                    Ok(frames)
+                })
+            })
+            .unwrap_or(Ok(Vec::new()))
+    }
+
+    fn read_stack_map_frame(
+        &self,
+        buf: &mut Buffer,
+        previous: Option<&StackMapFrame>,
+    ) -> Result<StackMapFrame> {
+        let frame_type = buf.read_u8()?;
+        let (offset_delta, kind) = match frame_type {
+            0..=63 => (frame_type as u16, StackMapFrameKind::Same),
+            64..=127 => (
+                frame_type as u16 - 64,
+                StackMapFrameKind::SameLocals1StackItem(self.read_verification_type(buf)?),
+            ),
+            247 => {
+                let offset_delta = buf.read_u16()?;
+                (
+                    offset_delta,
+                    StackMapFrameKind::SameLocals1StackItemExtended(
+                        self.read_verification_type(buf)?,
+                    ),
+                )
+            }
+            248..=250 => (buf.read_u16()?, StackMapFrameKind::Chop(251 - frame_type)),
+            251 => (buf.read_u16()?, StackMapFrameKind::SameExtended),
+            252..=254 => {
+                let offset_delta = buf.read_u16()?;
+                let locals = (0..frame_type - 251)
+                    .map(|_| self.read_verification_type(buf))
+                    .collect::<Result<Vec<VerificationType>>>()?;
+                (offset_delta, StackMapFrameKind::Append(locals))
+            }
+            255 => {
+                let offset_delta = buf.read_u16()?;
+                let num_locals = buf.read_u16()?;
+                let locals = (0..num_locals)
+                    .map(|_| self.read_verification_type(buf))
+                    .collect::<Result<Vec<VerificationType>>>()?;
+                let num_stack_items = buf.read_u16()?;
+                let stack = (0..num_stack_items)
+                    .map(|_| self.read_verification_type(buf))
+                    .collect::<Result<Vec<VerificationType>>>()?;
+                (offset_delta, StackMapFrameKind::Full { locals, stack })
+            }
+            _ => {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                    "invalid stack map frame type: {frame_type}"
+                )))
+            }
+        };
+
+        // The first frame is at offset_delta, the following ones are at
+        // offset_delta + 1 from the previous one, so that no two frames share an offset
+        let program_counter = match previous {
+            None => Some(offset_delta),
+            Some(previous) => previous
+                .program_counter
+                .0
+                .checked_add(offset_delta)
+                .and_then(|pc| pc.checked_add(1)),
+        }
+        .ok_or_else(|| {
+            ClassReaderError::invalid_class_data("stack map frame offset out of range".to_string())
+        })?;
+        Ok(StackMapFrame {
+            program_counter: ProgramCounter(program_counter),
+            kind,
+        })
+    }
+
+    fn read_verification_type(&self, buf: &mut Buffer) -> Result<VerificationType> {
+        let tag = buf.read_u8()?;
+        Ok(match tag {
+            0 => VerificationType::Top,
+            1 => VerificationType::Integer,
+            2 => VerificationType::Float,
+            3 => VerificationType::Double,
+            4 => VerificationType::Long,
+            5 => VerificationType::Null,
+            6 => VerificationType::UninitializedThis,
+            7 => VerificationType::Object(self.read_string_reference(buf.read_u16()?)?),
+            8 => VerificationType::Uninitialized(ProgramCounter(buf.read_u16()?)),
+            _ => {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                    "invalid verification type tag: {tag}"
+                )))
+            }
+        })
+    }
+
     fn extract_line_number_table(
         &self,
-        raw_attributes: &[Attribute],
+        raw_attributes: &[RawAttribute],
     ) -> Result<Option<LineNumberTable>> {
         raw_attributes
             .iter()
-            .find(|attr| attr.name == "LineNumberTable")
+            .find(|attr| attr.name() == "LineNumberTable")
             .map(|attr| {
-                let mut buf = Buffer::new(&attr.bytes);
-                let num_entries = buf.read_u16()?.into_usize_safe();
-                let mut entries = Vec::with_capacity(num_entries);
-                for _ in 0..num_entries {
-                    let program_counter = buf.read_u16()?;
-                    let line_number = buf.read_u16()?;
-                    entries.push(LineNumberTableEntry::new(
-                        ProgramCounter(program_counter),
-                        LineNumber(line_number),
-                    ));
-                }
-                Ok(LineNumberTable::new(entries))
+                attr.parse(|buf| {
+                    let num_entries = buf.read_u16()?.into_usize_safe();
+                    let entries = read_table(buf, "line_number_table", num_entries, |buf| {
+                        let program_counter = buf.read_u16()?;
+                        let line_number = buf.read_u16()?;
+                        Ok(LineNumberTableEntry::new(
+                            ProgramCounter(program_counter),
+                            LineNumber(line_number),
+                        ))
+                    })?;
+                    Ok(LineNumberTable::new(entries))
+                })
             })
             .invert()
     }
 
-    fn extract_thrown_exceptions(&self, raw_attributes: &[Attribute]) -> Result<Vec<String>> {
+    /// Merges the `LocalVariableTable` and `LocalVariableTypeTable` attributes. Note that
+    /// there can be more than one attribute of each kind for the same code.
+    fn extract_local_variable_table(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Option<LocalVariableTable>> {
+        if !raw_attributes
+            .iter()
+            .any(|attr| attr.name() == "LocalVariableTable")
+        {
+            return Ok(None);
+        }
+
+        let mut entries = Vec::new();
+        for attr in raw_attributes
+            .iter()
+            .filter(|attr| attr.name() == "LocalVariableTable")
+        {
+            for raw_entry in self.read_local_variable_table_entries(
+                attr,
+                "local_variable_table",
+                FieldType::parse,
+            )? {
+                entries.push(LocalVariableTableEntry {
+                    range: raw_entry.range,
+                    name: raw_entry.name,
+                    type_descriptor: raw_entry.descriptor,
+                    signature: None,
+                    index: raw_entry.index,
+                });
+            }
+        }
+        for attr in raw_attributes
+            .iter()
+            .filter(|attr| attr.name() == "LocalVariableTypeTable")
+        {
+            for raw_entry in self.read_local_variable_table_entries(
+                attr,
+                "local_variable_type_table",
+                TypeSignature::parse_field_signature,
+            )? {
+                match entries
+                    .iter_mut()
+                    .find(|entry| entry.range == raw_entry.range && entry.index == raw_entry.index)
+                {
+                    Some(entry) => entry.signature = Some(raw_entry.descriptor),
+                    None => warn!(
+                        "local variable type table entry for {} does not match any local variable",
+                        raw_entry.name
+                    ),
+                }
+            }
+        }
+        Ok(Some(LocalVariableTable::new(entries)))
+    }
+
+    /// The two local variable table attributes have the same layout, they only differ in
+    /// the meaning of the descriptor: a type descriptor or a signature, parsed with the
+    /// given function
+    fn read_local_variable_table_entries<T>(
+        &self,
+        attr: &RawAttribute,
+        table: &str,
+        parse_descriptor: impl Fn(&str) -> Result<T>,
+    ) -> Result<Vec<RawLocalVariableTableEntry<T>>> {
+        attr.parse(|buf| {
+            let num_entries = buf.read_u16()?.into_usize_safe();
+            read_table(buf, table, num_entries, |buf| {
+                let start_pc = buf.read_u16()?;
+                let length = buf.read_u16()?;
+                let end_pc = start_pc.checked_add(length).ok_or_else(|| {
+                    ClassReaderError::invalid_class_data(format!(
+                        "invalid local variable range: {start_pc} + {length}"
+                    ))
+                })?;
+                let name = self.read_string_reference(buf.read_u16()?)?;
+                let descriptor = parse_descriptor(&self.read_string_reference(buf.read_u16()?)?)?;
+                let index = buf.read_u16()?;
+                Ok(RawLocalVariableTableEntry {
+                    range: ProgramCounter(start_pc)..ProgramCounter(end_pc),
+                    name,
+                    descriptor,
+                    index,
+                })
+            })
+        })
+    }
+
+    fn extract_thrown_exceptions(&self, raw_attributes: &[RawAttribute]) -> Result<Vec<String>> {
         raw_attributes
             .iter()
-            .find(|attr| attr.name == "Exceptions")
+            .find(|attr| attr.name() == "Exceptions")
             .map(|attr| {
-                let mut buf = Buffer::new(&attr.bytes);
-                let num_entries = buf.read_u16()?.into_usize_safe();
-                let mut exceptions = Vec::with_capacity(num_entries);
-                for _ in 0..num_entries {
-                    let class_name = buf.read_u16()?;
-                    let exception_class_name = self.read_string_reference(class_name)?;
-                    exceptions.push(exception_class_name);
-                }
-                Ok(exceptions)
+                attr.parse(|buf| {
+                    let num_entries = buf.read_u16()?.into_usize_safe();
+                    read_table(buf, "exception_index_table", num_entries, |buf| {
+                        let class_name = buf.read_u16()?;
+                        self.read_string_reference(class_name)
+                    })
+                })
+            })
+            .unwrap_or(Ok(Vec::new()))
+    }
+
+    fn extract_method_parameters(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Vec<MethodParameter>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == "MethodParameters")
+            .map(|attr| {
+                attr.parse(|buf| {
+                    let parameters_count = buf.read_u8()?.into_usize_safe();
+                    read_table(buf, "parameters", parameters_count, |buf| {
+                        let name = self.read_string_reference_optional(buf.read_u16()?)?;
+                        let flags_bits = buf.read_u16()?;
+                        let flags =
+                            MethodParameterFlags::from_bits(flags_bits).ok_or_else(|| {
+                                ClassReaderError::invalid_class_data(format!(
+                                    "invalid method parameter flags: {flags_bits:#0x}"
+                                ))
+                            })?;
+                        Ok(MethodParameter { name, flags })
+                    })
+                })
             })
             .unwrap_or(Ok(Vec::new()))
     }
@@ -467,71 +1299,646 @@ impl<'a> ClassFileReader<'a> {
         let raw_attributes = self.read_raw_attributes()?;
         self.class_file.deprecated = self.search_deprecated_attribute(&raw_attributes);
         self.class_file.source_file = self.search_source_file_attribute(&raw_attributes)?;
+        self.class_file.bootstrap_methods = self.extract_bootstrap_methods(&raw_attributes)?;
+        self.check_bootstrap_method_indexes()?;
+        self.class_file.inner_classes = self.extract_inner_classes(&raw_attributes)?;
+        self.class_file.enclosing_method = self.search_enclosing_method(&raw_attributes)?;
+        self.class_file.signature =
+            self.search_signature_attribute(&raw_attributes, ClassSignature::parse)?;
+        self.class_file.annotations = self.extract_annotations(&raw_attributes)?;
+        self.class_file.type_annotations = self.extract_type_annotations(&raw_attributes)?;
+        self.class_file.module = self.search_module(&raw_attributes)?;
+        self.class_file.module_packages =
+            self.extract_constant_references(&raw_attributes, "ModulePackages", "package_index")?;
+        self.class_file.module_main_class =
+            self.search_constant_reference(&raw_attributes, "ModuleMainClass")?;
+        self.class_file.nest_host = self.search_constant_reference(&raw_attributes, "NestHost")?;
+        self.class_file.nest_members =
+            self.extract_constant_references(&raw_attributes, "NestMembers", "classes")?;
+        self.class_file.record_components = self.search_record(&raw_attributes)?;
+        self.class_file.permitted_subclasses =
+            self.extract_constant_references(&raw_attributes, "PermittedSubclasses", "classes")?;
+        self.class_file.custom_attributes = self.extract_custom_attributes(&raw_attributes)?;
+        self.class_file.attributes = Self::unmapped_attributes(raw_attributes, CLASS_ATTRIBUTES);
+        Ok(())
+    }
+
+    fn extract_inner_classes(&self, raw_attributes: &[RawAttribute]) -> Result<Vec<InnerClass>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == "InnerClasses")
+            .map(|attr| {
+                attr.parse(|buf| {
+                    let num_classes = buf.read_u16()?.into_usize_safe();
+                    read_table(buf, "classes", num_classes, |buf| {
+                        let name = self.read_string_reference(buf.read_u16()?)?;
+                        let outer_class = self.read_string_reference_optional(buf.read_u16()?)?;
+                        let simple_name = self.read_string_reference_optional(buf.read_u16()?)?;
+                        let flags_bits = buf.read_u16()?;
+                        let flags = InnerClassFlags::from_bits(flags_bits).ok_or_else(|| {
+                            ClassReaderError::invalid_class_data(format!(
+                                "invalid inner class flags: {flags_bits:#0x}"
+                            ))
+                        })?;
+                        Ok(InnerClass {
+                            name,
+                            outer_class,
+                            simple_name,
+                            flags,
+                        })
+                    })
+                })
+            })
+            .unwrap_or(Ok(Vec::new()))
+    }
+
+    fn search_enclosing_method(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Option<EnclosingMethod>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == "EnclosingMethod")
+            .map(|attr| attr.parse(|buf| {
+                let class_name = self.read_string_reference(buf.read_u16()?)?;
+                let method_index = buf.read_u16()?;
+                let (method_name, method_type_descriptor) = if method_index == 0 {
+                    (None, None)
+                } else {
+                    match self.class_file.constants.get(method_index)? {
+                        ConstantPoolEntry::NameAndTypeDescriptor(name_index, type_index) => (
+                            Some(self.read_string_reference(*name_index)?),
+                            Some(self.read_string_reference(*type_index)?),
+                        ),
+                        _ => {
+                            return Err(ClassReaderError::invalid_class_data(format!(
+                                "enclosing method should refer to a name and type, but found constant {method_index}"
+                            )))
+                        }
+                    }
+                };
+                Ok(EnclosingMethod {
+                    class_name,
+                    method_name,
+                    method_type_descriptor,
+                })
+            }))
+            .invert()
+    }
+
+    /// Reads an attribute that refers to a single constant, such as a class name
+    fn search_constant_reference(
+        &self,
+        raw_attributes: &[RawAttribute],
+        name: &str,
+    ) -> Result<Option<String>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == name)
+            .map(|attr| attr.parse(|buf| self.read_string_reference(buf.read_u16()?)))
+            .invert()
+    }
+
+    /// Reads an attribute made of a list of references to constants, such as class names
+    fn extract_constant_references(
+        &self,
+        raw_attributes: &[RawAttribute],
+        name: &str,
+        table: &str,
+    ) -> Result<Vec<String>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == name)
+            .map(|attr| {
+                attr.parse(|buf| {
+                    let num_entries = buf.read_u16()?.into_usize_safe();
+                    read_table(buf, table, num_entries, |buf| {
+                        self.read_string_reference(buf.read_u16()?)
+                    })
+                })
+            })
+            .unwrap_or(Ok(Vec::new()))
+    }
+
+    fn search_module(&self, raw_attributes: &[RawAttribute]) -> Result<Option<Module>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == "Module")
+            .map(|attr| attr.parse(|buf| self.read_module(buf)))
+            .invert()
+    }
+
+    fn read_module(&self, buf: &mut Buffer) -> Result<Module> {
+        let name = self.read_string_reference(buf.read_u16()?)?;
+        let flags_bits = buf.read_u16()?;
+        let flags = ModuleFlags::from_bits(flags_bits).ok_or_else(|| {
+            ClassReaderError::invalid_class_data(format!("invalid module flags: {flags_bits:#0x}"))
+        })?;
+        let version = self.read_string_reference_optional(buf.read_u16()?)?;
+
+        let requires_count = buf.read_u16()?.into_usize_safe();
+        let requires = read_table(buf, "requires", requires_count, |buf| {
+            let module = self.read_string_reference(buf.read_u16()?)?;
+            let flags_bits = buf.read_u16()?;
+            let flags = ModuleRequiresFlags::from_bits(flags_bits).ok_or_else(|| {
+                ClassReaderError::invalid_class_data(format!(
+                    "invalid module requires flags: {flags_bits:#0x}"
+                ))
+            })?;
+            let version = self.read_string_reference_optional(buf.read_u16()?)?;
+            Ok(ModuleRequires {
+                module,
+                flags,
+                version,
+            })
+        })?;
+        let exports = self.read_module_packages(buf, "exports")?;
+        let opens = self.read_module_packages(buf, "opens")?;
+
+        let uses_count = buf.read_u16()?.into_usize_safe();
+        let uses = read_table(buf, "uses_index", uses_count, |buf| {
+            self.read_string_reference(buf.read_u16()?)
+        })?;
+
+        let provides_count = buf.read_u16()?.into_usize_safe();
+        let provides = read_table(buf, "provides", provides_count, |buf| {
+            let service = self.read_string_reference(buf.read_u16()?)?;
+            let provides_with_count = buf.read_u16()?.into_usize_safe();
+            let implementations =
+                read_table(buf, "provides_with_index", provides_with_count, |buf| {
+                    self.read_string_reference(buf.read_u16()?)
+                })?;
+            Ok(ModuleProvides {
+                service,
+                implementations,
+            })
+        })?;
+
+        Ok(Module {
+            name,
+            flags,
+            version,
+            requires,
+            exports,
+            opens,
+            uses,
+            provides,
+        })
+    }
+
+    /// The `exports` and `opens` tables of a module have the same layout
+    fn read_module_packages(&self, buf: &mut Buffer, table: &str) -> Result<Vec<ModulePackage>> {
+        let num_entries = buf.read_u16()?.into_usize_safe();
+        read_table(buf, table, num_entries, |buf| {
+            let package = self.read_string_reference(buf.read_u16()?)?;
+            let flags_bits = buf.read_u16()?;
+            let flags = ModulePackageFlags::from_bits(flags_bits).ok_or_else(|| {
+                ClassReaderError::invalid_class_data(format!(
+                    "invalid module {table} flags: {flags_bits:#0x}"
+                ))
+            })?;
+            let to_count = buf.read_u16()?.into_usize_safe();
+            let to = read_table(buf, &format!("{table}_to_index"), to_count, |buf| {
+                self.read_string_reference(buf.read_u16()?)
+            })?;
+            Ok(ModulePackage { package, flags, to })
+        })
+    }
+
+    fn search_record(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Option<Vec<RecordComponent>>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == "Record")
+            .map(|attr| {
+                attr.parse(|buf| {
+                    let components_count = buf.read_u16()?.into_usize_safe();
+                    read_table(buf, "components", components_count, |buf| {
+                        self.read_record_component(buf)
+                    })
+                })
+            })
+            .invert()
+    }
+
+    fn read_record_component(&self, buf: &mut Buffer) -> Result<RecordComponent> {
+        let name = self.read_string_reference(buf.read_u16()?)?;
+        let type_descriptor = FieldType::parse(&self.read_string_reference(buf.read_u16()?)?)?;
+        let raw_attributes = Self::read_raw_attributes_from(&self.class_file.constants, buf)?;
+        let signature =
+            self.search_signature_attribute(&raw_attributes, TypeSignature::parse_field_signature)?;
+        let annotations = self.extract_annotations(&raw_attributes)?;
+        let type_annotations = self.extract_type_annotations(&raw_attributes)?;
+        let attributes = Self::unmapped_attributes(raw_attributes, RECORD_COMPONENT_ATTRIBUTES);
+        Ok(RecordComponent {
+            name,
+            type_descriptor,
+            signature,
+            annotations,
+            type_annotations,
+            attributes,
+        })
+    }
+
+    fn extract_bootstrap_methods(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Vec<BootstrapMethod>> {
+        raw_attributes
+            .iter()
+            .find(|attr| attr.name() == "BootstrapMethods")
+            .map(|attr| {
+                attr.parse(|buf| {
+                    let num_bootstrap_methods = buf.read_u16()?.into_usize_safe();
+                    read_table(buf, "bootstrap_methods", num_bootstrap_methods, |buf| {
+                        self.read_bootstrap_method(buf)
+                    })
+                })
+            })
+            .unwrap_or(Ok(Vec::new()))
+    }
+
+    /// The `Dynamic` and `InvokeDynamic` constants refer to their bootstrap method by its
+    /// index in the `BootstrapMethods` attribute, which is read after the constant pool
+    fn check_bootstrap_method_indexes(&self) -> Result<()> {
+        let num_bootstrap_methods = self.class_file.bootstrap_methods.len();
+        for (index, entry) in self.class_file.constants.iter() {
+            match entry {
+                ConstantPoolEntry::Dynamic(bootstrap_method_index, _)
+                | ConstantPoolEntry::InvokeDynamic(bootstrap_method_index, _)
+                    if *bootstrap_method_index as usize >= num_bootstrap_methods =>
+                {
+                    return Err(ClassReaderError::invalid_class_data(format!(
+                        "constant {index} refers to bootstrap method {bootstrap_method_index}, but the class has {num_bootstrap_methods}"
+                    )));
+                }
+                _ => {}
+            }
+        }
         Ok(())
     }
 
-    fn search_source_file_attribute(&self, raw_attributes: &[Attribute]) -> Result<Option<String>> {
+    fn read_bootstrap_method(&self, buf: &mut Buffer) -> Result<BootstrapMethod> {
+        let method_ref = buf.read_u16()?;
+        match self.class_file.constants.get(method_ref)? {
+            ConstantPoolEntry::MethodHandle(_, _) => {}
+            _ => {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                "bootstrap method should refer to a method handle, but found constant {method_ref}"
+            )))
+            }
+        }
+
+        let num_arguments = buf.read_u16()?.into_usize_safe();
+        let arguments = read_table(buf, "bootstrap_arguments", num_arguments, |buf| {
+            let argument = buf.read_u16()?;
+            if !Self::is_loadable_constant(self.class_file.constants.get(argument)?) {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                    "bootstrap method argument should be a loadable constant, but found constant {argument}"
+                )));
+            }
+            Ok(argument)
+        })?;
+        Ok(BootstrapMethod {
+            method_ref,
+            arguments,
+        })
+    }
+
+    /// Loadable constants are the ones that can be pushed on the stack via `ldc`,
+    /// and thus used as static arguments of a bootstrap method
+    fn is_loadable_constant(entry: &ConstantPoolEntry) -> bool {
+        matches!(
+            entry,
+            ConstantPoolEntry::Integer(_)
+                | ConstantPoolEntry::Float(_)
+                | ConstantPoolEntry::Long(_)
+                | ConstantPoolEntry::Double(_)
+                | ConstantPoolEntry::ClassReference(_)
+                | ConstantPoolEntry::StringReference(_)
+                | ConstantPoolEntry::MethodHandle(_, _)
+                | ConstantPoolEntry::MethodType(_)
+                | ConstantPoolEntry::Dynamic(_, _)
+        )
+    }
+
+    fn search_source_file_attribute(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Option<String>> {
         raw_attributes
             .iter()
-            .find(|attr| attr.name == "SourceFile")
+            .find(|attr| attr.name() == "SourceFile")
             .map(|attr| {
-                let attribute_bytes: &[u8] = &attr.bytes;
-                let constant_index = u16::from_be_bytes(attribute_bytes.try_into().unwrap());
-                self.class_file
-                    .constants
-                    .get(constant_index)
-                    .map_err(|err| err.into())
-                    .and_then(|entry| match entry {
+                attr.parse(|buf| {
+                    let constant_index = buf.read_u16()?;
+                    match self.class_file.constants.get(constant_index)? {
                         ConstantPoolEntry::Utf8(file_name) => Ok(file_name.clone()),
                         _ => Err(ClassReaderError::invalid_class_data(
                             "invalid SourceFile attribute".to_string(),
                         )),
-                    })
+                    }
+                })
             })
             .invert()
     }
 
-    fn read_raw_attributes(&mut self) -> Result<Vec<Attribute>> {
+    /// Parses the attributes that have a parser registered by the caller. Their raw form is
+    /// kept as well, so that the class can be written back without knowing about them.
+    fn extract_custom_attributes(
+        &self,
+        raw_attributes: &[RawAttribute],
+    ) -> Result<Vec<CustomAttribute>> {
+        raw_attributes
+            .iter()
+            .filter_map(|attr| {
+                self.attribute_parsers.get(attr.name()).map(|parser| {
+                    parser(&attr.attribute.bytes, &self.class_file.constants)
+                        .map(|value| CustomAttribute {
+                            name: attr.name().to_string(),
+                            value,
+                        })
+                        .map_err(|err| err.located(attr.offset, attr.name()))
+                })
+            })
+            .collect()
+    }
+
+    /// Keeps only the attributes that have not been parsed into the model
+    fn unmapped_attributes(
+        raw_attributes: Vec<RawAttribute>,
+        parsed_names: &[&str],
+    ) -> Vec<Attribute> {
+        raw_attributes
+            .into_iter()
+            .filter(|attr| !parsed_names.contains(&attr.name()))
+            .map(|attr| attr.attribute)
+            .collect()
+    }
+
+    fn all_attributes(raw_attributes: Vec<RawAttribute>) -> Vec<Attribute> {
+        Self::unmapped_attributes(raw_attributes, &[])
+    }
+
+    fn read_raw_attributes(&mut self) -> Result<Vec<RawAttribute>> {
         Self::read_raw_attributes_from(&self.class_file.constants, &mut self.buffer)
     }
 
     fn read_raw_attributes_from(
         constants_pool: &ConstantPool,
         buffer: &mut Buffer,
-    ) -> Result<Vec<Attribute>> {
-        let attributes_count = buffer.read_u16()?;
-        (0..attributes_count)
-            .map(|_| Self::read_raw_attribute(constants_pool, buffer))
-            .collect::<Result<Vec<Attribute>>>()
+    ) -> Result<Vec<RawAttribute>> {
+        let offset = buffer.position();
+        let attributes_count = buffer
+            .read_u16()
+            .map_err(|err| ClassReaderError::from(err).located(offset, "attributes_count"))?;
+        read_table(
+            buffer,
+            "attributes",
+            attributes_count.into_usize_safe(),
+            |buffer| Self::read_raw_attribute(constants_pool, buffer),
+        )
     }
 
-    fn read_raw_attribute(constants_pool: &ConstantPool, buffer: &mut Buffer) -> Result<Attribute> {
+    fn read_raw_attribute(
+        constants_pool: &ConstantPool,
+        buffer: &mut Buffer,
+    ) -> Result<RawAttribute> {
         let name_constant_index = buffer.read_u16()?;
         let name = Self::read_string_reference_from(constants_pool, name_constant_index)?;
         let len = buffer.read_u32()?;
+        let offset = buffer.position();
         let bytes = buffer.read_bytes(len.into_usize_safe())?;
-        Ok(Attribute {
-            name,
-            bytes: Vec::from(bytes),
+        Ok(RawAttribute {
+            attribute: Attribute {
+                name,
+                bytes: Vec::from(bytes),
+            },
+            offset,
         })
     }
 }
 
+/// Reads the entries of a table, attributing any error to the entry being read
+pub(crate) fn read_table<'a, T>(
+    buf: &mut Buffer<'a>,
+    table: &str,
+    num_entries: usize,
+    mut read_entry: impl FnMut(&mut Buffer<'a>) -> Result<T>,
+) -> Result<Vec<T>> {
+    let mut entries = Vec::with_capacity(num_entries);
+    for index in 0..num_entries {
+        let offset = buf.position();
+        let entry =
+            read_entry(buf).map_err(|err| err.located(offset, format!("{table}[{index}]")))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
 /// Reads a class from a byte slice.
 pub fn read_buffer(buf: &[u8]) -> Result<ClassFile> {
-    ClassFileReader::new(buf).read()
+    read_buffer_with_parsers(buf, &AttributeParsers::default())
+}
+
+/// Reads a class from a byte slice, using the given parsers for the attributes that are
+/// not part of the JVM specification
+pub fn read_buffer_with_parsers(
+    buf: &[u8],
+    attribute_parsers: &AttributeParsers,
+) -> Result<ClassFile> {
+    ClassFileReader::new(buf, attribute_parsers).read()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{class_reader::read_buffer, class_reader_error::ClassReaderError};
+    use crate::{
+        attribute::Attribute,
+        class_reader::{read_buffer, ClassFileReader, RawAttribute},
+        class_reader_error::ClassReaderError,
+        class_writer::write,
+        constant_pool::{ConstantPoolEntry, MethodHandleKind},
+        custom_attribute::AttributeParsers,
+        text_assembler::assemble,
+    };
+
+    #[test]
+    fn constants_must_refer_to_constants_of_the_right_kind() {
+        let class_with_constants = |constants: &[u8]| {
+            let mut data = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52, 0, 5];
+            data.extend_from_slice(constants);
+            data
+        };
+        let assert_error = |constants: &[u8], message: &str| {
+            let err = read_buffer(&class_with_constants(constants)).unwrap_err();
+            assert!(
+                matches!(
+                    err.without_location(),
+                    ClassReaderError::InvalidClassData(ref s, None) if s == message
+                ),
+                "unexpected error {err}"
+            );
+        };
+
+        // #1 Methodref #2 #3, #2 Class #4, #3 NameAndType #4 #4, #4 Utf8 "x"
+        let valid = [10, 0, 2, 0, 3, 7, 0, 4, 12, 0, 4, 0, 4, 1, 0, 1, b'x'];
+        assert!(!matches!(
+            read_buffer(&class_with_constants(&valid))
+                .unwrap_err()
+                .without_location(),
+            ClassReaderError::InvalidClassData(s, None) if s.starts_with("constant")
+        ));
+
+        // The name of the NameAndType refers to the Methodref, creating a cycle
+        assert_error(
+            &[10, 0, 2, 0, 3, 7, 0, 4, 12, 0, 1, 0, 4, 1, 0, 1, b'x'],
+            "constant 3 should refer to a Utf8, but found constant 1",
+        );
+        // The class of the Methodref refers to the NameAndType
+        assert_error(
+            &[10, 0, 3, 0, 3, 7, 0, 4, 12, 0, 4, 0, 4, 1, 0, 1, b'x'],
+            "constant 1 should refer to a Class, but found constant 3",
+        );
+        // The handle to a virtual method refers to a class
+        assert_error(
+            &[15, 5, 0, 2, 7, 0, 4, 12, 0, 4, 0, 4, 1, 0, 1, b'x'],
+            "constant 1 should refer to a Methodref, but found constant 2",
+        );
+    }
 
     #[test]
     fn magic_number_is_required() {
         let data = vec![0x00, 0x01, 0x02, 0x03];
+        let err = read_buffer(&data).unwrap_err();
         assert!(matches!(
-            read_buffer(&data),
-            Err(ClassReaderError::InvalidClassData(s, None)) if s == "invalid magic number"
+            err.without_location(),
+            ClassReaderError::InvalidClassData(s, None) if s == "invalid magic number"
         ));
+        assert_eq!(
+            "invalid class file: invalid magic number at offset 0x0 in magic",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn errors_report_the_offset_and_the_structures_being_read() {
+        let class = assemble(
+            r#"
+            .class rjvm/Foo
+            .method static foo()V
+                .limit stack 1
+            Start:
+                return
+            End:
+            Handler:
+                athrow
+                .catch java/lang/Exception from Start to End using Handler
+            .end method
+            "#,
+        )
+        .unwrap();
+        let mut bytes = write(&class).unwrap();
+
+        // Corrupt the catch type of the only entry of the exception table
+        let entry = [0, 1, 0, 0, 0, 1, 0, 1];
+        let catch_type_offset = bytes
+            .windows(entry.len())
+            .position(|window| window == entry)
+            .expect("should find the exception table")
+            + entry.len();
+        bytes[catch_type_offset] = 0xFF;
+        bytes[catch_type_offset + 1] = 0xFF;
+
+        let err = read_buffer(&bytes).unwrap_err();
+        match &err {
+            ClassReaderError::Located { offset, path, .. } => {
+                assert_eq!(catch_type_offset, *offset);
+                assert_eq!(
+                    vec![
+                        "methods[0] \"foo\"".to_string(),
+                        "Code".to_string(),
+                        "exception_table[0]".to_string(),
+                        "catch_type".to_string()
+                    ],
+                    *path
+                );
+            }
+            err => panic!("expected a located error, got {err:?}"),
+        }
+        assert_eq!(
+            format!(
+                "invalid class file: invalid constant pool index: 65535 at offset {:#x} in methods[0] \"foo\" -> Code -> exception_table[0] -> catch_type",
+                catch_type_offset
+            ),
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn bootstrap_methods_are_validated_against_the_constant_pool() {
+        let attribute_parsers = AttributeParsers::default();
+        let mut reader = ClassFileReader::new(&[], &attribute_parsers);
+        reader
+            .class_file
+            .constants
+            .add(ConstantPoolEntry::Utf8("x".to_string()));
+        reader
+            .class_file
+            .constants
+            .add(ConstantPoolEntry::Integer(42));
+        reader
+            .class_file
+            .constants
+            .add(ConstantPoolEntry::MethodHandle(
+                MethodHandleKind::InvokeStatic,
+                1,
+            ));
+        let attribute = |bytes: Vec<u8>| {
+            vec![RawAttribute {
+                attribute: Attribute {
+                    name: "BootstrapMethods".to_string(),
+                    bytes,
+                },
+                offset: 100,
+            }]
+        };
+
+        let bootstrap_methods = reader
+            .extract_bootstrap_methods(&attribute(vec![0, 1, 0, 3, 0, 1, 0, 2]))
+            .expect("should be able to read bootstrap methods");
+        assert_eq!(3, bootstrap_methods[0].method_ref);
+        assert_eq!(vec![2], bootstrap_methods[0].arguments);
+
+        assert_eq!(
+            "invalid class file: bootstrap method should refer to a method handle, but found constant 2 \
+             at offset 0x66 in BootstrapMethods -> bootstrap_methods[0]",
+            reader
+                .extract_bootstrap_methods(&attribute(vec![0, 1, 0, 2, 0, 0]))
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "invalid class file: bootstrap method argument should be a loadable constant, but found constant 1 \
+             at offset 0x6a in BootstrapMethods -> bootstrap_methods[0] -> bootstrap_arguments[0]",
+            reader
+                .extract_bootstrap_methods(&attribute(vec![0, 1, 0, 3, 0, 1, 0, 1]))
+                .unwrap_err()
+                .to_string()
+        );
+
+        reader
+            .class_file
+            .constants
+            .add(ConstantPoolEntry::InvokeDynamic(1, 1));
+        reader.class_file.bootstrap_methods = bootstrap_methods;
+        assert_eq!(
+            "invalid class file: constant 4 refers to bootstrap method 1, but the class has 1",
+            reader
+                .check_bootstrap_method_indexes()
+                .unwrap_err()
+                .to_string()
+        );
     }
 }
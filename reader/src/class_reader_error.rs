@@ -13,12 +13,54 @@ pub enum ClassReaderError {
     UnsupportedVersion(u16, u16),
     /// Error while parsing a given type descriptor in the file
     InvalidTypeDescriptor(String),
+    /// Wraps an error with the position in the class file where it was detected
+    Located {
+        error: Box<ClassReaderError>,
+        /// The offset of the reader, from the start of the class file
+        offset: usize,
+        /// The structures being read, from the outermost, i.e.
+        /// `methods[4] "foo"`, `Code`, `exception_table[2]`
+        path: Vec<String>,
+    },
 }
 
 impl ClassReaderError {
     pub fn invalid_class_data(message: String) -> Self {
         ClassReaderError::InvalidClassData(message, None)
     }
+
+    /// Records that the error happened while reading the given structure, which starts
+    /// at the given offset. Errors that were already located keep their offset, which is
+    /// more precise, and get the structure prepended to their path.
+    pub(crate) fn located(self, offset: usize, structure: impl Into<String>) -> Self {
+        match self {
+            ClassReaderError::Located {
+                error,
+                offset,
+                mut path,
+            } => {
+                path.insert(0, structure.into());
+                ClassReaderError::Located {
+                    error,
+                    offset,
+                    path,
+                }
+            }
+            error => ClassReaderError::Located {
+                error: Box::new(error),
+                offset,
+                path: vec![structure.into()],
+            },
+        }
+    }
+
+    /// Returns the underlying error, without its location
+    pub fn without_location(&self) -> &ClassReaderError {
+        match self {
+            ClassReaderError::Located { error, .. } => error.without_location(),
+            error => error,
+        }
+    }
 }
 
 impl Display for ClassReaderError {
@@ -33,6 +75,11 @@ impl Display for ClassReaderError {
             ClassReaderError::InvalidTypeDescriptor(descriptor) => {
                 write!(f, "invalid type descriptor: {descriptor}")
             }
+            ClassReaderError::Located {
+                error,
+                offset,
+                path,
+            } => write!(f, "{error} at offset {offset:#x} in {}", path.join(" -> ")),
         }
     }
 }
@@ -41,6 +88,7 @@ impl Error for ClassReaderError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             ClassReaderError::InvalidClassData(_, Some(source)) => Some(source),
+            ClassReaderError::Located { error, .. } => Some(error.as_ref()),
             _ => None,
         }
     }
@@ -0,0 +1,1019 @@
+use crate::{
+    annotation::{Annotation, ElementValue},
+    attribute::{
+        Attribute, CLASS_ATTRIBUTES, CODE_ATTRIBUTES, FIELD_ATTRIBUTES, METHOD_ATTRIBUTES,
+        RECORD_COMPONENT_ATTRIBUTES,
+    },
+    buffer_writer::BufferWriter,
+    class_file::ClassFile,
+    class_file_field::{ClassFileField, FieldConstantValue},
+    class_file_method::{ClassFileMethod, ClassFileMethodCode},
+    class_writer_error::{ClassWriterError, Result},
+    constant_pool::ConstantPoolEntry,
+    constant_pool_builder::ConstantPoolBuilder,
+    local_variable_table::LocalVariableTableEntry,
+    module::{Module, ModulePackage},
+    record_component::RecordComponent,
+    stack_map_table::{StackMapFrame, StackMapFrameKind, VerificationType},
+    type_annotation::{TypeAnnotation, TypeAnnotationTarget, TypePathElement},
+};
+
+/// A writer that serializes a [ClassFile] to the bytes of a .class file.
+///
+/// The constant pool of the class is kept as it is, so that the bytecode and the unparsed
+/// attributes, which refer to constants by index, stay valid. Any constant required by
+/// the other fields of the class and missing from the pool gets appended to it.
+struct ClassFileWriter<'a> {
+    class_file: &'a ClassFile,
+    constants: ConstantPoolBuilder,
+}
+
+/// Reference: https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html
+impl<'a> ClassFileWriter<'a> {
+    fn new(class_file: &'a ClassFile) -> Self {
+        Self {
+            class_file,
+            constants: ConstantPoolBuilder::from(class_file.constants.clone()),
+        }
+    }
+
+    fn write(mut self) -> Result<Vec<u8>> {
+        // Everything after the constant pool is written first, since it can add constants
+        let mut body = BufferWriter::new();
+        self.write_body(&mut body)?;
+
+        let mut out = BufferWriter::new();
+        out.write_u32(0xCAFEBABE);
+        out.write_u16(self.class_file.minor_version);
+        out.write_u16(self.class_file.version.major());
+        self.write_constants(&mut out)?;
+        out.write_bytes(&body.into_bytes());
+        Ok(out.into_bytes())
+    }
+
+    fn write_body(&mut self, out: &mut BufferWriter) -> Result<()> {
+        let class_file = self.class_file;
+        out.write_u16(class_file.flags.bits());
+        out.write_u16(self.constants.class(&class_file.name));
+        match &class_file.superclass {
+            Some(superclass) => out.write_u16(self.constants.class(superclass)),
+            None => out.write_u16(0),
+        }
+
+        out.write_len(class_file.interfaces.len(), "interfaces")?;
+        for interface in class_file.interfaces.iter() {
+            out.write_u16(self.constants.class(interface));
+        }
+
+        out.write_len(class_file.fields.len(), "fields")?;
+        for field in class_file.fields.iter() {
+            self.write_field(out, field)?;
+        }
+
+        out.write_len(class_file.methods.len(), "methods")?;
+        for method in class_file.methods.iter() {
+            self.write_method(out, method)?;
+        }
+
+        let attributes = self.class_attributes()?;
+        self.write_attributes(out, attributes, &class_file.attributes, CLASS_ATTRIBUTES)
+    }
+
+    fn write_constants(&self, out: &mut BufferWriter) -> Result<()> {
+        if self.constants.is_full() {
+            return Err(ClassWriterError::LimitExceeded(format!(
+                "too many constants: {}",
+                self.constants.constants().len()
+            )));
+        }
+        // The count includes the unused slot 0
+        out.write_u16(self.constants.constants().len() as u16 + 1);
+        for (_, entry) in self.constants.constants().iter() {
+            match entry {
+                ConstantPoolEntry::Utf8(text) => {
+                    out.write_u8(1);
+                    out.write_utf8(text)?;
+                }
+                ConstantPoolEntry::Integer(value) => {
+                    out.write_u8(3);
+                    out.write_i32(*value);
+                }
+                ConstantPoolEntry::Float(value) => {
+                    out.write_u8(4);
+                    out.write_f32(*value);
+                }
+                ConstantPoolEntry::Long(value) => {
+                    out.write_u8(5);
+                    out.write_i64(*value);
+                }
+                ConstantPoolEntry::Double(value) => {
+                    out.write_u8(6);
+                    out.write_f64(*value);
+                }
+                ConstantPoolEntry::ClassReference(index) => {
+                    out.write_u8(7);
+                    out.write_u16(*index);
+                }
+                ConstantPoolEntry::StringReference(index) => {
+                    out.write_u8(8);
+                    out.write_u16(*index);
+                }
+                ConstantPoolEntry::FieldReference(class_index, name_and_type_index) => {
+                    out.write_u8(9);
+                    out.write_u16(*class_index);
+                    out.write_u16(*name_and_type_index);
+                }
+                ConstantPoolEntry::MethodReference(class_index, name_and_type_index) => {
+                    out.write_u8(10);
+                    out.write_u16(*class_index);
+                    out.write_u16(*name_and_type_index);
+                }
+                ConstantPoolEntry::InterfaceMethodReference(class_index, name_and_type_index) => {
+                    out.write_u8(11);
+                    out.write_u16(*class_index);
+                    out.write_u16(*name_and_type_index);
+                }
+                ConstantPoolEntry::NameAndTypeDescriptor(name_index, type_index) => {
+                    out.write_u8(12);
+                    out.write_u16(*name_index);
+                    out.write_u16(*type_index);
+                }
+                ConstantPoolEntry::MethodHandle(kind, index) => {
+                    out.write_u8(15);
+                    out.write_u8(kind.reference_kind());
+                    out.write_u16(*index);
+                }
+                ConstantPoolEntry::MethodType(index) => {
+                    out.write_u8(16);
+                    out.write_u16(*index);
+                }
+                ConstantPoolEntry::Dynamic(bootstrap_method_index, name_and_type_index) => {
+                    out.write_u8(17);
+                    out.write_u16(*bootstrap_method_index);
+                    out.write_u16(*name_and_type_index);
+                }
+                ConstantPoolEntry::InvokeDynamic(bootstrap_method_index, name_and_type_index) => {
+                    out.write_u8(18);
+                    out.write_u16(*bootstrap_method_index);
+                    out.write_u16(*name_and_type_index);
+                }
+                ConstantPoolEntry::Module(index) => {
+                    out.write_u8(19);
+                    out.write_u16(*index);
+                }
+                ConstantPoolEntry::Package(index) => {
+                    out.write_u8(20);
+                    out.write_u16(*index);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_field(&mut self, out: &mut BufferWriter, field: &ClassFileField) -> Result<()> {
+        out.write_u16(field.flags.bits());
+        out.write_u16(self.constants.utf8(&field.name));
+        out.write_u16(self.constants.utf8(&field.type_descriptor.descriptor()));
+        let attributes = self.field_attributes(field)?;
+        self.write_attributes(out, attributes, &field.attributes, FIELD_ATTRIBUTES)
+    }
+
+    fn write_method(&mut self, out: &mut BufferWriter, method: &ClassFileMethod) -> Result<()> {
+        out.write_u16(method.flags.bits());
+        out.write_u16(self.constants.utf8(&method.name));
+        out.write_u16(self.constants.utf8(&method.type_descriptor));
+        let attributes = self.method_attributes(method)?;
+        self.write_attributes(out, attributes, &method.attributes, METHOD_ATTRIBUTES)
+    }
+
+    /// Writes the attributes serialized from the model together with the unparsed ones.
+    /// The order of the original attributes is kept, if known; the attributes of the model
+    /// that were not in the original list are written at the end.
+    fn write_attributes(
+        &mut self,
+        out: &mut BufferWriter,
+        mut parsed_attributes: Vec<Attribute>,
+        raw_attributes: &[Attribute],
+        parsed_names: &[&str],
+    ) -> Result<()> {
+        let mut attributes: Vec<Attribute> = Vec::new();
+        for raw_attribute in raw_attributes.iter() {
+            if !parsed_names.contains(&raw_attribute.name.as_str()) {
+                attributes.push(Attribute {
+                    name: raw_attribute.name.clone(),
+                    bytes: raw_attribute.bytes.clone(),
+                });
+            } else if let Some(position) = parsed_attributes
+                .iter()
+                .position(|attribute| attribute.name == raw_attribute.name)
+            {
+                attributes.push(parsed_attributes.remove(position));
+            }
+        }
+        attributes.append(&mut parsed_attributes);
+
+        out.write_len(attributes.len(), "attributes")?;
+        for attribute in attributes.iter() {
+            out.write_u16(self.constants.utf8(&attribute.name));
+            let len = u32::try_from(attribute.bytes.len()).map_err(|_| {
+                ClassWriterError::LimitExceeded(format!(
+                    "attribute {} too long: {} bytes",
+                    attribute.name,
+                    attribute.bytes.len()
+                ))
+            })?;
+            out.write_u32(len);
+            out.write_bytes(&attribute.bytes);
+        }
+        Ok(())
+    }
+
+    fn class_attributes(&mut self) -> Result<Vec<Attribute>> {
+        let class_file = self.class_file;
+        let mut attributes = Vec::new();
+        if let Some(source_file) = &class_file.source_file {
+            let mut buf = BufferWriter::new();
+            buf.write_u16(self.constants.utf8(source_file));
+            attributes.push(attribute("SourceFile", buf));
+        }
+        if !class_file.inner_classes.is_empty() {
+            let mut buf = BufferWriter::new();
+            buf.write_len(class_file.inner_classes.len(), "inner classes")?;
+            for inner_class in class_file.inner_classes.iter() {
+                buf.write_u16(self.constants.class(&inner_class.name));
+                match &inner_class.outer_class {
+                    Some(outer_class) => buf.write_u16(self.constants.class(outer_class)),
+                    None => buf.write_u16(0),
+                }
+                match &inner_class.simple_name {
+                    Some(simple_name) => buf.write_u16(self.constants.utf8(simple_name)),
+                    None => buf.write_u16(0),
+                }
+                buf.write_u16(inner_class.flags.bits());
+            }
+            attributes.push(attribute("InnerClasses", buf));
+        }
+        if let Some(enclosing_method) = &class_file.enclosing_method {
+            let mut buf = BufferWriter::new();
+            buf.write_u16(self.constants.class(&enclosing_method.class_name));
+            match (
+                &enclosing_method.method_name,
+                &enclosing_method.method_type_descriptor,
+            ) {
+                (Some(method_name), Some(method_type_descriptor)) => buf.write_u16(
+                    self.constants
+                        .name_and_type(method_name, method_type_descriptor),
+                ),
+                _ => buf.write_u16(0),
+            }
+            attributes.push(attribute("EnclosingMethod", buf));
+        }
+        if let Some(signature) = &class_file.signature {
+            attributes.push(self.signature_attribute(&signature.encode()));
+        }
+        if class_file.deprecated {
+            attributes.push(deprecated_attribute());
+        }
+        self.append_annotations_attributes(&mut attributes, &class_file.annotations)?;
+        self.append_type_annotations_attributes(&mut attributes, &class_file.type_annotations)?;
+        if !class_file.bootstrap_methods.is_empty() {
+            let mut buf = BufferWriter::new();
+            buf.write_len(class_file.bootstrap_methods.len(), "bootstrap methods")?;
+            for bootstrap_method in class_file.bootstrap_methods.iter() {
+                buf.write_u16(bootstrap_method.method_ref);
+                buf.write_len(
+                    bootstrap_method.arguments.len(),
+                    "bootstrap method arguments",
+                )?;
+                for argument in bootstrap_method.arguments.iter() {
+                    buf.write_u16(*argument);
+                }
+            }
+            attributes.push(attribute("BootstrapMethods", buf));
+        }
+        if let Some(module) = &class_file.module {
+            attributes.push(self.module_attribute(module)?);
+        }
+        if !class_file.module_packages.is_empty() {
+            let mut buf = BufferWriter::new();
+            buf.write_len(class_file.module_packages.len(), "module packages")?;
+            for package in class_file.module_packages.iter() {
+                buf.write_u16(self.constants.package(package));
+            }
+            attributes.push(attribute("ModulePackages", buf));
+        }
+        if let Some(module_main_class) = &class_file.module_main_class {
+            let mut buf = BufferWriter::new();
+            buf.write_u16(self.constants.class(module_main_class));
+            attributes.push(attribute("ModuleMainClass", buf));
+        }
+        if let Some(nest_host) = &class_file.nest_host {
+            let mut buf = BufferWriter::new();
+            buf.write_u16(self.constants.class(nest_host));
+            attributes.push(attribute("NestHost", buf));
+        }
+        if !class_file.nest_members.is_empty() {
+            attributes.push(self.classes_attribute("NestMembers", &class_file.nest_members)?);
+        }
+        if let Some(record_components) = &class_file.record_components {
+            attributes.push(self.record_attribute(record_components)?);
+        }
+        if !class_file.permitted_subclasses.is_empty() {
+            attributes.push(
+                self.classes_attribute("PermittedSubclasses", &class_file.permitted_subclasses)?,
+            );
+        }
+        Ok(attributes)
+    }
+
+    /// Writes an attribute made of a list of classes
+    fn classes_attribute(&mut self, name: &str, classes: &[String]) -> Result<Attribute> {
+        let mut buf = BufferWriter::new();
+        buf.write_len(classes.len(), &format!("classes in {name}"))?;
+        for class in classes.iter() {
+            buf.write_u16(self.constants.class(class));
+        }
+        Ok(attribute(name, buf))
+    }
+
+    fn module_attribute(&mut self, module: &Module) -> Result<Attribute> {
+        let mut buf = BufferWriter::new();
+        buf.write_u16(self.constants.module(&module.name));
+        buf.write_u16(module.flags.bits());
+        self.write_optional_utf8(&mut buf, &module.version);
+
+        buf.write_len(module.requires.len(), "module requires")?;
+        for requires in module.requires.iter() {
+            buf.write_u16(self.constants.module(&requires.module));
+            buf.write_u16(requires.flags.bits());
+            self.write_optional_utf8(&mut buf, &requires.version);
+        }
+        self.write_module_packages(&mut buf, &module.exports, "module exports")?;
+        self.write_module_packages(&mut buf, &module.opens, "module opens")?;
+
+        buf.write_len(module.uses.len(), "module uses")?;
+        for service in module.uses.iter() {
+            buf.write_u16(self.constants.class(service));
+        }
+
+        buf.write_len(module.provides.len(), "module provides")?;
+        for provides in module.provides.iter() {
+            buf.write_u16(self.constants.class(&provides.service));
+            buf.write_len(
+                provides.implementations.len(),
+                "module service implementations",
+            )?;
+            for implementation in provides.implementations.iter() {
+                buf.write_u16(self.constants.class(implementation));
+            }
+        }
+        Ok(attribute("Module", buf))
+    }
+
+    fn write_module_packages(
+        &mut self,
+        buf: &mut BufferWriter,
+        packages: &[ModulePackage],
+        what: &str,
+    ) -> Result<()> {
+        buf.write_len(packages.len(), what)?;
+        for package in packages.iter() {
+            buf.write_u16(self.constants.package(&package.package));
+            buf.write_u16(package.flags.bits());
+            buf.write_len(package.to.len(), "target modules")?;
+            for module in package.to.iter() {
+                buf.write_u16(self.constants.module(module));
+            }
+        }
+        Ok(())
+    }
+
+    fn write_optional_utf8(&mut self, buf: &mut BufferWriter, text: &Option<String>) {
+        match text {
+            Some(text) => buf.write_u16(self.constants.utf8(text)),
+            None => buf.write_u16(0),
+        }
+    }
+
+    fn record_attribute(&mut self, components: &[RecordComponent]) -> Result<Attribute> {
+        let mut buf = BufferWriter::new();
+        buf.write_len(components.len(), "record components")?;
+        for component in components.iter() {
+            buf.write_u16(self.constants.utf8(&component.name));
+            buf.write_u16(self.constants.utf8(&component.type_descriptor.descriptor()));
+            let mut attributes = Vec::new();
+            if let Some(signature) = &component.signature {
+                attributes.push(self.signature_attribute(&signature.encode()));
+            }
+            self.append_annotations_attributes(&mut attributes, &component.annotations)?;
+            self.append_type_annotations_attributes(&mut attributes, &component.type_annotations)?;
+            self.write_attributes(
+                &mut buf,
+                attributes,
+                &component.attributes,
+                RECORD_COMPONENT_ATTRIBUTES,
+            )?;
+        }
+        Ok(attribute("Record", buf))
+    }
+
+    fn field_attributes(&mut self, field: &ClassFileField) -> Result<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        if let Some(constant_value) = &field.constant_value {
+            let constant_index = match constant_value {
+                FieldConstantValue::Int(value) => self.constants.integer(*value),
+                FieldConstantValue::Float(value) => self.constants.float(*value),
+                FieldConstantValue::Long(value) => self.constants.long(*value),
+                FieldConstantValue::Double(value) => self.constants.double(*value),
+                FieldConstantValue::String(value) => self.constants.string(value),
+            };
+            let mut buf = BufferWriter::new();
+            buf.write_u16(constant_index);
+            attributes.push(attribute("ConstantValue", buf));
+        }
+        if let Some(signature) = &field.signature {
+            attributes.push(self.signature_attribute(&signature.encode()));
+        }
+        if field.deprecated {
+            attributes.push(deprecated_attribute());
+        }
+        self.append_annotations_attributes(&mut attributes, &field.annotations)?;
+        self.append_type_annotations_attributes(&mut attributes, &field.type_annotations)?;
+        Ok(attributes)
+    }
+
+    fn method_attributes(&mut self, method: &ClassFileMethod) -> Result<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        if let Some(code) = &method.code {
+            attributes.push(self.code_attribute(code)?);
+        }
+        if !method.thrown_exceptions.is_empty() {
+            let mut buf = BufferWriter::new();
+            buf.write_len(method.thrown_exceptions.len(), "thrown exceptions")?;
+            for exception in method.thrown_exceptions.iter() {
+                buf.write_u16(self.constants.class(exception));
+            }
+            attributes.push(attribute("Exceptions", buf));
+        }
+        if let Some(signature) = &method.signature {
+            attributes.push(self.signature_attribute(&signature.encode()));
+        }
+        if method.deprecated {
+            attributes.push(deprecated_attribute());
+        }
+        self.append_annotations_attributes(&mut attributes, &method.annotations)?;
+        self.append_parameter_annotations_attributes(
+            &mut attributes,
+            &method.parameter_annotations,
+        )?;
+        if let Some(annotation_default) = &method.annotation_default {
+            let mut buf = BufferWriter::new();
+            self.write_element_value(&mut buf, annotation_default)?;
+            attributes.push(attribute("AnnotationDefault", buf));
+        }
+        if !method.parameters.is_empty() {
+            let mut buf = BufferWriter::new();
+            let parameters_count = u8::try_from(method.parameters.len()).map_err(|_| {
+                ClassWriterError::LimitExceeded(format!(
+                    "too many method parameters: {}",
+                    method.parameters.len()
+                ))
+            })?;
+            buf.write_u8(parameters_count);
+            for parameter in method.parameters.iter() {
+                match &parameter.name {
+                    Some(name) => buf.write_u16(self.constants.utf8(name)),
+                    None => buf.write_u16(0),
+                }
+                buf.write_u16(parameter.flags.bits());
+            }
+            attributes.push(attribute("MethodParameters", buf));
+        }
+        self.append_type_annotations_attributes(&mut attributes, &method.type_annotations)?;
+        Ok(attributes)
+    }
+
+    fn code_attribute(&mut self, code: &ClassFileMethodCode) -> Result<Attribute> {
+        let mut buf = BufferWriter::new();
+        buf.write_u16(code.max_stack);
+        buf.write_u16(code.max_locals);
+        if code.code.len() > u16::MAX as usize {
+            return Err(ClassWriterError::LimitExceeded(format!(
+                "method code too long: {} bytes",
+                code.code.len()
+            )));
+        }
+        buf.write_u32(code.code.len() as u32);
+        buf.write_bytes(&code.code);
+
+        let exception_table = code.exception_table.entries();
+        buf.write_len(exception_table.len(), "exception table entries")?;
+        for entry in exception_table.iter() {
+            buf.write_u16(entry.range.start.0);
+            buf.write_u16(entry.range.end.0);
+            buf.write_u16(entry.handler_pc.0);
+            match &entry.catch_class {
+                Some(catch_class) => buf.write_u16(self.constants.class(catch_class)),
+                None => buf.write_u16(0),
+            }
+        }
+
+        let attributes = self.code_attributes(code)?;
+        self.write_attributes(&mut buf, attributes, &code.attributes, CODE_ATTRIBUTES)?;
+        Ok(attribute("Code", buf))
+    }
+
+    fn code_attributes(&mut self, code: &ClassFileMethodCode) -> Result<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        if let Some(line_number_table) = &code.line_number_table {
+            let entries = line_number_table.entries();
+            let mut buf = BufferWriter::new();
+            buf.write_len(entries.len(), "line number table entries")?;
+            for entry in entries.iter() {
+                buf.write_u16(entry.program_counter.0);
+                buf.write_u16(entry.line_number.0);
+            }
+            attributes.push(attribute("LineNumberTable", buf));
+        }
+
+        if let Some(local_variable_table) = &code.local_variable_table {
+            let entries = local_variable_table.entries();
+            let mut buf = BufferWriter::new();
+            buf.write_len(entries.len(), "local variable table entries")?;
+            for entry in entries.iter() {
+                let descriptor = entry.type_descriptor.descriptor();
+                self.write_local_variable_table_entry(&mut buf, entry, &descriptor)?;
+            }
+            attributes.push(attribute("LocalVariableTable", buf));
+
+            // Only the variables with a generic type are in the LocalVariableTypeTable
+            let generic_entries: Vec<_> = entries
+                .iter()
+                .filter_map(|entry| entry.signature.as_ref().map(|signature| (entry, signature)))
+                .collect();
+            if !generic_entries.is_empty() {
+                let mut buf = BufferWriter::new();
+                buf.write_len(generic_entries.len(), "local variable type table entries")?;
+                for (entry, signature) in generic_entries {
+                    self.write_local_variable_table_entry(&mut buf, entry, &signature.encode())?;
+                }
+                attributes.push(attribute("LocalVariableTypeTable", buf));
+            }
+        }
+
+        if !code.stack_map_table.is_empty() {
+            let mut buf = BufferWriter::new();
+            self.write_stack_map_table(&mut buf, &code.stack_map_table)?;
+            attributes.push(attribute("StackMapTable", buf));
+        }
+        self.append_type_annotations_attributes(&mut attributes, &code.type_annotations)?;
+        Ok(attributes)
+    }
+
+    fn write_local_variable_table_entry(
+        &mut self,
+        buf: &mut BufferWriter,
+        entry: &LocalVariableTableEntry,
+        descriptor: &str,
+    ) -> Result<()> {
+        let length = entry
+            .range
+            .end
+            .0
+            .checked_sub(entry.range.start.0)
+            .ok_or_else(|| {
+                ClassWriterError::InvalidClassData(format!(
+                    "invalid range of local variable {}: {}..{}",
+                    entry.name, entry.range.start, entry.range.end
+                ))
+            })?;
+        buf.write_u16(entry.range.start.0);
+        buf.write_u16(length);
+        buf.write_u16(self.constants.utf8(&entry.name));
+        buf.write_u16(self.constants.utf8(descriptor));
+        buf.write_u16(entry.index);
+        Ok(())
+    }
+
+    /// Frames store the offset from the previous one rather than the program counter.
+    /// The compact encodings are replaced by the extended ones if the offset does not fit.
+    fn write_stack_map_table(
+        &mut self,
+        buf: &mut BufferWriter,
+        frames: &[StackMapFrame],
+    ) -> Result<()> {
+        buf.write_len(frames.len(), "stack map frames")?;
+        let mut previous_pc: Option<u16> = None;
+        for frame in frames.iter() {
+            let pc = frame.program_counter.0;
+            let offset_delta = match previous_pc {
+                None => Some(pc),
+                Some(previous_pc) => pc.checked_sub(previous_pc).and_then(|d| d.checked_sub(1)),
+            }
+            .ok_or_else(|| {
+                ClassWriterError::InvalidClassData(format!(
+                    "stack map frames are not sorted by program counter: {pc}"
+                ))
+            })?;
+            previous_pc = Some(pc);
+
+            match &frame.kind {
+                StackMapFrameKind::Same if offset_delta <= 63 => buf.write_u8(offset_delta as u8),
+                StackMapFrameKind::Same | StackMapFrameKind::SameExtended => {
+                    buf.write_u8(251);
+                    buf.write_u16(offset_delta);
+                }
+                StackMapFrameKind::SameLocals1StackItem(item) if offset_delta <= 63 => {
+                    buf.write_u8(64 + offset_delta as u8);
+                    self.write_verification_type(buf, item)?;
+                }
+                StackMapFrameKind::SameLocals1StackItem(item)
+                | StackMapFrameKind::SameLocals1StackItemExtended(item) => {
+                    buf.write_u8(247);
+                    buf.write_u16(offset_delta);
+                    self.write_verification_type(buf, item)?;
+                }
+                StackMapFrameKind::Chop(count) => {
+                    if !(1..=3).contains(count) {
+                        return Err(ClassWriterError::InvalidClassData(format!(
+                            "invalid chop frame at {pc}: {count} locals"
+                        )));
+                    }
+                    buf.write_u8(251 - count);
+                    buf.write_u16(offset_delta);
+                }
+                StackMapFrameKind::Append(locals) => {
+                    if !(1..=3).contains(&locals.len()) {
+                        return Err(ClassWriterError::InvalidClassData(format!(
+                            "invalid append frame at {pc}: {} locals",
+                            locals.len()
+                        )));
+                    }
+                    buf.write_u8(251 + locals.len() as u8);
+                    buf.write_u16(offset_delta);
+                    for local in locals.iter() {
+                        self.write_verification_type(buf, local)?;
+                    }
+                }
+                StackMapFrameKind::Full { locals, stack } => {
+                    buf.write_u8(255);
+                    buf.write_u16(offset_delta);
+                    buf.write_len(locals.len(), "locals in stack map frame")?;
+                    for local in locals.iter() {
+                        self.write_verification_type(buf, local)?;
+                    }
+                    buf.write_len(stack.len(), "stack items in stack map frame")?;
+                    for item in stack.iter() {
+                        self.write_verification_type(buf, item)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_verification_type(
+        &mut self,
+        buf: &mut BufferWriter,
+        item: &VerificationType,
+    ) -> Result<()> {
+        match item {
+            VerificationType::Top => buf.write_u8(0),
+            VerificationType::Integer => buf.write_u8(1),
+            VerificationType::Float => buf.write_u8(2),
+            VerificationType::Double => buf.write_u8(3),
+            VerificationType::Long => buf.write_u8(4),
+            VerificationType::Null => buf.write_u8(5),
+            VerificationType::UninitializedThis => buf.write_u8(6),
+            VerificationType::Object(class_name) => {
+                buf.write_u8(7);
+                buf.write_u16(self.constants.class(class_name));
+            }
+            VerificationType::Uninitialized(pc) => {
+                buf.write_u8(8);
+                buf.write_u16(pc.0);
+            }
+            VerificationType::ReturnAddress(_) => {
+                return Err(ClassWriterError::InvalidClassData(
+                    "return addresses cannot appear in a stack map frame".to_string(),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn signature_attribute(&mut self, signature: &str) -> Attribute {
+        let mut buf = BufferWriter::new();
+        buf.write_u16(self.constants.utf8(signature));
+        attribute("Signature", buf)
+    }
+
+    /// Splits the annotations in the runtime visible and invisible attributes
+    fn append_annotations_attributes(
+        &mut self,
+        attributes: &mut Vec<Attribute>,
+        annotations: &[Annotation],
+    ) -> Result<()> {
+        for (name, runtime_visible) in [
+            ("RuntimeVisibleAnnotations", true),
+            ("RuntimeInvisibleAnnotations", false),
+        ] {
+            let annotations: Vec<&Annotation> = annotations
+                .iter()
+                .filter(|annotation| annotation.runtime_visible == runtime_visible)
+                .collect();
+            if !annotations.is_empty() {
+                let mut buf = BufferWriter::new();
+                self.write_annotations(&mut buf, &annotations)?;
+                attributes.push(attribute(name, buf));
+            }
+        }
+        Ok(())
+    }
+
+    /// Splits the annotations of the parameters in the runtime visible and invisible
+    /// attributes. At least one is written if the list is not empty, to retain
+    /// the number of parameters.
+    fn append_parameter_annotations_attributes(
+        &mut self,
+        attributes: &mut Vec<Attribute>,
+        parameter_annotations: &[Vec<Annotation>],
+    ) -> Result<()> {
+        if parameter_annotations.is_empty() {
+            return Ok(());
+        }
+        let num_parameters = u8::try_from(parameter_annotations.len()).map_err(|_| {
+            ClassWriterError::LimitExceeded(format!(
+                "too many annotated parameters: {}",
+                parameter_annotations.len()
+            ))
+        })?;
+        let has_annotations = |runtime_visible: bool| {
+            parameter_annotations
+                .iter()
+                .flatten()
+                .any(|annotation| annotation.runtime_visible == runtime_visible)
+        };
+        let has_visible_annotations = has_annotations(true);
+        for (name, runtime_visible, required) in [
+            ("RuntimeVisibleParameterAnnotations", true, false),
+            (
+                "RuntimeInvisibleParameterAnnotations",
+                false,
+                !has_visible_annotations,
+            ),
+        ] {
+            if !required && !has_annotations(runtime_visible) {
+                continue;
+            }
+            let mut buf = BufferWriter::new();
+            buf.write_u8(num_parameters);
+            for annotations in parameter_annotations.iter() {
+                let annotations: Vec<&Annotation> = annotations
+                    .iter()
+                    .filter(|annotation| annotation.runtime_visible == runtime_visible)
+                    .collect();
+                self.write_annotations(&mut buf, &annotations)?;
+            }
+            attributes.push(attribute(name, buf));
+        }
+        Ok(())
+    }
+
+    /// Splits the type annotations in the runtime visible and invisible attributes
+    fn append_type_annotations_attributes(
+        &mut self,
+        attributes: &mut Vec<Attribute>,
+        type_annotations: &[TypeAnnotation],
+    ) -> Result<()> {
+        for (name, runtime_visible) in [
+            ("RuntimeVisibleTypeAnnotations", true),
+            ("RuntimeInvisibleTypeAnnotations", false),
+        ] {
+            let type_annotations: Vec<&TypeAnnotation> = type_annotations
+                .iter()
+                .filter(|type_annotation| {
+                    type_annotation.annotation.runtime_visible == runtime_visible
+                })
+                .collect();
+            if !type_annotations.is_empty() {
+                let mut buf = BufferWriter::new();
+                buf.write_len(type_annotations.len(), "type annotations")?;
+                for type_annotation in type_annotations {
+                    self.write_type_annotation(&mut buf, type_annotation)?;
+                }
+                attributes.push(attribute(name, buf));
+            }
+        }
+        Ok(())
+    }
+
+    fn write_type_annotation(
+        &mut self,
+        buf: &mut BufferWriter,
+        type_annotation: &TypeAnnotation,
+    ) -> Result<()> {
+        let target = &type_annotation.target;
+        buf.write_u8(target.target_type());
+        match target {
+            TypeAnnotationTarget::ClassTypeParameter {
+                type_parameter_index,
+            }
+            | TypeAnnotationTarget::MethodTypeParameter {
+                type_parameter_index,
+            } => buf.write_u8(*type_parameter_index),
+            TypeAnnotationTarget::ClassExtends { interface_index } => {
+                buf.write_u16(interface_index.unwrap_or(u16::MAX))
+            }
+            TypeAnnotationTarget::ClassTypeParameterBound {
+                type_parameter_index,
+                bound_index,
+            }
+            | TypeAnnotationTarget::MethodTypeParameterBound {
+                type_parameter_index,
+                bound_index,
+            } => {
+                buf.write_u8(*type_parameter_index);
+                buf.write_u8(*bound_index);
+            }
+            TypeAnnotationTarget::Field
+            | TypeAnnotationTarget::MethodReturn
+            | TypeAnnotationTarget::MethodReceiver => {}
+            TypeAnnotationTarget::MethodFormalParameter {
+                formal_parameter_index,
+            } => buf.write_u8(*formal_parameter_index),
+            TypeAnnotationTarget::Throws { throws_type_index } => buf.write_u16(*throws_type_index),
+            TypeAnnotationTarget::LocalVariable(variables)
+            | TypeAnnotationTarget::ResourceVariable(variables) => {
+                buf.write_len(variables.len(), "local variable targets")?;
+                for variable in variables.iter() {
+                    let length = variable
+                        .range
+                        .end
+                        .0
+                        .checked_sub(variable.range.start.0)
+                        .ok_or_else(|| {
+                            ClassWriterError::InvalidClassData(format!(
+                                "invalid range of local variable target: {}..{}",
+                                variable.range.start, variable.range.end
+                            ))
+                        })?;
+                    buf.write_u16(variable.range.start.0);
+                    buf.write_u16(length);
+                    buf.write_u16(variable.index);
+                }
+            }
+            TypeAnnotationTarget::ExceptionParameter {
+                exception_table_index,
+            } => buf.write_u16(*exception_table_index),
+            TypeAnnotationTarget::InstanceOf { offset }
+            | TypeAnnotationTarget::New { offset }
+            | TypeAnnotationTarget::ConstructorReference { offset }
+            | TypeAnnotationTarget::MethodReference { offset } => buf.write_u16(offset.0),
+            TypeAnnotationTarget::Cast {
+                offset,
+                type_argument_index,
+            }
+            | TypeAnnotationTarget::ConstructorInvocationTypeArgument {
+                offset,
+                type_argument_index,
+            }
+            | TypeAnnotationTarget::MethodInvocationTypeArgument {
+                offset,
+                type_argument_index,
+            }
+            | TypeAnnotationTarget::ConstructorReferenceTypeArgument {
+                offset,
+                type_argument_index,
+            }
+            | TypeAnnotationTarget::MethodReferenceTypeArgument {
+                offset,
+                type_argument_index,
+            } => {
+                buf.write_u16(offset.0);
+                buf.write_u8(*type_argument_index);
+            }
+        }
+
+        let type_path = &type_annotation.type_path;
+        let path_length = u8::try_from(type_path.len()).map_err(|_| {
+            ClassWriterError::LimitExceeded(format!("type path too long: {}", type_path.len()))
+        })?;
+        buf.write_u8(path_length);
+        for element in type_path.iter() {
+            buf.write_u8(element.kind());
+            match element {
+                TypePathElement::TypeArgument(index) => buf.write_u8(*index),
+                _ => buf.write_u8(0),
+            }
+        }
+        self.write_annotation(buf, &type_annotation.annotation)
+    }
+
+    fn write_annotations(
+        &mut self,
+        buf: &mut BufferWriter,
+        annotations: &[&Annotation],
+    ) -> Result<()> {
+        buf.write_len(annotations.len(), "annotations")?;
+        for annotation in annotations.iter() {
+            self.write_annotation(buf, annotation)?;
+        }
+        Ok(())
+    }
+
+    fn write_annotation(&mut self, buf: &mut BufferWriter, annotation: &Annotation) -> Result<()> {
+        buf.write_u16(self.constants.utf8(&annotation.type_descriptor));
+        buf.write_len(annotation.elements.len(), "annotation elements")?;
+        for element in annotation.elements.iter() {
+            buf.write_u16(self.constants.utf8(&element.name));
+            self.write_element_value(buf, &element.value)?;
+        }
+        Ok(())
+    }
+
+    fn write_element_value(&mut self, buf: &mut BufferWriter, value: &ElementValue) -> Result<()> {
+        match value {
+            ElementValue::Byte(value) => {
+                buf.write_u8(b'B');
+                buf.write_u16(self.constants.integer(*value as i32));
+            }
+            ElementValue::Char(value) => {
+                buf.write_u8(b'C');
+                buf.write_u16(self.constants.integer(*value as i32));
+            }
+            ElementValue::Double(value) => {
+                buf.write_u8(b'D');
+                buf.write_u16(self.constants.double(*value));
+            }
+            ElementValue::Float(value) => {
+                buf.write_u8(b'F');
+                buf.write_u16(self.constants.float(*value));
+            }
+            ElementValue::Int(value) => {
+                buf.write_u8(b'I');
+                buf.write_u16(self.constants.integer(*value));
+            }
+            ElementValue::Long(value) => {
+                buf.write_u8(b'J');
+                buf.write_u16(self.constants.long(*value));
+            }
+            ElementValue::Short(value) => {
+                buf.write_u8(b'S');
+                buf.write_u16(self.constants.integer(*value as i32));
+            }
+            ElementValue::Boolean(value) => {
+                buf.write_u8(b'Z');
+                buf.write_u16(self.constants.integer(*value as i32));
+            }
+            ElementValue::String(value) => {
+                buf.write_u8(b's');
+                buf.write_u16(self.constants.utf8(value));
+            }
+            ElementValue::Enum {
+                type_descriptor,
+                const_name,
+            } => {
+                buf.write_u8(b'e');
+                buf.write_u16(self.constants.utf8(type_descriptor));
+                buf.write_u16(self.constants.utf8(const_name));
+            }
+            ElementValue::Class(descriptor) => {
+                buf.write_u8(b'c');
+                buf.write_u16(self.constants.utf8(descriptor));
+            }
+            ElementValue::Annotation(annotation) => {
+                buf.write_u8(b'@');
+                self.write_annotation(buf, annotation)?;
+            }
+            ElementValue::Array(values) => {
+                buf.write_u8(b'[');
+                buf.write_len(values.len(), "annotation element values")?;
+                for value in values.iter() {
+                    self.write_element_value(buf, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn attribute(name: &str, buf: BufferWriter) -> Attribute {
+    Attribute {
+        name: name.to_string(),
+        bytes: buf.into_bytes(),
+    }
+}
+
+fn deprecated_attribute() -> Attribute {
+    Attribute {
+        name: "Deprecated".to_string(),
+        bytes: Vec::new(),
+    }
+}
+
+/// Writes a class to a byte array, in the .class file format. Reading the result with
+/// [crate::class_reader::read_buffer] gives back an equivalent class.
+pub fn write(class_file: &ClassFile) -> Result<Vec<u8>> {
+    ClassFileWriter::new(class_file).write()
+}
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+/// Models the possible errors returned when writing a .class file
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ClassWriterError {
+    /// The class does not fit in the limits of the class file format, for example because
+    /// it has too many constants or a method is too long
+    #[error("class file limit exceeded: {0}")]
+    LimitExceeded(String),
+
+    /// Generic error meaning that the model of the class is not consistent
+    #[error("invalid class: {0}")]
+    InvalidClassData(String),
+}
+
+pub type Result<T> = std::result::Result<T, ClassWriterError>;
@@ -0,0 +1,621 @@
+use crate::{
+    class_writer_error::{ClassWriterError, Result},
+    exception_table::{ExceptionTable, ExceptionTableEntry},
+    instruction::{Instruction, LookupSwitch, TableSwitch},
+    line_number::LineNumber,
+    line_number_table::{LineNumberTable, LineNumberTableEntry},
+    program_counter::ProgramCounter,
+};
+
+/// A symbolic position in the code, whose address is known only when the code is assembled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Label(usize);
+
+/// Jump instructions that can target a [Label]. The assembler picks the encoding:
+/// `goto` and `jsr` become `goto_w` and `jsr_w` if the target is too far, and the
+/// conditional jumps are replaced by the opposite condition skipping over a `goto_w`.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jump {
+    Goto,
+    Jsr,
+    If_acmpeq,
+    If_acmpne,
+    If_icmpeq,
+    If_icmpne,
+    If_icmplt,
+    If_icmpge,
+    If_icmpgt,
+    If_icmple,
+    Ifeq,
+    Ifne,
+    Iflt,
+    Ifge,
+    Ifgt,
+    Ifle,
+    Ifnonnull,
+    Ifnull,
+}
+
+impl Jump {
+    /// The short form of the jump, with a 16 bits offset
+    fn instruction(self, jump_address: u16) -> Instruction {
+        match self {
+            Jump::Goto => Instruction::Goto(jump_address),
+            Jump::Jsr => Instruction::Jsr(jump_address),
+            Jump::If_acmpeq => Instruction::If_acmpeq(jump_address),
+            Jump::If_acmpne => Instruction::If_acmpne(jump_address),
+            Jump::If_icmpeq => Instruction::If_icmpeq(jump_address),
+            Jump::If_icmpne => Instruction::If_icmpne(jump_address),
+            Jump::If_icmplt => Instruction::If_icmplt(jump_address),
+            Jump::If_icmpge => Instruction::If_icmpge(jump_address),
+            Jump::If_icmpgt => Instruction::If_icmpgt(jump_address),
+            Jump::If_icmple => Instruction::If_icmple(jump_address),
+            Jump::Ifeq => Instruction::Ifeq(jump_address),
+            Jump::Ifne => Instruction::Ifne(jump_address),
+            Jump::Iflt => Instruction::Iflt(jump_address),
+            Jump::Ifge => Instruction::Ifge(jump_address),
+            Jump::Ifgt => Instruction::Ifgt(jump_address),
+            Jump::Ifle => Instruction::Ifle(jump_address),
+            Jump::Ifnonnull => Instruction::Ifnonnull(jump_address),
+            Jump::Ifnull => Instruction::Ifnull(jump_address),
+        }
+    }
+
+    /// The conditional jump taken exactly when this one is not. None for unconditional jumps.
+    fn negated(self) -> Option<Jump> {
+        match self {
+            Jump::Goto | Jump::Jsr => None,
+            Jump::If_acmpeq => Some(Jump::If_acmpne),
+            Jump::If_acmpne => Some(Jump::If_acmpeq),
+            Jump::If_icmpeq => Some(Jump::If_icmpne),
+            Jump::If_icmpne => Some(Jump::If_icmpeq),
+            Jump::If_icmplt => Some(Jump::If_icmpge),
+            Jump::If_icmpge => Some(Jump::If_icmplt),
+            Jump::If_icmpgt => Some(Jump::If_icmple),
+            Jump::If_icmple => Some(Jump::If_icmpgt),
+            Jump::Ifeq => Some(Jump::Ifne),
+            Jump::Ifne => Some(Jump::Ifeq),
+            Jump::Iflt => Some(Jump::Ifge),
+            Jump::Ifge => Some(Jump::Iflt),
+            Jump::Ifgt => Some(Jump::Ifle),
+            Jump::Ifle => Some(Jump::Ifgt),
+            Jump::Ifnonnull => Some(Jump::Ifnull),
+            Jump::Ifnull => Some(Jump::Ifnonnull),
+        }
+    }
+
+    /// Size in bytes of the encoding of the jump
+    fn size(self, wide: bool) -> usize {
+        match (wide, self.negated()) {
+            (false, _) => 3,
+            (true, None) => 5,
+            // The negated condition, followed by a goto_w
+            (true, Some(_)) => 8,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum CodeItem {
+    Instruction(Instruction),
+    Jump(Jump, Label),
+    TableSwitch {
+        low: i32,
+        default: Label,
+        jumps: Vec<Label>,
+    },
+    LookupSwitch {
+        default: Label,
+        pairs: Vec<(i32, Label)>,
+    },
+    Label(Label),
+}
+
+#[derive(Debug)]
+struct ExceptionHandler {
+    start: Label,
+    end: Label,
+    handler: Label,
+    catch_class: Option<String>,
+}
+
+/// Builds the bytecode of a method from a sequence of instructions, where jumps refer
+/// to [Label]s rather than to addresses. The exception table and the line numbers are
+/// expressed with labels as well, so they follow the instructions when the encoding of
+/// a jump grows.
+#[derive(Debug, Default)]
+pub struct CodeAssembler {
+    items: Vec<CodeItem>,
+    num_labels: usize,
+    exception_handlers: Vec<ExceptionHandler>,
+    line_numbers: Vec<(Label, LineNumber)>,
+}
+
+/// The output of [CodeAssembler::assemble]
+#[derive(Debug)]
+pub struct AssembledCode {
+    pub code: Vec<u8>,
+    pub exception_table: ExceptionTable,
+    /// Missing if no line number was added to the assembler
+    pub line_number_table: Option<LineNumberTable>,
+    label_addresses: Vec<Option<ProgramCounter>>,
+}
+
+impl AssembledCode {
+    /// Returns the address of the given label, or None if it was never placed
+    pub fn address_of(&self, label: Label) -> Option<ProgramCounter> {
+        self.label_addresses.get(label.0).copied().flatten()
+    }
+}
+
+/// The addresses of the items and of the labels, given the encoding chosen for the jumps
+struct Layout {
+    item_addresses: Vec<usize>,
+    label_addresses: Vec<Option<usize>>,
+    code_length: usize,
+}
+
+impl CodeAssembler {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a new label, that must then be placed with [CodeAssembler::place_label]
+    pub fn new_label(&mut self) -> Label {
+        self.num_labels += 1;
+        Label(self.num_labels - 1)
+    }
+
+    /// Places the label at the current position, i.e. at the next instruction
+    pub fn place_label(&mut self, label: Label) {
+        self.items.push(CodeItem::Label(label));
+    }
+
+    /// Creates a new label, placed at the current position
+    pub fn mark(&mut self) -> Label {
+        let label = self.new_label();
+        self.place_label(label);
+        label
+    }
+
+    /// Appends an instruction. Jumps and switches should use [CodeAssembler::jump],
+    /// [CodeAssembler::tableswitch] and [CodeAssembler::lookupswitch] instead, since
+    /// the addresses in the instruction are used as they are.
+    pub fn emit(&mut self, instruction: Instruction) {
+        self.items.push(CodeItem::Instruction(instruction));
+    }
+
+    pub fn jump(&mut self, jump: Jump, target: Label) {
+        self.items.push(CodeItem::Jump(jump, target));
+    }
+
+    /// Appends a `tableswitch` that jumps to `jumps[i]` for the value `low + i`
+    pub fn tableswitch(&mut self, low: i32, default: Label, jumps: Vec<Label>) {
+        self.items.push(CodeItem::TableSwitch {
+            low,
+            default,
+            jumps,
+        });
+    }
+
+    /// Appends a `lookupswitch`. The pairs do not need to be sorted.
+    pub fn lookupswitch(&mut self, default: Label, mut pairs: Vec<(i32, Label)>) {
+        pairs.sort_by_key(|(key, _)| *key);
+        self.items.push(CodeItem::LookupSwitch { default, pairs });
+    }
+
+    /// Adds an entry to the exception table. Handlers are matched in the order they are added.
+    pub fn exception_handler(
+        &mut self,
+        start: Label,
+        end: Label,
+        handler: Label,
+        catch_class: Option<String>,
+    ) {
+        self.exception_handlers.push(ExceptionHandler {
+            start,
+            end,
+            handler,
+            catch_class,
+        });
+    }
+
+    /// Declares that the code starting at the current position comes from the given line
+    pub fn line_number(&mut self, line_number: LineNumber) {
+        let label = self.mark();
+        self.line_numbers.push((label, line_number));
+    }
+
+    /// Resolves the labels and encodes the instructions
+    pub fn assemble(self) -> Result<AssembledCode> {
+        self.check_labels()?;
+
+        // Jumps start in the short form, and are widened until all the offsets fit.
+        // Since jumps never get shorter, this terminates.
+        let mut wide_jumps = vec![false; self.items.len()];
+        let layout = loop {
+            let layout = self.layout(&wide_jumps)?;
+            let mut changed = false;
+            for (index, item) in self.items.iter().enumerate() {
+                if let CodeItem::Jump(_, target) = item {
+                    let offset =
+                        layout.label_address(*target) as i64 - layout.item_addresses[index] as i64;
+                    if !wide_jumps[index] && i16::try_from(offset).is_err() {
+                        wide_jumps[index] = true;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break layout;
+            }
+        };
+
+        if layout.code_length > u16::MAX as usize {
+            return Err(ClassWriterError::LimitExceeded(format!(
+                "method code too long: {} bytes",
+                layout.code_length
+            )));
+        }
+        let pc = |label: Label| ProgramCounter(layout.label_address(label) as u16);
+
+        let mut code = Vec::with_capacity(layout.code_length);
+        for (index, item) in self.items.iter().enumerate() {
+            let address = layout.item_addresses[index];
+            match item {
+                CodeItem::Instruction(instruction) => instruction.encode(address, &mut code)?,
+                CodeItem::Jump(jump, target) => {
+                    let target = pc(*target).0;
+                    match (wide_jumps[index], jump.negated()) {
+                        (false, _) => jump.instruction(target).encode(address, &mut code)?,
+                        (true, None) => {
+                            let instruction = if *jump == Jump::Jsr {
+                                Instruction::Jsr_w(target)
+                            } else {
+                                Instruction::Goto_w(target)
+                            };
+                            instruction.encode(address, &mut code)?
+                        }
+                        (true, Some(negated)) => {
+                            let next_address = (address + jump.size(true)) as u16;
+                            negated
+                                .instruction(next_address)
+                                .encode(address, &mut code)?;
+                            Instruction::Goto_w(target).encode(address + 3, &mut code)?;
+                        }
+                    }
+                }
+                CodeItem::TableSwitch { .. } | CodeItem::LookupSwitch { .. } => {
+                    self.switch_instruction(item, |label| pc(label).0)
+                        .encode(address, &mut code)?;
+                }
+                CodeItem::Label(_) => {}
+            }
+        }
+
+        let exception_table = ExceptionTable::new(
+            self.exception_handlers
+                .iter()
+                .map(|handler| ExceptionTableEntry {
+                    range: pc(handler.start)..pc(handler.end),
+                    handler_pc: pc(handler.handler),
+                    catch_class: handler.catch_class.clone(),
+                })
+                .collect(),
+        );
+        let line_number_table = if self.line_numbers.is_empty() {
+            None
+        } else {
+            Some(LineNumberTable::new(
+                self.line_numbers
+                    .iter()
+                    .map(|(label, line_number)| LineNumberTableEntry::new(pc(*label), *line_number))
+                    .collect(),
+            ))
+        };
+        let label_addresses = layout
+            .label_addresses
+            .iter()
+            .map(|address| address.map(|address| ProgramCounter(address as u16)))
+            .collect();
+
+        Ok(AssembledCode {
+            code,
+            exception_table,
+            line_number_table,
+            label_addresses,
+        })
+    }
+
+    /// Checks that every label is placed exactly once, and that every used label is placed
+    fn check_labels(&self) -> Result<()> {
+        let mut placed = vec![false; self.num_labels];
+        for item in self.items.iter() {
+            if let CodeItem::Label(label) = item {
+                if placed[label.0] {
+                    return Err(ClassWriterError::InvalidClassData(format!(
+                        "label {} placed more than once",
+                        label.0
+                    )));
+                }
+                placed[label.0] = true;
+            }
+        }
+
+        let mut used_labels: Vec<Label> = Vec::new();
+        for item in self.items.iter() {
+            match item {
+                CodeItem::Jump(_, target) => used_labels.push(*target),
+                CodeItem::TableSwitch { default, jumps, .. } => {
+                    used_labels.push(*default);
+                    used_labels.extend(jumps.iter());
+                }
+                CodeItem::LookupSwitch { default, pairs } => {
+                    used_labels.push(*default);
+                    used_labels.extend(pairs.iter().map(|(_, label)| *label));
+                }
+                CodeItem::Instruction(_) | CodeItem::Label(_) => {}
+            }
+        }
+        for handler in self.exception_handlers.iter() {
+            used_labels.extend([handler.start, handler.end, handler.handler]);
+        }
+        match used_labels.iter().find(|label| !placed[label.0]) {
+            Some(label) => Err(ClassWriterError::InvalidClassData(format!(
+                "label {} is used but never placed",
+                label.0
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn layout(&self, wide_jumps: &[bool]) -> Result<Layout> {
+        let mut item_addresses = Vec::with_capacity(self.items.len());
+        let mut label_addresses = vec![None; self.num_labels];
+        let mut address = 0;
+        for (index, item) in self.items.iter().enumerate() {
+            item_addresses.push(address);
+            address += match item {
+                CodeItem::Instruction(instruction) => instruction.size(address)?,
+                CodeItem::Jump(jump, _) => jump.size(wide_jumps[index]),
+                // The size of a switch depends only on its address and number of jumps
+                CodeItem::TableSwitch { .. } | CodeItem::LookupSwitch { .. } => {
+                    self.switch_instruction(item, |_| 0).size(address)?
+                }
+                CodeItem::Label(label) => {
+                    label_addresses[label.0] = Some(address);
+                    0
+                }
+            };
+        }
+        Ok(Layout {
+            item_addresses,
+            label_addresses,
+            code_length: address,
+        })
+    }
+
+    fn switch_instruction(
+        &self,
+        item: &CodeItem,
+        address_of: impl Fn(Label) -> u16,
+    ) -> Instruction {
+        match item {
+            CodeItem::TableSwitch {
+                low,
+                default,
+                jumps,
+            } => Instruction::Tableswitch(TableSwitch {
+                default: address_of(*default),
+                low: *low,
+                high: low.wrapping_add(jumps.len() as i32).wrapping_sub(1),
+                jumps: jumps.iter().map(|label| address_of(*label)).collect(),
+            }),
+            CodeItem::LookupSwitch { default, pairs } => Instruction::Lookupswitch(LookupSwitch {
+                default: address_of(*default),
+                pairs: pairs
+                    .iter()
+                    .map(|(key, label)| (*key, address_of(*label)))
+                    .collect(),
+            }),
+            _ => unreachable!("not a switch"),
+        }
+    }
+}
+
+impl Layout {
+    /// Labels have been checked before computing the layout, so they are all placed
+    fn label_address(&self, label: Label) -> usize {
+        self.label_addresses[label.0].unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        class_writer_error::ClassWriterError,
+        code_assembler::{CodeAssembler, Jump},
+        instruction::{Instruction, LookupSwitch, TableSwitch},
+        line_number::LineNumber,
+        program_counter::ProgramCounter,
+    };
+
+    #[test]
+    fn can_assemble_forward_and_backward_jumps() {
+        let mut assembler = CodeAssembler::new();
+        let end = assembler.new_label();
+        let start = assembler.mark();
+        assembler.emit(Instruction::Iload_0);
+        assembler.jump(Jump::Ifeq, end);
+        assembler.emit(Instruction::Iinc(0, -1));
+        assembler.jump(Jump::Goto, start);
+        assembler.place_label(end);
+        assembler.emit(Instruction::Return);
+
+        let assembled = assembler.assemble().unwrap();
+        assert_eq!(
+            vec![
+                (0, Instruction::Iload_0),
+                (1, Instruction::Ifeq(10)),
+                (4, Instruction::Iinc(0, -1)),
+                (7, Instruction::Goto(0)),
+                (10, Instruction::Return),
+            ],
+            Instruction::parse_instructions(&assembled.code).unwrap()
+        );
+        assert_eq!(Some(ProgramCounter(10)), assembled.address_of(end));
+    }
+
+    #[test]
+    fn far_jumps_are_widened() {
+        let mut assembler = CodeAssembler::new();
+        let far = assembler.new_label();
+        let near = assembler.new_label();
+        assembler.emit(Instruction::Iconst_0);
+        assembler.jump(Jump::Ifne, far);
+        assembler.jump(Jump::Goto, far);
+        assembler.place_label(near);
+        for _ in 0..40000 {
+            assembler.emit(Instruction::Nop);
+        }
+        assembler.place_label(far);
+        assembler.jump(Jump::Goto, near);
+        assembler.emit(Instruction::Return);
+
+        let assembled = assembler.assemble().unwrap();
+        let instructions = Instruction::parse_instructions(&assembled.code).unwrap();
+        let far_address = 1 + 8 + 5 + 40000;
+        assert_eq!(
+            vec![
+                (0, Instruction::Iconst_0),
+                // The condition is negated, and jumps over the goto_w
+                (1, Instruction::Ifeq(9)),
+                (4, Instruction::Goto_w(far_address as u16)),
+                (9, Instruction::Goto_w(far_address as u16)),
+                (14, Instruction::Nop),
+            ],
+            instructions[0..5]
+        );
+        assert_eq!(
+            vec![
+                (far_address, Instruction::Goto_w(14)),
+                (far_address + 5, Instruction::Return),
+            ],
+            instructions[instructions.len() - 2..]
+        );
+    }
+
+    #[test]
+    fn switches_are_padded() {
+        for num_nops in 0..4 {
+            let mut assembler = CodeAssembler::new();
+            let first = assembler.new_label();
+            let second = assembler.new_label();
+            let default = assembler.new_label();
+            for _ in 0..num_nops {
+                assembler.emit(Instruction::Nop);
+            }
+            assembler.emit(Instruction::Iload_0);
+            assembler.tableswitch(1, default, vec![first, second]);
+            assembler.emit(Instruction::Iload_0);
+            assembler.lookupswitch(default, vec![(7, second), (-3, first)]);
+            assembler.place_label(first);
+            assembler.place_label(second);
+            assembler.emit(Instruction::Nop);
+            assembler.place_label(default);
+            assembler.emit(Instruction::Return);
+
+            let assembled = assembler.assemble().unwrap();
+            let instructions = Instruction::parse_instructions(&assembled.code).unwrap();
+            let first_address = assembled.address_of(first).unwrap().0;
+            let default_address = assembled.address_of(default).unwrap().0;
+            assert_eq!(
+                Instruction::Tableswitch(TableSwitch {
+                    default: default_address,
+                    low: 1,
+                    high: 2,
+                    jumps: vec![first_address, first_address],
+                }),
+                instructions[num_nops + 1].1
+            );
+            assert_eq!(
+                Instruction::Lookupswitch(LookupSwitch {
+                    default: default_address,
+                    pairs: vec![(-3, first_address), (7, first_address)],
+                }),
+                instructions[num_nops + 3].1
+            );
+        }
+    }
+
+    #[test]
+    fn exception_table_and_line_numbers_follow_the_instructions() {
+        let mut assembler = CodeAssembler::new();
+        let far = assembler.new_label();
+        assembler.line_number(LineNumber(3));
+        assembler.jump(Jump::Goto, far);
+        let try_start = assembler.mark();
+        assembler.line_number(LineNumber(4));
+        for _ in 0..40000 {
+            assembler.emit(Instruction::Nop);
+        }
+        let try_end = assembler.mark();
+        assembler.place_label(far);
+        assembler.line_number(LineNumber(5));
+        assembler.emit(Instruction::Return);
+        let handler = assembler.mark();
+        assembler.emit(Instruction::Athrow);
+        assembler.exception_handler(
+            try_start,
+            try_end,
+            handler,
+            Some("java/lang/Exception".to_string()),
+        );
+
+        let assembled = assembler.assemble().unwrap();
+        let entries = assembled.exception_table.entries();
+        assert_eq!(1, entries.len());
+        assert_eq!(ProgramCounter(5)..ProgramCounter(40005), entries[0].range);
+        assert_eq!(ProgramCounter(40006), entries[0].handler_pc);
+
+        let line_number_table = assembled.line_number_table.unwrap();
+        assert_eq!(
+            LineNumber(3),
+            line_number_table.lookup_pc(ProgramCounter(0))
+        );
+        assert_eq!(
+            LineNumber(4),
+            line_number_table.lookup_pc(ProgramCounter(5))
+        );
+        assert_eq!(
+            LineNumber(5),
+            line_number_table.lookup_pc(ProgramCounter(40005))
+        );
+    }
+
+    #[test]
+    fn labels_must_be_placed() {
+        let mut assembler = CodeAssembler::new();
+        let label = assembler.new_label();
+        assembler.jump(Jump::Goto, label);
+        assert_eq!(
+            Err(ClassWriterError::InvalidClassData(
+                "label 0 is used but never placed".to_string()
+            )),
+            assembler.assemble().map(|assembled| assembled.code)
+        );
+    }
+
+    #[test]
+    fn code_cannot_be_too_long() {
+        let mut assembler = CodeAssembler::new();
+        for _ in 0..70000 {
+            assembler.emit(Instruction::Nop);
+        }
+        assert!(matches!(
+            assembler.assemble(),
+            Err(ClassWriterError::LimitExceeded(_))
+        ));
+    }
+}
@@ -1,9 +1,12 @@
 use std::{fmt, vec::Vec};
 use thiserror::Error;
 
+use crate::class_reader_error::ClassReaderError;
+
 /// Types of a constant in the constant pool of a class, following the JVM spec:
-/// https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-4.html#jvms-4.4
-#[derive(Debug, PartialEq)]
+/// https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html#jvms-4.4
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConstantPoolEntry {
     Utf8(String),
     Integer(i32),
@@ -16,11 +19,78 @@ pub enum ConstantPoolEntry {
     MethodReference(u16, u16),
     InterfaceMethodReference(u16, u16),
     NameAndTypeDescriptor(u16, u16),
+    MethodHandle(MethodHandleKind, u16),
+    MethodType(u16),
+    Dynamic(u16, u16),
+    InvokeDynamic(u16, u16),
+    Module(u16),
+    Package(u16),
+}
+
+/// Kinds of method handle, i.e. the bytecode behavior of a [ConstantPoolEntry::MethodHandle]:
+/// https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-5.html#jvms-5.4.3.5
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MethodHandleKind {
+    #[strum(serialize = "REF_getField")]
+    GetField,
+    #[strum(serialize = "REF_getStatic")]
+    GetStatic,
+    #[strum(serialize = "REF_putField")]
+    PutField,
+    #[strum(serialize = "REF_putStatic")]
+    PutStatic,
+    #[strum(serialize = "REF_invokeVirtual")]
+    InvokeVirtual,
+    #[strum(serialize = "REF_invokeStatic")]
+    InvokeStatic,
+    #[strum(serialize = "REF_invokeSpecial")]
+    InvokeSpecial,
+    #[strum(serialize = "REF_newInvokeSpecial")]
+    NewInvokeSpecial,
+    #[strum(serialize = "REF_invokeInterface")]
+    InvokeInterface,
+}
+
+impl MethodHandleKind {
+    /// Creates a kind from the `reference_kind` value specified in the class file
+    pub fn from(reference_kind: u8) -> Result<MethodHandleKind, ClassReaderError> {
+        match reference_kind {
+            1 => Ok(MethodHandleKind::GetField),
+            2 => Ok(MethodHandleKind::GetStatic),
+            3 => Ok(MethodHandleKind::PutField),
+            4 => Ok(MethodHandleKind::PutStatic),
+            5 => Ok(MethodHandleKind::InvokeVirtual),
+            6 => Ok(MethodHandleKind::InvokeStatic),
+            7 => Ok(MethodHandleKind::InvokeSpecial),
+            8 => Ok(MethodHandleKind::NewInvokeSpecial),
+            9 => Ok(MethodHandleKind::InvokeInterface),
+            _ => Err(ClassReaderError::invalid_class_data(format!(
+                "invalid method handle reference kind: {reference_kind}"
+            ))),
+        }
+    }
+
+    /// Returns the `reference_kind` value stored in the class file, i.e. the inverse of
+    /// [MethodHandleKind::from]
+    pub fn reference_kind(&self) -> u8 {
+        match self {
+            MethodHandleKind::GetField => 1,
+            MethodHandleKind::GetStatic => 2,
+            MethodHandleKind::PutField => 3,
+            MethodHandleKind::PutStatic => 4,
+            MethodHandleKind::InvokeVirtual => 5,
+            MethodHandleKind::InvokeStatic => 6,
+            MethodHandleKind::InvokeSpecial => 7,
+            MethodHandleKind::NewInvokeSpecial => 8,
+            MethodHandleKind::InvokeInterface => 9,
+        }
+    }
 }
 
 /// Constants in the pool generally take one slot, but long and double take two. We do not use
 /// the second one, so we have a tombstone to ensure the indexes match.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum ConstantPoolPhysicalEntry {
     Entry(ConstantPoolEntry),
     MultiByteEntryTombstone(),
@@ -28,7 +98,7 @@ enum ConstantPoolPhysicalEntry {
 
 /// Implementation of the constant pool of a java class.
 /// Note that constants are 1-based in java.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct ConstantPool {
     entries: Vec<ConstantPoolPhysicalEntry>,
 }
@@ -46,6 +116,32 @@ impl InvalidConstantPoolIndexError {
     }
 }
 
+/// Serialized as a map from the index of each entry to the entry
+#[cfg(feature = "serde")]
+impl serde::Serialize for ConstantPool {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ConstantPool {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries =
+            std::collections::BTreeMap::<u16, ConstantPoolEntry>::deserialize(deserializer)?;
+        let mut constants_pool = ConstantPool::new();
+        for (index, entry) in entries {
+            if index as usize != constants_pool.len() + 1 {
+                return Err(serde::de::Error::custom(format!(
+                    "unexpected constant pool index: {index}"
+                )));
+            }
+            constants_pool.add(entry);
+        }
+        Ok(constants_pool)
+    }
+}
+
 impl ConstantPool {
     pub fn new() -> ConstantPool {
         Default::default()
@@ -84,8 +180,52 @@ impl ConstantPool {
         }
     }
 
+    /// Returns the number of slots used in the pool, counting the two slots of long and
+    /// double constants. The next constant added will have index `len() + 1`.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the constants, together with their (1-based) index
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &ConstantPoolEntry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(raw_idx, entry)| match entry {
+                ConstantPoolPhysicalEntry::Entry(entry) => Some(((raw_idx + 1) as u16, entry)),
+                ConstantPoolPhysicalEntry::MultiByteEntryTombstone() => None,
+            })
+    }
+
+    /// Like [ConstantPool::get], but also fails if the entry is reached by following more
+    /// references than any valid constant needs, which means that the references form a cycle
+    fn get_nested(
+        &self,
+        idx: u16,
+        depth: usize,
+    ) -> Result<&ConstantPoolEntry, InvalidConstantPoolIndexError> {
+        if depth > MAX_REFERENCE_DEPTH {
+            Err(InvalidConstantPoolIndexError::new(idx))
+        } else {
+            self.get(idx)
+        }
+    }
+
     fn fmt_entry(&self, idx: u16) -> Result<String, InvalidConstantPoolIndexError> {
-        let entry = self.get(idx)?;
+        self.fmt_entry_nested(idx, 0)
+    }
+
+    fn fmt_entry_nested(
+        &self,
+        idx: u16,
+        depth: usize,
+    ) -> Result<String, InvalidConstantPoolIndexError> {
+        let entry = self.get_nested(idx, depth)?;
+        let fmt_entry = |idx: u16| self.fmt_entry_nested(idx, depth + 1);
         let text = match entry {
             ConstantPoolEntry::Utf8(ref s) => format!("String: \"{s}\""),
             ConstantPoolEntry::Integer(n) => format!("Integer: {n}"),
@@ -93,18 +233,18 @@ impl ConstantPool {
             ConstantPoolEntry::Long(n) => format!("Long: {n}"),
             ConstantPoolEntry::Double(n) => format!("Double: {n}"),
             ConstantPoolEntry::ClassReference(n) => {
-                format!("ClassReference: {} => ({})", n, self.fmt_entry(*n)?)
+                format!("ClassReference: {} => ({})", n, fmt_entry(*n)?)
             }
             ConstantPoolEntry::StringReference(n) => {
-                format!("StringReference: {} => ({})", n, self.fmt_entry(*n)?)
+                format!("StringReference: {} => ({})", n, fmt_entry(*n)?)
             }
             ConstantPoolEntry::FieldReference(i, j) => {
                 format!(
                     "FieldReference: {}, {} => ({}), ({})",
                     i,
                     j,
-                    self.fmt_entry(*i)?,
-                    self.fmt_entry(*j)?
+                    fmt_entry(*i)?,
+                    fmt_entry(*j)?
                 )
             }
             ConstantPoolEntry::MethodReference(i, j) => {
@@ -112,8 +252,8 @@ impl ConstantPool {
                     "MethodReference: {}, {} => ({}), ({})",
                     i,
                     j,
-                    self.fmt_entry(*i)?,
-                    self.fmt_entry(*j)?
+                    fmt_entry(*i)?,
+                    fmt_entry(*j)?
                 )
             }
             ConstantPoolEntry::InterfaceMethodReference(i, j) => {
@@ -121,8 +261,8 @@ impl ConstantPool {
                     "InterfaceMethodReference: {}, {} => ({}), ({})",
                     i,
                     j,
-                    self.fmt_entry(*i)?,
-                    self.fmt_entry(*j)?
+                    fmt_entry(*i)?,
+                    fmt_entry(*j)?
                 )
             }
             &ConstantPoolEntry::NameAndTypeDescriptor(i, j) => {
@@ -130,41 +270,81 @@ impl ConstantPool {
                     "NameAndTypeDescriptor: {}, {} => ({}), ({})",
                     i,
                     j,
-                    self.fmt_entry(i)?,
-                    self.fmt_entry(j)?
+                    fmt_entry(i)?,
+                    fmt_entry(j)?
                 )
             }
+            ConstantPoolEntry::MethodHandle(kind, n) => {
+                format!("MethodHandle: {}, {} => ({})", kind, n, fmt_entry(*n)?)
+            }
+            ConstantPoolEntry::MethodType(n) => {
+                format!("MethodType: {} => ({})", n, fmt_entry(*n)?)
+            }
+            ConstantPoolEntry::Dynamic(i, j) => {
+                format!("Dynamic: {}, {} => ({})", i, j, fmt_entry(*j)?)
+            }
+            ConstantPoolEntry::InvokeDynamic(i, j) => {
+                format!("InvokeDynamic: {}, {} => ({})", i, j, fmt_entry(*j)?)
+            }
+            ConstantPoolEntry::Module(n) => {
+                format!("Module: {} => ({})", n, fmt_entry(*n)?)
+            }
+            ConstantPoolEntry::Package(n) => {
+                format!("Package: {} => ({})", n, fmt_entry(*n)?)
+            }
         };
         Ok(text)
     }
 
     pub fn text_of(&self, idx: u16) -> Result<String, InvalidConstantPoolIndexError> {
-        let entry = self.get(idx)?;
+        self.text_of_nested(idx, 0)
+    }
+
+    fn text_of_nested(
+        &self,
+        idx: u16,
+        depth: usize,
+    ) -> Result<String, InvalidConstantPoolIndexError> {
+        let entry = self.get_nested(idx, depth)?;
+        let text_of = |idx: u16| self.text_of_nested(idx, depth + 1);
         let text = match entry {
             ConstantPoolEntry::Utf8(ref s) => s.clone(),
             ConstantPoolEntry::Integer(n) => n.to_string(),
             ConstantPoolEntry::Float(n) => n.to_string(),
             ConstantPoolEntry::Long(n) => n.to_string(),
             ConstantPoolEntry::Double(n) => n.to_string(),
-            ConstantPoolEntry::ClassReference(n) => self.text_of(*n)?,
-            ConstantPoolEntry::StringReference(n) => self.text_of(*n)?,
+            ConstantPoolEntry::ClassReference(n) => text_of(*n)?,
+            ConstantPoolEntry::StringReference(n) => text_of(*n)?,
             ConstantPoolEntry::FieldReference(i, j) => {
-                format!("{}.{}", self.text_of(*i)?, self.text_of(*j)?)
+                format!("{}.{}", text_of(*i)?, text_of(*j)?)
             }
             ConstantPoolEntry::MethodReference(i, j) => {
-                format!("{}.{}", self.text_of(*i)?, self.text_of(*j)?)
+                format!("{}.{}", text_of(*i)?, text_of(*j)?)
             }
             ConstantPoolEntry::InterfaceMethodReference(i, j) => {
-                format!("{}.{}", self.text_of(*i)?, self.text_of(*j)?)
+                format!("{}.{}", text_of(*i)?, text_of(*j)?)
             }
             ConstantPoolEntry::NameAndTypeDescriptor(i, j) => {
-                format!("{}: {}", self.text_of(*i)?, self.text_of(*j)?)
+                format!("{}: {}", text_of(*i)?, text_of(*j)?)
+            }
+            ConstantPoolEntry::MethodHandle(kind, n) => {
+                format!("{} {}", kind, text_of(*n)?)
             }
+            ConstantPoolEntry::MethodType(n) => text_of(*n)?,
+            // The first index refers to the bootstrap methods attribute, not to the constant pool
+            ConstantPoolEntry::Dynamic(i, j) => format!("#{}:{}", i, text_of(*j)?),
+            ConstantPoolEntry::InvokeDynamic(i, j) => format!("#{}:{}", i, text_of(*j)?),
+            ConstantPoolEntry::Module(n) => text_of(*n)?,
+            ConstantPoolEntry::Package(n) => text_of(*n)?,
         };
         Ok(text)
     }
 }
 
+/// The longest chain of references of a valid constant is a method handle, referring to
+/// a method, referring to a name and type, referring to a string
+pub(crate) const MAX_REFERENCE_DEPTH: usize = 3;
+
 impl fmt::Display for ConstantPool {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Constant pool: (size: {})", self.entries.len())?;
@@ -179,7 +359,9 @@ impl fmt::Display for ConstantPool {
 
 #[cfg(test)]
 mod tests {
-    use crate::constant_pool::{ConstantPool, ConstantPoolEntry, InvalidConstantPoolIndexError};
+    use crate::constant_pool::{
+        ConstantPool, ConstantPoolEntry, InvalidConstantPoolIndexError, MethodHandleKind,
+    };
 
     #[test]
     fn constant_pool_works() {
@@ -244,5 +426,82 @@ mod tests {
         assert_eq!("hey.joe", cp.text_of(12).unwrap());
         assert_eq!("hey.joe", cp.text_of(13).unwrap());
         assert_eq!("hey: joe", cp.text_of(14).unwrap());
+
+        assert_eq!(14, cp.len());
+        let indexes: Vec<u16> = cp.iter().map(|(index, _)| index).collect();
+        assert_eq!(vec![1, 2, 3, 4, 6, 8, 9, 10, 11, 12, 13, 14], indexes);
+    }
+
+    #[test]
+    fn constant_pool_supports_dynamic_entries() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("java/lang/Object".to_string()));
+        cp.add(ConstantPoolEntry::ClassReference(1));
+        cp.add(ConstantPoolEntry::Utf8("run".to_string()));
+        cp.add(ConstantPoolEntry::Utf8("()V".to_string()));
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(3, 4));
+        cp.add(ConstantPoolEntry::MethodReference(2, 5));
+        cp.add(ConstantPoolEntry::MethodHandle(
+            MethodHandleKind::InvokeStatic,
+            6,
+        ));
+        cp.add(ConstantPoolEntry::MethodType(4));
+        cp.add(ConstantPoolEntry::InvokeDynamic(0, 5));
+        cp.add(ConstantPoolEntry::Dynamic(1, 5));
+        cp.add(ConstantPoolEntry::Utf8("java.base".to_string()));
+        cp.add(ConstantPoolEntry::Module(11));
+        cp.add(ConstantPoolEntry::Utf8("java/lang".to_string()));
+        cp.add(ConstantPoolEntry::Package(13));
+
+        assert_eq!(
+            "REF_invokeStatic java/lang/Object.run: ()V",
+            cp.text_of(7).unwrap()
+        );
+        assert_eq!("()V", cp.text_of(8).unwrap());
+        assert_eq!("#0:run: ()V", cp.text_of(9).unwrap());
+        assert_eq!("#1:run: ()V", cp.text_of(10).unwrap());
+        assert_eq!("java.base", cp.text_of(12).unwrap());
+        assert_eq!("java/lang", cp.text_of(14).unwrap());
+
+        assert_eq!(
+            "MethodType: 4 => (String: \"()V\")",
+            cp.fmt_entry(8).unwrap()
+        );
+        assert_eq!(
+            "InvokeDynamic: 0, 5 => (NameAndTypeDescriptor: 3, 4 => (String: \"run\"), (String: \"()V\"))",
+            cp.fmt_entry(9).unwrap()
+        );
+    }
+
+    #[test]
+    fn method_handle_kind_is_validated() {
+        assert_eq!(
+            MethodHandleKind::GetField,
+            MethodHandleKind::from(1).unwrap()
+        );
+        assert_eq!(
+            MethodHandleKind::InvokeInterface,
+            MethodHandleKind::from(9).unwrap()
+        );
+        for reference_kind in 1..=9 {
+            assert_eq!(
+                reference_kind,
+                MethodHandleKind::from(reference_kind)
+                    .unwrap()
+                    .reference_kind()
+            );
+        }
+        assert!(MethodHandleKind::from(0).is_err());
+        assert!(MethodHandleKind::from(10).is_err());
+    }
+
+    #[test]
+    fn references_cannot_form_a_cycle() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::ClassReference(2));
+        cp.add(ConstantPoolEntry::StringReference(1));
+
+        assert!(cp.text_of(1).is_err());
+        assert!(cp.text_of(2).is_err());
     }
 }
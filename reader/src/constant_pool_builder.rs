@@ -0,0 +1,247 @@
+use std::{collections::HashMap, mem::Discriminant};
+
+use crate::constant_pool::{ConstantPool, ConstantPoolEntry, MethodHandleKind};
+
+/// Helper to add constants to a [ConstantPool], which reuses the existing entries
+/// whenever possible rather than adding duplicates.
+///
+/// A builder can start from an existing pool: its entries keep their indexes,
+/// so that the bytecode and attributes that refer to them stay valid.
+#[derive(Debug, Default)]
+pub struct ConstantPoolBuilder {
+    constants: ConstantPool,
+    indexes: HashMap<EntryKey, u16>,
+}
+
+/// A hashable version of [ConstantPoolEntry]. Floating point numbers are compared by
+/// their bits, so that NaN constants can be reused too.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum EntryKey {
+    Utf8(String),
+    Integer(i32),
+    Float(u32),
+    Long(i64),
+    Double(u64),
+    /// All the other entries, which contain only indexes
+    Reference(Discriminant<ConstantPoolEntry>, u16, u16),
+}
+
+impl EntryKey {
+    fn of(entry: &ConstantPoolEntry) -> Self {
+        let reference = |first: u16, second: u16| {
+            EntryKey::Reference(std::mem::discriminant(entry), first, second)
+        };
+        match entry {
+            ConstantPoolEntry::Utf8(text) => EntryKey::Utf8(text.clone()),
+            ConstantPoolEntry::Integer(value) => EntryKey::Integer(*value),
+            ConstantPoolEntry::Float(value) => EntryKey::Float(value.to_bits()),
+            ConstantPoolEntry::Long(value) => EntryKey::Long(*value),
+            ConstantPoolEntry::Double(value) => EntryKey::Double(value.to_bits()),
+            ConstantPoolEntry::ClassReference(index)
+            | ConstantPoolEntry::StringReference(index)
+            | ConstantPoolEntry::MethodType(index)
+            | ConstantPoolEntry::Module(index)
+            | ConstantPoolEntry::Package(index) => reference(*index, 0),
+            ConstantPoolEntry::FieldReference(first, second)
+            | ConstantPoolEntry::MethodReference(first, second)
+            | ConstantPoolEntry::InterfaceMethodReference(first, second)
+            | ConstantPoolEntry::NameAndTypeDescriptor(first, second)
+            | ConstantPoolEntry::Dynamic(first, second)
+            | ConstantPoolEntry::InvokeDynamic(first, second) => reference(*first, *second),
+            ConstantPoolEntry::MethodHandle(kind, index) => {
+                reference(kind.reference_kind() as u16, *index)
+            }
+        }
+    }
+}
+
+impl From<ConstantPool> for ConstantPoolBuilder {
+    fn from(constants: ConstantPool) -> Self {
+        let mut indexes = HashMap::new();
+        for (index, entry) in constants.iter() {
+            // If the pool contains duplicates, we reuse the first one
+            indexes.entry(EntryKey::of(entry)).or_insert(index);
+        }
+        Self { constants, indexes }
+    }
+}
+
+impl ConstantPoolBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the index of the given entry, adding it to the pool if it is not present yet.
+    /// Note that the class file format limits the pool to 65535 slots: indexes
+    /// beyond that are returned as 0, and [ConstantPoolBuilder::is_full] becomes true.
+    pub fn add(&mut self, entry: ConstantPoolEntry) -> u16 {
+        let key = EntryKey::of(&entry);
+        if let Some(index) = self.indexes.get(&key) {
+            return *index;
+        }
+        let index = u16::try_from(self.constants.len() + 1).unwrap_or(0);
+        self.constants.add(entry);
+        self.indexes.insert(key, index);
+        index
+    }
+
+    /// Returns true if the pool does not fit in a class file anymore
+    pub fn is_full(&self) -> bool {
+        self.constants.len() >= u16::MAX as usize
+    }
+
+    pub fn utf8(&mut self, text: &str) -> u16 {
+        self.add(ConstantPoolEntry::Utf8(text.to_string()))
+    }
+
+    pub fn integer(&mut self, value: i32) -> u16 {
+        self.add(ConstantPoolEntry::Integer(value))
+    }
+
+    pub fn float(&mut self, value: f32) -> u16 {
+        self.add(ConstantPoolEntry::Float(value))
+    }
+
+    pub fn long(&mut self, value: i64) -> u16 {
+        self.add(ConstantPoolEntry::Long(value))
+    }
+
+    pub fn double(&mut self, value: f64) -> u16 {
+        self.add(ConstantPoolEntry::Double(value))
+    }
+
+    pub fn class(&mut self, class_name: &str) -> u16 {
+        let name_index = self.utf8(class_name);
+        self.add(ConstantPoolEntry::ClassReference(name_index))
+    }
+
+    pub fn string(&mut self, text: &str) -> u16 {
+        let text_index = self.utf8(text);
+        self.add(ConstantPoolEntry::StringReference(text_index))
+    }
+
+    pub fn name_and_type(&mut self, name: &str, type_descriptor: &str) -> u16 {
+        let name_index = self.utf8(name);
+        let type_descriptor_index = self.utf8(type_descriptor);
+        self.add(ConstantPoolEntry::NameAndTypeDescriptor(
+            name_index,
+            type_descriptor_index,
+        ))
+    }
+
+    pub fn field(&mut self, class_name: &str, name: &str, type_descriptor: &str) -> u16 {
+        let class_index = self.class(class_name);
+        let name_and_type_index = self.name_and_type(name, type_descriptor);
+        self.add(ConstantPoolEntry::FieldReference(
+            class_index,
+            name_and_type_index,
+        ))
+    }
+
+    pub fn method(
+        &mut self,
+        class_name: &str,
+        name: &str,
+        type_descriptor: &str,
+        is_interface: bool,
+    ) -> u16 {
+        let class_index = self.class(class_name);
+        let name_and_type_index = self.name_and_type(name, type_descriptor);
+        if is_interface {
+            self.add(ConstantPoolEntry::InterfaceMethodReference(
+                class_index,
+                name_and_type_index,
+            ))
+        } else {
+            self.add(ConstantPoolEntry::MethodReference(
+                class_index,
+                name_and_type_index,
+            ))
+        }
+    }
+
+    /// Adds a method handle, given the index of the field or method reference
+    pub fn method_handle(&mut self, kind: MethodHandleKind, reference_index: u16) -> u16 {
+        self.add(ConstantPoolEntry::MethodHandle(kind, reference_index))
+    }
+
+    pub fn method_type(&mut self, type_descriptor: &str) -> u16 {
+        let type_descriptor_index = self.utf8(type_descriptor);
+        self.add(ConstantPoolEntry::MethodType(type_descriptor_index))
+    }
+
+    /// Adds the call site of an `invokedynamic`, given the index of its bootstrap method
+    /// in the `BootstrapMethods` attribute
+    pub fn invoke_dynamic(
+        &mut self,
+        bootstrap_method_index: u16,
+        name: &str,
+        type_descriptor: &str,
+    ) -> u16 {
+        let name_and_type_index = self.name_and_type(name, type_descriptor);
+        self.add(ConstantPoolEntry::InvokeDynamic(
+            bootstrap_method_index,
+            name_and_type_index,
+        ))
+    }
+
+    pub fn module(&mut self, module_name: &str) -> u16 {
+        let name_index = self.utf8(module_name);
+        self.add(ConstantPoolEntry::Module(name_index))
+    }
+
+    pub fn package(&mut self, package_name: &str) -> u16 {
+        let name_index = self.utf8(package_name);
+        self.add(ConstantPoolEntry::Package(name_index))
+    }
+
+    pub fn constants(&self) -> &ConstantPool {
+        &self.constants
+    }
+
+    pub fn build(self) -> ConstantPool {
+        self.constants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constant_pool::{ConstantPool, ConstantPoolEntry},
+        constant_pool_builder::ConstantPoolBuilder,
+    };
+
+    #[test]
+    fn entries_are_reused() {
+        let mut builder = ConstantPoolBuilder::new();
+        assert_eq!(6, builder.method("rjvm/Foo", "bar", "()V", false));
+        assert_eq!(1, builder.utf8("rjvm/Foo"));
+        assert_eq!(2, builder.class("rjvm/Foo"));
+        assert_eq!(9, builder.field("rjvm/Foo", "bar", "I"));
+        assert_eq!(10, builder.long(42));
+        // Long constants take two slots
+        assert_eq!(12, builder.float(f32::NAN));
+        assert_eq!(12, builder.float(f32::NAN));
+        assert_eq!(10, builder.long(42));
+        assert_eq!(13, builder.integer(42));
+
+        let constants = builder.build();
+        assert_eq!(13, constants.len());
+        assert_eq!("rjvm/Foo.bar: ()V", constants.text_of(6).unwrap());
+        assert_eq!("rjvm/Foo.bar: I", constants.text_of(9).unwrap());
+    }
+
+    #[test]
+    fn existing_entries_keep_their_index() {
+        let mut constants = ConstantPool::new();
+        constants.add(ConstantPoolEntry::Double(1.0));
+        constants.add(ConstantPoolEntry::Utf8("java/lang/Object".to_string()));
+        constants.add(ConstantPoolEntry::ClassReference(3));
+
+        let mut builder = ConstantPoolBuilder::from(constants);
+        assert_eq!(1, builder.double(1.0));
+        assert_eq!(4, builder.class("java/lang/Object"));
+        assert_eq!(5, builder.string("java/lang/Object"));
+        assert_eq!(7, builder.method_type("()V"));
+    }
+}
@@ -0,0 +1,772 @@
+//! Control flow graph of the bytecode of a method.
+//!
+//! The code is split into [BasicBlock]s, linked by [Edge]s for branches, jumps, switches,
+//! fall-through and exception handlers. Instructions that leave the method, i.e. the returns and
+//! `athrow`, have an edge to a synthetic exit block, which is always the last one of the graph.
+//!
+//! Subroutines are modelled like calls: `jsr` has an edge to the subroutine and a fall-through
+//! edge to the following instruction, whereas `ret` has no successors.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fmt::Write,
+};
+
+use crate::{
+    class_file_method::ClassFileMethodCode,
+    class_reader_error::{ClassReaderError, Result},
+    exception_table::ExceptionTable,
+    instruction::{Instruction, WideInstruction},
+    program_counter::ProgramCounter,
+};
+
+/// Identifies a block of a [ControlFlowGraph], by its index in [ControlFlowGraph::blocks]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlockId(pub usize);
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "block{}", self.0)
+    }
+}
+
+/// A sequence of instructions that can only be entered from the first one and left from the
+/// last one, or via an exception
+#[derive(Debug, PartialEq)]
+pub struct BasicBlock {
+    pub id: BlockId,
+    /// Address of the first instruction
+    pub start: ProgramCounter,
+    /// Address following the last instruction
+    pub end: ProgramCounter,
+    /// Pairs of (address, instruction). Empty only for the exit block.
+    pub instructions: Vec<(usize, Instruction)>,
+}
+
+/// Why control can flow from one block to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Execution continues with the following instruction
+    FallThrough,
+    /// A conditional branch is taken
+    Branch,
+    /// An unconditional `goto` or `goto_w`
+    Jump,
+    /// A case, or the default, of a `tableswitch` or `lookupswitch`
+    Switch,
+    /// A `jsr` or `jsr_w` to a subroutine
+    Subroutine,
+    /// A return instruction, always to the exit block
+    Return,
+    /// An `athrow`, always to the exit block since the exception might not be caught
+    Throw,
+    /// An exception thrown in the block is caught by a handler. The catch class is `None` for
+    /// the handlers matching any exception, i.e. the `finally` blocks.
+    Exception(Option<String>),
+}
+
+/// An edge of the [ControlFlowGraph]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub from: BlockId,
+    pub to: BlockId,
+    pub kind: EdgeKind,
+}
+
+/// The control flow graph of a method. The first block is the entry, the last one is the
+/// synthetic exit block.
+#[derive(Debug, PartialEq)]
+pub struct ControlFlowGraph {
+    blocks: Vec<BasicBlock>,
+    edges: Vec<Edge>,
+}
+
+/// What can happen after executing the last instruction of a block
+struct Successors {
+    falls_through: bool,
+    jumps: Vec<(u16, EdgeKind)>,
+    exit: Option<EdgeKind>,
+}
+
+impl Successors {
+    fn next() -> Self {
+        Self {
+            falls_through: true,
+            jumps: Vec::new(),
+            exit: None,
+        }
+    }
+
+    fn branch(target: u16, kind: EdgeKind) -> Self {
+        Self {
+            falls_through: true,
+            jumps: vec![(target, kind)],
+            exit: None,
+        }
+    }
+
+    fn jump(targets: Vec<u16>, kind: EdgeKind) -> Self {
+        Self {
+            falls_through: false,
+            jumps: targets
+                .into_iter()
+                .map(|target| (target, kind.clone()))
+                .collect(),
+            exit: None,
+        }
+    }
+
+    fn exit(kind: Option<EdgeKind>) -> Self {
+        Self {
+            falls_through: false,
+            jumps: Vec::new(),
+            exit: kind,
+        }
+    }
+
+    fn of(instruction: &Instruction) -> Self {
+        match instruction {
+            Instruction::Ifeq(target)
+            | Instruction::Ifne(target)
+            | Instruction::Iflt(target)
+            | Instruction::Ifge(target)
+            | Instruction::Ifgt(target)
+            | Instruction::Ifle(target)
+            | Instruction::If_icmpeq(target)
+            | Instruction::If_icmpne(target)
+            | Instruction::If_icmplt(target)
+            | Instruction::If_icmpge(target)
+            | Instruction::If_icmpgt(target)
+            | Instruction::If_icmple(target)
+            | Instruction::If_acmpeq(target)
+            | Instruction::If_acmpne(target)
+            | Instruction::Ifnull(target)
+            | Instruction::Ifnonnull(target) => Self::branch(*target, EdgeKind::Branch),
+            Instruction::Jsr(target) | Instruction::Jsr_w(target) => {
+                Self::branch(*target, EdgeKind::Subroutine)
+            }
+            Instruction::Goto(target) | Instruction::Goto_w(target) => {
+                Self::jump(vec![*target], EdgeKind::Jump)
+            }
+            Instruction::Tableswitch(table_switch) => {
+                let mut targets = table_switch.jumps.clone();
+                targets.push(table_switch.default);
+                Self::jump(targets, EdgeKind::Switch)
+            }
+            Instruction::Lookupswitch(lookup_switch) => {
+                let mut targets: Vec<u16> = lookup_switch
+                    .pairs
+                    .iter()
+                    .map(|(_, target)| *target)
+                    .collect();
+                targets.push(lookup_switch.default);
+                Self::jump(targets, EdgeKind::Switch)
+            }
+            Instruction::Ireturn
+            | Instruction::Lreturn
+            | Instruction::Freturn
+            | Instruction::Dreturn
+            | Instruction::Areturn
+            | Instruction::Return => Self::exit(Some(EdgeKind::Return)),
+            Instruction::Athrow => Self::exit(Some(EdgeKind::Throw)),
+            Instruction::Ret(_) | Instruction::Wide(WideInstruction::Ret(_)) => Self::exit(None),
+            _ => Self::next(),
+        }
+    }
+
+    fn ends_block(&self) -> bool {
+        !self.falls_through || !self.jumps.is_empty()
+    }
+}
+
+impl ControlFlowGraph {
+    /// Builds the graph of the code of a method
+    pub fn build(code: &ClassFileMethodCode) -> Result<Self> {
+        Self::from_bytecode(&code.code, &code.exception_table)
+    }
+
+    /// Builds the graph of the given raw bytecode and its exception table
+    pub fn from_bytecode(raw_code: &[u8], exception_table: &ExceptionTable) -> Result<Self> {
+        let instructions = Instruction::parse_instructions(raw_code)?;
+        let code_length = raw_code.len();
+        let is_instruction_start = |address: usize| {
+            instructions
+                .binary_search_by_key(&address, |(instruction_address, _)| *instruction_address)
+                .is_ok()
+        };
+
+        let mut leaders = BTreeSet::new();
+        if !instructions.is_empty() {
+            leaders.insert(0);
+        }
+        for (index, (address, instruction)) in instructions.iter().enumerate() {
+            let successors = Successors::of(instruction);
+            for (target, _) in successors.jumps.iter() {
+                let target = *target as usize;
+                if !is_instruction_start(target) {
+                    return Err(ClassReaderError::invalid_class_data(format!(
+                        "invalid jump target {target} of instruction at pc {address}"
+                    )));
+                }
+                leaders.insert(target);
+            }
+            if successors.ends_block() {
+                if let Some((next_address, _)) = instructions.get(index + 1) {
+                    leaders.insert(*next_address);
+                }
+            }
+        }
+        for entry in exception_table.entries() {
+            let start = entry.range.start.0 as usize;
+            let end = entry.range.end.0 as usize;
+            let handler = entry.handler_pc.0 as usize;
+            if start >= end
+                || !is_instruction_start(start)
+                || !is_instruction_start(handler)
+                || (end != code_length && !is_instruction_start(end))
+            {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                    "invalid exception table entry {start}..{end} with handler at pc {handler}"
+                )));
+            }
+            leaders.insert(start);
+            leaders.insert(handler);
+            if end != code_length {
+                leaders.insert(end);
+            }
+        }
+
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        for (address, instruction) in instructions.into_iter() {
+            if leaders.contains(&address) {
+                if let Some(previous) = blocks.last_mut() {
+                    previous.end = ProgramCounter(address as u16);
+                }
+                blocks.push(BasicBlock {
+                    id: BlockId(blocks.len()),
+                    start: ProgramCounter(address as u16),
+                    end: ProgramCounter(address as u16),
+                    instructions: Vec::new(),
+                });
+            }
+            blocks
+                .last_mut()
+                .expect("the first instruction is always a leader")
+                .instructions
+                .push((address, instruction));
+        }
+        if let Some(last) = blocks.last_mut() {
+            last.end = ProgramCounter(code_length as u16);
+        }
+        let exit = BlockId(blocks.len());
+        blocks.push(BasicBlock {
+            id: exit,
+            start: ProgramCounter(code_length as u16),
+            end: ProgramCounter(code_length as u16),
+            instructions: Vec::new(),
+        });
+
+        let block_ids: BTreeMap<u16, BlockId> = blocks
+            .iter()
+            .map(|block| (block.start.0, block.id))
+            .collect();
+        let mut graph = Self {
+            blocks,
+            edges: Vec::new(),
+        };
+        for index in 0..exit.0 {
+            let from = BlockId(index);
+            let (address, last_instruction) = graph.blocks[index]
+                .instructions
+                .last()
+                .expect("blocks other than the exit are never empty");
+            let successors = Successors::of(last_instruction);
+            if successors.falls_through {
+                if index + 1 == exit.0 {
+                    return Err(ClassReaderError::invalid_class_data(format!(
+                        "execution can fall off the end of the code after pc {address}"
+                    )));
+                }
+                graph.add_edge(from, BlockId(index + 1), EdgeKind::FallThrough);
+            }
+            for (target, kind) in successors.jumps {
+                graph.add_edge(from, block_ids[&target], kind);
+            }
+            if let Some(kind) = successors.exit {
+                graph.add_edge(from, exit, kind);
+            }
+
+            let range = graph.blocks[index].start..graph.blocks[index].end;
+            for entry in exception_table.entries() {
+                if entry.range.start < range.end && range.start < entry.range.end {
+                    let handler = block_ids[&entry.handler_pc.0];
+                    graph.add_edge(
+                        from,
+                        handler,
+                        EdgeKind::Exception(entry.catch_class.clone()),
+                    );
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+
+    fn add_edge(&mut self, from: BlockId, to: BlockId, kind: EdgeKind) {
+        let edge = Edge { from, to, kind };
+        if !self.edges.contains(&edge) {
+            self.edges.push(edge);
+        }
+    }
+
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    pub fn block(&self, id: BlockId) -> &BasicBlock {
+        &self.blocks[id.0]
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// The block where execution starts. For an empty method, this is the exit block.
+    pub fn entry(&self) -> BlockId {
+        BlockId(0)
+    }
+
+    /// The synthetic block, without instructions, reached by the returns and `athrow`
+    pub fn exit(&self) -> BlockId {
+        BlockId(self.blocks.len() - 1)
+    }
+
+    /// Returns the block containing the instruction at the given address
+    pub fn block_containing(&self, pc: ProgramCounter) -> Option<BlockId> {
+        self.blocks
+            .iter()
+            .find(|block| block.start <= pc && pc < block.end)
+            .map(|block| block.id)
+    }
+
+    /// Returns the edges leaving the given block
+    pub fn successors(&self, id: BlockId) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.from == id)
+    }
+
+    /// Returns the edges entering the given block
+    pub fn predecessors(&self, id: BlockId) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.to == id)
+    }
+
+    /// Computes the dominator tree of the graph, including the exceptional edges
+    pub fn dominators(&self) -> Dominators {
+        // Iterative algorithm from "A Simple, Fast Dominance Algorithm", by Cooper, Harvey
+        // and Kennedy
+        let successors = self.adjacency(|edge| (edge.from, edge.to));
+        let predecessors = self.adjacency(|edge| (edge.to, edge.from));
+
+        let post_order = self.post_order(&successors);
+        let mut post_order_index = vec![None; self.blocks.len()];
+        for (index, block) in post_order.iter().enumerate() {
+            post_order_index[block.0] = Some(index);
+        }
+
+        let entry = self.entry();
+        let mut immediate_dominators: Vec<Option<BlockId>> = vec![None; self.blocks.len()];
+        immediate_dominators[entry.0] = Some(entry);
+
+        let intersect = |immediate_dominators: &[Option<BlockId>], first: BlockId, second| {
+            let index = |block: BlockId| post_order_index[block.0].expect("block is reachable");
+            let mut first: BlockId = first;
+            let mut second: BlockId = second;
+            while first != second {
+                while index(first) < index(second) {
+                    first = immediate_dominators[first.0].expect("block was processed");
+                }
+                while index(second) < index(first) {
+                    second = immediate_dominators[second.0].expect("block was processed");
+                }
+            }
+            first
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in post_order.iter().rev().filter(|block| **block != entry) {
+                let mut new_dominator = None;
+                for predecessor in predecessors[block.0].iter() {
+                    if immediate_dominators[predecessor.0].is_none() {
+                        continue;
+                    }
+                    new_dominator = Some(match new_dominator {
+                        None => *predecessor,
+                        Some(dominator) => {
+                            intersect(&immediate_dominators, *predecessor, dominator)
+                        }
+                    });
+                }
+                if new_dominator.is_some() && immediate_dominators[block.0] != new_dominator {
+                    immediate_dominators[block.0] = new_dominator;
+                    changed = true;
+                }
+            }
+        }
+
+        immediate_dominators[entry.0] = None;
+        Dominators {
+            entry,
+            immediate_dominators,
+        }
+    }
+
+    /// Finds the natural loops of the graph, i.e. the ones with a single entry point, sorted
+    /// by header. Loops sharing the same header are merged into one.
+    pub fn loops(&self) -> Vec<Loop> {
+        let dominators = self.dominators();
+        let predecessors = self.adjacency(|edge| (edge.to, edge.from));
+
+        let mut back_edges: BTreeMap<BlockId, Vec<BlockId>> = BTreeMap::new();
+        for edge in self.edges.iter() {
+            if dominators.dominates(edge.to, edge.from) {
+                let latches = back_edges.entry(edge.to).or_default();
+                if !latches.contains(&edge.from) {
+                    latches.push(edge.from);
+                }
+            }
+        }
+
+        back_edges
+            .into_iter()
+            .map(|(header, latches)| {
+                let mut blocks = BTreeSet::from([header]);
+                let mut to_visit = latches.clone();
+                while let Some(block) = to_visit.pop() {
+                    if blocks.insert(block) {
+                        to_visit.extend(predecessors[block.0].iter().copied());
+                    }
+                }
+                Loop {
+                    header,
+                    latches,
+                    blocks,
+                }
+            })
+            .collect()
+    }
+
+    /// Exports the graph in the [DOT](https://graphviz.org/doc/info/lang.html) format of Graphviz
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        self.write_dot(&mut out)
+            .expect("writing to a string should not fail");
+        out
+    }
+
+    fn write_dot(&self, out: &mut String) -> fmt::Result {
+        writeln!(out, "digraph cfg {{")?;
+        writeln!(out, "    node [shape=box, fontname=\"monospace\"];")?;
+        for block in self.blocks.iter() {
+            if block.id == self.exit() {
+                writeln!(out, "    {} [label=\"exit\", shape=oval];", block.id)?;
+                continue;
+            }
+            let mut label = String::new();
+            for (address, instruction) in block.instructions.iter() {
+                write!(label, "{address}: {instruction:?}\\l")?;
+            }
+            writeln!(
+                out,
+                "    {} [label=\"{}\"];",
+                block.id,
+                label.replace('"', "\\\"")
+            )?;
+        }
+        for edge in self.edges.iter() {
+            let attributes = match &edge.kind {
+                EdgeKind::FallThrough => String::new(),
+                EdgeKind::Branch => " [label=\"branch\"]".to_string(),
+                EdgeKind::Jump => " [label=\"goto\"]".to_string(),
+                EdgeKind::Switch => " [label=\"switch\"]".to_string(),
+                EdgeKind::Subroutine => " [label=\"jsr\"]".to_string(),
+                EdgeKind::Return => " [label=\"return\"]".to_string(),
+                EdgeKind::Throw => " [label=\"athrow\"]".to_string(),
+                EdgeKind::Exception(catch_class) => format!(
+                    " [label=\"{}\", style=dashed]",
+                    catch_class.as_deref().unwrap_or("any")
+                ),
+            };
+            writeln!(out, "    {} -> {}{attributes};", edge.from, edge.to)?;
+        }
+        writeln!(out, "}}")
+    }
+
+    fn adjacency(&self, endpoints: impl Fn(&Edge) -> (BlockId, BlockId)) -> Vec<Vec<BlockId>> {
+        let mut adjacency = vec![Vec::new(); self.blocks.len()];
+        for edge in self.edges.iter() {
+            let (from, to) = endpoints(edge);
+            if !adjacency[from.0].contains(&to) {
+                adjacency[from.0].push(to);
+            }
+        }
+        adjacency
+    }
+
+    /// Returns the blocks reachable from the entry, in post order
+    fn post_order(&self, successors: &[Vec<BlockId>]) -> Vec<BlockId> {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut post_order = Vec::new();
+        let mut stack = vec![(self.entry(), 0)];
+        visited[self.entry().0] = true;
+        while let Some((block, next_successor)) = stack.pop() {
+            match successors[block.0].get(next_successor) {
+                Some(successor) => {
+                    stack.push((block, next_successor + 1));
+                    if !visited[successor.0] {
+                        visited[successor.0] = true;
+                        stack.push((*successor, 0));
+                    }
+                }
+                None => post_order.push(block),
+            }
+        }
+        post_order
+    }
+}
+
+/// The dominator tree of a [ControlFlowGraph]. A block dominates another if every path from
+/// the entry to the latter goes through the former.
+#[derive(Debug, PartialEq)]
+pub struct Dominators {
+    entry: BlockId,
+    immediate_dominators: Vec<Option<BlockId>>,
+}
+
+impl Dominators {
+    /// Returns the closest strict dominator of the given block. It is `None` for the entry
+    /// and for unreachable blocks.
+    pub fn immediate_dominator(&self, block: BlockId) -> Option<BlockId> {
+        self.immediate_dominators[block.0]
+    }
+
+    pub fn is_reachable(&self, block: BlockId) -> bool {
+        block == self.entry || self.immediate_dominators[block.0].is_some()
+    }
+
+    /// Returns whether the first block dominates the second one. Every reachable block
+    /// dominates itself.
+    pub fn dominates(&self, dominator: BlockId, block: BlockId) -> bool {
+        if !self.is_reachable(block) {
+            return false;
+        }
+        let mut current = Some(block);
+        while let Some(candidate) = current {
+            if candidate == dominator {
+                return true;
+            }
+            current = self.immediate_dominators[candidate.0];
+        }
+        false
+    }
+}
+
+/// A natural loop of a [ControlFlowGraph]
+#[derive(Debug, PartialEq)]
+pub struct Loop {
+    /// The only block through which the loop can be entered
+    pub header: BlockId,
+    /// The blocks with a back edge to the header
+    pub latches: Vec<BlockId>,
+    /// All the blocks of the loop, including the header
+    pub blocks: BTreeSet<BlockId>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{
+        code_assembler::{CodeAssembler, Jump},
+        control_flow_graph::{BlockId, ControlFlowGraph, Edge, EdgeKind},
+        exception_table::{ExceptionTable, ExceptionTableEntry},
+        instruction::Instruction,
+        program_counter::ProgramCounter,
+    };
+
+    fn edge(from: usize, to: usize, kind: EdgeKind) -> Edge {
+        Edge {
+            from: BlockId(from),
+            to: BlockId(to),
+            kind,
+        }
+    }
+
+    fn blocks(ids: &[usize]) -> BTreeSet<BlockId> {
+        ids.iter().map(|id| BlockId(*id)).collect()
+    }
+
+    #[test]
+    fn can_split_code_in_blocks() {
+        // int abs(int x) { if (x < 0) x = -x; return x; }
+        let code = Instruction::encode_instructions(&[
+            Instruction::Iload_0,
+            Instruction::Ifge(7),
+            Instruction::Iload_0,
+            Instruction::Ineg,
+            Instruction::Istore_0,
+            Instruction::Iload_0,
+            Instruction::Ireturn,
+        ])
+        .unwrap();
+
+        let graph = ControlFlowGraph::from_bytecode(&code, &ExceptionTable::default()).unwrap();
+
+        let ranges: Vec<(u16, u16)> = graph
+            .blocks()
+            .iter()
+            .map(|block| (block.start.0, block.end.0))
+            .collect();
+        assert_eq!(vec![(0, 4), (4, 7), (7, 9), (9, 9)], ranges);
+        assert_eq!(BlockId(3), graph.exit());
+        assert_eq!(
+            vec![
+                edge(0, 1, EdgeKind::FallThrough),
+                edge(0, 2, EdgeKind::Branch),
+                edge(1, 2, EdgeKind::FallThrough),
+                edge(2, 3, EdgeKind::Return),
+            ],
+            graph.edges()
+        );
+        assert_eq!(Some(BlockId(1)), graph.block_containing(ProgramCounter(5)));
+
+        let dominators = graph.dominators();
+        assert_eq!(None, dominators.immediate_dominator(BlockId(0)));
+        assert_eq!(Some(BlockId(0)), dominators.immediate_dominator(BlockId(1)));
+        assert_eq!(Some(BlockId(0)), dominators.immediate_dominator(BlockId(2)));
+        assert!(dominators.dominates(BlockId(0), BlockId(3)));
+        assert!(!dominators.dominates(BlockId(1), BlockId(2)));
+        assert!(graph.loops().is_empty());
+    }
+
+    #[test]
+    fn can_find_loops() {
+        // for (int i = 0; i < 10; ++i) { for (int j = 0; j < i; ++j) {} }
+        let mut assembler = CodeAssembler::new();
+        let outer = assembler.new_label();
+        let outer_end = assembler.new_label();
+        let inner = assembler.new_label();
+        let inner_end = assembler.new_label();
+        assembler.emit(Instruction::Iconst_0);
+        assembler.emit(Instruction::Istore_0);
+        assembler.place_label(outer);
+        assembler.emit(Instruction::Iload_0);
+        assembler.emit(Instruction::Bipush(10));
+        assembler.jump(Jump::If_icmpge, outer_end);
+        assembler.emit(Instruction::Iconst_0);
+        assembler.emit(Instruction::Istore_1);
+        assembler.place_label(inner);
+        assembler.emit(Instruction::Iload_1);
+        assembler.emit(Instruction::Iload_0);
+        assembler.jump(Jump::If_icmpge, inner_end);
+        assembler.emit(Instruction::Iinc(1, 1));
+        assembler.jump(Jump::Goto, inner);
+        assembler.place_label(inner_end);
+        assembler.emit(Instruction::Iinc(0, 1));
+        assembler.jump(Jump::Goto, outer);
+        assembler.place_label(outer_end);
+        assembler.emit(Instruction::Return);
+        let code = assembler.assemble().unwrap();
+
+        let graph =
+            ControlFlowGraph::from_bytecode(&code.code, &ExceptionTable::default()).unwrap();
+
+        // Blocks: 0 init, 1 outer condition, 2 inner init, 3 inner condition, 4 inner body,
+        // 5 outer increment, 6 return, 7 exit
+        assert_eq!(8, graph.blocks().len());
+        let loops = graph.loops();
+        assert_eq!(2, loops.len());
+        assert_eq!(BlockId(1), loops[0].header);
+        assert_eq!(vec![BlockId(5)], loops[0].latches);
+        assert_eq!(blocks(&[1, 2, 3, 4, 5]), loops[0].blocks);
+        assert_eq!(BlockId(3), loops[1].header);
+        assert_eq!(vec![BlockId(4)], loops[1].latches);
+        assert_eq!(blocks(&[3, 4]), loops[1].blocks);
+
+        let dominators = graph.dominators();
+        assert_eq!(Some(BlockId(1)), dominators.immediate_dominator(BlockId(6)));
+        assert_eq!(Some(BlockId(3)), dominators.immediate_dominator(BlockId(5)));
+    }
+
+    #[test]
+    fn exception_handlers_have_exceptional_edges() {
+        // try { foo(); } catch (RuntimeException e) { } return;
+        let code = Instruction::encode_instructions(&[
+            Instruction::Invokestatic(1),
+            Instruction::Goto(7),
+            Instruction::Astore_0,
+            Instruction::Return,
+        ])
+        .unwrap();
+        let exception_table = ExceptionTable::new(vec![ExceptionTableEntry {
+            range: ProgramCounter(0)..ProgramCounter(3),
+            handler_pc: ProgramCounter(6),
+            catch_class: Some("java/lang/RuntimeException".to_string()),
+        }]);
+
+        let graph = ControlFlowGraph::from_bytecode(&code, &exception_table).unwrap();
+
+        // Blocks: 0 try, 1 goto, 2 handler, 3 return, 4 exit
+        assert_eq!(
+            vec![
+                edge(0, 1, EdgeKind::FallThrough),
+                edge(
+                    0,
+                    2,
+                    EdgeKind::Exception(Some("java/lang/RuntimeException".to_string()))
+                ),
+                edge(1, 3, EdgeKind::Jump),
+                edge(2, 3, EdgeKind::FallThrough),
+                edge(3, 4, EdgeKind::Return),
+            ],
+            graph.edges()
+        );
+        assert_eq!(
+            Some(BlockId(0)),
+            graph.dominators().immediate_dominator(BlockId(3))
+        );
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph cfg {\n"));
+        assert!(dot.contains("    block1 [label=\"3: Goto(7)\\l\"];\n"));
+        assert!(dot.contains("    block1 -> block3 [label=\"goto\"];\n"));
+        assert!(dot.contains(
+            "    block0 -> block2 [label=\"java/lang/RuntimeException\", style=dashed];\n"
+        ));
+        assert!(dot.contains("    block2 -> block3;\n"));
+        assert!(dot.contains("    block4 [label=\"exit\", shape=oval];\n"));
+    }
+
+    #[test]
+    fn unreachable_blocks_are_not_dominated() {
+        let code =
+            Instruction::encode_instructions(&[Instruction::Return, Instruction::Return]).unwrap();
+
+        let graph = ControlFlowGraph::from_bytecode(&code, &ExceptionTable::default()).unwrap();
+
+        let dominators = graph.dominators();
+        assert!(!dominators.is_reachable(BlockId(1)));
+        assert!(!dominators.dominates(BlockId(0), BlockId(1)));
+        assert!(dominators.dominates(BlockId(0), graph.exit()));
+    }
+
+    #[test]
+    fn rejects_invalid_code() {
+        let code = Instruction::encode_instructions(&[Instruction::Goto(1)]).unwrap();
+        assert!(ControlFlowGraph::from_bytecode(&code, &ExceptionTable::default()).is_err());
+
+        let code = Instruction::encode_instructions(&[Instruction::Nop]).unwrap();
+        assert!(ControlFlowGraph::from_bytecode(&code, &ExceptionTable::default()).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use std::{any::Any, collections::HashMap, fmt, fmt::Formatter};
+
+use crate::{class_reader_error::Result, constant_pool::ConstantPool};
+
+/// The value of an attribute that is not part of the JVM specification, such as a
+/// vendor-specific one, as returned by a parser registered in [AttributeParsers]
+#[derive(Debug)]
+pub struct CustomAttribute {
+    pub name: String,
+    pub value: Box<dyn CustomAttributeValue>,
+}
+
+impl CustomAttribute {
+    /// Returns the parsed value, if it has the given type
+    pub fn value<T: CustomAttributeValue>(&self) -> Option<&T> {
+        self.value.as_any().downcast_ref()
+    }
+}
+
+impl fmt::Display for CustomAttribute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?}", self.name, self.value)
+    }
+}
+
+/// Values of custom attributes can have any type that can be debugged and compared,
+/// and are recovered via [CustomAttribute::value]
+pub trait CustomAttributeValue: Any + fmt::Debug {
+    fn as_any(&self) -> &dyn Any;
+
+    fn dyn_eq(&self, other: &dyn CustomAttributeValue) -> bool;
+}
+
+impl<T: Any + fmt::Debug + PartialEq> CustomAttributeValue for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_eq(&self, other: &dyn CustomAttributeValue) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+}
+
+impl PartialEq for CustomAttribute {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.value.dyn_eq(other.value.as_ref())
+    }
+}
+
+/// Parses the content of an attribute, given the constant pool of the class it belongs to
+type CustomAttributeParser =
+    Box<dyn Fn(&[u8], &ConstantPool) -> Result<Box<dyn CustomAttributeValue>>>;
+
+/// Parsers for the attributes that the class reader does not know about, identified by
+/// the name of the attribute. Parsed values are attached to the class, field, method or code
+/// that contains the attribute, whose raw bytes are kept among its generic attributes.
+#[derive(Default)]
+pub struct AttributeParsers {
+    parsers: HashMap<String, CustomAttributeParser>,
+}
+
+impl AttributeParsers {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers the parser of the attributes with the given name, replacing any
+    /// previous one. Errors returned by the parser fail the reading of the whole class.
+    pub fn register<T, F>(&mut self, name: &str, parser: F)
+    where
+        T: CustomAttributeValue,
+        F: Fn(&[u8], &ConstantPool) -> Result<T> + 'static,
+    {
+        self.parsers.insert(
+            name.to_string(),
+            Box::new(move |bytes, constants| {
+                parser(bytes, constants)
+                    .map(|value| Box::new(value) as Box<dyn CustomAttributeValue>)
+            }),
+        );
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&CustomAttributeParser> {
+        self.parsers.get(name)
+    }
+}
+
+impl fmt::Debug for AttributeParsers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "AttributeParsers={:?}", self.parsers.keys())
+    }
+}
@@ -0,0 +1,211 @@
+//! Generic forward data flow analysis over the [ControlFlowGraph] of a method.
+//!
+//! An analysis defines the facts known before each instruction, how each instruction
+//! transforms them, and how the facts reaching the same instruction from different paths
+//! are merged. [analyze] then propagates them through the graph until a fixed point is reached.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{
+    control_flow_graph::{BlockId, ControlFlowGraph, EdgeKind},
+    instruction::Instruction,
+};
+
+/// A forward data flow analysis, to be run with [analyze]
+pub trait DataflowAnalysis {
+    /// The facts known before executing an instruction
+    type State: Clone + PartialEq;
+    type Error;
+
+    /// Simulates the effect of the instruction at the given address on the state
+    fn transfer(
+        &mut self,
+        state: &mut Self::State,
+        address: usize,
+        instruction: &Instruction,
+    ) -> Result<(), Self::Error>;
+
+    /// Returns the state with which an exception handler is entered, when an exception is
+    /// thrown by an instruction executed in the given state
+    fn exception_state(
+        &mut self,
+        state: &Self::State,
+        catch_class: Option<&str>,
+    ) -> Result<Self::State, Self::Error>;
+
+    /// Merges two states reaching the instruction at the given address from different paths
+    fn join(
+        &mut self,
+        address: usize,
+        first: &Self::State,
+        second: &Self::State,
+    ) -> Result<Self::State, Self::Error>;
+}
+
+/// Runs the analysis until a fixed point is reached, starting with the given state at the
+/// entry of the method. Returns the state before each reachable instruction, by address.
+pub fn analyze<A: DataflowAnalysis>(
+    graph: &ControlFlowGraph,
+    initial_state: A::State,
+    analysis: &mut A,
+) -> Result<BTreeMap<usize, A::State>, A::Error> {
+    let mut states = BTreeMap::new();
+    let mut block_states: Vec<Option<A::State>> = vec![None; graph.blocks().len()];
+    let mut queued = vec![false; graph.blocks().len()];
+    let mut worklist = VecDeque::new();
+
+    let entry = graph.entry();
+    if entry == graph.exit() {
+        return Ok(states);
+    }
+    block_states[entry.0] = Some(initial_state);
+    queued[entry.0] = true;
+    worklist.push_back(entry);
+
+    while let Some(block_id) = worklist.pop_front() {
+        queued[block_id.0] = false;
+        let mut state = block_states[block_id.0]
+            .clone()
+            .expect("queued blocks have a state");
+        let mut new_states = Vec::new();
+
+        for (address, instruction) in graph.block(block_id).instructions.iter() {
+            states.insert(*address, state.clone());
+            for edge in graph.successors(block_id) {
+                if let EdgeKind::Exception(catch_class) = &edge.kind {
+                    let exception_state =
+                        analysis.exception_state(&state, catch_class.as_deref())?;
+                    new_states.push((edge.to, exception_state));
+                }
+            }
+            analysis.transfer(&mut state, *address, instruction)?;
+        }
+        for edge in graph.successors(block_id) {
+            if edge.to != graph.exit() && !matches!(edge.kind, EdgeKind::Exception(_)) {
+                new_states.push((edge.to, state.clone()));
+            }
+        }
+
+        for (target, new_state) in new_states {
+            if merge_into(graph, analysis, &mut block_states, target, new_state)?
+                && !queued[target.0]
+            {
+                queued[target.0] = true;
+                worklist.push_back(target);
+            }
+        }
+    }
+    Ok(states)
+}
+
+/// Merges a state into the one at the entry of a block, returning whether it changed
+fn merge_into<A: DataflowAnalysis>(
+    graph: &ControlFlowGraph,
+    analysis: &mut A,
+    block_states: &mut [Option<A::State>],
+    target: BlockId,
+    new_state: A::State,
+) -> Result<bool, A::Error> {
+    let merged = match &block_states[target.0] {
+        None => new_state,
+        Some(old_state) => {
+            let address = graph.block(target).start.0 as usize;
+            let merged = analysis.join(address, old_state, &new_state)?;
+            if &merged == old_state {
+                return Ok(false);
+            }
+            merged
+        }
+    };
+    block_states[target.0] = Some(merged);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        control_flow_graph::ControlFlowGraph,
+        dataflow::{analyze, DataflowAnalysis},
+        exception_table::ExceptionTable,
+        instruction::Instruction,
+    };
+
+    /// Computes the range of values that the first local variable can have
+    struct Interval;
+
+    impl DataflowAnalysis for Interval {
+        type State = (i32, i32);
+        type Error = ();
+
+        fn transfer(
+            &mut self,
+            state: &mut Self::State,
+            _address: usize,
+            instruction: &Instruction,
+        ) -> Result<(), Self::Error> {
+            if let Instruction::Iinc(0, increment) = instruction {
+                let increment = *increment as i32;
+                *state = (state.0 + increment, state.1 + increment);
+            }
+            Ok(())
+        }
+
+        fn exception_state(
+            &mut self,
+            state: &Self::State,
+            _catch_class: Option<&str>,
+        ) -> Result<Self::State, Self::Error> {
+            Ok(*state)
+        }
+
+        fn join(
+            &mut self,
+            _address: usize,
+            first: &Self::State,
+            second: &Self::State,
+        ) -> Result<Self::State, Self::Error> {
+            Ok((first.0.min(second.0), first.1.max(second.1)))
+        }
+    }
+
+    #[test]
+    fn states_are_merged_where_paths_join() {
+        let code = Instruction::encode_instructions(&[
+            Instruction::Iload_1,
+            Instruction::Ifeq(10),
+            Instruction::Iinc(0, 1),
+            Instruction::Goto(13),
+            Instruction::Iinc(0, -2),
+            Instruction::Return,
+        ])
+        .unwrap();
+        let graph = ControlFlowGraph::from_bytecode(&code, &ExceptionTable::default()).unwrap();
+
+        let states = analyze(&graph, (0, 0), &mut Interval).unwrap();
+
+        assert_eq!(
+            BTreeMap::from([
+                (0, (0, 0)),
+                (1, (0, 0)),
+                (4, (0, 0)),
+                (7, (1, 1)),
+                (10, (0, 0)),
+                (13, (-2, 1)),
+            ]),
+            states
+        );
+    }
+
+    #[test]
+    fn unreachable_instructions_have_no_state() {
+        let code =
+            Instruction::encode_instructions(&[Instruction::Return, Instruction::Return]).unwrap();
+        let graph = ControlFlowGraph::from_bytecode(&code, &ExceptionTable::default()).unwrap();
+
+        let states = analyze(&graph, (0, 0), &mut Interval).unwrap();
+
+        assert_eq!(BTreeMap::from([(0, (0, 0))]), states);
+    }
+}
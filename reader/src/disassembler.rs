@@ -0,0 +1,1118 @@
+use std::fmt::{self, Write};
+
+use itertools::Itertools;
+
+use crate::{
+    attribute::{Attribute, CODE_ATTRIBUTES, METHOD_ATTRIBUTES},
+    class_access_flags::ClassAccessFlags,
+    class_file::ClassFile,
+    class_file_field::{ClassFileField, FieldConstantValue},
+    class_file_method::{ClassFileMethod, ClassFileMethodCode},
+    constant_pool::{ConstantPool, ConstantPoolEntry, MAX_REFERENCE_DEPTH},
+    field_flags::FieldFlags,
+    field_type::{BaseType, FieldType},
+    instruction::{Instruction, WideInstruction},
+    method_flags::MethodFlags,
+    method_parameter_flags::MethodParameterFlags,
+    module::Module,
+    record_component::RecordComponent,
+    type_annotation::TypeAnnotation,
+};
+
+/// Returns a description of the class in a format similar to the one of `javap -v`:
+/// the constant pool, the fields and the methods with their bytecode, where the
+/// references to the constant pool are resolved inline.
+pub fn disassemble(class_file: &ClassFile) -> String {
+    let mut out = String::new();
+    Disassembler {
+        class_file,
+        constants: &class_file.constants,
+    }
+    .write_class(&mut out)
+    .expect("writing to a string cannot fail");
+    out
+}
+
+struct Disassembler<'a> {
+    class_file: &'a ClassFile,
+    constants: &'a ConstantPool,
+}
+
+impl<'a> Disassembler<'a> {
+    fn write_class(&self, out: &mut String) -> fmt::Result {
+        let class_file = self.class_file;
+        match &class_file.module {
+            Some(module) => {
+                write!(out, "module {}", module.name)?;
+                if let Some(version) = &module.version {
+                    write!(out, "@{version}")?;
+                }
+            }
+            None => self.write_class_declaration(out)?,
+        }
+        writeln!(out)?;
+        writeln!(out, "  minor version: {}", class_file.minor_version)?;
+        writeln!(out, "  major version: {}", class_file.version.major())?;
+        writeln!(
+            out,
+            "  flags: {}",
+            flags_text(class_file.flags.bits(), &class_file.flags)
+        )?;
+        if let Some(signature) = &class_file.signature {
+            writeln!(out, "  Signature: {}", signature.encode())?;
+        }
+
+        self.write_constant_pool(out)?;
+
+        writeln!(out, "{{")?;
+        for field in class_file.fields.iter() {
+            self.write_field(out, field)?;
+            writeln!(out)?;
+        }
+        for method in class_file.methods.iter() {
+            self.write_method(out, method)?;
+            writeln!(out)?;
+        }
+        writeln!(out, "}}")?;
+
+        if let Some(source_file) = &class_file.source_file {
+            writeln!(out, "SourceFile: \"{source_file}\"")?;
+        }
+        if class_file.deprecated {
+            writeln!(out, "Deprecated: true")?;
+        }
+        if let Some(enclosing_method) = &class_file.enclosing_method {
+            writeln!(out, "EnclosingMethod: {enclosing_method}")?;
+        }
+        if !class_file.inner_classes.is_empty() {
+            writeln!(out, "InnerClasses:")?;
+            for inner_class in class_file.inner_classes.iter() {
+                writeln!(out, "  {inner_class}")?;
+            }
+        }
+        if !class_file.annotations.is_empty() {
+            writeln!(out, "Annotations:")?;
+            for annotation in class_file.annotations.iter() {
+                writeln!(out, "  {annotation}")?;
+            }
+        }
+        write_type_annotations(out, "", &class_file.type_annotations)?;
+        if !class_file.bootstrap_methods.is_empty() {
+            writeln!(out, "BootstrapMethods:")?;
+            for (index, bootstrap_method) in class_file.bootstrap_methods.iter().enumerate() {
+                writeln!(out, "  {index}: {bootstrap_method}")?;
+            }
+        }
+        if let Some(nest_host) = &class_file.nest_host {
+            writeln!(out, "NestHost: class {nest_host}")?;
+        }
+        write_names(out, "NestMembers", &class_file.nest_members)?;
+        write_names(out, "PermittedSubclasses", &class_file.permitted_subclasses)?;
+        if let Some(record_components) = &class_file.record_components {
+            writeln!(out, "Record:")?;
+            for component in record_components.iter() {
+                self.write_record_component(out, component)?;
+            }
+        }
+        if let Some(module) = &class_file.module {
+            write_module(out, module)?;
+        }
+        write_names(out, "ModulePackages", &class_file.module_packages)?;
+        if let Some(module_main_class) = &class_file.module_main_class {
+            writeln!(out, "ModuleMainClass: {module_main_class}")?;
+        }
+        write_attributes(out, "", class_file.attributes.iter())
+    }
+
+    fn write_record_component(&self, out: &mut String, component: &RecordComponent) -> fmt::Result {
+        writeln!(
+            out,
+            "  {} {};",
+            java_type_name(&component.type_descriptor),
+            component.name
+        )?;
+        writeln!(
+            out,
+            "    descriptor: {}",
+            component.type_descriptor.descriptor()
+        )?;
+        if let Some(signature) = &component.signature {
+            writeln!(out, "    Signature: {}", signature.encode())?;
+        }
+        if !component.annotations.is_empty() {
+            writeln!(out, "    Annotations:")?;
+            for annotation in component.annotations.iter() {
+                writeln!(out, "      {annotation}")?;
+            }
+        }
+        write_type_annotations(out, "    ", &component.type_annotations)?;
+        write_attributes(out, "    ", component.attributes.iter())
+    }
+
+    /// Writes the modifiers, the name and the supertypes of the class
+    fn write_class_declaration(&self, out: &mut String) -> fmt::Result {
+        let class_file = self.class_file;
+        let is_interface = class_file.flags.contains(ClassAccessFlags::INTERFACE);
+        let mut modifiers = Vec::new();
+        if class_file.flags.contains(ClassAccessFlags::PUBLIC) {
+            modifiers.push("public");
+        }
+        if class_file.flags.contains(ClassAccessFlags::FINAL) {
+            modifiers.push("final");
+        }
+        if class_file.flags.contains(ClassAccessFlags::ABSTRACT) && !is_interface {
+            modifiers.push("abstract");
+        }
+        modifiers.push(if is_interface { "interface" } else { "class" });
+        write!(
+            out,
+            "{} {}",
+            modifiers.join(" "),
+            java_class_name(&class_file.name)
+        )?;
+        if let Some(superclass) = class_file.superclass.as_ref().filter(|_| !is_interface) {
+            write!(out, " extends {}", java_class_name(superclass))?;
+        }
+        if !class_file.interfaces.is_empty() {
+            let keyword = if is_interface {
+                "extends"
+            } else {
+                "implements"
+            };
+            let interfaces = class_file
+                .interfaces
+                .iter()
+                .map(|interface| java_class_name(interface))
+                .join(", ");
+            write!(out, " {keyword} {interfaces}")?;
+        }
+        Ok(())
+    }
+
+    fn write_constant_pool(&self, out: &mut String) -> fmt::Result {
+        writeln!(out, "Constant pool:")?;
+        // Like javap, align the indexes on the largest one
+        let width = format!("#{}", self.constants.len() + 1).len() + 2;
+        for (index, entry) in self.constants.iter() {
+            let (kind, arguments) = match entry {
+                ConstantPoolEntry::Utf8(text) => ("Utf8", escape(text)),
+                ConstantPoolEntry::Integer(value) => ("Integer", value.to_string()),
+                ConstantPoolEntry::Float(value) => ("Float", format!("{value:?}f")),
+                ConstantPoolEntry::Long(value) => ("Long", format!("{value}l")),
+                ConstantPoolEntry::Double(value) => ("Double", format!("{value:?}d")),
+                ConstantPoolEntry::ClassReference(name) => ("Class", format!("#{name}")),
+                ConstantPoolEntry::StringReference(text) => ("String", format!("#{text}")),
+                ConstantPoolEntry::FieldReference(class, name_and_type) => {
+                    ("Fieldref", format!("#{class}.#{name_and_type}"))
+                }
+                ConstantPoolEntry::MethodReference(class, name_and_type) => {
+                    ("Methodref", format!("#{class}.#{name_and_type}"))
+                }
+                ConstantPoolEntry::InterfaceMethodReference(class, name_and_type) => {
+                    ("InterfaceMethodref", format!("#{class}.#{name_and_type}"))
+                }
+                ConstantPoolEntry::NameAndTypeDescriptor(name, type_descriptor) => {
+                    ("NameAndType", format!("#{name}:#{type_descriptor}"))
+                }
+                ConstantPoolEntry::MethodHandle(kind, reference) => (
+                    "MethodHandle",
+                    format!("{}:#{reference}", kind.reference_kind()),
+                ),
+                ConstantPoolEntry::MethodType(type_descriptor) => {
+                    ("MethodType", format!("#{type_descriptor}"))
+                }
+                ConstantPoolEntry::Dynamic(bootstrap_method, name_and_type) => {
+                    ("Dynamic", format!("#{bootstrap_method}:#{name_and_type}"))
+                }
+                ConstantPoolEntry::InvokeDynamic(bootstrap_method, name_and_type) => (
+                    "InvokeDynamic",
+                    format!("#{bootstrap_method}:#{name_and_type}"),
+                ),
+                ConstantPoolEntry::Module(name) => ("Module", format!("#{name}")),
+                ConstantPoolEntry::Package(name) => ("Package", format!("#{name}")),
+            };
+            let index = format!("#{index}");
+            match entry {
+                ConstantPoolEntry::Utf8(_)
+                | ConstantPoolEntry::Integer(_)
+                | ConstantPoolEntry::Float(_)
+                | ConstantPoolEntry::Long(_)
+                | ConstantPoolEntry::Double(_) => {
+                    writeln!(out, "{index:>width$} = {kind:<18} {arguments}")?
+                }
+                _ => writeln!(
+                    out,
+                    "{index:>width$} = {kind:<18} {arguments:<14} // {}",
+                    self.constant_text(entry, 0)
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_field(&self, out: &mut String, field: &ClassFileField) -> fmt::Result {
+        let mut modifiers = Vec::new();
+        for (flag, modifier) in [
+            (FieldFlags::PUBLIC, "public"),
+            (FieldFlags::PRIVATE, "private"),
+            (FieldFlags::PROTECTED, "protected"),
+            (FieldFlags::STATIC, "static"),
+            (FieldFlags::FINAL, "final"),
+            (FieldFlags::VOLATILE, "volatile"),
+            (FieldFlags::TRANSIENT, "transient"),
+        ] {
+            if field.flags.contains(flag) {
+                modifiers.push(modifier.to_string());
+            }
+        }
+        modifiers.push(java_type_name(&field.type_descriptor));
+        writeln!(out, "  {} {};", modifiers.join(" "), field.name)?;
+        writeln!(
+            out,
+            "    descriptor: {}",
+            field.type_descriptor.descriptor()
+        )?;
+        writeln!(
+            out,
+            "    flags: {}",
+            flags_text(field.flags.bits(), &field.flags)
+        )?;
+        if let Some(constant_value) = &field.constant_value {
+            let constant_value = match constant_value {
+                FieldConstantValue::Int(value) => format!("int {value}"),
+                FieldConstantValue::Float(value) => format!("float {value:?}f"),
+                FieldConstantValue::Long(value) => format!("long {value}l"),
+                FieldConstantValue::Double(value) => format!("double {value:?}d"),
+                FieldConstantValue::String(value) => format!("String {}", escape(value)),
+            };
+            writeln!(out, "    ConstantValue: {constant_value}")?;
+        }
+        if let Some(signature) = &field.signature {
+            writeln!(out, "    Signature: {}", signature.encode())?;
+        }
+        if field.deprecated {
+            writeln!(out, "    Deprecated: true")?;
+        }
+        if !field.annotations.is_empty() {
+            writeln!(out, "    Annotations:")?;
+            for annotation in field.annotations.iter() {
+                writeln!(out, "      {annotation}")?;
+            }
+        }
+        write_type_annotations(out, "    ", &field.type_annotations)?;
+        write_attributes(out, "    ", field.attributes.iter())
+    }
+
+    fn write_method(&self, out: &mut String, method: &ClassFileMethod) -> fmt::Result {
+        let mut modifiers = Vec::new();
+        for (flag, modifier) in [
+            (MethodFlags::PUBLIC, "public"),
+            (MethodFlags::PRIVATE, "private"),
+            (MethodFlags::PROTECTED, "protected"),
+            (MethodFlags::STATIC, "static"),
+            (MethodFlags::FINAL, "final"),
+            (MethodFlags::SYNCHRONIZED, "synchronized"),
+            (MethodFlags::NATIVE, "native"),
+            (MethodFlags::ABSTRACT, "abstract"),
+            (MethodFlags::STRICT, "strictfp"),
+        ] {
+            if method.flags.contains(flag) {
+                modifiers.push(modifier.to_string());
+            }
+        }
+        let parameters = method
+            .parsed_type_descriptor
+            .parameters
+            .iter()
+            .map(java_type_name)
+            .join(", ");
+        match method.name.as_str() {
+            "<clinit>" => modifiers.push("{}".to_string()),
+            "<init>" => modifiers.push(format!(
+                "{}({parameters})",
+                java_class_name(&self.class_file.name)
+            )),
+            name => {
+                modifiers.push(match &method.parsed_type_descriptor.return_type {
+                    Some(return_type) => java_type_name(return_type),
+                    None => "void".to_string(),
+                });
+                modifiers.push(format!("{name}({parameters})"));
+            }
+        }
+        writeln!(out, "  {};", modifiers.join(" "))?;
+        writeln!(out, "    descriptor: {}", method.type_descriptor)?;
+        writeln!(
+            out,
+            "    flags: {}",
+            flags_text(method.flags.bits(), &method.flags)
+        )?;
+
+        if let Some(code) = &method.code {
+            let this_size = if method.is_static() { 0 } else { 1 };
+            let parameters_size: usize = method
+                .parsed_type_descriptor
+                .parameters
+                .iter()
+                .map(|parameter| match parameter {
+                    FieldType::Base(BaseType::Long) | FieldType::Base(BaseType::Double) => 2,
+                    _ => 1,
+                })
+                .sum();
+            self.write_code(out, code, this_size + parameters_size)?;
+        }
+        if !method.thrown_exceptions.is_empty() {
+            writeln!(out, "    Exceptions:")?;
+            writeln!(
+                out,
+                "      throws {}",
+                method
+                    .thrown_exceptions
+                    .iter()
+                    .map(|exception| java_class_name(exception))
+                    .join(", ")
+            )?;
+        }
+        if let Some(signature) = &method.signature {
+            writeln!(out, "    Signature: {}", signature.encode())?;
+        }
+        if method.deprecated {
+            writeln!(out, "    Deprecated: true")?;
+        }
+        if !method.annotations.is_empty() {
+            writeln!(out, "    Annotations:")?;
+            for annotation in method.annotations.iter() {
+                writeln!(out, "      {annotation}")?;
+            }
+        }
+        if method
+            .parameter_annotations
+            .iter()
+            .any(|annotations| !annotations.is_empty())
+        {
+            writeln!(out, "    ParameterAnnotations:")?;
+            for (index, annotations) in method.parameter_annotations.iter().enumerate() {
+                for annotation in annotations.iter() {
+                    writeln!(out, "      parameter {index}: {annotation}")?;
+                }
+            }
+        }
+        if let Some(default) = &method.annotation_default {
+            writeln!(out, "    AnnotationDefault:")?;
+            writeln!(out, "      default_value: {default}")?;
+        }
+        if !method.parameters.is_empty() {
+            writeln!(out, "    MethodParameters:")?;
+            writeln!(out, "      Name                           Flags")?;
+            for parameter in method.parameters.iter() {
+                let flags = [
+                    (MethodParameterFlags::FINAL, "final"),
+                    (MethodParameterFlags::SYNTHETIC, "synthetic"),
+                    (MethodParameterFlags::MANDATED, "mandated"),
+                ]
+                .into_iter()
+                .filter(|(flag, _)| parameter.flags.contains(*flag))
+                .map(|(_, name)| name)
+                .join(" ");
+                let name = parameter.name.as_deref().unwrap_or("<no name>");
+                writeln!(out, "{}", format!("      {name:<30} {flags}").trim_end())?;
+            }
+        }
+        write_type_annotations(out, "    ", &method.type_annotations)?;
+        write_attributes(
+            out,
+            "    ",
+            method
+                .attributes
+                .iter()
+                .filter(|attribute| !METHOD_ATTRIBUTES.contains(&attribute.name.as_str())),
+        )
+    }
+
+    fn write_code(
+        &self,
+        out: &mut String,
+        code: &ClassFileMethodCode,
+        arguments_size: usize,
+    ) -> fmt::Result {
+        writeln!(out, "    Code:")?;
+        writeln!(
+            out,
+            "      stack={}, locals={}, args_size={arguments_size}",
+            code.max_stack, code.max_locals
+        )?;
+        match Instruction::parse_instructions(&code.code) {
+            Ok(instructions) => {
+                for (address, instruction) in instructions.iter() {
+                    self.write_instruction(out, *address, instruction)?;
+                }
+            }
+            Err(err) => writeln!(out, "      unparsable code: {err}")?,
+        }
+
+        if !code.exception_table.entries().is_empty() {
+            writeln!(out, "      Exception table:")?;
+            writeln!(out, "         from    to  target type")?;
+            for entry in code.exception_table.entries() {
+                let catch_class = match &entry.catch_class {
+                    Some(class) => format!("Class {class}"),
+                    None => "any".to_string(),
+                };
+                writeln!(
+                    out,
+                    "         {:>5} {:>5} {:>5}   {catch_class}",
+                    entry.range.start.0, entry.range.end.0, entry.handler_pc.0
+                )?;
+            }
+        }
+        if let Some(line_number_table) = &code.line_number_table {
+            writeln!(out, "      LineNumberTable:")?;
+            for entry in line_number_table.entries() {
+                writeln!(
+                    out,
+                    "        line {}: {}",
+                    entry.line_number, entry.program_counter
+                )?;
+            }
+        }
+        if let Some(local_variable_table) = &code.local_variable_table {
+            writeln!(out, "      LocalVariableTable:")?;
+            writeln!(out, "        Start  Length  Slot  Name   Signature")?;
+            for entry in local_variable_table.entries() {
+                writeln!(
+                    out,
+                    "        {:>5} {:>7} {:>5} {:>5}   {}",
+                    entry.range.start.0,
+                    entry.range.end.0 - entry.range.start.0,
+                    entry.index,
+                    entry.name,
+                    entry.type_descriptor.descriptor()
+                )?;
+            }
+            let generic_entries: Vec<_> = local_variable_table
+                .entries()
+                .iter()
+                .filter_map(|entry| Some((entry, entry.signature.as_ref()?)))
+                .collect();
+            if !generic_entries.is_empty() {
+                writeln!(out, "      LocalVariableTypeTable:")?;
+                writeln!(out, "        Start  Length  Slot  Name   Signature")?;
+                for (entry, signature) in generic_entries {
+                    writeln!(
+                        out,
+                        "        {:>5} {:>7} {:>5} {:>5}   {}",
+                        entry.range.start.0,
+                        entry.range.end.0 - entry.range.start.0,
+                        entry.index,
+                        entry.name,
+                        signature.encode()
+                    )?;
+                }
+            }
+        }
+        if !code.stack_map_table.is_empty() {
+            writeln!(
+                out,
+                "      StackMapTable: number_of_entries = {}",
+                code.stack_map_table.len()
+            )?;
+            for frame in code.stack_map_table.iter() {
+                writeln!(
+                    out,
+                    "        frame at {}: {}",
+                    frame.program_counter, frame.kind
+                )?;
+            }
+        }
+        write_type_annotations(out, "      ", &code.type_annotations)?;
+        write_attributes(
+            out,
+            "      ",
+            code.attributes
+                .iter()
+                .filter(|attribute| !CODE_ATTRIBUTES.contains(&attribute.name.as_str())),
+        )
+    }
+
+    fn write_instruction(
+        &self,
+        out: &mut String,
+        address: usize,
+        instruction: &Instruction,
+    ) -> fmt::Result {
+        let mnemonic: &str = instruction.into();
+        let (operands, constant) = match instruction {
+            Instruction::Anewarray(index)
+            | Instruction::Checkcast(index)
+            | Instruction::Getfield(index)
+            | Instruction::Getstatic(index)
+            | Instruction::Instanceof(index)
+            | Instruction::Invokespecial(index)
+            | Instruction::Invokestatic(index)
+            | Instruction::Invokevirtual(index)
+            | Instruction::Ldc_w(index)
+            | Instruction::Ldc2_w(index)
+            | Instruction::New(index)
+            | Instruction::Putfield(index)
+            | Instruction::Putstatic(index) => (format!("#{index}"), Some(*index)),
+            Instruction::Ldc(index) => (format!("#{index}"), Some(*index as u16)),
+            Instruction::Invokedynamic(index) => (format!("#{index},  0"), Some(*index)),
+            Instruction::Invokeinterface(index, count) => {
+                (format!("#{index},  {count}"), Some(*index))
+            }
+            Instruction::Multianewarray(index, dimensions) => {
+                (format!("#{index},  {dimensions}"), Some(*index))
+            }
+
+            Instruction::Aload(index)
+            | Instruction::Astore(index)
+            | Instruction::Dload(index)
+            | Instruction::Dstore(index)
+            | Instruction::Fload(index)
+            | Instruction::Fstore(index)
+            | Instruction::Iload(index)
+            | Instruction::Istore(index)
+            | Instruction::Lload(index)
+            | Instruction::Lstore(index)
+            | Instruction::Ret(index) => (index.to_string(), None),
+            Instruction::Bipush(value) => ((*value as i8).to_string(), None),
+            Instruction::Sipush(value) => (value.to_string(), None),
+            Instruction::Iinc(index, increment) => (format!("{index}, {increment}"), None),
+            Instruction::Newarray(array_type) => (format!("{array_type:?}").to_lowercase(), None),
+            Instruction::Wide(wide_instruction) => {
+                let wide_mnemonic: &str = wide_instruction.into();
+                let operands = match wide_instruction {
+                    WideInstruction::Iinc(index, increment) => format!("{index}, {increment}"),
+                    WideInstruction::Aload(index)
+                    | WideInstruction::Astore(index)
+                    | WideInstruction::Dload(index)
+                    | WideInstruction::Dstore(index)
+                    | WideInstruction::Fload(index)
+                    | WideInstruction::Fstore(index)
+                    | WideInstruction::Iload(index)
+                    | WideInstruction::Istore(index)
+                    | WideInstruction::Lload(index)
+                    | WideInstruction::Lstore(index)
+                    | WideInstruction::Ret(index) => index.to_string(),
+                };
+                // javap shows the wide form as a distinct instruction, i.e. `iinc_w`
+                let wide_mnemonic = format!("{wide_mnemonic}_w");
+                return writeln!(out, "{address:>10}: {wide_mnemonic:<13} {operands}");
+            }
+
+            Instruction::Goto(jump_address)
+            | Instruction::Goto_w(jump_address)
+            | Instruction::If_acmpeq(jump_address)
+            | Instruction::If_acmpne(jump_address)
+            | Instruction::If_icmpeq(jump_address)
+            | Instruction::If_icmpne(jump_address)
+            | Instruction::If_icmplt(jump_address)
+            | Instruction::If_icmpge(jump_address)
+            | Instruction::If_icmpgt(jump_address)
+            | Instruction::If_icmple(jump_address)
+            | Instruction::Ifeq(jump_address)
+            | Instruction::Ifne(jump_address)
+            | Instruction::Iflt(jump_address)
+            | Instruction::Ifge(jump_address)
+            | Instruction::Ifgt(jump_address)
+            | Instruction::Ifle(jump_address)
+            | Instruction::Ifnonnull(jump_address)
+            | Instruction::Ifnull(jump_address)
+            | Instruction::Jsr(jump_address)
+            | Instruction::Jsr_w(jump_address) => (jump_address.to_string(), None),
+
+            Instruction::Tableswitch(table_switch) => {
+                writeln!(
+                    out,
+                    "{address:>10}: {mnemonic:<13} {{ // {} to {}",
+                    table_switch.low, table_switch.high
+                )?;
+                for (value, jump_address) in (table_switch.low..).zip(table_switch.jumps.iter()) {
+                    writeln!(out, "{value:>24}: {jump_address}")?;
+                }
+                writeln!(out, "{:>24}: {}", "default", table_switch.default)?;
+                return writeln!(out, "            }}");
+            }
+            Instruction::Lookupswitch(lookup_switch) => {
+                writeln!(
+                    out,
+                    "{address:>10}: {mnemonic:<13} {{ // {}",
+                    lookup_switch.pairs.len()
+                )?;
+                for (value, jump_address) in lookup_switch.pairs.iter() {
+                    writeln!(out, "{value:>24}: {jump_address}")?;
+                }
+                writeln!(out, "{:>24}: {}", "default", lookup_switch.default)?;
+                return writeln!(out, "            }}");
+            }
+
+            _ => return writeln!(out, "{address:>10}: {mnemonic}"),
+        };
+
+        match constant {
+            Some(index) => writeln!(
+                out,
+                "{address:>10}: {mnemonic:<13} {operands:<19} // {}",
+                self.operand_text(index)
+            ),
+            None => writeln!(out, "{address:>10}: {mnemonic:<13} {operands}"),
+        }
+    }
+
+    /// The description of a constant used as an operand, for example `Method Foo.bar:()V`
+    fn operand_text(&self, index: u16) -> String {
+        let Ok(entry) = self.constants.get(index) else {
+            return format!("<invalid #{index}>");
+        };
+        let kind = match entry {
+            ConstantPoolEntry::Utf8(_) => "Utf8",
+            ConstantPoolEntry::Integer(_) => "int",
+            ConstantPoolEntry::Float(_) => "float",
+            ConstantPoolEntry::Long(_) => "long",
+            ConstantPoolEntry::Double(_) => "double",
+            ConstantPoolEntry::ClassReference(_) => "class",
+            ConstantPoolEntry::StringReference(_) => "String",
+            ConstantPoolEntry::FieldReference(..) => "Field",
+            ConstantPoolEntry::MethodReference(..) => "Method",
+            ConstantPoolEntry::InterfaceMethodReference(..) => "InterfaceMethod",
+            ConstantPoolEntry::NameAndTypeDescriptor(..) => "NameAndType",
+            ConstantPoolEntry::MethodHandle(..) => "MethodHandle",
+            ConstantPoolEntry::MethodType(_) => "MethodType",
+            ConstantPoolEntry::Dynamic(..) => "Dynamic",
+            ConstantPoolEntry::InvokeDynamic(..) => "InvokeDynamic",
+            ConstantPoolEntry::Module(_) => "Module",
+            ConstantPoolEntry::Package(_) => "Package",
+        };
+        format!("{kind} {}", self.constant_text(entry, 0))
+    }
+
+    /// Resolves the given constant to text, following the references to other constants.
+    /// Like [ConstantPool::text_of], we stop at [MAX_REFERENCE_DEPTH], so that a cycle
+    /// of references cannot overflow the stack.
+    fn constant_text(&self, entry: &ConstantPoolEntry, depth: usize) -> String {
+        let text_at = |index: u16| self.text_at(index, depth + 1);
+        match entry {
+            ConstantPoolEntry::Utf8(text) => escape(text),
+            ConstantPoolEntry::Integer(value) => value.to_string(),
+            ConstantPoolEntry::Float(value) => format!("{value:?}f"),
+            ConstantPoolEntry::Long(value) => format!("{value}l"),
+            ConstantPoolEntry::Double(value) => format!("{value:?}d"),
+            ConstantPoolEntry::ClassReference(name) => {
+                let name = text_at(*name);
+                // Like javap, we quote the array types
+                if name.starts_with('[') {
+                    format!("\"{name}\"")
+                } else {
+                    name
+                }
+            }
+            ConstantPoolEntry::StringReference(index)
+            | ConstantPoolEntry::MethodType(index)
+            | ConstantPoolEntry::Module(index)
+            | ConstantPoolEntry::Package(index) => text_at(*index),
+            ConstantPoolEntry::FieldReference(class, name_and_type)
+            | ConstantPoolEntry::MethodReference(class, name_and_type)
+            | ConstantPoolEntry::InterfaceMethodReference(class, name_and_type) => {
+                format!("{}.{}", text_at(*class), text_at(*name_and_type))
+            }
+            ConstantPoolEntry::NameAndTypeDescriptor(name, type_descriptor) => {
+                let name = text_at(*name);
+                let name = if name.starts_with('<') {
+                    format!("\"{name}\"")
+                } else {
+                    name
+                };
+                format!("{name}:{}", text_at(*type_descriptor))
+            }
+            ConstantPoolEntry::MethodHandle(kind, reference) => {
+                format!("{kind} {}", text_at(*reference))
+            }
+            // The first index refers to the bootstrap methods attribute, not to the constant pool
+            ConstantPoolEntry::Dynamic(bootstrap_method, name_and_type)
+            | ConstantPoolEntry::InvokeDynamic(bootstrap_method, name_and_type) => {
+                format!("#{bootstrap_method}:{}", text_at(*name_and_type))
+            }
+        }
+    }
+
+    fn text_at(&self, index: u16, depth: usize) -> String {
+        match self.constants.get(index) {
+            Ok(entry) if depth <= MAX_REFERENCE_DEPTH => self.constant_text(entry, depth),
+            _ => format!("<invalid #{index}>"),
+        }
+    }
+}
+
+/// Writes the attributes that are not modeled, of which we only know the size
+fn write_attributes<'a>(
+    out: &mut String,
+    indent: &str,
+    attributes: impl Iterator<Item = &'a Attribute>,
+) -> fmt::Result {
+    for attribute in attributes {
+        writeln!(
+            out,
+            "{indent}{}: length = 0x{:x}",
+            attribute.name,
+            attribute.bytes.len()
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a list of classes, packages or modules, one per line
+fn write_names(out: &mut String, title: &str, names: &[String]) -> fmt::Result {
+    if !names.is_empty() {
+        writeln!(out, "{title}:")?;
+        for name in names.iter() {
+            writeln!(out, "  {name}")?;
+        }
+    }
+    Ok(())
+}
+
+fn write_module(out: &mut String, module: &Module) -> fmt::Result {
+    writeln!(out, "Module:")?;
+    writeln!(out, "  name: {}", module.name)?;
+    writeln!(
+        out,
+        "  flags: {}",
+        flags_text(module.flags.bits(), &module.flags)
+    )?;
+    if let Some(version) = &module.version {
+        writeln!(out, "  version: {version}")?;
+    }
+    for requires in module.requires.iter() {
+        write!(out, "  requires {}", requires.module)?;
+        if let Some(version) = &requires.version {
+            write!(out, "@{version}")?;
+        }
+        writeln!(
+            out,
+            " {}",
+            flags_text(requires.flags.bits(), &requires.flags)
+        )?;
+    }
+    for (keyword, packages) in [("exports", &module.exports), ("opens", &module.opens)] {
+        for package in packages.iter() {
+            write!(
+                out,
+                "  {keyword} {} {}",
+                package.package,
+                flags_text(package.flags.bits(), &package.flags)
+            )?;
+            if !package.to.is_empty() {
+                write!(out, " to {}", package.to.iter().join(", "))?;
+            }
+            writeln!(out)?;
+        }
+    }
+    for service in module.uses.iter() {
+        writeln!(out, "  uses {service}")?;
+    }
+    for provides in module.provides.iter() {
+        writeln!(out, "  provides {provides}")?;
+    }
+    Ok(())
+}
+
+fn write_type_annotations(
+    out: &mut String,
+    indent: &str,
+    type_annotations: &[TypeAnnotation],
+) -> fmt::Result {
+    if !type_annotations.is_empty() {
+        writeln!(out, "{indent}TypeAnnotations:")?;
+        for type_annotation in type_annotations.iter() {
+            writeln!(out, "{indent}  {type_annotation}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Formats the flags like javap, i.e. `(0x0009) ACC_PUBLIC, ACC_STATIC`
+fn flags_text(bits: u16, flags: &impl fmt::Debug) -> String {
+    let names = if bits == 0 {
+        String::new()
+    } else {
+        format!("{flags:?}")
+            .split(" | ")
+            .map(|name| format!("ACC_{name}"))
+            .join(", ")
+    };
+    format!("(0x{bits:04x}) {names}").trim_end().to_string()
+}
+
+/// Escapes the control characters, like javap does
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            char if char.is_control() => escaped.push_str(&format!("\\u{:04x}", char as u32)),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+fn java_class_name(class_name: &str) -> String {
+    class_name.replace('/', ".")
+}
+
+/// The name of a type as it would appear in Java source code, for example `java.lang.String[]`
+fn java_type_name(field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Base(base_type) => base_type.to_string().to_lowercase(),
+        FieldType::Object(class_name) => java_class_name(class_name),
+        FieldType::Array(component_type) => format!("{}[]", java_type_name(component_type)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        class_file::ClassFile, class_reader::read_buffer, class_writer::write,
+        constant_pool::ConstantPoolEntry, disassembler::disassemble, text_assembler::assemble,
+    };
+
+    #[test]
+    fn can_disassemble_classes() {
+        let class = assemble(
+            r#"
+        .source Switches.java
+        .class public final super rjvm/Switches
+        .implements java/lang/Runnable
+        .field private static final NAME Ljava/lang/String; = "a\tb"
+        .method public static select(I)Ljava/lang/String;
+            .limit stack 2
+            .line 7
+        Start:
+            iload_0
+            tableswitch 1
+                One
+                Two
+                default : Other
+        One:
+            ldc "one"
+            areturn
+        Two:
+            iload_0
+            lookupswitch
+                -5 : One
+                default : Other
+        Other:
+            iinc 0 -1
+            ldc2_w 2.5
+            pop2
+            aconst_null
+            areturn
+        End:
+            .catch java/lang/RuntimeException from Start to End using End
+            athrow
+        .end method
+            "#,
+        )
+        .unwrap();
+
+        let expected = r#"public final class rjvm.Switches extends java.lang.Object implements java.lang.Runnable
+  minor version: 0
+  major version: 49
+  flags: (0x0031) ACC_PUBLIC, ACC_FINAL, ACC_SUPER
+Constant pool:
+  #1 = Utf8               one
+  #2 = String             #1             // one
+  #3 = Double             2.5d
+{
+  private static final java.lang.String NAME;
+    descriptor: Ljava/lang/String;
+    flags: (0x001a) ACC_PRIVATE, ACC_STATIC, ACC_FINAL
+    ConstantValue: String a\tb
+
+  public static java.lang.String select(int);
+    descriptor: (I)Ljava/lang/String;
+    flags: (0x0009) ACC_PUBLIC, ACC_STATIC
+    Code:
+      stack=2, locals=1, args_size=1
+         0: iload_0
+         1: tableswitch   { // 1 to 2
+                       1: 24
+                       2: 27
+                 default: 48
+            }
+        24: ldc           #2                  // String one
+        26: areturn
+        27: iload_0
+        28: lookupswitch  { // 1
+                      -5: 24
+                 default: 48
+            }
+        48: iinc          0, -1
+        51: ldc2_w        #3                  // double 2.5d
+        54: pop2
+        55: aconst_null
+        56: areturn
+        57: athrow
+      Exception table:
+         from    to  target type
+             0    57    57   Class java/lang/RuntimeException
+      LineNumberTable:
+        line 7: 0
+
+}
+SourceFile: "Switches.java"
+"#;
+        assert_eq!(expected, disassemble(&class));
+    }
+
+    #[test]
+    fn can_disassemble_wide_instructions_and_exception_tables() {
+        let class = assemble(
+            r#"
+        .source Wide.java
+        .class public super rjvm/Wide
+        .method public static sum([I)I
+            .limit stack 3
+            .limit locals 300
+            .line 5
+            iconst_0
+            istore 299
+            iconst_0
+            istore_1
+        Loop:
+            iload_1
+            aload_0
+            arraylength
+            if_icmpge End
+        Start:
+            iload 299
+            aload_0
+            iload_1
+            iaload
+            iadd
+            istore 299
+            wide iinc 1 1
+            iinc 299 -200
+        Finish:
+            goto Loop
+        Handler:
+            astore_2
+            aload_2
+            athrow
+        Cleanup:
+            pop
+            iconst_m1
+            ireturn
+        End:
+            iload 299
+            lookupswitch
+                -1 : Cleanup
+                0 : Loop
+                1000 : End
+                default : Done
+        Done:
+            iload 299
+            ireturn
+            .catch java/lang/ArrayIndexOutOfBoundsException from Start to Finish using Handler
+            .catch all from Start to Finish using Cleanup
+        .end method
+            "#,
+        )
+        .unwrap();
+        // Read the class back, to have the complete constant pool
+        let class = read_buffer(&write(&class).unwrap()).unwrap();
+
+        let expected = r#"public class rjvm.Wide extends java.lang.Object
+  minor version: 0
+  major version: 49
+  flags: (0x0021) ACC_PUBLIC, ACC_SUPER
+Constant pool:
+   #1 = Utf8               rjvm/Wide
+   #2 = Class              #1             // rjvm/Wide
+   #3 = Utf8               java/lang/Object
+   #4 = Class              #3             // java/lang/Object
+   #5 = Utf8               sum
+   #6 = Utf8               ([I)I
+   #7 = Utf8               java/lang/ArrayIndexOutOfBoundsException
+   #8 = Class              #7             // java/lang/ArrayIndexOutOfBoundsException
+   #9 = Utf8               LineNumberTable
+  #10 = Utf8               Code
+  #11 = Utf8               Wide.java
+  #12 = Utf8               SourceFile
+{
+  public static int sum(int[]);
+    descriptor: ([I)I
+    flags: (0x0009) ACC_PUBLIC, ACC_STATIC
+    Code:
+      stack=3, locals=300, args_size=1
+         0: iconst_0
+         1: istore_w      299
+         5: iconst_0
+         6: istore_1
+         7: iload_1
+         8: aload_0
+         9: arraylength
+        10: if_icmpge     46
+        13: iload_w       299
+        17: aload_0
+        18: iload_1
+        19: iaload
+        20: iadd
+        21: istore_w      299
+        25: iinc_w        1, 1
+        31: iinc_w        299, -200
+        37: goto          7
+        40: astore_2
+        41: aload_2
+        42: athrow
+        43: pop
+        44: iconst_m1
+        45: ireturn
+        46: iload_w       299
+        50: lookupswitch  { // 3
+                      -1: 43
+                       0: 7
+                    1000: 46
+                 default: 84
+            }
+        84: iload_w       299
+        88: ireturn
+      Exception table:
+         from    to  target type
+            13    37    40   Class java/lang/ArrayIndexOutOfBoundsException
+            13    37    43   any
+      LineNumberTable:
+        line 5: 0
+
+}
+SourceFile: "Wide.java"
+"#;
+        assert_eq!(expected, disassemble(&class));
+    }
+
+    #[test]
+    fn cycles_of_constants_are_disassembled_as_invalid() {
+        let mut class = ClassFile::default();
+        class
+            .constants
+            .add(ConstantPoolEntry::MethodReference(2, 3));
+        class.constants.add(ConstantPoolEntry::ClassReference(4));
+        class
+            .constants
+            .add(ConstantPoolEntry::NameAndTypeDescriptor(1, 4));
+        class
+            .constants
+            .add(ConstantPoolEntry::Utf8("x".to_string()));
+
+        let disassembly = disassemble(&class);
+        assert!(
+            disassembly.contains(
+                r#"#3 = NameAndType        #1:#4          // x."<invalid #2>.<invalid #3>":x:x"#
+            ),
+            "{disassembly}"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt;
+
+/// The content of the `EnclosingMethod` attribute, which is present
+/// only in local and anonymous classes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnclosingMethod {
+    /// The innermost class that encloses the declaration of this class
+    pub class_name: String,
+    /// The method that contains the declaration of this class. Missing if the class
+    /// is declared in an initializer of a field or in a static block.
+    pub method_name: Option<String>,
+    /// The descriptor of the enclosing method, set whenever `method_name` is
+    pub method_type_descriptor: Option<String>,
+}
+
+impl fmt::Display for EnclosingMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.class_name)?;
+        if let (Some(method_name), Some(method_type_descriptor)) =
+            (&self.method_name, &self.method_type_descriptor)
+        {
+            write!(f, ".{method_name}: {method_type_descriptor}")?;
+        }
+        Ok(())
+    }
+}
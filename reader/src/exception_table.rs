@@ -4,6 +4,7 @@ use crate::program_counter::ProgramCounter;
 
 /// Exception table of a method's code
 #[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExceptionTable {
     entries: Vec<ExceptionTableEntry>,
 }
@@ -19,10 +20,15 @@ impl ExceptionTable {
             .filter(|entry| entry.range.contains(&pc))
             .collect()
     }
+
+    pub fn entries(&self) -> &[ExceptionTableEntry] {
+        &self.entries
+    }
 }
 
 /// Entries of the exception table
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExceptionTableEntry {
     /// The range of program counters that this entry covers
     pub range: Range<ProgramCounter>,
@@ -18,3 +18,6 @@ impl Default for FieldFlags {
         FieldFlags::empty()
     }
 }
+
+#[cfg(feature = "serde")]
+crate::flags_serde::impl_serde_for_flags!(FieldFlags);
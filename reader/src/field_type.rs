@@ -8,6 +8,7 @@ use crate::class_reader_error::ClassReaderError;
 
 /// Models the type of one field, or one parameter of a method
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FieldType {
     /// Primitive types
     Base(BaseType),
@@ -29,8 +30,12 @@ impl fmt::Display for FieldType {
     }
 }
 
+/// Arrays cannot have more dimensions than this, according to the JVM specs
+pub const MAX_ARRAY_DIMENSIONS: usize = 255;
+
 /// Possible primitive types
 #[derive(Debug, Clone, PartialEq, strum_macros::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum BaseType {
     Byte,
@@ -80,12 +85,39 @@ impl FieldType {
                 }
             }
             '[' => {
-                let component_type = Self::parse_from(type_descriptor, chars)?;
-                FieldType::Array(Box::new(component_type))
+                let mut dimensions = 1;
+                while chars.clone().next() == Some('[') {
+                    chars.next();
+                    dimensions += 1;
+                }
+                if dimensions > MAX_ARRAY_DIMENSIONS {
+                    return Err(InvalidTypeDescriptor(type_descriptor.to_string()));
+                }
+                let mut field_type = Self::parse_from(type_descriptor, chars)?;
+                for _ in 0..dimensions {
+                    field_type = FieldType::Array(Box::new(field_type));
+                }
+                field_type
             }
             _ => return Err(InvalidTypeDescriptor(type_descriptor.to_string())),
         })
     }
+
+    /// Returns the type descriptor in the JVM internal form, i.e. the inverse of [FieldType::parse]
+    pub fn descriptor(&self) -> String {
+        match self {
+            FieldType::Base(BaseType::Byte) => "B".to_string(),
+            FieldType::Base(BaseType::Char) => "C".to_string(),
+            FieldType::Base(BaseType::Double) => "D".to_string(),
+            FieldType::Base(BaseType::Float) => "F".to_string(),
+            FieldType::Base(BaseType::Int) => "I".to_string(),
+            FieldType::Base(BaseType::Long) => "J".to_string(),
+            FieldType::Base(BaseType::Short) => "S".to_string(),
+            FieldType::Base(BaseType::Boolean) => "Z".to_string(),
+            FieldType::Object(class_name) => format!("L{class_name};"),
+            FieldType::Array(component_type) => format!("[{}", component_type.descriptor()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -127,6 +159,16 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn cannot_parse_arrays_with_too_many_dimensions() {
+        let descriptor = format!("{}I", "[".repeat(256));
+        assert!(matches!(
+            FieldType::parse(&descriptor),
+            Err(ClassReaderError::InvalidTypeDescriptor(s)) if s == descriptor
+        ));
+        assert!(FieldType::parse(&descriptor[1..]).is_ok());
+    }
+
     #[test]
     fn can_parse_primitive_descriptors() {
         assert_eq!(Ok(FieldType::Base(BaseType::Byte)), FieldType::parse("B"));
@@ -188,4 +230,25 @@ mod tests {
     fn can_format_array() {
         assert_eq!("Int[]", format!("{}", FieldType::parse("[I").unwrap()));
     }
+
+    #[test]
+    fn descriptor_is_the_inverse_of_parse() {
+        for descriptor in [
+            "B",
+            "C",
+            "D",
+            "F",
+            "I",
+            "J",
+            "S",
+            "Z",
+            "Ljava/lang/String;",
+            "[[I",
+        ] {
+            assert_eq!(
+                descriptor,
+                FieldType::parse(descriptor).unwrap().descriptor()
+            );
+        }
+    }
 }
@@ -0,0 +1,50 @@
+/// Returns the names of the flags set in the given bits, given a function that returns
+/// the name of a single flag
+pub(crate) fn flag_names(bits: u16, name_of: impl Fn(u16) -> Option<String>) -> Vec<String> {
+    (0..u16::BITS)
+        .map(|shift| 1 << shift)
+        .filter(|bit| bits & bit != 0)
+        .filter_map(name_of)
+        .collect()
+}
+
+/// The inverse of [flag_names]
+pub(crate) fn flag_bits<E: serde::de::Error>(
+    names: &[String],
+    name_of: impl Fn(u16) -> Option<String>,
+) -> Result<u16, E> {
+    names.iter().try_fold(0, |bits, name| {
+        (0..u16::BITS)
+            .map(|shift| 1 << shift)
+            .find(|bit| name_of(*bit).as_ref() == Some(name))
+            .map(|bit| bits | bit)
+            .ok_or_else(|| E::custom(format!("unknown flag: {name}")))
+    })
+}
+
+/// Implements `Serialize` and `Deserialize` for a type declared with `bitflags!`, as the
+/// list of the names of its flags, i.e. `["PUBLIC", "STATIC"]`. Relies on all the flags
+/// being a single bit, and on their debug format being their name.
+macro_rules! impl_serde_for_flags {
+    ($flags:ident) => {
+        impl serde::Serialize for $flags {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_seq(crate::flags_serde::flag_names(self.bits(), |bit| {
+                    Self::from_bits(bit).map(|flag| format!("{flag:?}"))
+                }))
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $flags {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let names = Vec::<String>::deserialize(deserializer)?;
+                let bits = crate::flags_serde::flag_bits(&names, |bit| {
+                    Self::from_bits(bit).map(|flag| format!("{flag:?}"))
+                })?;
+                Ok(Self::from_bits_truncate(bits))
+            }
+        }
+    };
+}
+
+pub(crate) use impl_serde_for_flags;
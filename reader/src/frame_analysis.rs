@@ -0,0 +1,1927 @@
+//! Infers the types of the local variables and of the operand stack before each instruction
+//! of a method, by simulating the instructions with a [crate::dataflow] analysis.
+//!
+//! The results can be used to recompute `max_stack`, `max_locals` and the `StackMapTable`
+//! of a method after its bytecode has been modified, before writing it with
+//! [crate::class_writer]; see [recompute_frames].
+//!
+//! The simulation of the instructions is implemented by [FrameInterpreter], which is also
+//! the engine of the bytecode verifier of the vm. The analysis of this module assumes that
+//! the code is valid: it only checks what it needs to compute the frames, such as the height
+//! of the stack, but not that the operands have the correct type. The verifier enables
+//! those checks through [FrameChecks].
+//! The analysis does not support subroutines (`jsr` and `ret`), since they cannot be
+//! described by a `StackMapTable` anyway.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::{
+    class_access_flags::ClassAccessFlags,
+    class_file::ClassFile,
+    class_file_method::ClassFileMethod,
+    class_file_version::ClassFileVersion,
+    class_writer_error::{ClassWriterError, Result},
+    constant_pool::ConstantPoolEntry,
+    control_flow_graph::{ControlFlowGraph, EdgeKind},
+    dataflow::{analyze, DataflowAnalysis},
+    field_type::{BaseType, FieldType},
+    instruction::{Instruction, NewArrayType, WideInstruction},
+    method_descriptor::MethodDescriptor,
+    program_counter::ProgramCounter,
+    stack_map_table::{StackMapFrame, StackMapFrameKind, VerificationType},
+};
+
+/// Provides the information about the class hierarchy needed to merge and compare object
+/// types. Implementations might need to load or parse the classes, hence `&mut self`.
+pub trait ClassHierarchy {
+    /// The error returned when a class cannot be looked up
+    type Error;
+
+    fn lookup_class(
+        &mut self,
+        class_name: &str,
+    ) -> std::result::Result<ClassHierarchyEntry, Self::Error>;
+}
+
+/// What a [ClassHierarchy] knows about a class
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassHierarchyEntry {
+    pub superclass: Option<String>,
+    pub is_interface: bool,
+}
+
+/// A [ClassHierarchy] that does not know any class, so it assumes that they all extend
+/// `java/lang/Object` directly. This is enough for methods where no two different classes meet.
+pub struct UnknownClassHierarchy;
+
+impl ClassHierarchy for UnknownClassHierarchy {
+    type Error = ClassWriterError;
+
+    fn lookup_class(&mut self, class_name: &str) -> Result<ClassHierarchyEntry> {
+        Ok(ClassHierarchyEntry {
+            superclass: (class_name != OBJECT).then(|| OBJECT.to_string()),
+            is_interface: false,
+        })
+    }
+}
+
+/// The types of the local variables and of the operand stack before an instruction.
+/// Locals of type `long` and `double` occupy two slots, the second one being `top`;
+/// while on the stack they are stored as a single entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub locals: Vec<VerificationType>,
+    pub stack: Vec<VerificationType>,
+    /// Set in constructors until the superclass constructor has been invoked
+    pub this_uninitialized: bool,
+}
+
+impl Frame {
+    /// Creates a frame from locals where `long` and `double` take a single entry, as in
+    /// the `StackMapTable`
+    pub fn from_compact_locals(
+        compact_locals: &[VerificationType],
+        stack: Vec<VerificationType>,
+    ) -> Self {
+        let mut frame = Self {
+            locals: Vec::new(),
+            stack,
+            this_uninitialized: compact_locals.contains(&VerificationType::UninitializedThis),
+        };
+        for local in compact_locals {
+            frame.set_local(frame.locals.len(), local.clone());
+        }
+        frame
+    }
+
+    /// Returns the number of words used by the stack
+    pub fn stack_size(&self) -> usize {
+        self.stack.iter().map(size_of).sum()
+    }
+
+    /// Returns the locals in the form used by the `StackMapTable`, where `long` and `double`
+    /// take a single entry, without the trailing `top` entries
+    fn compact_locals(&self) -> Vec<VerificationType> {
+        let mut locals = Vec::new();
+        let mut index = 0;
+        while index < self.locals.len() {
+            let local = &self.locals[index];
+            locals.push(local.clone());
+            index += size_of(local);
+        }
+        while locals.last() == Some(&VerificationType::Top) {
+            locals.pop();
+        }
+        locals
+    }
+
+    /// Stores a value in a local variable, adding the missing locals if needed
+    pub fn set_local(&mut self, index: usize, value: VerificationType) {
+        let size = size_of(&value);
+        if self.locals.len() < index + size {
+            self.locals.resize(index + size, VerificationType::Top);
+        }
+        // Overwriting the second half of a long or double invalidates it
+        if index > 0 && is_category_2(&self.locals[index - 1]) {
+            self.locals[index - 1] = VerificationType::Top;
+        }
+        if size == 2 {
+            self.locals[index + 1] = VerificationType::Top;
+        }
+        self.locals[index] = value;
+    }
+
+    /// Replaces all occurrences of an uninitialized type, after its constructor was invoked
+    fn initialize(&mut self, uninitialized: &VerificationType, initialized: VerificationType) {
+        for value in self.locals.iter_mut().chain(self.stack.iter_mut()) {
+            if value == uninitialized {
+                *value = initialized.clone();
+            }
+        }
+    }
+}
+
+/// The frame with which an exception handler is entered from an instruction
+pub fn exception_frame(frame: &Frame, catch_class: Option<&str>) -> Frame {
+    Frame {
+        locals: frame.locals.clone(),
+        stack: vec![VerificationType::Object(
+            catch_class.unwrap_or(THROWABLE).to_string(),
+        )],
+        this_uninitialized: frame.this_uninitialized,
+    }
+}
+
+/// What can happen after executing an instruction
+pub struct Successors {
+    pub falls_through: bool,
+    /// The addresses to which the instruction can jump
+    pub jumps: Vec<u16>,
+}
+
+impl Successors {
+    fn next() -> Self {
+        Self {
+            falls_through: true,
+            jumps: Vec::new(),
+        }
+    }
+
+    fn none() -> Self {
+        Self {
+            falls_through: false,
+            jumps: Vec::new(),
+        }
+    }
+
+    fn branch(target: u16) -> Self {
+        Self {
+            falls_through: true,
+            jumps: vec![target],
+        }
+    }
+
+    fn jump(targets: Vec<u16>) -> Self {
+        Self {
+            falls_through: false,
+            jumps: targets,
+        }
+    }
+}
+
+/// Customizes what [FrameInterpreter] checks, and how it reports errors
+pub trait FrameChecks {
+    type Error;
+
+    /// Returns the error reported for invalid code
+    fn invalid(&self, message: String) -> Self::Error;
+
+    /// Returns the hierarchy used to merge and compare object types
+    fn hierarchy(&mut self) -> &mut dyn ClassHierarchy<Error = Self::Error>;
+
+    /// Whether the operands must have the types expected by the instructions. Otherwise, the
+    /// code is trusted, and only the types of the values produced by the instructions are
+    /// computed.
+    fn checks_operand_types(&self) -> bool;
+
+    /// Called after values are pushed or local variables are stored
+    fn check_size(&mut self, frame: &Frame) -> std::result::Result<(), Self::Error>;
+}
+
+/// Simulates the instructions of a method on [Frame]s
+pub struct FrameInterpreter<'a, C> {
+    class_file: &'a ClassFile,
+    method: &'a ClassFileMethod,
+    instructions: Vec<(usize, Instruction)>,
+    index_by_pc: HashMap<usize, usize>,
+    code_length: usize,
+    pub checks: C,
+}
+
+impl<'a, C: FrameChecks> FrameInterpreter<'a, C> {
+    pub fn new(
+        class_file: &'a ClassFile,
+        method: &'a ClassFileMethod,
+        checks: C,
+    ) -> std::result::Result<Self, C::Error> {
+        let Some(code) = &method.code else {
+            return Err(checks.invalid(format!("method {} has no code", method.name)));
+        };
+        let instructions = match Instruction::parse_instructions(&code.code) {
+            Ok(instructions) if !instructions.is_empty() => instructions,
+            Ok(_) => return Err(checks.invalid("empty code".to_string())),
+            Err(err) => return Err(checks.invalid(err.to_string())),
+        };
+        let index_by_pc = instructions
+            .iter()
+            .enumerate()
+            .map(|(index, (pc, _))| (*pc, index))
+            .collect();
+        Ok(Self {
+            class_file,
+            method,
+            instructions,
+            index_by_pc,
+            code_length: code.code.len(),
+            checks,
+        })
+    }
+
+    /// Returns the instructions of the method, along with their address
+    pub fn instructions(&self) -> &[(usize, Instruction)] {
+        &self.instructions
+    }
+
+    /// Returns the position in [Self::instructions] of the instruction at the given address
+    pub fn instruction_index(&self, pc: usize) -> std::result::Result<usize, C::Error> {
+        match self.index_by_pc.get(&pc) {
+            Some(index) => Ok(*index),
+            None => self.error(format!("pc {pc} is not the start of an instruction")),
+        }
+    }
+
+    /// Returns the address of the instruction following the one at the given address, which
+    /// is the length of the code for the last instruction
+    pub fn next_pc(&self, pc: usize) -> std::result::Result<usize, C::Error> {
+        let index = self.instruction_index(pc)?;
+        Ok(self
+            .instructions
+            .get(index + 1)
+            .map_or(self.code_length, |(next_pc, _)| *next_pc))
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> std::result::Result<T, C::Error> {
+        Err(self.checks.invalid(message.into()))
+    }
+
+    fn is_constructor(&self) -> bool {
+        self.method.name == "<init>"
+    }
+
+    /// Returns the types of the `this` argument and of the method parameters, where
+    /// `long` and `double` values are a single entry
+    pub fn parameters_types(&self) -> Vec<VerificationType> {
+        let mut locals = Vec::new();
+        if !self.method.is_static() {
+            if self.is_constructor() && self.class_file.superclass.is_some() {
+                locals.push(VerificationType::UninitializedThis);
+            } else {
+                locals.push(VerificationType::Object(self.class_file.name.clone()));
+            }
+        }
+        locals.extend(
+            self.method
+                .parsed_type_descriptor
+                .parameters
+                .iter()
+                .map(VerificationType::from),
+        );
+        locals
+    }
+
+    /// Returns the frame at the start of the method
+    pub fn initial_frame(&mut self) -> std::result::Result<Frame, C::Error> {
+        let frame = Frame::from_compact_locals(&self.parameters_types(), Vec::new());
+        self.checks.check_size(&frame)?;
+        Ok(frame)
+    }
+
+    /// Merges two frames reaching the same instruction from different paths
+    pub fn merge_frames(
+        &mut self,
+        first: &Frame,
+        second: &Frame,
+    ) -> std::result::Result<Frame, C::Error> {
+        if first.stack.len() != second.stack.len() {
+            return self.error("inconsistent stack height");
+        }
+        let mut locals = Vec::with_capacity(first.locals.len().max(second.locals.len()));
+        for index in 0..first.locals.len().max(second.locals.len()) {
+            locals.push(match (first.locals.get(index), second.locals.get(index)) {
+                (Some(first), Some(second)) => self.merge_types(first, second)?,
+                _ => VerificationType::Top,
+            });
+        }
+        let mut stack = Vec::with_capacity(first.stack.len());
+        for (first, second) in first.stack.iter().zip(second.stack.iter()) {
+            let merged = self.merge_types(first, second)?;
+            if merged == VerificationType::Top {
+                return self.error(format!("mismatched stack types {first} and {second}"));
+            }
+            stack.push(merged);
+        }
+        Ok(Frame {
+            locals,
+            stack,
+            this_uninitialized: first.this_uninitialized || second.this_uninitialized,
+        })
+    }
+
+    /// Returns the most specific type to which both types are assignable
+    fn merge_types(
+        &mut self,
+        first: &VerificationType,
+        second: &VerificationType,
+    ) -> std::result::Result<VerificationType, C::Error> {
+        Ok(match (first, second) {
+            _ if first == second => first.clone(),
+            (VerificationType::Null, VerificationType::Object(_)) => second.clone(),
+            (VerificationType::Object(_), VerificationType::Null) => first.clone(),
+            (VerificationType::Object(first), VerificationType::Object(second)) => {
+                VerificationType::Object(self.common_superclass(first, second)?)
+            }
+            _ => VerificationType::Top,
+        })
+    }
+
+    fn common_superclass(
+        &mut self,
+        first: &str,
+        second: &str,
+    ) -> std::result::Result<String, C::Error> {
+        if first.starts_with('[') || second.starts_with('[') {
+            let first_component = first.strip_prefix('[').map(|c| self.component_type(c));
+            let second_component = second.strip_prefix('[').map(|c| self.component_type(c));
+            if let (Some(first_component), Some(second_component)) =
+                (first_component, second_component)
+            {
+                if let (VerificationType::Object(first), VerificationType::Object(second)) =
+                    (first_component?, second_component?)
+                {
+                    return Ok(array_of(&self.common_superclass(&first, &second)?));
+                }
+            }
+            return Ok(OBJECT.to_string());
+        }
+
+        if self.is_interface(first)? || self.is_interface(second)? {
+            return Ok(OBJECT.to_string());
+        }
+        let first_superclasses = self.superclasses(first)?;
+        let mut current = Some(second.to_string());
+        while let Some(class_name) = current {
+            if first_superclasses.contains(&class_name) {
+                return Ok(class_name);
+            }
+            current = self.lookup_class(&class_name)?.superclass;
+        }
+        Ok(OBJECT.to_string())
+    }
+
+    /// Returns the given class and all its superclasses
+    fn superclasses(&mut self, class_name: &str) -> std::result::Result<HashSet<String>, C::Error> {
+        let mut superclasses = HashSet::new();
+        let mut current = Some(class_name.to_string());
+        while let Some(class_name) = current {
+            if !superclasses.insert(class_name.clone()) {
+                return self.error(format!("circular class hierarchy for {class_name}"));
+            }
+            current = self.lookup_class(&class_name)?.superclass;
+        }
+        Ok(superclasses)
+    }
+
+    /// Looks up a class in the hierarchy, except for the class of the method, which might
+    /// not be known to the hierarchy yet
+    fn lookup_class(
+        &mut self,
+        class_name: &str,
+    ) -> std::result::Result<ClassHierarchyEntry, C::Error> {
+        if class_name == self.class_file.name {
+            Ok(ClassHierarchyEntry {
+                superclass: self.class_file.superclass.clone(),
+                is_interface: self.class_file.flags.contains(ClassAccessFlags::INTERFACE),
+            })
+        } else {
+            self.checks.hierarchy().lookup_class(class_name)
+        }
+    }
+
+    fn is_interface(&mut self, class_name: &str) -> std::result::Result<bool, C::Error> {
+        Ok(self.lookup_class(class_name)?.is_interface)
+    }
+
+    /// Checks whether a value of type `from` can be assigned to a variable of type `to`
+    pub fn is_assignable(
+        &mut self,
+        from: &VerificationType,
+        to: &VerificationType,
+    ) -> std::result::Result<bool, C::Error> {
+        Ok(match (from, to) {
+            _ if from == to => true,
+            (_, VerificationType::Top) => true,
+            (VerificationType::Null, VerificationType::Object(_)) => true,
+            (VerificationType::Object(from), VerificationType::Object(to)) => {
+                self.is_class_assignable(from, to)?
+            }
+            _ => false,
+        })
+    }
+
+    /// Checks whether a reference of type `from` can be assigned to a variable of type `to`.
+    /// As the specs require, interfaces are treated like `java.lang.Object`.
+    fn is_class_assignable(&mut self, from: &str, to: &str) -> std::result::Result<bool, C::Error> {
+        if from == to || to == OBJECT {
+            return Ok(true);
+        }
+        if let Some(to_component) = to.strip_prefix('[') {
+            let Some(from_component) = from.strip_prefix('[') else {
+                return Ok(false);
+            };
+            return match (
+                self.component_type(from_component)?,
+                self.component_type(to_component)?,
+            ) {
+                (VerificationType::Object(from), VerificationType::Object(to)) => {
+                    self.is_class_assignable(&from, &to)
+                }
+                _ => Ok(from_component == to_component),
+            };
+        }
+        if from.starts_with('[') {
+            return Ok(to == "java/lang/Cloneable" || to == "java/io/Serializable");
+        }
+        if self.is_interface(to)? {
+            return Ok(true);
+        }
+        Ok(self.superclasses(from)?.contains(to))
+    }
+
+    /// Checks that a value of type `from` can be used where `to` is expected, unless the
+    /// operand types are not checked
+    pub fn check_assignable(
+        &mut self,
+        from: &VerificationType,
+        to: &VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        if !self.checks.checks_operand_types() || self.is_assignable(from, to)? {
+            Ok(())
+        } else {
+            self.error(format!("expected {to} but found {from}"))
+        }
+    }
+
+    /// Returns the type of the elements of an array type, given its descriptor component
+    fn component_type(
+        &self,
+        component_descriptor: &str,
+    ) -> std::result::Result<VerificationType, C::Error> {
+        match FieldType::parse(component_descriptor) {
+            Ok(field_type) => Ok(VerificationType::from(&field_type)),
+            Err(_) => self.error(format!(
+                "invalid array component type {component_descriptor}"
+            )),
+        }
+    }
+
+    fn push(
+        &mut self,
+        frame: &mut Frame,
+        value: VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        frame.stack.push(value);
+        self.checks.check_size(frame)
+    }
+
+    fn push_all(
+        &mut self,
+        frame: &mut Frame,
+        groups: &[&[VerificationType]],
+    ) -> std::result::Result<(), C::Error> {
+        for group in groups {
+            for value in group.iter() {
+                self.push(frame, value.clone())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn pop(&self, frame: &mut Frame) -> std::result::Result<VerificationType, C::Error> {
+        match frame.stack.pop() {
+            Some(value) => Ok(value),
+            None => self.error("attempt to pop an empty stack"),
+        }
+    }
+
+    fn pop_expecting(
+        &mut self,
+        frame: &mut Frame,
+        expected: &VerificationType,
+    ) -> std::result::Result<VerificationType, C::Error> {
+        let value = self.pop(frame)?;
+        self.check_assignable(&value, expected)?;
+        Ok(value)
+    }
+
+    fn pop_reference(&self, frame: &mut Frame) -> std::result::Result<VerificationType, C::Error> {
+        let value = self.pop(frame)?;
+        if is_reference(&value) || !self.checks.checks_operand_types() {
+            Ok(value)
+        } else {
+            self.error(format!("expected a reference but found {value}"))
+        }
+    }
+
+    /// Pops the given number of words, which must not split a `long` or `double` value.
+    /// Returns the values in stack order, i.e. with the top of the stack last.
+    fn pop_words(
+        &self,
+        frame: &mut Frame,
+        words: usize,
+    ) -> std::result::Result<Vec<VerificationType>, C::Error> {
+        let mut values = Vec::new();
+        let mut size = 0;
+        while size < words {
+            let value = self.pop(frame)?;
+            size += size_of(&value);
+            values.insert(0, value);
+        }
+        if size == words {
+            Ok(values)
+        } else {
+            self.error("cannot split a long or double value on the stack")
+        }
+    }
+
+    fn local<'f>(
+        &self,
+        frame: &'f Frame,
+        index: u16,
+    ) -> std::result::Result<&'f VerificationType, C::Error> {
+        match frame.locals.get(index as usize) {
+            Some(value) => Ok(value),
+            None => self.error(format!("invalid local variable index {index}")),
+        }
+    }
+
+    fn set_local(
+        &mut self,
+        frame: &mut Frame,
+        index: u16,
+        value: VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        frame.set_local(index as usize, value);
+        self.checks.check_size(frame)
+    }
+
+    fn load(
+        &mut self,
+        frame: &mut Frame,
+        index: u16,
+        expected: VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        let value = self.local(frame, index)?;
+        if *value != expected && self.checks.checks_operand_types() {
+            return self.error(format!(
+                "expected {expected} in local variable {index} but found {value}"
+            ));
+        }
+        self.push(frame, expected)
+    }
+
+    fn load_reference(
+        &mut self,
+        frame: &mut Frame,
+        index: u16,
+    ) -> std::result::Result<(), C::Error> {
+        let value = self.local(frame, index)?.clone();
+        if !is_reference(&value) && self.checks.checks_operand_types() {
+            return self.error(format!(
+                "expected a reference in local variable {index} but found {value}"
+            ));
+        }
+        self.push(frame, value)
+    }
+
+    fn store(
+        &mut self,
+        frame: &mut Frame,
+        index: u16,
+        expected: VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        self.pop_expecting(frame, &expected)?;
+        self.set_local(frame, index, expected)
+    }
+
+    /// Stores a reference, or the return address pushed by `jsr`
+    fn store_reference(
+        &mut self,
+        frame: &mut Frame,
+        index: u16,
+    ) -> std::result::Result<(), C::Error> {
+        let value = match frame.stack.last() {
+            Some(VerificationType::ReturnAddress(_)) => self.pop(frame)?,
+            _ => self.pop_reference(frame)?,
+        };
+        self.set_local(frame, index, value)
+    }
+
+    fn ret(&self, frame: &Frame, index: u16) -> std::result::Result<(), C::Error> {
+        match self.local(frame, index)? {
+            VerificationType::ReturnAddress(_) => Ok(()),
+            value => self.error(format!(
+                "expected a return address in local variable {index} but found {value}"
+            )),
+        }
+    }
+
+    fn iinc(&self, frame: &Frame, index: u16) -> std::result::Result<(), C::Error> {
+        let value = self.local(frame, index)?;
+        if *value != VerificationType::Integer && self.checks.checks_operand_types() {
+            return self.error(format!(
+                "expected int in local variable {index} but found {value}"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Pops the operand of type `operand` and pushes the result of type `result`
+    fn unary(
+        &mut self,
+        frame: &mut Frame,
+        operand: VerificationType,
+        result: VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        self.pop_expecting(frame, &operand)?;
+        self.push(frame, result)
+    }
+
+    fn binary(
+        &mut self,
+        frame: &mut Frame,
+        operands: VerificationType,
+        result: VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        self.pop_expecting(frame, &operands)?;
+        self.pop_expecting(frame, &operands)?;
+        self.push(frame, result)
+    }
+
+    fn shift(
+        &mut self,
+        frame: &mut Frame,
+        value: VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        self.pop_expecting(frame, &VerificationType::Integer)?;
+        self.pop_expecting(frame, &value)?;
+        self.push(frame, value)
+    }
+
+    /// Pops an array reference, returning the descriptor of its elements, or `None`
+    /// if the reference is `null`
+    fn pop_array(&self, frame: &mut Frame) -> std::result::Result<Option<String>, C::Error> {
+        match self.pop(frame)? {
+            VerificationType::Null => Ok(None),
+            VerificationType::Object(name) if name.starts_with('[') => {
+                Ok(Some(name[1..].to_string()))
+            }
+            _ if !self.checks.checks_operand_types() => Ok(None),
+            value => self.error(format!("expected an array but found {value}")),
+        }
+    }
+
+    fn array_load(
+        &mut self,
+        frame: &mut Frame,
+        components: &[&str],
+        result: VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        self.pop_expecting(frame, &VerificationType::Integer)?;
+        self.check_array_component(frame, components)?;
+        self.push(frame, result)
+    }
+
+    fn array_store(
+        &mut self,
+        frame: &mut Frame,
+        components: &[&str],
+        value: VerificationType,
+    ) -> std::result::Result<(), C::Error> {
+        self.pop_expecting(frame, &value)?;
+        self.pop_expecting(frame, &VerificationType::Integer)?;
+        self.check_array_component(frame, components)
+    }
+
+    fn check_array_component(
+        &self,
+        frame: &mut Frame,
+        components: &[&str],
+    ) -> std::result::Result<(), C::Error> {
+        match self.pop_array(frame)? {
+            Some(component) if !components.contains(&component.as_str()) => self.error(format!(
+                "expected an array of {components:?} but found [{component}"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Pops an array of references, returning the type of its elements
+    fn pop_reference_array(
+        &self,
+        frame: &mut Frame,
+    ) -> std::result::Result<VerificationType, C::Error> {
+        let Some(component) = self.pop_array(frame)? else {
+            return Ok(VerificationType::Null);
+        };
+        match self.component_type(&component)? {
+            element @ VerificationType::Object(_) => Ok(element),
+            _ => self.error(format!(
+                "expected an array of references but found [{component}"
+            )),
+        }
+    }
+
+    fn return_value(
+        &mut self,
+        frame: &mut Frame,
+        kind: &[BaseType],
+    ) -> std::result::Result<Successors, C::Error> {
+        let expected = match &self.method.parsed_type_descriptor.return_type {
+            Some(FieldType::Base(base_type)) if kind.contains(base_type) => {
+                VerificationType::from(&FieldType::Base(base_type.clone()))
+            }
+            return_type => {
+                return self.error(format!(
+                    "wrong return instruction for return type {return_type:?}"
+                ))
+            }
+        };
+        self.pop_expecting(frame, &expected)?;
+        Ok(Successors::none())
+    }
+
+    fn constant(&self, index: u16) -> std::result::Result<&'a ConstantPoolEntry, C::Error> {
+        match self.class_file.constants.get(index) {
+            Ok(entry) => Ok(entry),
+            Err(err) => self.error(err.to_string()),
+        }
+    }
+
+    fn utf8_constant(&self, index: u16) -> std::result::Result<&'a str, C::Error> {
+        match self.constant(index)? {
+            ConstantPoolEntry::Utf8(string) => Ok(string),
+            _ => self.error(format!("constant {index} should be an utf8 string")),
+        }
+    }
+
+    fn class_constant(&self, index: u16) -> std::result::Result<&'a str, C::Error> {
+        match self.constant(index)? {
+            ConstantPoolEntry::ClassReference(name_index) => self.utf8_constant(*name_index),
+            _ => self.error(format!("constant {index} should be a class reference")),
+        }
+    }
+
+    /// Returns the name and the descriptor of a name and type constant
+    fn name_and_type_constant(
+        &self,
+        index: u16,
+    ) -> std::result::Result<(&'a str, &'a str), C::Error> {
+        match self.constant(index)? {
+            ConstantPoolEntry::NameAndTypeDescriptor(name_index, type_index) => Ok((
+                self.utf8_constant(*name_index)?,
+                self.utf8_constant(*type_index)?,
+            )),
+            _ => self.error(format!("constant {index} should be a name and type")),
+        }
+    }
+
+    fn parse_field_type(&self, descriptor: &str) -> std::result::Result<FieldType, C::Error> {
+        match FieldType::parse(descriptor) {
+            Ok(field_type) => Ok(field_type),
+            Err(err) => self.error(err.to_string()),
+        }
+    }
+
+    fn parse_method_descriptor(
+        &self,
+        descriptor: &str,
+    ) -> std::result::Result<MethodDescriptor, C::Error> {
+        match MethodDescriptor::parse(descriptor) {
+            Ok(descriptor) => Ok(descriptor),
+            Err(err) => self.error(err.to_string()),
+        }
+    }
+
+    /// Returns the class name, member name and type of a field reference
+    fn field_constant(
+        &self,
+        index: u16,
+    ) -> std::result::Result<(&'a str, &'a str, VerificationType), C::Error> {
+        match self.constant(index)? {
+            ConstantPoolEntry::FieldReference(class_index, name_and_type_index) => {
+                let (name, descriptor) = self.name_and_type_constant(*name_and_type_index)?;
+                let field_type = self.parse_field_type(descriptor)?;
+                Ok((
+                    self.class_constant(*class_index)?,
+                    name,
+                    VerificationType::from(&field_type),
+                ))
+            }
+            _ => self.error(format!("constant {index} should be a field reference")),
+        }
+    }
+
+    /// Returns the class name, member name and type descriptor of a method reference
+    fn method_constant(
+        &self,
+        index: u16,
+        allow_interface_methods: bool,
+    ) -> std::result::Result<(&'a str, &'a str, MethodDescriptor), C::Error> {
+        let (class_index, name_and_type_index) = match self.constant(index)? {
+            ConstantPoolEntry::MethodReference(class_index, name_and_type_index) => {
+                (class_index, name_and_type_index)
+            }
+            ConstantPoolEntry::InterfaceMethodReference(class_index, name_and_type_index)
+                if allow_interface_methods =>
+            {
+                (class_index, name_and_type_index)
+            }
+            _ => return self.error(format!("constant {index} should be a method reference")),
+        };
+        let (name, descriptor) = self.name_and_type_constant(*name_and_type_index)?;
+        Ok((
+            self.class_constant(*class_index)?,
+            name,
+            self.parse_method_descriptor(descriptor)?,
+        ))
+    }
+
+    fn pop_arguments(
+        &mut self,
+        frame: &mut Frame,
+        descriptor: &MethodDescriptor,
+    ) -> std::result::Result<(), C::Error> {
+        for parameter in descriptor.parameters.iter().rev() {
+            self.pop_expecting(frame, &VerificationType::from(parameter))?;
+        }
+        Ok(())
+    }
+
+    fn push_return_value(
+        &mut self,
+        frame: &mut Frame,
+        descriptor: &MethodDescriptor,
+    ) -> std::result::Result<(), C::Error> {
+        match &descriptor.return_type {
+            Some(return_type) => self.push(frame, VerificationType::from(return_type)),
+            None => Ok(()),
+        }
+    }
+
+    fn invoke(
+        &mut self,
+        frame: &mut Frame,
+        instruction: &Instruction,
+        index: u16,
+    ) -> std::result::Result<(), C::Error> {
+        let allow_interface_methods = !matches!(instruction, Instruction::Invokevirtual(_));
+        let (class_name, method_name, descriptor) =
+            self.method_constant(index, allow_interface_methods)?;
+        let is_constructor = method_name == "<init>";
+        if method_name.starts_with('<')
+            && !(is_constructor && matches!(instruction, Instruction::Invokespecial(_)))
+        {
+            return self.error(format!("invalid invocation of method {method_name}"));
+        }
+        if let Instruction::Invokeinterface(_, count) = instruction {
+            let words: usize = descriptor
+                .parameters
+                .iter()
+                .map(|parameter| size_of(&VerificationType::from(parameter)))
+                .sum();
+            if *count as usize != words + 1 {
+                return self.error("invalid argument count in invokeinterface");
+            }
+        }
+
+        self.pop_arguments(frame, &descriptor)?;
+        match instruction {
+            Instruction::Invokestatic(_) => {}
+            Instruction::Invokespecial(_) if is_constructor => {
+                if descriptor.return_type.is_some() {
+                    return self.error("constructors must return void");
+                }
+                self.invoke_constructor(frame, class_name)?;
+            }
+            Instruction::Invokespecial(_) => {
+                let this_class = VerificationType::Object(self.class_file.name.clone());
+                self.pop_expecting(frame, &this_class)?;
+            }
+            _ => {
+                let class = VerificationType::Object(class_name.to_string());
+                self.pop_expecting(frame, &class)?;
+            }
+        }
+        self.push_return_value(frame, &descriptor)
+    }
+
+    fn invoke_constructor(
+        &mut self,
+        frame: &mut Frame,
+        class_name: &str,
+    ) -> std::result::Result<(), C::Error> {
+        let receiver = self.pop(frame)?;
+        match &receiver {
+            VerificationType::UninitializedThis => {
+                if class_name != self.class_file.name
+                    && Some(class_name) != self.class_file.superclass.as_deref()
+                {
+                    return self.error(format!(
+                        "bad constructor invocation of {class_name} on uninitialized this"
+                    ));
+                }
+                frame.initialize(
+                    &receiver,
+                    VerificationType::Object(self.class_file.name.clone()),
+                );
+                frame.this_uninitialized = false;
+            }
+            VerificationType::Uninitialized(new_pc) => {
+                let new_index = self.instruction_index(new_pc.0 as usize)?;
+                let Instruction::New(new_class_index) = self.instructions[new_index].1 else {
+                    return self.error(format!(
+                        "uninitialized type refers to pc {new_pc}, which is not a new instruction"
+                    ));
+                };
+                if self.class_constant(new_class_index)? != class_name {
+                    return self.error(format!(
+                        "bad constructor invocation of {class_name} on {receiver}"
+                    ));
+                }
+                frame.initialize(&receiver, VerificationType::Object(class_name.to_string()));
+            }
+            _ => {
+                return self.error(format!(
+                    "expected an uninitialized object but found {receiver}"
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn ldc(
+        &mut self,
+        frame: &mut Frame,
+        index: u16,
+        wide_value: bool,
+    ) -> std::result::Result<(), C::Error> {
+        let value = match self.constant(index)? {
+            ConstantPoolEntry::Integer(_) => VerificationType::Integer,
+            ConstantPoolEntry::Float(_) => VerificationType::Float,
+            ConstantPoolEntry::Long(_) => VerificationType::Long,
+            ConstantPoolEntry::Double(_) => VerificationType::Double,
+            ConstantPoolEntry::StringReference(_) => {
+                VerificationType::Object("java/lang/String".to_string())
+            }
+            ConstantPoolEntry::ClassReference(_) => {
+                VerificationType::Object("java/lang/Class".to_string())
+            }
+            ConstantPoolEntry::MethodType(_) => {
+                VerificationType::Object("java/lang/invoke/MethodType".to_string())
+            }
+            ConstantPoolEntry::MethodHandle(_, _) => {
+                VerificationType::Object("java/lang/invoke/MethodHandle".to_string())
+            }
+            ConstantPoolEntry::Dynamic(_, name_and_type_index) => {
+                let (_, descriptor) = self.name_and_type_constant(*name_and_type_index)?;
+                VerificationType::from(&self.parse_field_type(descriptor)?)
+            }
+            _ => return self.error(format!("constant {index} cannot be loaded")),
+        };
+        if is_category_2(&value) != wide_value {
+            return self.error(format!("wrong ldc instruction for constant {index}"));
+        }
+        self.push(frame, value)
+    }
+
+    /// Simulates the effect of the given instruction on the types of the locals and of
+    /// the operand stack, checking that its operands have the correct type if required.
+    /// The only successor of `jsr` is the subroutine, and `ret` has none: where a subroutine
+    /// returns depends on how it was called, which is left to the callers.
+    pub fn execute(
+        &mut self,
+        frame: &mut Frame,
+        pc: usize,
+        instruction: &Instruction,
+    ) -> std::result::Result<Successors, C::Error> {
+        use VerificationType::{Double, Float, Integer, Long};
+
+        match instruction {
+            Instruction::Nop => {}
+
+            Instruction::Aconst_null => self.push(frame, VerificationType::Null)?,
+            Instruction::Iconst_m1
+            | Instruction::Iconst_0
+            | Instruction::Iconst_1
+            | Instruction::Iconst_2
+            | Instruction::Iconst_3
+            | Instruction::Iconst_4
+            | Instruction::Iconst_5
+            | Instruction::Bipush(_)
+            | Instruction::Sipush(_) => self.push(frame, Integer)?,
+            Instruction::Lconst_0 | Instruction::Lconst_1 => self.push(frame, Long)?,
+            Instruction::Fconst_0 | Instruction::Fconst_1 | Instruction::Fconst_2 => {
+                self.push(frame, Float)?
+            }
+            Instruction::Dconst_0 | Instruction::Dconst_1 => self.push(frame, Double)?,
+            Instruction::Ldc(index) => self.ldc(frame, *index as u16, false)?,
+            Instruction::Ldc_w(index) => self.ldc(frame, *index, false)?,
+            Instruction::Ldc2_w(index) => self.ldc(frame, *index, true)?,
+
+            Instruction::Iload(index) => self.load(frame, *index as u16, Integer)?,
+            Instruction::Iload_0 => self.load(frame, 0, Integer)?,
+            Instruction::Iload_1 => self.load(frame, 1, Integer)?,
+            Instruction::Iload_2 => self.load(frame, 2, Integer)?,
+            Instruction::Iload_3 => self.load(frame, 3, Integer)?,
+            Instruction::Lload(index) => self.load(frame, *index as u16, Long)?,
+            Instruction::Lload_0 => self.load(frame, 0, Long)?,
+            Instruction::Lload_1 => self.load(frame, 1, Long)?,
+            Instruction::Lload_2 => self.load(frame, 2, Long)?,
+            Instruction::Lload_3 => self.load(frame, 3, Long)?,
+            Instruction::Fload(index) => self.load(frame, *index as u16, Float)?,
+            Instruction::Fload_0 => self.load(frame, 0, Float)?,
+            Instruction::Fload_1 => self.load(frame, 1, Float)?,
+            Instruction::Fload_2 => self.load(frame, 2, Float)?,
+            Instruction::Fload_3 => self.load(frame, 3, Float)?,
+            Instruction::Dload(index) => self.load(frame, *index as u16, Double)?,
+            Instruction::Dload_0 => self.load(frame, 0, Double)?,
+            Instruction::Dload_1 => self.load(frame, 1, Double)?,
+            Instruction::Dload_2 => self.load(frame, 2, Double)?,
+            Instruction::Dload_3 => self.load(frame, 3, Double)?,
+            Instruction::Aload(index) => self.load_reference(frame, *index as u16)?,
+            Instruction::Aload_0 => self.load_reference(frame, 0)?,
+            Instruction::Aload_1 => self.load_reference(frame, 1)?,
+            Instruction::Aload_2 => self.load_reference(frame, 2)?,
+            Instruction::Aload_3 => self.load_reference(frame, 3)?,
+
+            Instruction::Istore(index) => self.store(frame, *index as u16, Integer)?,
+            Instruction::Istore_0 => self.store(frame, 0, Integer)?,
+            Instruction::Istore_1 => self.store(frame, 1, Integer)?,
+            Instruction::Istore_2 => self.store(frame, 2, Integer)?,
+            Instruction::Istore_3 => self.store(frame, 3, Integer)?,
+            Instruction::Lstore(index) => self.store(frame, *index as u16, Long)?,
+            Instruction::Lstore_0 => self.store(frame, 0, Long)?,
+            Instruction::Lstore_1 => self.store(frame, 1, Long)?,
+            Instruction::Lstore_2 => self.store(frame, 2, Long)?,
+            Instruction::Lstore_3 => self.store(frame, 3, Long)?,
+            Instruction::Fstore(index) => self.store(frame, *index as u16, Float)?,
+            Instruction::Fstore_0 => self.store(frame, 0, Float)?,
+            Instruction::Fstore_1 => self.store(frame, 1, Float)?,
+            Instruction::Fstore_2 => self.store(frame, 2, Float)?,
+            Instruction::Fstore_3 => self.store(frame, 3, Float)?,
+            Instruction::Dstore(index) => self.store(frame, *index as u16, Double)?,
+            Instruction::Dstore_0 => self.store(frame, 0, Double)?,
+            Instruction::Dstore_1 => self.store(frame, 1, Double)?,
+            Instruction::Dstore_2 => self.store(frame, 2, Double)?,
+            Instruction::Dstore_3 => self.store(frame, 3, Double)?,
+            Instruction::Astore(index) => self.store_reference(frame, *index as u16)?,
+            Instruction::Astore_0 => self.store_reference(frame, 0)?,
+            Instruction::Astore_1 => self.store_reference(frame, 1)?,
+            Instruction::Astore_2 => self.store_reference(frame, 2)?,
+            Instruction::Astore_3 => self.store_reference(frame, 3)?,
+            Instruction::Iinc(index, _) => self.iinc(frame, *index as u16)?,
+
+            Instruction::Wide(wide) => match wide {
+                WideInstruction::Iload(index) => self.load(frame, *index, Integer)?,
+                WideInstruction::Lload(index) => self.load(frame, *index, Long)?,
+                WideInstruction::Fload(index) => self.load(frame, *index, Float)?,
+                WideInstruction::Dload(index) => self.load(frame, *index, Double)?,
+                WideInstruction::Aload(index) => self.load_reference(frame, *index)?,
+                WideInstruction::Istore(index) => self.store(frame, *index, Integer)?,
+                WideInstruction::Lstore(index) => self.store(frame, *index, Long)?,
+                WideInstruction::Fstore(index) => self.store(frame, *index, Float)?,
+                WideInstruction::Dstore(index) => self.store(frame, *index, Double)?,
+                WideInstruction::Astore(index) => self.store_reference(frame, *index)?,
+                WideInstruction::Iinc(index, _) => self.iinc(frame, *index)?,
+                WideInstruction::Ret(index) => {
+                    self.ret(frame, *index)?;
+                    return Ok(Successors::none());
+                }
+            },
+
+            Instruction::Iaload => self.array_load(frame, &["I"], Integer)?,
+            Instruction::Baload => self.array_load(frame, &["B", "Z"], Integer)?,
+            Instruction::Caload => self.array_load(frame, &["C"], Integer)?,
+            Instruction::Saload => self.array_load(frame, &["S"], Integer)?,
+            Instruction::Laload => self.array_load(frame, &["J"], Long)?,
+            Instruction::Faload => self.array_load(frame, &["F"], Float)?,
+            Instruction::Daload => self.array_load(frame, &["D"], Double)?,
+            Instruction::Aaload => {
+                self.pop_expecting(frame, &Integer)?;
+                let element = self.pop_reference_array(frame)?;
+                self.push(frame, element)?
+            }
+            Instruction::Iastore => self.array_store(frame, &["I"], Integer)?,
+            Instruction::Bastore => self.array_store(frame, &["B", "Z"], Integer)?,
+            Instruction::Castore => self.array_store(frame, &["C"], Integer)?,
+            Instruction::Sastore => self.array_store(frame, &["S"], Integer)?,
+            Instruction::Lastore => self.array_store(frame, &["J"], Long)?,
+            Instruction::Fastore => self.array_store(frame, &["F"], Float)?,
+            Instruction::Dastore => self.array_store(frame, &["D"], Double)?,
+            Instruction::Aastore => {
+                self.pop_expecting(frame, &object())?;
+                self.pop_expecting(frame, &Integer)?;
+                self.pop_reference_array(frame)?;
+            }
+
+            Instruction::Pop => {
+                self.pop_words(frame, 1)?;
+            }
+            Instruction::Pop2 => {
+                self.pop_words(frame, 2)?;
+            }
+            Instruction::Dup => {
+                let value = self.pop_words(frame, 1)?;
+                self.push_all(frame, &[&value, &value])?
+            }
+            Instruction::Dup_x1 => {
+                let value = self.pop_words(frame, 1)?;
+                let under = self.pop_words(frame, 1)?;
+                self.push_all(frame, &[&value, &under, &value])?
+            }
+            Instruction::Dup_x2 => {
+                let value = self.pop_words(frame, 1)?;
+                let under = self.pop_words(frame, 2)?;
+                self.push_all(frame, &[&value, &under, &value])?
+            }
+            Instruction::Dup2 => {
+                let values = self.pop_words(frame, 2)?;
+                self.push_all(frame, &[&values, &values])?
+            }
+            Instruction::Dup2_x1 => {
+                let values = self.pop_words(frame, 2)?;
+                let under = self.pop_words(frame, 1)?;
+                self.push_all(frame, &[&values, &under, &values])?
+            }
+            Instruction::Dup2_x2 => {
+                let values = self.pop_words(frame, 2)?;
+                let under = self.pop_words(frame, 2)?;
+                self.push_all(frame, &[&values, &under, &values])?
+            }
+            Instruction::Swap => {
+                let first = self.pop_words(frame, 1)?;
+                let second = self.pop_words(frame, 1)?;
+                self.push_all(frame, &[&first, &second])?
+            }
+
+            Instruction::Iadd
+            | Instruction::Isub
+            | Instruction::Imul
+            | Instruction::Idiv
+            | Instruction::Irem
+            | Instruction::Iand
+            | Instruction::Ior
+            | Instruction::Ixor => self.binary(frame, Integer, Integer)?,
+            Instruction::Ladd
+            | Instruction::Lsub
+            | Instruction::Lmul
+            | Instruction::Ldiv
+            | Instruction::Lrem
+            | Instruction::Land
+            | Instruction::Lor
+            | Instruction::Lxor => self.binary(frame, Long, Long)?,
+            Instruction::Fadd
+            | Instruction::Fsub
+            | Instruction::Fmul
+            | Instruction::Fdiv
+            | Instruction::Frem => self.binary(frame, Float, Float)?,
+            Instruction::Dadd
+            | Instruction::Dsub
+            | Instruction::Dmul
+            | Instruction::Ddiv
+            | Instruction::Drem => self.binary(frame, Double, Double)?,
+            Instruction::Ishl | Instruction::Ishr | Instruction::Iushr => {
+                self.shift(frame, Integer)?
+            }
+            Instruction::Lshl | Instruction::Lshr | Instruction::Lushr => {
+                self.shift(frame, Long)?
+            }
+            Instruction::Ineg => self.unary(frame, Integer, Integer)?,
+            Instruction::Lneg => self.unary(frame, Long, Long)?,
+            Instruction::Fneg => self.unary(frame, Float, Float)?,
+            Instruction::Dneg => self.unary(frame, Double, Double)?,
+            Instruction::Lcmp => self.binary(frame, Long, Integer)?,
+            Instruction::Fcmpl | Instruction::Fcmpg => self.binary(frame, Float, Integer)?,
+            Instruction::Dcmpl | Instruction::Dcmpg => self.binary(frame, Double, Integer)?,
+
+            Instruction::I2l => self.unary(frame, Integer, Long)?,
+            Instruction::I2f => self.unary(frame, Integer, Float)?,
+            Instruction::I2d => self.unary(frame, Integer, Double)?,
+            Instruction::I2b | Instruction::I2c | Instruction::I2s => {
+                self.unary(frame, Integer, Integer)?
+            }
+            Instruction::L2i => self.unary(frame, Long, Integer)?,
+            Instruction::L2f => self.unary(frame, Long, Float)?,
+            Instruction::L2d => self.unary(frame, Long, Double)?,
+            Instruction::F2i => self.unary(frame, Float, Integer)?,
+            Instruction::F2l => self.unary(frame, Float, Long)?,
+            Instruction::F2d => self.unary(frame, Float, Double)?,
+            Instruction::D2i => self.unary(frame, Double, Integer)?,
+            Instruction::D2l => self.unary(frame, Double, Long)?,
+            Instruction::D2f => self.unary(frame, Double, Float)?,
+
+            Instruction::Ifeq(target)
+            | Instruction::Ifne(target)
+            | Instruction::Iflt(target)
+            | Instruction::Ifge(target)
+            | Instruction::Ifgt(target)
+            | Instruction::Ifle(target) => {
+                self.pop_expecting(frame, &Integer)?;
+                return Ok(Successors::branch(*target));
+            }
+            Instruction::If_icmpeq(target)
+            | Instruction::If_icmpne(target)
+            | Instruction::If_icmplt(target)
+            | Instruction::If_icmpge(target)
+            | Instruction::If_icmpgt(target)
+            | Instruction::If_icmple(target) => {
+                self.pop_expecting(frame, &Integer)?;
+                self.pop_expecting(frame, &Integer)?;
+                return Ok(Successors::branch(*target));
+            }
+            Instruction::If_acmpeq(target) | Instruction::If_acmpne(target) => {
+                self.pop_reference(frame)?;
+                self.pop_reference(frame)?;
+                return Ok(Successors::branch(*target));
+            }
+            Instruction::Ifnull(target) | Instruction::Ifnonnull(target) => {
+                self.pop_reference(frame)?;
+                return Ok(Successors::branch(*target));
+            }
+            Instruction::Goto(target) | Instruction::Goto_w(target) => {
+                return Ok(Successors::jump(vec![*target]))
+            }
+            Instruction::Tableswitch(table_switch) => {
+                self.pop_expecting(frame, &Integer)?;
+                let mut targets = table_switch.jumps.clone();
+                targets.push(table_switch.default);
+                return Ok(Successors::jump(targets));
+            }
+            Instruction::Lookupswitch(lookup_switch) => {
+                self.pop_expecting(frame, &Integer)?;
+                let mut targets: Vec<u16> = lookup_switch
+                    .pairs
+                    .iter()
+                    .map(|(_, target)| *target)
+                    .collect();
+                targets.push(lookup_switch.default);
+                return Ok(Successors::jump(targets));
+            }
+            Instruction::Jsr(target) | Instruction::Jsr_w(target) => {
+                let return_address = ProgramCounter(self.next_pc(pc)? as u16);
+                self.push(frame, VerificationType::ReturnAddress(return_address))?;
+                return Ok(Successors::jump(vec![*target]));
+            }
+            Instruction::Ret(index) => {
+                self.ret(frame, *index as u16)?;
+                return Ok(Successors::none());
+            }
+
+            Instruction::Ireturn => {
+                return self.return_value(
+                    frame,
+                    &[
+                        BaseType::Boolean,
+                        BaseType::Byte,
+                        BaseType::Char,
+                        BaseType::Short,
+                        BaseType::Int,
+                    ],
+                )
+            }
+            Instruction::Lreturn => return self.return_value(frame, &[BaseType::Long]),
+            Instruction::Freturn => return self.return_value(frame, &[BaseType::Float]),
+            Instruction::Dreturn => return self.return_value(frame, &[BaseType::Double]),
+            Instruction::Areturn => {
+                let expected = match &self.method.parsed_type_descriptor.return_type {
+                    Some(return_type @ (FieldType::Object(_) | FieldType::Array(_))) => {
+                        VerificationType::from(return_type)
+                    }
+                    return_type => {
+                        return self.error(format!(
+                            "wrong return instruction for return type {return_type:?}"
+                        ))
+                    }
+                };
+                self.pop_expecting(frame, &expected)?;
+                return Ok(Successors::none());
+            }
+            Instruction::Return => {
+                if self.method.parsed_type_descriptor.return_type.is_some() {
+                    return self.error("wrong return instruction for non void method");
+                }
+                if self.is_constructor() && frame.this_uninitialized {
+                    return self.error("constructor must call super() or this()");
+                }
+                return Ok(Successors::none());
+            }
+            Instruction::Athrow => {
+                self.pop_expecting(frame, &throwable())?;
+                return Ok(Successors::none());
+            }
+
+            Instruction::Getstatic(index) => {
+                let (_, _, field_type) = self.field_constant(*index)?;
+                self.push(frame, field_type)?
+            }
+            Instruction::Putstatic(index) => {
+                let (_, _, field_type) = self.field_constant(*index)?;
+                self.pop_expecting(frame, &field_type)?;
+            }
+            Instruction::Getfield(index) => {
+                let (class_name, _, field_type) = self.field_constant(*index)?;
+                self.pop_expecting(frame, &VerificationType::Object(class_name.to_string()))?;
+                self.push(frame, field_type)?
+            }
+            Instruction::Putfield(index) => {
+                let (class_name, field_name, field_type) = self.field_constant(*index)?;
+                self.pop_expecting(frame, &field_type)?;
+                let receiver = self.pop(frame)?;
+                // Constructors can assign the fields of their class before calling super()
+                let is_own_field = class_name == self.class_file.name
+                    && self
+                        .class_file
+                        .fields
+                        .iter()
+                        .any(|field| field.name == field_name);
+                if !(receiver == VerificationType::UninitializedThis && is_own_field) {
+                    self.check_assignable(
+                        &receiver,
+                        &VerificationType::Object(class_name.to_string()),
+                    )?;
+                }
+            }
+
+            Instruction::Invokevirtual(index)
+            | Instruction::Invokespecial(index)
+            | Instruction::Invokestatic(index)
+            | Instruction::Invokeinterface(index, _) => self.invoke(frame, instruction, *index)?,
+            Instruction::Invokedynamic(index) => {
+                let ConstantPoolEntry::InvokeDynamic(_, name_and_type_index) =
+                    self.constant(*index)?
+                else {
+                    return self.error(format!("constant {index} should be an invoke dynamic"));
+                };
+                let (name, descriptor) = self.name_and_type_constant(*name_and_type_index)?;
+                if name.starts_with('<') {
+                    return self.error(format!("invalid invokedynamic method name {name}"));
+                }
+                let descriptor = self.parse_method_descriptor(descriptor)?;
+                self.pop_arguments(frame, &descriptor)?;
+                self.push_return_value(frame, &descriptor)?
+            }
+
+            Instruction::New(index) => {
+                let class_name = self.class_constant(*index)?;
+                if class_name.starts_with('[') {
+                    return self.error(format!("cannot use new on array type {class_name}"));
+                }
+                let uninitialized = VerificationType::Uninitialized(ProgramCounter(pc as u16));
+                if frame.stack.contains(&uninitialized) {
+                    return self.error("uninitialized object is already on the stack");
+                }
+                // Executing again a `new`, for example in a loop, creates a different object
+                frame.initialize(&uninitialized, VerificationType::Top);
+                self.push(frame, uninitialized)?
+            }
+            Instruction::Newarray(array_type) => {
+                self.pop_expecting(frame, &Integer)?;
+                let descriptor = match array_type {
+                    NewArrayType::Boolean => "[Z",
+                    NewArrayType::Char => "[C",
+                    NewArrayType::Float => "[F",
+                    NewArrayType::Double => "[D",
+                    NewArrayType::Byte => "[B",
+                    NewArrayType::Short => "[S",
+                    NewArrayType::Int => "[I",
+                    NewArrayType::Long => "[J",
+                };
+                self.push(frame, VerificationType::Object(descriptor.to_string()))?
+            }
+            Instruction::Anewarray(index) => {
+                self.pop_expecting(frame, &Integer)?;
+                let class_name = self.class_constant(*index)?;
+                self.push(frame, VerificationType::Object(array_of(class_name)))?
+            }
+            Instruction::Multianewarray(index, dimensions) => {
+                let class_name = self.class_constant(*index)?;
+                let array_dimensions = class_name.chars().take_while(|c| *c == '[').count();
+                if *dimensions == 0 || *dimensions as usize > array_dimensions {
+                    return self.error(format!(
+                        "invalid dimensions {dimensions} for multianewarray of {class_name}"
+                    ));
+                }
+                for _ in 0..*dimensions {
+                    self.pop_expecting(frame, &Integer)?;
+                }
+                self.push(frame, VerificationType::Object(class_name.to_string()))?
+            }
+            Instruction::Arraylength => {
+                self.pop_array(frame)?;
+                self.push(frame, Integer)?
+            }
+
+            Instruction::Checkcast(index) => {
+                let class_name = self.class_constant(*index)?;
+                self.pop_expecting(frame, &object())?;
+                self.push(frame, VerificationType::Object(class_name.to_string()))?
+            }
+            Instruction::Instanceof(index) => {
+                self.class_constant(*index)?;
+                self.pop_expecting(frame, &object())?;
+                self.push(frame, Integer)?
+            }
+            Instruction::Monitorenter | Instruction::Monitorexit => {
+                self.pop_expecting(frame, &object())?;
+            }
+        }
+        Ok(Successors::next())
+    }
+}
+
+/// The frames of a method, as computed by [analyze_method]
+#[derive(Debug, PartialEq)]
+pub struct MethodFrames {
+    /// The frame before each reachable instruction, by address
+    pub frames: BTreeMap<usize, Frame>,
+    pub max_stack: u16,
+    pub max_locals: u16,
+    /// The locals of the implicit frame at the start of the method, in compact form
+    initial_locals: Vec<VerificationType>,
+    /// The addresses that need an entry in the `StackMapTable`: jump targets, exception
+    /// handlers, and the instructions following an unconditional jump
+    stack_map_addresses: BTreeSet<usize>,
+}
+
+impl MethodFrames {
+    /// Returns the frames of the `StackMapTable`, in the most compact form. Fails if some
+    /// unreachable code needs a frame, since its types are unknown.
+    pub fn stack_map_table(&self) -> Result<Vec<StackMapFrame>> {
+        let mut previous_locals = self.initial_locals.clone();
+        let mut previous_address = None;
+        let mut stack_map_table = Vec::with_capacity(self.stack_map_addresses.len());
+        for address in self.stack_map_addresses.iter().copied() {
+            let frame = self.frames.get(&address).ok_or_else(|| {
+                invalid(format!(
+                    "unreachable code at pc {address} needs a stack map frame"
+                ))
+            })?;
+            let offset_delta = match previous_address {
+                None => address,
+                Some(previous_address) => address - previous_address - 1,
+            };
+            let locals = frame.compact_locals();
+            let kind = match frame.stack.as_slice() {
+                [] if locals == previous_locals && offset_delta <= 63 => StackMapFrameKind::Same,
+                [] if locals == previous_locals => StackMapFrameKind::SameExtended,
+                [item] if locals == previous_locals && offset_delta <= 63 => {
+                    StackMapFrameKind::SameLocals1StackItem(item.clone())
+                }
+                [item] if locals == previous_locals => {
+                    StackMapFrameKind::SameLocals1StackItemExtended(item.clone())
+                }
+                [] if locals.len() > previous_locals.len()
+                    && locals.len() - previous_locals.len() <= 3
+                    && locals.starts_with(&previous_locals) =>
+                {
+                    StackMapFrameKind::Append(locals[previous_locals.len()..].to_vec())
+                }
+                [] if locals.len() < previous_locals.len()
+                    && previous_locals.len() - locals.len() <= 3
+                    && previous_locals.starts_with(&locals) =>
+                {
+                    StackMapFrameKind::Chop((previous_locals.len() - locals.len()) as u8)
+                }
+                stack => StackMapFrameKind::Full {
+                    locals: locals.clone(),
+                    stack: stack.to_vec(),
+                },
+            };
+            stack_map_table.push(StackMapFrame {
+                program_counter: ProgramCounter(address as u16),
+                kind,
+            });
+            previous_locals = locals;
+            previous_address = Some(address);
+        }
+        Ok(stack_map_table)
+    }
+}
+
+/// Computes the frames of the given method, which must have code
+pub fn analyze_method(
+    class_file: &ClassFile,
+    method: &ClassFileMethod,
+    hierarchy: &mut dyn ClassHierarchy<Error = ClassWriterError>,
+) -> Result<MethodFrames> {
+    let code = method
+        .code
+        .as_ref()
+        .ok_or_else(|| invalid(format!("method {} has no code", method.name)))?;
+    let graph = ControlFlowGraph::build(code).map_err(|err| invalid(err.to_string()))?;
+
+    let checks = AnalysisChecks {
+        hierarchy,
+        max_stack: 0,
+        max_locals: 0,
+    };
+    let mut analysis = FrameAnalysis(FrameInterpreter::new(class_file, method, checks)?);
+    let initial_locals = analysis.0.parameters_types();
+    let initial_frame = analysis.0.initial_frame()?;
+    let frames = analyze(&graph, initial_frame, &mut analysis)?;
+    let AnalysisChecks {
+        max_stack,
+        max_locals,
+        ..
+    } = analysis.0.checks;
+    let too_big = |what| ClassWriterError::LimitExceeded(format!("{what} of {}", method.name));
+    let max_stack = u16::try_from(max_stack).map_err(|_| too_big("max_stack"))?;
+    let max_locals = u16::try_from(max_locals).map_err(|_| too_big("max_locals"))?;
+
+    let mut stack_map_addresses = BTreeSet::new();
+    let blocks = graph.blocks();
+    for (index, block) in blocks.iter().enumerate() {
+        if block.id == graph.exit() {
+            continue;
+        }
+        let jumped_to = graph
+            .predecessors(block.id)
+            .any(|edge| edge.kind != EdgeKind::FallThrough);
+        let falls_through_from_previous = index == 0
+            || graph
+                .successors(blocks[index - 1].id)
+                .any(|edge| edge.to == block.id && edge.kind == EdgeKind::FallThrough);
+        if jumped_to || !falls_through_from_previous {
+            stack_map_addresses.insert(block.start.0 as usize);
+        }
+    }
+
+    Ok(MethodFrames {
+        frames,
+        max_stack,
+        max_locals,
+        initial_locals,
+        stack_map_addresses,
+    })
+}
+
+/// Recomputes `max_stack`, `max_locals` and the `StackMapTable` of all the methods of the
+/// given class, for instance after their bytecode has been modified. Class files older than
+/// Java 6 do not use stack maps, so their `StackMapTable` is cleared.
+pub fn recompute_frames(
+    class_file: &mut ClassFile,
+    hierarchy: &mut dyn ClassHierarchy<Error = ClassWriterError>,
+) -> Result<()> {
+    for index in 0..class_file.methods.len() {
+        let method = &class_file.methods[index];
+        if method.code.is_none() {
+            continue;
+        }
+        let frames = analyze_method(class_file, method, hierarchy).map_err(|err| match err {
+            ClassWriterError::InvalidClassData(message) => invalid(format!(
+                "method {}{}: {message}",
+                method.name, method.type_descriptor
+            )),
+            err => err,
+        })?;
+        let stack_map_table = if class_file.version >= ClassFileVersion::Jdk6 {
+            frames.stack_map_table()?
+        } else {
+            Vec::new()
+        };
+
+        let code = class_file.methods[index]
+            .code
+            .as_mut()
+            .expect("methods without code were skipped");
+        code.max_stack = frames.max_stack;
+        code.max_locals = frames.max_locals;
+        code.stack_map_table = stack_map_table;
+    }
+    Ok(())
+}
+
+/// The checks of [FrameAnalysis], which trusts the code and only records the maximum
+/// sizes of the stack and of the locals
+struct AnalysisChecks<'h> {
+    hierarchy: &'h mut dyn ClassHierarchy<Error = ClassWriterError>,
+    max_stack: usize,
+    max_locals: usize,
+}
+
+impl FrameChecks for AnalysisChecks<'_> {
+    type Error = ClassWriterError;
+
+    fn invalid(&self, message: String) -> ClassWriterError {
+        invalid(message)
+    }
+
+    fn hierarchy(&mut self) -> &mut dyn ClassHierarchy<Error = ClassWriterError> {
+        self.hierarchy
+    }
+
+    fn checks_operand_types(&self) -> bool {
+        false
+    }
+
+    fn check_size(&mut self, frame: &Frame) -> Result<()> {
+        self.max_stack = self.max_stack.max(frame.stack_size());
+        self.max_locals = self.max_locals.max(frame.locals.len());
+        Ok(())
+    }
+}
+
+struct FrameAnalysis<'a, 'h>(FrameInterpreter<'a, AnalysisChecks<'h>>);
+
+impl DataflowAnalysis for FrameAnalysis<'_, '_> {
+    type State = Frame;
+    type Error = ClassWriterError;
+
+    fn transfer(
+        &mut self,
+        frame: &mut Frame,
+        address: usize,
+        instruction: &Instruction,
+    ) -> Result<()> {
+        if is_subroutine_instruction(instruction) {
+            return Err(invalid(format!(
+                "pc {address}: subroutines are not supported"
+            )));
+        }
+        self.0
+            .execute(frame, address, instruction)
+            .map_err(|err| with_address(err, address))?;
+        Ok(())
+    }
+
+    fn exception_state(&mut self, frame: &Frame, catch_class: Option<&str>) -> Result<Frame> {
+        Ok(exception_frame(frame, catch_class))
+    }
+
+    fn join(&mut self, address: usize, first: &Frame, second: &Frame) -> Result<Frame> {
+        self.0
+            .merge_frames(first, second)
+            .map_err(|err| with_address(err, address))
+    }
+}
+
+/// Returns whether the instruction calls or returns from a subroutine
+pub fn is_subroutine_instruction(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::Jsr(_)
+            | Instruction::Jsr_w(_)
+            | Instruction::Ret(_)
+            | Instruction::Wide(WideInstruction::Ret(_))
+    )
+}
+
+fn with_address(err: ClassWriterError, address: usize) -> ClassWriterError {
+    match err {
+        ClassWriterError::InvalidClassData(message) => invalid(format!("pc {address}: {message}")),
+        err => err,
+    }
+}
+
+const OBJECT: &str = "java/lang/Object";
+const THROWABLE: &str = "java/lang/Throwable";
+
+fn invalid(message: impl Into<String>) -> ClassWriterError {
+    ClassWriterError::InvalidClassData(message.into())
+}
+
+fn object() -> VerificationType {
+    VerificationType::Object(OBJECT.to_string())
+}
+
+fn throwable() -> VerificationType {
+    VerificationType::Object(THROWABLE.to_string())
+}
+
+fn size_of(value: &VerificationType) -> usize {
+    if is_category_2(value) {
+        2
+    } else {
+        1
+    }
+}
+
+fn is_category_2(value: &VerificationType) -> bool {
+    matches!(value, VerificationType::Long | VerificationType::Double)
+}
+
+fn is_reference(value: &VerificationType) -> bool {
+    matches!(
+        value,
+        VerificationType::Null
+            | VerificationType::Object(_)
+            | VerificationType::UninitializedThis
+            | VerificationType::Uninitialized(_)
+    )
+}
+
+/// Returns the descriptor of an array whose elements have the given type,
+/// which should be a class name or an array descriptor
+fn array_of(class_name: &str) -> String {
+    if class_name.starts_with('[') {
+        format!("[{class_name}")
+    } else {
+        format!("[L{class_name};")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        class_writer_error::{ClassWriterError, Result},
+        frame_analysis::{
+            analyze_method, ClassHierarchy, ClassHierarchyEntry, Frame, UnknownClassHierarchy,
+        },
+        program_counter::ProgramCounter,
+        stack_map_table::{StackMapFrame, StackMapFrameKind, VerificationType},
+        text_assembler::assemble,
+    };
+
+    struct TestHierarchy;
+
+    impl ClassHierarchy for TestHierarchy {
+        type Error = ClassWriterError;
+
+        fn lookup_class(&mut self, class_name: &str) -> Result<ClassHierarchyEntry> {
+            let superclass = match class_name {
+                "rjvm/First" | "rjvm/Second" => Some("rjvm/Base"),
+                "java/lang/Object" => None,
+                _ => Some("java/lang/Object"),
+            };
+            Ok(ClassHierarchyEntry {
+                superclass: superclass.map(str::to_string),
+                is_interface: false,
+            })
+        }
+    }
+
+    fn frame(pc: u16, kind: StackMapFrameKind) -> StackMapFrame {
+        StackMapFrame {
+            program_counter: ProgramCounter(pc),
+            kind,
+        }
+    }
+
+    fn object(class_name: &str) -> VerificationType {
+        VerificationType::Object(class_name.to_string())
+    }
+
+    #[test]
+    fn can_compute_frames() {
+        let class = assemble(
+            r#"
+            .class rjvm/Foo
+            .method static choose(ZJ)Ljava/lang/Object;
+                iload_0
+                ifeq Second
+                new rjvm/First
+                dup
+                invokespecial rjvm/First/<init>()V
+                goto End
+            Second:
+                new rjvm/Second
+                dup
+                invokespecial rjvm/Second/<init>()V
+            End:
+                astore_3
+            Start:
+                aload_3
+                invokestatic rjvm/Foo/check(Ljava/lang/Object;)V
+            Finish:
+                aload_3
+                areturn
+            Handler:
+                pop
+                aconst_null
+                areturn
+                .catch java/lang/RuntimeException from Start to Finish using Handler
+            .end method
+            "#,
+        )
+        .unwrap();
+
+        let frames = analyze_method(&class, &class.methods[0], &mut TestHierarchy).unwrap();
+
+        assert_eq!(2, frames.max_stack);
+        assert_eq!(4, frames.max_locals);
+        assert_eq!(
+            Some(&Frame {
+                locals: vec![
+                    VerificationType::Integer,
+                    VerificationType::Long,
+                    VerificationType::Top,
+                ],
+                stack: vec![
+                    VerificationType::Uninitialized(ProgramCounter(4)),
+                    VerificationType::Uninitialized(ProgramCounter(4)),
+                ],
+                this_uninitialized: false,
+            }),
+            frames.frames.get(&8)
+        );
+        assert_eq!(
+            vec![
+                frame(14, StackMapFrameKind::Same),
+                frame(
+                    21,
+                    StackMapFrameKind::SameLocals1StackItem(object("rjvm/Base"))
+                ),
+                frame(
+                    28,
+                    StackMapFrameKind::Full {
+                        locals: vec![
+                            VerificationType::Integer,
+                            VerificationType::Long,
+                            object("rjvm/Base"),
+                        ],
+                        stack: vec![object("java/lang/RuntimeException")],
+                    }
+                ),
+            ],
+            frames.stack_map_table().unwrap()
+        );
+    }
+
+    #[test]
+    fn constructors_start_with_uninitialized_this() {
+        let class = assemble(
+            r#"
+            .class rjvm/Foo
+            .method <init>(I)V
+                iload_1
+                ifeq Zero
+                aload_0
+                invokespecial java/lang/Object/<init>()V
+                return
+            Zero:
+                aload_0
+                invokespecial rjvm/Foo/<init>()V
+                return
+            .end method
+            "#,
+        )
+        .unwrap();
+
+        let frames = analyze_method(&class, &class.methods[0], &mut UnknownClassHierarchy).unwrap();
+
+        assert_eq!(
+            vec![frame(9, StackMapFrameKind::Same)],
+            frames.stack_map_table().unwrap()
+        );
+        assert_eq!(
+            Some(&Frame {
+                locals: vec![object("rjvm/Foo"), VerificationType::Integer],
+                stack: vec![],
+                this_uninitialized: false,
+            }),
+            frames.frames.get(&8)
+        );
+    }
+
+    #[test]
+    fn unreachable_code_cannot_have_a_stack_map_frame() {
+        let class = assemble(
+            r#"
+            .class rjvm/Foo
+            .method static foo()V
+                return
+                return
+            .end method
+            "#,
+        )
+        .unwrap();
+
+        let frames = analyze_method(&class, &class.methods[0], &mut UnknownClassHierarchy).unwrap();
+
+        assert_eq!(1, frames.frames.len());
+        assert_eq!(
+            "invalid class: unreachable code at pc 1 needs a stack map frame",
+            frames.stack_map_table().unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use std::fmt;
+
+use crate::inner_class_flags::InnerClassFlags;
+
+/// An entry of the `InnerClasses` attribute, describing a nested class that
+/// is either a member of the class being read, or referenced by it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InnerClass {
+    /// The binary name of the nested class, for example `rjvm/Outer$Inner`
+    pub name: String,
+    /// The class of which this is a member. Missing for top-level, local and anonymous classes.
+    pub outer_class: Option<String>,
+    /// The name in the source code. Missing for anonymous classes.
+    pub simple_name: Option<String>,
+    pub flags: InnerClassFlags,
+}
+
+impl fmt::Display for InnerClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {}", self.flags, self.name)?;
+        if let Some(simple_name) = &self.simple_name {
+            write!(f, " ({simple_name})")?;
+        }
+        if let Some(outer_class) = &self.outer_class {
+            write!(f, " of {outer_class}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+bitflags! {
+    /// Flags of a nested class, as declared in the source code
+    pub struct InnerClassFlags: u16 {
+        const PUBLIC = 0x0001;
+        const PRIVATE = 0x0002;
+        const PROTECTED = 0x0004;
+        const STATIC = 0x0008;
+        const FINAL = 0x0010;
+        const INTERFACE = 0x0200;
+        const ABSTRACT = 0x0400;
+        const SYNTHETIC = 0x1000;
+        const ANNOTATION = 0x2000;
+        const ENUM = 0x4000;
+    }
+}
+
+impl Default for InnerClassFlags {
+    fn default() -> InnerClassFlags {
+        InnerClassFlags::empty()
+    }
+}
+
+#[cfg(feature = "serde")]
+crate::flags_serde::impl_serde_for_flags!(InnerClassFlags);
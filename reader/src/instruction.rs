@@ -1,9 +1,11 @@
-use crate::class_reader_error::ClassReaderError;
+use crate::{class_reader_error::ClassReaderError, class_writer_error::ClassWriterError};
 
 /// Represents a Java bytecode instruction.
 //noinspection SpellCheckingInspection
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, strum_macros::IntoStaticStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[strum(serialize_all = "lowercase")]
 pub enum Instruction {
     Aaload,
     Aastore,
@@ -90,7 +92,7 @@ pub enum Instruction {
     Getfield(u16),
     Getstatic(u16),
     Goto(u16),
-    Goto_w,
+    Goto_w(u16),
     I2b,
     I2c,
     I2d,
@@ -153,7 +155,7 @@ pub enum Instruction {
     Iushr,
     Ixor,
     Jsr(u16),
-    Jsr_w,
+    Jsr_w(u16),
     L2d,
     L2f,
     L2i,
@@ -175,7 +177,7 @@ pub enum Instruction {
     Lload_3,
     Lmul,
     Lneg,
-    Lookupswitch,
+    Lookupswitch(LookupSwitch),
     Lor,
     Lrem,
     Lreturn,
@@ -205,12 +207,13 @@ pub enum Instruction {
     Sastore,
     Sipush(i16),
     Swap,
-    Tableswitch,
-    Wide,
+    Tableswitch(TableSwitch),
+    Wide(WideInstruction),
 }
 
 /// Possible arguments of instruction `newarray`
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NewArrayType {
     Boolean,
     Char,
@@ -222,6 +225,73 @@ pub enum NewArrayType {
     Long,
 }
 
+/// Instructions that can be modified by the `wide` prefix, which extends the local variable
+/// index to 16 bits (and, for `iinc`, the constant as well)
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, strum_macros::IntoStaticStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[strum(serialize_all = "lowercase")]
+pub enum WideInstruction {
+    Aload(u16),
+    Astore(u16),
+    Dload(u16),
+    Dstore(u16),
+    Fload(u16),
+    Fstore(u16),
+    Iinc(u16, i16),
+    Iload(u16),
+    Istore(u16),
+    Lload(u16),
+    Lstore(u16),
+    Ret(u16),
+}
+
+/// Arguments of instruction `tableswitch`. All addresses are absolute, like the other jumps.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableSwitch {
+    pub default: u16,
+    pub low: i32,
+    pub high: i32,
+    /// The jump addresses for the values `low..=high`
+    pub jumps: Vec<u16>,
+}
+
+impl TableSwitch {
+    /// Returns the address to jump to for the given value. The offset in the table is
+    /// computed in `i64`, since `value - low` can overflow an `i32`.
+    pub fn jump_address(&self, value: i32) -> u16 {
+        if value < self.low || value > self.high {
+            return self.default;
+        }
+        let offset = i64::from(value) - i64::from(self.low);
+        usize::try_from(offset)
+            .ok()
+            .and_then(|offset| self.jumps.get(offset))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Arguments of instruction `lookupswitch`. All addresses are absolute, like the other jumps.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LookupSwitch {
+    pub default: u16,
+    /// Pairs of (match, jump address), sorted by match
+    pub pairs: Vec<(i32, u16)>,
+}
+
+impl LookupSwitch {
+    /// Returns the address to jump to for the given value
+    pub fn jump_address(&self, value: i32) -> u16 {
+        match self.pairs.binary_search_by_key(&value, |(key, _)| *key) {
+            Ok(index) => self.pairs[index].1,
+            Err(_) => self.default,
+        }
+    }
+}
+
 impl Instruction {
     /// Reads one instruction from the bytecode, and returns it along
     /// with the address of the start of the next instruction
@@ -314,7 +384,7 @@ impl Instruction {
             0xb4 => Instruction::Getfield(Self::read_u16(raw_code, &mut address)?),
             0xb2 => Instruction::Getstatic(Self::read_u16(raw_code, &mut address)?),
             0xa7 => Instruction::Goto(Self::read_offset(raw_code, &mut address)?),
-            0xc8 => todo!("OpCode::Goto_w"),
+            0xc8 => Instruction::Goto_w(Self::read_wide_offset(raw_code, &mut address)?),
             0x91 => Instruction::I2b,
             0x92 => Instruction::I2c,
             0x87 => Instruction::I2d,
@@ -397,7 +467,7 @@ impl Instruction {
             0x7c => Instruction::Iushr,
             0x82 => Instruction::Ixor,
             0xa8 => Instruction::Jsr(Self::read_offset(raw_code, &mut address)?),
-            0xc9 => todo!("OpCode::Jsr_w"),
+            0xc9 => Instruction::Jsr_w(Self::read_wide_offset(raw_code, &mut address)?),
             0x8a => Instruction::L2d,
             0x89 => Instruction::L2f,
             0x88 => Instruction::L2i,
@@ -419,7 +489,7 @@ impl Instruction {
             0x21 => Instruction::Lload_3,
             0x69 => Instruction::Lmul,
             0x75 => Instruction::Lneg,
-            0xab => todo!("OpCode::Lookupswitch"),
+            0xab => Instruction::Lookupswitch(Self::read_lookupswitch(raw_code, &mut address)?),
             0x81 => Instruction::Lor,
             0x71 => Instruction::Lrem,
             0xad => Instruction::Lreturn,
@@ -470,8 +540,8 @@ impl Instruction {
             0x56 => Instruction::Sastore,
             0x11 => Instruction::Sipush(Self::read_i16(raw_code, &mut address)?),
             0x5f => Instruction::Swap,
-            0xaa => todo!("OpCode::Tableswitch"),
-            0xc4 => todo!("OpCode::Wide"),
+            0xaa => Instruction::Tableswitch(Self::read_tableswitch(raw_code, &mut address)?),
+            0xc4 => Instruction::Wide(Self::read_wide(raw_code, &mut address)?),
             _ => {
                 return Err(ClassReaderError::invalid_class_data(format!(
                     "invalid op code: {op_byte:#04x} at address {address}"
@@ -483,7 +553,7 @@ impl Instruction {
     }
 
     /// Parses all instructions in the given raw code.
-    pub(crate) fn parse_instructions(
+    pub fn parse_instructions(
         raw_code: &[u8],
     ) -> Result<Vec<(usize, Instruction)>, ClassReaderError> {
         let mut instructions: Vec<(usize, Self)> = Vec::new();
@@ -498,6 +568,474 @@ impl Instruction {
         Ok(instructions)
     }
 
+    /// Appends the bytecode of the instruction, placed at the given address, to `out`.
+    /// It is the inverse of [Instruction::parse]: jump addresses are converted back to offsets,
+    /// and switches are padded according to the address.
+    pub fn encode(&self, address: usize, out: &mut Vec<u8>) -> Result<(), ClassWriterError> {
+        match self {
+            Instruction::Aaload => out.push(0x32),
+            Instruction::Aastore => out.push(0x53),
+            Instruction::Aconst_null => out.push(0x01),
+            Instruction::Aload(argument) => {
+                out.push(0x19);
+                out.push(*argument);
+            }
+            Instruction::Aload_0 => out.push(0x2a),
+            Instruction::Aload_1 => out.push(0x2b),
+            Instruction::Aload_2 => out.push(0x2c),
+            Instruction::Aload_3 => out.push(0x2d),
+            Instruction::Anewarray(argument) => {
+                out.push(0xbd);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Areturn => out.push(0xb0),
+            Instruction::Arraylength => out.push(0xbe),
+            Instruction::Astore(argument) => {
+                out.push(0x3a);
+                out.push(*argument);
+            }
+            Instruction::Astore_0 => out.push(0x4b),
+            Instruction::Astore_1 => out.push(0x4c),
+            Instruction::Astore_2 => out.push(0x4d),
+            Instruction::Astore_3 => out.push(0x4e),
+            Instruction::Athrow => out.push(0xbf),
+            Instruction::Baload => out.push(0x33),
+            Instruction::Bastore => out.push(0x54),
+            Instruction::Bipush(value) => {
+                out.push(0x10);
+                out.push(*value);
+            }
+            Instruction::Caload => out.push(0x34),
+            Instruction::Castore => out.push(0x55),
+            Instruction::Checkcast(argument) => {
+                out.push(0xc0);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::D2f => out.push(0x90),
+            Instruction::D2i => out.push(0x8e),
+            Instruction::D2l => out.push(0x8f),
+            Instruction::Dadd => out.push(0x63),
+            Instruction::Daload => out.push(0x31),
+            Instruction::Dastore => out.push(0x52),
+            Instruction::Dcmpg => out.push(0x98),
+            Instruction::Dcmpl => out.push(0x97),
+            Instruction::Dconst_0 => out.push(0x0e),
+            Instruction::Dconst_1 => out.push(0x0f),
+            Instruction::Ddiv => out.push(0x6f),
+            Instruction::Dload(argument) => {
+                out.push(0x18);
+                out.push(*argument);
+            }
+            Instruction::Dload_0 => out.push(0x26),
+            Instruction::Dload_1 => out.push(0x27),
+            Instruction::Dload_2 => out.push(0x28),
+            Instruction::Dload_3 => out.push(0x29),
+            Instruction::Dmul => out.push(0x6b),
+            Instruction::Dneg => out.push(0x77),
+            Instruction::Drem => out.push(0x73),
+            Instruction::Dreturn => out.push(0xaf),
+            Instruction::Dstore(argument) => {
+                out.push(0x39);
+                out.push(*argument);
+            }
+            Instruction::Dstore_0 => out.push(0x47),
+            Instruction::Dstore_1 => out.push(0x48),
+            Instruction::Dstore_2 => out.push(0x49),
+            Instruction::Dstore_3 => out.push(0x4a),
+            Instruction::Dsub => out.push(0x67),
+            Instruction::Dup => out.push(0x59),
+            Instruction::Dup2 => out.push(0x5c),
+            Instruction::Dup2_x1 => out.push(0x5d),
+            Instruction::Dup2_x2 => out.push(0x5e),
+            Instruction::Dup_x1 => out.push(0x5a),
+            Instruction::Dup_x2 => out.push(0x5b),
+            Instruction::F2d => out.push(0x8d),
+            Instruction::F2i => out.push(0x8b),
+            Instruction::F2l => out.push(0x8c),
+            Instruction::Fadd => out.push(0x62),
+            Instruction::Faload => out.push(0x30),
+            Instruction::Fastore => out.push(0x51),
+            Instruction::Fcmpg => out.push(0x96),
+            Instruction::Fcmpl => out.push(0x95),
+            Instruction::Fconst_0 => out.push(0x0b),
+            Instruction::Fconst_1 => out.push(0x0c),
+            Instruction::Fconst_2 => out.push(0x0d),
+            Instruction::Fdiv => out.push(0x6e),
+            Instruction::Fload(argument) => {
+                out.push(0x17);
+                out.push(*argument);
+            }
+            Instruction::Fload_0 => out.push(0x22),
+            Instruction::Fload_1 => out.push(0x23),
+            Instruction::Fload_2 => out.push(0x24),
+            Instruction::Fload_3 => out.push(0x25),
+            Instruction::Fmul => out.push(0x6a),
+            Instruction::Fneg => out.push(0x76),
+            Instruction::Frem => out.push(0x72),
+            Instruction::Freturn => out.push(0xae),
+            Instruction::Fstore(argument) => {
+                out.push(0x38);
+                out.push(*argument);
+            }
+            Instruction::Fstore_0 => out.push(0x43),
+            Instruction::Fstore_1 => out.push(0x44),
+            Instruction::Fstore_2 => out.push(0x45),
+            Instruction::Fstore_3 => out.push(0x46),
+            Instruction::Fsub => out.push(0x66),
+            Instruction::Getfield(argument) => {
+                out.push(0xb4);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Getstatic(argument) => {
+                out.push(0xb2);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Goto(jump_address) => {
+                out.push(0xa7);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Goto_w(jump_address) => {
+                out.push(0xc8);
+                out.extend_from_slice(&Self::wide_offset(address, *jump_address).to_be_bytes());
+            }
+            Instruction::I2b => out.push(0x91),
+            Instruction::I2c => out.push(0x92),
+            Instruction::I2d => out.push(0x87),
+            Instruction::I2f => out.push(0x86),
+            Instruction::I2l => out.push(0x85),
+            Instruction::I2s => out.push(0x93),
+            Instruction::Iadd => out.push(0x60),
+            Instruction::Iaload => out.push(0x2e),
+            Instruction::Iand => out.push(0x7e),
+            Instruction::Iastore => out.push(0x4f),
+            Instruction::Iconst_0 => out.push(0x03),
+            Instruction::Iconst_1 => out.push(0x04),
+            Instruction::Iconst_2 => out.push(0x05),
+            Instruction::Iconst_3 => out.push(0x06),
+            Instruction::Iconst_4 => out.push(0x07),
+            Instruction::Iconst_5 => out.push(0x08),
+            Instruction::Iconst_m1 => out.push(0x02),
+            Instruction::Idiv => out.push(0x6c),
+            Instruction::Ifeq(jump_address) => {
+                out.push(0x99);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Ifge(jump_address) => {
+                out.push(0x9c);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Ifgt(jump_address) => {
+                out.push(0x9d);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Ifle(jump_address) => {
+                out.push(0x9e);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Iflt(jump_address) => {
+                out.push(0x9b);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Ifne(jump_address) => {
+                out.push(0x9a);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Ifnonnull(jump_address) => {
+                out.push(0xc7);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Ifnull(jump_address) => {
+                out.push(0xc6);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::If_acmpeq(jump_address) => {
+                out.push(0xa5);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::If_acmpne(jump_address) => {
+                out.push(0xa6);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::If_icmpeq(jump_address) => {
+                out.push(0x9f);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::If_icmpge(jump_address) => {
+                out.push(0xa2);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::If_icmpgt(jump_address) => {
+                out.push(0xa3);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::If_icmple(jump_address) => {
+                out.push(0xa4);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::If_icmplt(jump_address) => {
+                out.push(0xa1);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::If_icmpne(jump_address) => {
+                out.push(0xa0);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Iinc(index, value) => {
+                out.push(0x84);
+                out.push(*index);
+                out.push(*value as u8);
+            }
+            Instruction::Iload(argument) => {
+                out.push(0x15);
+                out.push(*argument);
+            }
+            Instruction::Iload_0 => out.push(0x1a),
+            Instruction::Iload_1 => out.push(0x1b),
+            Instruction::Iload_2 => out.push(0x1c),
+            Instruction::Iload_3 => out.push(0x1d),
+            Instruction::Imul => out.push(0x68),
+            Instruction::Ineg => out.push(0x74),
+            Instruction::Instanceof(argument) => {
+                out.push(0xc1);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Invokedynamic(index) => {
+                out.push(0xba);
+                out.extend_from_slice(&index.to_be_bytes());
+                out.extend_from_slice(&[0, 0]);
+            }
+            Instruction::Invokeinterface(index, count) => {
+                out.push(0xb9);
+                out.extend_from_slice(&index.to_be_bytes());
+                out.push(*count);
+                out.push(0);
+            }
+            Instruction::Invokespecial(argument) => {
+                out.push(0xb7);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Invokestatic(argument) => {
+                out.push(0xb8);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Invokevirtual(argument) => {
+                out.push(0xb6);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Ior => out.push(0x80),
+            Instruction::Irem => out.push(0x70),
+            Instruction::Ireturn => out.push(0xac),
+            Instruction::Ishl => out.push(0x78),
+            Instruction::Ishr => out.push(0x7a),
+            Instruction::Istore(argument) => {
+                out.push(0x36);
+                out.push(*argument);
+            }
+            Instruction::Istore_0 => out.push(0x3b),
+            Instruction::Istore_1 => out.push(0x3c),
+            Instruction::Istore_2 => out.push(0x3d),
+            Instruction::Istore_3 => out.push(0x3e),
+            Instruction::Isub => out.push(0x64),
+            Instruction::Iushr => out.push(0x7c),
+            Instruction::Ixor => out.push(0x82),
+            Instruction::Jsr(jump_address) => {
+                out.push(0xa8);
+                out.extend_from_slice(&Self::offset(address, *jump_address)?.to_be_bytes());
+            }
+            Instruction::Jsr_w(jump_address) => {
+                out.push(0xc9);
+                out.extend_from_slice(&Self::wide_offset(address, *jump_address).to_be_bytes());
+            }
+            Instruction::L2d => out.push(0x8a),
+            Instruction::L2f => out.push(0x89),
+            Instruction::L2i => out.push(0x88),
+            Instruction::Ladd => out.push(0x61),
+            Instruction::Laload => out.push(0x2f),
+            Instruction::Land => out.push(0x7f),
+            Instruction::Lastore => out.push(0x50),
+            Instruction::Lcmp => out.push(0x94),
+            Instruction::Lconst_0 => out.push(0x09),
+            Instruction::Lconst_1 => out.push(0x0a),
+            Instruction::Ldc(argument) => {
+                out.push(0x12);
+                out.push(*argument);
+            }
+            Instruction::Ldc2_w(argument) => {
+                out.push(0x14);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Ldc_w(argument) => {
+                out.push(0x13);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Ldiv => out.push(0x6d),
+            Instruction::Lload(argument) => {
+                out.push(0x16);
+                out.push(*argument);
+            }
+            Instruction::Lload_0 => out.push(0x1e),
+            Instruction::Lload_1 => out.push(0x1f),
+            Instruction::Lload_2 => out.push(0x20),
+            Instruction::Lload_3 => out.push(0x21),
+            Instruction::Lmul => out.push(0x69),
+            Instruction::Lneg => out.push(0x75),
+            Instruction::Lookupswitch(lookup_switch) => {
+                out.push(0xab);
+                Self::write_switch_padding(address, out);
+                out.extend_from_slice(
+                    &Self::wide_offset(address, lookup_switch.default).to_be_bytes(),
+                );
+                out.extend_from_slice(&(lookup_switch.pairs.len() as i32).to_be_bytes());
+                for (key, jump_address) in lookup_switch.pairs.iter() {
+                    out.extend_from_slice(&key.to_be_bytes());
+                    out.extend_from_slice(&Self::wide_offset(address, *jump_address).to_be_bytes());
+                }
+            }
+            Instruction::Lor => out.push(0x81),
+            Instruction::Lrem => out.push(0x71),
+            Instruction::Lreturn => out.push(0xad),
+            Instruction::Lshl => out.push(0x79),
+            Instruction::Lshr => out.push(0x7b),
+            Instruction::Lstore(argument) => {
+                out.push(0x37);
+                out.push(*argument);
+            }
+            Instruction::Lstore_0 => out.push(0x3f),
+            Instruction::Lstore_1 => out.push(0x40),
+            Instruction::Lstore_2 => out.push(0x41),
+            Instruction::Lstore_3 => out.push(0x42),
+            Instruction::Lsub => out.push(0x65),
+            Instruction::Lushr => out.push(0x7d),
+            Instruction::Lxor => out.push(0x83),
+            Instruction::Monitorenter => out.push(0xc2),
+            Instruction::Monitorexit => out.push(0xc3),
+            Instruction::Multianewarray(index, dimensions) => {
+                out.push(0xc5);
+                out.extend_from_slice(&index.to_be_bytes());
+                out.push(*dimensions);
+            }
+            Instruction::New(argument) => {
+                out.push(0xbb);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Newarray(array_type) => {
+                out.push(0xbc);
+                out.push(match array_type {
+                    NewArrayType::Boolean => 4,
+                    NewArrayType::Char => 5,
+                    NewArrayType::Float => 6,
+                    NewArrayType::Double => 7,
+                    NewArrayType::Byte => 8,
+                    NewArrayType::Short => 9,
+                    NewArrayType::Int => 10,
+                    NewArrayType::Long => 11,
+                });
+            }
+            Instruction::Nop => out.push(0x00),
+            Instruction::Pop => out.push(0x57),
+            Instruction::Pop2 => out.push(0x58),
+            Instruction::Putfield(argument) => {
+                out.push(0xb5);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Putstatic(argument) => {
+                out.push(0xb3);
+                out.extend_from_slice(&argument.to_be_bytes());
+            }
+            Instruction::Ret(argument) => {
+                out.push(0xa9);
+                out.push(*argument);
+            }
+            Instruction::Return => out.push(0xb1),
+            Instruction::Saload => out.push(0x35),
+            Instruction::Sastore => out.push(0x56),
+            Instruction::Sipush(value) => {
+                out.push(0x11);
+                out.extend_from_slice(&value.to_be_bytes());
+            }
+            Instruction::Swap => out.push(0x5f),
+            Instruction::Tableswitch(table_switch) => {
+                let num_jumps = table_switch.high as i64 - table_switch.low as i64 + 1;
+                if num_jumps != table_switch.jumps.len() as i64 {
+                    return Err(ClassWriterError::InvalidClassData(format!(
+                        "tableswitch at address {address} has {} jumps for bounds {}..{}",
+                        table_switch.jumps.len(),
+                        table_switch.low,
+                        table_switch.high
+                    )));
+                }
+                out.push(0xaa);
+                Self::write_switch_padding(address, out);
+                out.extend_from_slice(
+                    &Self::wide_offset(address, table_switch.default).to_be_bytes(),
+                );
+                out.extend_from_slice(&table_switch.low.to_be_bytes());
+                out.extend_from_slice(&table_switch.high.to_be_bytes());
+                for jump_address in table_switch.jumps.iter() {
+                    out.extend_from_slice(&Self::wide_offset(address, *jump_address).to_be_bytes());
+                }
+            }
+            Instruction::Wide(wide_instruction) => {
+                out.push(0xc4);
+                let (op_byte, index) = match wide_instruction {
+                    WideInstruction::Aload(index) => (0x19, index),
+                    WideInstruction::Astore(index) => (0x3a, index),
+                    WideInstruction::Dload(index) => (0x18, index),
+                    WideInstruction::Dstore(index) => (0x39, index),
+                    WideInstruction::Fload(index) => (0x17, index),
+                    WideInstruction::Fstore(index) => (0x38, index),
+                    WideInstruction::Iinc(index, _) => (0x84, index),
+                    WideInstruction::Iload(index) => (0x15, index),
+                    WideInstruction::Istore(index) => (0x36, index),
+                    WideInstruction::Lload(index) => (0x16, index),
+                    WideInstruction::Lstore(index) => (0x37, index),
+                    WideInstruction::Ret(index) => (0xa9, index),
+                };
+                out.push(op_byte);
+                out.extend_from_slice(&index.to_be_bytes());
+                if let WideInstruction::Iinc(_, value) = wide_instruction {
+                    out.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes all the given instructions, placed one after the other starting at address 0
+    pub fn encode_instructions(instructions: &[Instruction]) -> Result<Vec<u8>, ClassWriterError> {
+        let mut code = Vec::new();
+        for instruction in instructions.iter() {
+            instruction.encode(code.len(), &mut code)?;
+        }
+        Ok(code)
+    }
+
+    /// Returns the size in bytes of the instruction, when placed at the given address
+    pub fn size(&self, address: usize) -> Result<usize, ClassWriterError> {
+        let mut bytes = Vec::new();
+        self.encode(address, &mut bytes)?;
+        Ok(bytes.len())
+    }
+
+    fn offset(address: usize, jump_address: u16) -> Result<i16, ClassWriterError> {
+        let offset = jump_address as i64 - address as i64;
+        i16::try_from(offset).map_err(|_| {
+            ClassWriterError::LimitExceeded(format!(
+                "jump offset from address {address} to {jump_address} does not fit in 16 bits"
+            ))
+        })
+    }
+
+    fn wide_offset(address: usize, jump_address: u16) -> i32 {
+        (jump_address as i64 - address as i64) as i32
+    }
+
+    fn write_switch_padding(address: usize, out: &mut Vec<u8>) {
+        for _ in 0..(3 - address % 4) {
+            out.push(0);
+        }
+    }
+
     fn byte_at(raw_code: &[u8], address: usize) -> Result<u8, ClassReaderError> {
         let op_byte = *raw_code
             .get(address)
@@ -533,14 +1071,372 @@ impl Instruction {
         Ok(unsafe { std::mem::transmute(value) })
     }
 
+    fn read_wide(
+        raw_code: &[u8],
+        address: &mut usize,
+    ) -> Result<WideInstruction, ClassReaderError> {
+        let op_byte = Self::read_u8(raw_code, address)?;
+        let index = Self::read_u16(raw_code, address)?;
+        Ok(match op_byte {
+            0x19 => WideInstruction::Aload(index),
+            0x3a => WideInstruction::Astore(index),
+            0x18 => WideInstruction::Dload(index),
+            0x39 => WideInstruction::Dstore(index),
+            0x17 => WideInstruction::Fload(index),
+            0x38 => WideInstruction::Fstore(index),
+            0x84 => WideInstruction::Iinc(index, Self::read_i16(raw_code, address)?),
+            0x15 => WideInstruction::Iload(index),
+            0x36 => WideInstruction::Istore(index),
+            0x16 => WideInstruction::Lload(index),
+            0x37 => WideInstruction::Lstore(index),
+            0xa9 => WideInstruction::Ret(index),
+            _ => {
+                return Err(ClassReaderError::invalid_class_data(format!(
+                    "invalid op code after wide: {op_byte:#04x} at address {address}"
+                )))
+            }
+        })
+    }
+
+    fn read_i32(raw_code: &[u8], address: &mut usize) -> Result<i32, ClassReaderError> {
+        let b1 = Self::read_u8(raw_code, address)? as u32;
+        let b2 = Self::read_u8(raw_code, address)? as u32;
+        let b3 = Self::read_u8(raw_code, address)? as u32;
+        let b4 = Self::read_u8(raw_code, address)? as u32;
+        Ok(((b1 << 24) | (b2 << 16) | (b3 << 8) | b4) as i32)
+    }
+
     fn read_offset(raw_code: &[u8], address: &mut usize) -> Result<u16, ClassReaderError> {
         let instruction_address = *address - 1;
         let offset = Self::read_i16(raw_code, address)?;
-        let jump_address = (instruction_address as i32) + (offset as i32);
+        Self::to_jump_address(instruction_address, offset as i32, *address)
+    }
+
+    fn read_wide_offset(raw_code: &[u8], address: &mut usize) -> Result<u16, ClassReaderError> {
+        let instruction_address = *address - 1;
+        let offset = Self::read_i32(raw_code, address)?;
+        Self::to_jump_address(instruction_address, offset, *address)
+    }
+
+    fn to_jump_address(
+        instruction_address: usize,
+        offset: i32,
+        address: usize,
+    ) -> Result<u16, ClassReaderError> {
+        let jump_address = (instruction_address as i64) + (offset as i64);
         u16::try_from(jump_address).map_err(|_| {
             ClassReaderError::invalid_class_data(format!(
                 "invalid jump offset at address {address}"
             ))
         })
     }
+
+    /// Switch instructions have between zero and three bytes of padding after the opcode,
+    /// so that the following arguments start at an address that is a multiple of four
+    fn skip_switch_padding(raw_code: &[u8], address: &mut usize) -> Result<(), ClassReaderError> {
+        while !address.is_multiple_of(4) {
+            Self::read_u8(raw_code, address)?;
+        }
+        Ok(())
+    }
+
+    fn check_switch_table_fits(
+        raw_code: &[u8],
+        address: usize,
+        num_entries: usize,
+        entry_size: usize,
+    ) -> Result<(), ClassReaderError> {
+        if num_entries > (raw_code.len() - address) / entry_size {
+            Err(ClassReaderError::invalid_class_data(format!(
+                "switch table does not fit in the code at address {address}"
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn read_tableswitch(
+        raw_code: &[u8],
+        address: &mut usize,
+    ) -> Result<TableSwitch, ClassReaderError> {
+        let instruction_address = *address - 1;
+        Self::skip_switch_padding(raw_code, address)?;
+        let default_offset = Self::read_i32(raw_code, address)?;
+        let default = Self::to_jump_address(instruction_address, default_offset, *address)?;
+        let low = Self::read_i32(raw_code, address)?;
+        let high = Self::read_i32(raw_code, address)?;
+        if low > high {
+            return Err(ClassReaderError::invalid_class_data(format!(
+                "invalid tableswitch bounds {low}..{high} at address {address}"
+            )));
+        }
+
+        let num_jumps = (high as i64 - low as i64 + 1) as usize;
+        Self::check_switch_table_fits(raw_code, *address, num_jumps, 4)?;
+        let mut jumps = Vec::with_capacity(num_jumps);
+        for _ in 0..num_jumps {
+            let offset = Self::read_i32(raw_code, address)?;
+            jumps.push(Self::to_jump_address(
+                instruction_address,
+                offset,
+                *address,
+            )?);
+        }
+        Ok(TableSwitch {
+            default,
+            low,
+            high,
+            jumps,
+        })
+    }
+
+    fn read_lookupswitch(
+        raw_code: &[u8],
+        address: &mut usize,
+    ) -> Result<LookupSwitch, ClassReaderError> {
+        let instruction_address = *address - 1;
+        Self::skip_switch_padding(raw_code, address)?;
+        let default_offset = Self::read_i32(raw_code, address)?;
+        let default = Self::to_jump_address(instruction_address, default_offset, *address)?;
+        let num_pairs = Self::read_i32(raw_code, address)?;
+        if num_pairs < 0 {
+            return Err(ClassReaderError::invalid_class_data(format!(
+                "invalid number of lookupswitch pairs {num_pairs} at address {address}"
+            )));
+        }
+
+        Self::check_switch_table_fits(raw_code, *address, num_pairs as usize, 8)?;
+        let mut pairs: Vec<(i32, u16)> = Vec::with_capacity(num_pairs as usize);
+        for _ in 0..num_pairs {
+            let key = Self::read_i32(raw_code, address)?;
+            let offset = Self::read_i32(raw_code, address)?;
+            let jump_address = Self::to_jump_address(instruction_address, offset, *address)?;
+            if let Some((previous_key, _)) = pairs.last() {
+                if *previous_key >= key {
+                    return Err(ClassReaderError::invalid_class_data(format!(
+                        "lookupswitch keys are not sorted at address {address}"
+                    )));
+                }
+            }
+            pairs.push((key, jump_address));
+        }
+        Ok(LookupSwitch { default, pairs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        class_writer_error::ClassWriterError,
+        instruction::{Instruction, LookupSwitch, TableSwitch, WideInstruction},
+    };
+
+    #[test]
+    fn can_parse_tableswitch() {
+        let code = vec![
+            0x1a, // iload_0
+            0xaa, 0, 0, // tableswitch, with two bytes of padding
+            0, 0, 0, 39, // default offset
+            0, 0, 0, 1, // low
+            0, 0, 0, 3, // high
+            0, 0, 0, 27, // offset for 1
+            0, 0, 0, 31, // offset for 2
+            0, 0, 0, 35, // offset for 3
+        ];
+
+        let instructions = Instruction::parse_instructions(&code).unwrap();
+        assert_eq!(
+            vec![
+                (0, Instruction::Iload_0),
+                (
+                    1,
+                    Instruction::Tableswitch(TableSwitch {
+                        default: 40,
+                        low: 1,
+                        high: 3,
+                        jumps: vec![28, 32, 36],
+                    })
+                ),
+            ],
+            instructions
+        );
+    }
+
+    #[test]
+    fn can_parse_lookupswitch() {
+        let code = vec![
+            0x1a, 0x1a, 0x1a, // iload_0 x3
+            0xab, // lookupswitch, no padding needed
+            0, 0, 0, 30, // default offset
+            0, 0, 0, 2, // number of pairs
+            0xff, 0xff, 0xff, 0xfe, 0, 0, 0, 22, // -2 => offset 22
+            0, 0, 0x01, 0, 0, 0, 0, 26, // 256 => offset 26
+        ];
+
+        let (instruction, next_address) = Instruction::parse(&code, 3).unwrap();
+        assert_eq!(
+            Instruction::Lookupswitch(LookupSwitch {
+                default: 33,
+                pairs: vec![(-2, 25), (256, 29)],
+            }),
+            instruction
+        );
+        assert_eq!(code.len(), next_address);
+    }
+
+    #[test]
+    fn cannot_parse_truncated_tableswitch() {
+        let code = vec![
+            0xaa, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 4,
+        ];
+        assert!(Instruction::parse(&code, 0).is_err());
+    }
+
+    #[test]
+    fn switch_jump_addresses_fall_back_to_default() {
+        let table_switch = TableSwitch {
+            default: 100,
+            low: -1,
+            high: 1,
+            jumps: vec![10, 20, 30],
+        };
+        assert_eq!(10, table_switch.jump_address(-1));
+        assert_eq!(30, table_switch.jump_address(1));
+        assert_eq!(100, table_switch.jump_address(2));
+        assert_eq!(100, table_switch.jump_address(i32::MIN));
+
+        let table_switch = TableSwitch {
+            default: 100,
+            low: i32::MIN,
+            high: i32::MAX,
+            jumps: vec![10, 20],
+        };
+        assert_eq!(10, table_switch.jump_address(i32::MIN));
+        assert_eq!(20, table_switch.jump_address(i32::MIN + 1));
+        assert_eq!(100, table_switch.jump_address(0));
+        assert_eq!(100, table_switch.jump_address(i32::MAX));
+
+        let lookup_switch = LookupSwitch {
+            default: 100,
+            pairs: vec![(-5, 10), (7, 20)],
+        };
+        assert_eq!(10, lookup_switch.jump_address(-5));
+        assert_eq!(20, lookup_switch.jump_address(7));
+        assert_eq!(100, lookup_switch.jump_address(0));
+    }
+
+    #[test]
+    fn can_parse_wide() {
+        let code = vec![
+            0xc4, 0x15, 0x01, 0x02, // wide iload 258
+            0xc4, 0x84, 0x01, 0x00, 0xff, 0xfe, // wide iinc 256 -2
+            0xc4, 0xa9, 0x00, 0x03, // wide ret 3
+        ];
+
+        let instructions = Instruction::parse_instructions(&code).unwrap();
+        assert_eq!(
+            vec![
+                (0, Instruction::Wide(WideInstruction::Iload(258))),
+                (4, Instruction::Wide(WideInstruction::Iinc(256, -2))),
+                (10, Instruction::Wide(WideInstruction::Ret(3))),
+            ],
+            instructions
+        );
+    }
+
+    #[test]
+    fn cannot_parse_wide_with_invalid_instruction() {
+        let code = vec![0xc4, 0x60, 0x00, 0x01];
+        assert!(Instruction::parse(&code, 0).is_err());
+    }
+
+    #[test]
+    fn can_parse_wide_jumps() {
+        let code = vec![
+            0x00, // nop
+            0xc8, 0x00, 0x00, 0x00, 0x0b, // goto_w +11
+            0xc9, 0xff, 0xff, 0xff, 0xff, // jsr_w -1
+            0xa9, 0x01, // ret 1
+        ];
+
+        let instructions = Instruction::parse_instructions(&code).unwrap();
+        assert_eq!(
+            vec![
+                (0, Instruction::Nop),
+                (1, Instruction::Goto_w(12)),
+                (6, Instruction::Jsr_w(5)),
+                (11, Instruction::Ret(1)),
+            ],
+            instructions
+        );
+    }
+
+    #[test]
+    fn cannot_parse_wide_jump_outside_of_code() {
+        let code = vec![0xc8, 0xff, 0xff, 0xff, 0xf0];
+        assert!(Instruction::parse(&code, 0).is_err());
+    }
+
+    #[test]
+    fn encoding_is_the_inverse_of_parsing() {
+        let codes = vec![
+            vec![
+                0x1a, 0xaa, 0, 0, 0, 0, 0, 39, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 27, 0, 0, 0, 31, 0,
+                0, 0, 35,
+            ],
+            vec![
+                0x1a, 0x1a, 0x1a, 0xab, 0, 0, 0, 30, 0, 0, 0, 2, 0xff, 0xff, 0xff, 0xfe, 0, 0, 0,
+                22, 0, 0, 0x01, 0, 0, 0, 0, 26,
+            ],
+            vec![
+                0xc4, 0x15, 0x01, 0x02, 0xc4, 0x84, 0x01, 0x00, 0xff, 0xfe, 0xc4, 0xa9, 0x00, 0x03,
+            ],
+            vec![
+                0x00, 0xc8, 0x00, 0x00, 0x00, 0x0b, 0xc9, 0xff, 0xff, 0xff, 0xff, 0xa9, 0x01,
+            ],
+            vec![
+                0x2a, // aload_0
+                0xc6, 0x00, 0x06, // ifnull +6
+                0x13, 0x01, 0x02, // ldc_w #258
+                0xa7, 0xff, 0xfc, // goto -4
+                0xb9, 0x00, 0x05, 0x02, 0x00, // invokeinterface #5, 2
+                0xbc, 0x0a, // newarray int
+                0xc5, 0x00, 0x03, 0x02, // multianewarray #3, 2
+            ],
+        ];
+
+        for code in codes {
+            let instructions: Vec<Instruction> = Instruction::parse_instructions(&code)
+                .unwrap()
+                .into_iter()
+                .map(|(_, instruction)| instruction)
+                .collect();
+            assert_eq!(
+                code,
+                Instruction::encode_instructions(&instructions).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn switches_are_padded_according_to_their_address() {
+        let lookup_switch = Instruction::Lookupswitch(LookupSwitch {
+            default: 20,
+            pairs: vec![],
+        });
+        assert_eq!(12, lookup_switch.size(0).unwrap());
+        assert_eq!(9, lookup_switch.size(3).unwrap());
+        assert_eq!(10, lookup_switch.size(6).unwrap());
+    }
+
+    #[test]
+    fn cannot_encode_jumps_too_far_for_their_offset() {
+        assert!(matches!(
+            Instruction::Goto(40000).encode(0, &mut Vec::new()),
+            Err(ClassWriterError::LimitExceeded(_))
+        ));
+        assert!(Instruction::Goto_w(40000)
+            .encode(0, &mut Vec::new())
+            .is_ok());
+    }
 }
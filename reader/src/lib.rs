@@ -1,23 +1,52 @@
 #[macro_use]
 extern crate bitflags;
 
-mod attribute;
+pub mod annotation;
+pub mod attribute;
+pub mod bootstrap_method;
 mod buffer;
+mod buffer_writer;
 pub mod class_access_flags;
 pub mod class_file;
 pub mod class_file_field;
 pub mod class_file_method;
 pub mod class_file_version;
+pub mod class_file_view;
 pub mod class_reader;
 pub mod class_reader_error;
+pub mod class_writer;
+pub mod class_writer_error;
+pub mod code_assembler;
 pub mod constant_pool;
+pub mod constant_pool_builder;
+pub mod control_flow_graph;
+pub mod custom_attribute;
+pub mod dataflow;
+pub mod disassembler;
+pub mod enclosing_method;
 pub mod exception_table;
 pub mod field_flags;
 pub mod field_type;
+#[cfg(feature = "serde")]
+mod flags_serde;
+pub mod frame_analysis;
+pub mod inner_class;
+pub mod inner_class_flags;
 pub mod instruction;
 pub mod line_number;
 pub mod line_number_table;
+pub mod local_variable_table;
 pub mod method_descriptor;
 pub mod method_flags;
+pub mod method_parameter;
+pub mod method_parameter_flags;
+pub mod module;
+pub mod module_flags;
 pub mod program_counter;
+pub mod record_component;
+pub mod signature;
+pub mod stack_map_table;
+pub mod text_assembler;
+pub mod text_assembler_error;
+pub mod type_annotation;
 pub mod type_conversion;
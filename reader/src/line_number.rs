@@ -5,6 +5,7 @@ use std::{
 
 /// Line number in the source code
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineNumber(pub u16);
 
 impl Display for LineNumber {
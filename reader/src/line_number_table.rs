@@ -10,6 +10,7 @@ use crate::program_counter::ProgramCounter;
 /// the second at 3, means that the first three instructions in the bytecode correspond to line 1
 /// and the rest to line 2.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineNumberTable {
     entries: Vec<LineNumberTableEntry>,
 }
@@ -31,10 +32,15 @@ impl LineNumberTable {
         };
         self.entries[best_matching_entry_index].line_number
     }
+
+    pub fn entries(&self) -> &[LineNumberTableEntry] {
+        &self.entries
+    }
 }
 
 /// Entries of a [LineNumberTable]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineNumberTableEntry {
     pub program_counter: ProgramCounter,
     pub line_number: LineNumber,
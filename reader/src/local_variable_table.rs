@@ -0,0 +1,96 @@
+use std::{fmt, fmt::Formatter, ops::Range};
+
+use crate::{field_type::FieldType, program_counter::ProgramCounter, signature::TypeSignature};
+
+/// Table that models the names and types of the local variables of a method, as generated
+/// by the compiler when debug information is enabled. It merges the content of both the
+/// `LocalVariableTable` and `LocalVariableTypeTable` attributes.
+/// Note that the same slot can be used by different variables in different parts of the code,
+/// so entries must be looked up by both the program counter and the slot.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocalVariableTable {
+    entries: Vec<LocalVariableTableEntry>,
+}
+
+impl LocalVariableTable {
+    pub fn new(entries: Vec<LocalVariableTableEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Returns the variable stored in the given slot at the given program counter, if any
+    pub fn lookup(&self, pc: ProgramCounter, index: u16) -> Option<&LocalVariableTableEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.index == index && entry.range.contains(&pc))
+    }
+
+    pub fn entries(&self) -> &[LocalVariableTableEntry] {
+        &self.entries
+    }
+}
+
+/// Entries of a [LocalVariableTable]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocalVariableTableEntry {
+    /// The range of program counters in which the variable has a value
+    pub range: Range<ProgramCounter>,
+    pub name: String,
+    pub type_descriptor: FieldType,
+    /// Generic signature, present only if the type of the variable is generic
+    pub signature: Option<TypeSignature>,
+    /// The slot in the local variables. Variables of type long or double occupy
+    /// both this slot and the next one.
+    pub index: u16,
+}
+
+impl fmt::Display for LocalVariableTableEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.signature {
+            Some(signature) => write!(f, "{}: {}", self.name, signature)?,
+            None => write!(f, "{}: {}", self.name, self.type_descriptor)?,
+        }
+        write!(
+            f,
+            " (slot {}, pc {}..{})",
+            self.index, self.range.start, self.range.end
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        field_type::{BaseType, FieldType},
+        local_variable_table::{LocalVariableTable, LocalVariableTableEntry},
+        program_counter::ProgramCounter,
+    };
+
+    fn entry(name: &str, start: u16, end: u16, index: u16) -> LocalVariableTableEntry {
+        LocalVariableTableEntry {
+            range: ProgramCounter(start)..ProgramCounter(end),
+            name: name.to_string(),
+            type_descriptor: FieldType::Base(BaseType::Int),
+            signature: None,
+            index,
+        }
+    }
+
+    #[test]
+    fn can_lookup_variable_by_pc_and_slot() {
+        let table = LocalVariableTable::new(vec![
+            entry("first", 0, 10, 0),
+            entry("second", 2, 6, 1),
+            entry("reused", 6, 10, 1),
+        ]);
+
+        assert_eq!("first", table.lookup(ProgramCounter(0), 0).unwrap().name);
+        assert_eq!("first", table.lookup(ProgramCounter(9), 0).unwrap().name);
+        assert_eq!(None, table.lookup(ProgramCounter(10), 0));
+        assert_eq!(None, table.lookup(ProgramCounter(1), 1));
+        assert_eq!("second", table.lookup(ProgramCounter(5), 1).unwrap().name);
+        assert_eq!("reused", table.lookup(ProgramCounter(6), 1).unwrap().name);
+        assert_eq!(None, table.lookup(ProgramCounter(0), 2));
+    }
+}
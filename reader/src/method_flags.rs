@@ -21,3 +21,6 @@ impl Default for MethodFlags {
         MethodFlags::empty()
     }
 }
+
+#[cfg(feature = "serde")]
+crate::flags_serde::impl_serde_for_flags!(MethodFlags);
@@ -0,0 +1,24 @@
+use std::fmt;
+
+use crate::method_parameter_flags::MethodParameterFlags;
+
+/// An entry of the `MethodParameters` attribute, which javac generates when invoked
+/// with `-parameters`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MethodParameter {
+    /// The name in the source code. Missing if the compiler did not record it.
+    pub name: Option<String>,
+    pub flags: MethodParameterFlags,
+}
+
+impl fmt::Display for MethodParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {}",
+            self.flags,
+            self.name.as_deref().unwrap_or("<no name>")
+        )
+    }
+}
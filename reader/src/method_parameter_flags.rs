@@ -0,0 +1,19 @@
+bitflags! {
+    /// Flags of a method parameter, from the `MethodParameters` attribute
+    pub struct MethodParameterFlags: u16 {
+        const FINAL = 0x0010;
+        const SYNTHETIC = 0x1000;
+        /// The parameter is implicitly declared, as mandated by the language,
+        /// like the outer instance of the constructor of an inner class
+        const MANDATED = 0x8000;
+    }
+}
+
+impl Default for MethodParameterFlags {
+    fn default() -> MethodParameterFlags {
+        MethodParameterFlags::empty()
+    }
+}
+
+#[cfg(feature = "serde")]
+crate::flags_serde::impl_serde_for_flags!(MethodParameterFlags);
@@ -0,0 +1,107 @@
+use std::{fmt, fmt::Formatter};
+
+use itertools::Itertools;
+
+use crate::module_flags::{ModuleFlags, ModulePackageFlags, ModuleRequiresFlags};
+
+/// The content of the `Module` attribute, found in the `module-info.class` of a module.
+/// Modules are named with dots, like `java.base`, whereas packages and classes are
+/// in the internal form, like `java/lang`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Module {
+    pub name: String,
+    pub flags: ModuleFlags,
+    pub version: Option<String>,
+    pub requires: Vec<ModuleRequires>,
+    pub exports: Vec<ModulePackage>,
+    pub opens: Vec<ModulePackage>,
+    /// The services that the module consumes via `java.util.ServiceLoader`
+    pub uses: Vec<String>,
+    pub provides: Vec<ModuleProvides>,
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {}", self.flags, self.name)?;
+        if let Some(version) = &self.version {
+            write!(f, "@{version}")?;
+        }
+        for requires in self.requires.iter() {
+            write!(f, "\n  requires {requires}")?;
+        }
+        for exports in self.exports.iter() {
+            write!(f, "\n  exports {exports}")?;
+        }
+        for opens in self.opens.iter() {
+            write!(f, "\n  opens {opens}")?;
+        }
+        for uses in self.uses.iter() {
+            write!(f, "\n  uses {uses}")?;
+        }
+        for provides in self.provides.iter() {
+            write!(f, "\n  provides {provides}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A dependence of a module on another one
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleRequires {
+    pub module: String,
+    pub flags: ModuleRequiresFlags,
+    /// The version of the required module at compile time, if known
+    pub version: Option<String>,
+}
+
+impl fmt::Display for ModuleRequires {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {}", self.flags, self.module)?;
+        if let Some(version) = &self.version {
+            write!(f, "@{version}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A package exported or opened by a module, either to all the other modules or only
+/// to the given ones
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModulePackage {
+    pub package: String,
+    pub flags: ModulePackageFlags,
+    /// The modules that can access the package. Empty if every module can.
+    pub to: Vec<String>,
+}
+
+impl fmt::Display for ModulePackage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {}", self.flags, self.package)?;
+        if !self.to.is_empty() {
+            write!(f, " to {}", self.to.iter().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The implementations of a service provided by a module
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleProvides {
+    pub service: String,
+    pub implementations: Vec<String>,
+}
+
+impl fmt::Display for ModuleProvides {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} with {}",
+            self.service,
+            self.implementations.iter().join(", ")
+        )
+    }
+}
@@ -0,0 +1,56 @@
+bitflags! {
+    /// Flags of a module, from the `Module` attribute
+    pub struct ModuleFlags: u16 {
+        /// All the packages of the module are open to deep reflection
+        const OPEN = 0x0020;
+        const SYNTHETIC = 0x1000;
+        const MANDATED = 0x8000;
+    }
+}
+
+impl Default for ModuleFlags {
+    fn default() -> ModuleFlags {
+        ModuleFlags::empty()
+    }
+}
+
+bitflags! {
+    /// Flags of a dependence of a module
+    pub struct ModuleRequiresFlags: u16 {
+        /// Modules that depend on this one implicitly depend on the required module too
+        const TRANSITIVE = 0x0020;
+        /// The dependence is mandatory at compile time, but optional at run time
+        const STATIC_PHASE = 0x0040;
+        const SYNTHETIC = 0x1000;
+        const MANDATED = 0x8000;
+    }
+}
+
+impl Default for ModuleRequiresFlags {
+    fn default() -> ModuleRequiresFlags {
+        ModuleRequiresFlags::empty()
+    }
+}
+
+bitflags! {
+    /// Flags of a package exported or opened by a module
+    pub struct ModulePackageFlags: u16 {
+        const SYNTHETIC = 0x1000;
+        const MANDATED = 0x8000;
+    }
+}
+
+impl Default for ModulePackageFlags {
+    fn default() -> ModulePackageFlags {
+        ModulePackageFlags::empty()
+    }
+}
+
+#[cfg(feature = "serde")]
+crate::flags_serde::impl_serde_for_flags!(ModuleFlags);
+
+#[cfg(feature = "serde")]
+crate::flags_serde::impl_serde_for_flags!(ModuleRequiresFlags);
+
+#[cfg(feature = "serde")]
+crate::flags_serde::impl_serde_for_flags!(ModulePackageFlags);
@@ -5,6 +5,7 @@ use std::{
 
 /// Models the program counter, i.e. the address of an instruction in the bytecode of a method
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProgramCounter(pub u16);
 
 impl Display for ProgramCounter {
@@ -0,0 +1,36 @@
+use std::{fmt, fmt::Formatter};
+
+use crate::{
+    annotation::Annotation, attribute::Attribute, field_type::FieldType, signature::FieldSignature,
+    type_annotation::TypeAnnotation,
+};
+
+/// A component of a record class, from the `Record` attribute
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordComponent {
+    pub name: String,
+    pub type_descriptor: FieldType,
+    /// Generic signature, present only if the type of the component is generic
+    pub signature: Option<FieldSignature>,
+    pub annotations: Vec<Annotation>,
+    pub type_annotations: Vec<TypeAnnotation>,
+    /// Generic attributes of the component that are not parsed into the other fields
+    pub attributes: Vec<Attribute>,
+}
+
+impl fmt::Display for RecordComponent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.type_descriptor)?;
+        if let Some(signature) = &self.signature {
+            write!(f, " signature: {signature}")?;
+        }
+        for annotation in self.annotations.iter() {
+            write!(f, " {annotation}")?;
+        }
+        for type_annotation in self.type_annotations.iter() {
+            write!(f, " {type_annotation}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,829 @@
+use std::{fmt, fmt::Formatter, iter::Peekable, str::Chars};
+
+use itertools::Itertools;
+
+use crate::{
+    class_reader_error::{ClassReaderError, ClassReaderError::InvalidTypeDescriptor},
+    field_type::{BaseType, FieldType, MAX_ARRAY_DIMENSIONS},
+};
+
+/// Models a type in a generic signature. Unlike [crate::field_type::FieldType], it retains
+/// the type arguments of classes and can refer to type variables.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeSignature {
+    /// Primitive types
+    Base(BaseType),
+
+    /// A class or interface, possibly parameterized
+    Class(ClassTypeSignature),
+
+    /// A type variable, such as `T`
+    TypeVariable(String),
+
+    /// Array
+    Array(Box<TypeSignature>),
+}
+
+impl fmt::Display for TypeSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeSignature::Base(base) => write!(f, "{}", base.to_string().to_lowercase()),
+            TypeSignature::Class(class) => write!(f, "{class}"),
+            TypeSignature::TypeVariable(name) => f.write_str(name),
+            TypeSignature::Array(component_type) => write!(f, "{component_type}[]"),
+        }
+    }
+}
+
+/// A class type, such as `java.util.Map<K, V>.Entry<K, V>`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassTypeSignature {
+    /// The package, in the internal form, for example `java/util/`. Empty for the default package.
+    pub package: String,
+    /// The outermost class, followed by the nested classes
+    pub classes: Vec<SimpleClassTypeSignature>,
+}
+
+impl ClassTypeSignature {
+    /// Returns the binary name of the class, i.e. the name used in a field descriptor
+    pub fn binary_name(&self) -> String {
+        format!(
+            "{}{}",
+            self.package,
+            self.classes.iter().map(|class| &class.name).join("$")
+        )
+    }
+
+    fn encode_into(&self, out: &mut String) {
+        out.push('L');
+        out.push_str(&self.package);
+        for (index, class) in self.classes.iter().enumerate() {
+            if index > 0 {
+                out.push('.');
+            }
+            out.push_str(&class.name);
+            if !class.type_arguments.is_empty() {
+                out.push('<');
+                for type_argument in class.type_arguments.iter() {
+                    match type_argument {
+                        TypeArgument::Exact(type_signature) => type_signature.encode_into(out),
+                        TypeArgument::Wildcard => out.push('*'),
+                        TypeArgument::Extends(type_signature) => {
+                            out.push('+');
+                            type_signature.encode_into(out)
+                        }
+                        TypeArgument::Super(type_signature) => {
+                            out.push('-');
+                            type_signature.encode_into(out)
+                        }
+                    }
+                }
+                out.push('>');
+            }
+        }
+        out.push(';');
+    }
+}
+
+/// Renders the class as in Java source code, i.e. `java.util.Map<K, V>.Entry<K, V>`
+impl fmt::Display for ClassTypeSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.package.replace('/', "."),
+            self.classes.iter().join(".")
+        )
+    }
+}
+
+/// One class in a [ClassTypeSignature], with its type arguments
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimpleClassTypeSignature {
+    pub name: String,
+    pub type_arguments: Vec<TypeArgument>,
+}
+
+impl fmt::Display for SimpleClassTypeSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if !self.type_arguments.is_empty() {
+            write!(f, "<{}>", self.type_arguments.iter().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A type argument of a parameterized class
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeArgument {
+    /// An exact type, such as `String` in `List<String>`
+    Exact(TypeSignature),
+    /// The unbounded wildcard `?`
+    Wildcard,
+    /// A wildcard with an upper bound, i.e. `? extends T`
+    Extends(TypeSignature),
+    /// A wildcard with a lower bound, i.e. `? super T`
+    Super(TypeSignature),
+}
+
+impl fmt::Display for TypeArgument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeArgument::Exact(type_signature) => write!(f, "{type_signature}"),
+            TypeArgument::Wildcard => f.write_str("?"),
+            TypeArgument::Extends(type_signature) => write!(f, "? extends {type_signature}"),
+            TypeArgument::Super(type_signature) => write!(f, "? super {type_signature}"),
+        }
+    }
+}
+
+/// A formal type parameter of a class or method, such as `T extends Comparable<T>`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeParameter {
+    pub name: String,
+    /// Missing if the parameter is bound only by interfaces
+    pub class_bound: Option<TypeSignature>,
+    pub interface_bounds: Vec<TypeSignature>,
+}
+
+impl fmt::Display for TypeParameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        let bounds = self
+            .class_bound
+            .iter()
+            .chain(self.interface_bounds.iter())
+            .join(" & ");
+        if !bounds.is_empty() {
+            write!(f, " extends {bounds}")?;
+        }
+        Ok(())
+    }
+}
+
+fn encode_type_parameters(out: &mut String, type_parameters: &[TypeParameter]) {
+    if type_parameters.is_empty() {
+        return;
+    }
+    out.push('<');
+    for type_parameter in type_parameters.iter() {
+        out.push_str(&type_parameter.name);
+        // The colon of the class bound is present even if the bound is missing
+        out.push(':');
+        if let Some(class_bound) = &type_parameter.class_bound {
+            class_bound.encode_into(out);
+        }
+        for interface_bound in type_parameter.interface_bounds.iter() {
+            out.push(':');
+            interface_bound.encode_into(out);
+        }
+    }
+    out.push('>');
+}
+
+fn fmt_type_parameters(f: &mut Formatter<'_>, type_parameters: &[TypeParameter]) -> fmt::Result {
+    if type_parameters.is_empty() {
+        Ok(())
+    } else {
+        write!(f, "<{}> ", type_parameters.iter().join(", "))
+    }
+}
+
+/// The generic signature of a class
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub superclass: ClassTypeSignature,
+    pub interfaces: Vec<ClassTypeSignature>,
+}
+
+impl fmt::Display for ClassSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_type_parameters(f, &self.type_parameters)?;
+        write!(f, "extends {}", self.superclass)?;
+        if !self.interfaces.is_empty() {
+            write!(f, " implements {}", self.interfaces.iter().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl ClassSignature {
+    /// Parses a class signature as specified in the JVM specs:
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-4.html#jvms-4.7.9.1
+    pub fn parse(signature: &str) -> Result<ClassSignature, ClassReaderError> {
+        let mut parser = SignatureParser::new(signature);
+        let type_parameters = parser.parse_type_parameters()?;
+        let superclass = parser.parse_class_type()?;
+        let mut interfaces = Vec::new();
+        while parser.peek().is_some() {
+            interfaces.push(parser.parse_class_type()?);
+        }
+        Ok(ClassSignature {
+            type_parameters,
+            superclass,
+            interfaces,
+        })
+    }
+
+    /// Returns the signature in the JVM internal form, i.e. the inverse of [ClassSignature::parse]
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        encode_type_parameters(&mut out, &self.type_parameters);
+        self.superclass.encode_into(&mut out);
+        for interface in self.interfaces.iter() {
+            interface.encode_into(&mut out);
+        }
+        out
+    }
+}
+
+/// The generic signature of a method
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MethodSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub parameters: Vec<TypeSignature>,
+    pub return_type: Option<TypeSignature>,
+    /// The exceptions in the `throws` clause: either class types or type variables
+    pub thrown_exceptions: Vec<TypeSignature>,
+}
+
+impl fmt::Display for MethodSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_type_parameters(f, &self.type_parameters)?;
+        write!(f, "({})", self.parameters.iter().join(", "))?;
+        match &self.return_type {
+            Some(return_type) => write!(f, " -> {return_type}")?,
+            None => f.write_str(" -> void")?,
+        }
+        if !self.thrown_exceptions.is_empty() {
+            write!(f, " throws {}", self.thrown_exceptions.iter().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl MethodSignature {
+    /// Parses a method signature as specified in the JVM specs:
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-4.html#jvms-4.7.9.1
+    pub fn parse(signature: &str) -> Result<MethodSignature, ClassReaderError> {
+        let mut parser = SignatureParser::new(signature);
+        let type_parameters = parser.parse_type_parameters()?;
+
+        parser.expect('(')?;
+        let mut parameters = Vec::new();
+        while parser.peek() != Some(')') {
+            parameters.push(parser.parse_java_type()?);
+        }
+        parser.expect(')')?;
+
+        let return_type = if parser.peek() == Some('V') {
+            parser.next()?;
+            None
+        } else {
+            Some(parser.parse_java_type()?)
+        };
+
+        let mut thrown_exceptions = Vec::new();
+        while parser.peek().is_some() {
+            parser.expect('^')?;
+            let exception = match parser.peek() {
+                Some('T') => parser.parse_type_variable()?,
+                _ => TypeSignature::Class(parser.parse_class_type()?),
+            };
+            thrown_exceptions.push(exception);
+        }
+
+        Ok(MethodSignature {
+            type_parameters,
+            parameters,
+            return_type,
+            thrown_exceptions,
+        })
+    }
+
+    /// Returns the signature in the JVM internal form, i.e. the inverse of [MethodSignature::parse]
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        encode_type_parameters(&mut out, &self.type_parameters);
+        out.push('(');
+        for parameter in self.parameters.iter() {
+            parameter.encode_into(&mut out);
+        }
+        out.push(')');
+        match &self.return_type {
+            Some(return_type) => return_type.encode_into(&mut out),
+            None => out.push('V'),
+        }
+        for exception in self.thrown_exceptions.iter() {
+            out.push('^');
+            exception.encode_into(&mut out);
+        }
+        out
+    }
+}
+
+/// The generic signature of a field: it is always a reference type
+pub type FieldSignature = TypeSignature;
+
+impl TypeSignature {
+    /// Parses a field signature as specified in the JVM specs:
+    /// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-4.html#jvms-4.7.9.1
+    pub fn parse_field_signature(signature: &str) -> Result<FieldSignature, ClassReaderError> {
+        let mut parser = SignatureParser::new(signature);
+        let field_signature = parser.parse_reference_type()?;
+        match parser.peek() {
+            None => Ok(field_signature),
+            Some(_) => Err(parser.error()),
+        }
+    }
+
+    /// Returns the signature in the JVM internal form, i.e. the inverse of
+    /// [TypeSignature::parse_field_signature]
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        self.encode_into(&mut out);
+        out
+    }
+
+    fn encode_into(&self, out: &mut String) {
+        match self {
+            TypeSignature::Base(base) => out.push_str(&FieldType::Base(base.clone()).descriptor()),
+            TypeSignature::Class(class) => class.encode_into(out),
+            TypeSignature::TypeVariable(name) => {
+                out.push('T');
+                out.push_str(name);
+                out.push(';');
+            }
+            TypeSignature::Array(component_type) => {
+                out.push('[');
+                component_type.encode_into(out);
+            }
+        }
+    }
+}
+
+/// Bounds the nesting of type arguments, so that malformed signatures cannot overflow
+/// the stack of the recursive descent parser. Real code nests them a few levels at most.
+const MAX_NESTING_DEPTH: usize = 64;
+
+/// Recursive descent parser for the signature grammar
+struct SignatureParser<'s> {
+    signature: &'s str,
+    chars: Peekable<Chars<'s>>,
+    /// The number of reference types being parsed, one inside the other, not counting
+    /// the dimensions of arrays
+    depth: usize,
+}
+
+impl<'s> SignatureParser<'s> {
+    fn new(signature: &'s str) -> Self {
+        Self {
+            signature,
+            chars: signature.chars().peekable(),
+            depth: 0,
+        }
+    }
+
+    fn error(&self) -> ClassReaderError {
+        InvalidTypeDescriptor(self.signature.to_string())
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn next(&mut self) -> Result<char, ClassReaderError> {
+        self.chars.next().ok_or_else(|| self.error())
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ClassReaderError> {
+        if self.next()? == expected {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<String, ClassReaderError> {
+        let mut identifier = String::new();
+        while let Some(c) = self.peek() {
+            if matches!(c, '.' | ';' | '[' | '/' | '<' | '>' | ':') {
+                break;
+            }
+            identifier.push(c);
+            self.chars.next();
+        }
+        if identifier.is_empty() {
+            Err(self.error())
+        } else {
+            Ok(identifier)
+        }
+    }
+
+    fn parse_type_parameters(&mut self) -> Result<Vec<TypeParameter>, ClassReaderError> {
+        let mut type_parameters = Vec::new();
+        if self.peek() != Some('<') {
+            return Ok(type_parameters);
+        }
+        self.next()?;
+        while self.peek() != Some('>') {
+            type_parameters.push(self.parse_type_parameter()?);
+        }
+        self.expect('>')?;
+        if type_parameters.is_empty() {
+            Err(self.error())
+        } else {
+            Ok(type_parameters)
+        }
+    }
+
+    fn parse_type_parameter(&mut self) -> Result<TypeParameter, ClassReaderError> {
+        let name = self.parse_identifier()?;
+        self.expect(':')?;
+        let class_bound = match self.peek() {
+            Some(':') => None,
+            _ => Some(self.parse_reference_type()?),
+        };
+        let mut interface_bounds = Vec::new();
+        while self.peek() == Some(':') {
+            self.next()?;
+            interface_bounds.push(self.parse_reference_type()?);
+        }
+        Ok(TypeParameter {
+            name,
+            class_bound,
+            interface_bounds,
+        })
+    }
+
+    fn parse_java_type(&mut self) -> Result<TypeSignature, ClassReaderError> {
+        let base_type = match self.peek() {
+            Some('B') => BaseType::Byte,
+            Some('C') => BaseType::Char,
+            Some('D') => BaseType::Double,
+            Some('F') => BaseType::Float,
+            Some('I') => BaseType::Int,
+            Some('J') => BaseType::Long,
+            Some('S') => BaseType::Short,
+            Some('Z') => BaseType::Boolean,
+            _ => return self.parse_reference_type(),
+        };
+        self.next()?;
+        Ok(TypeSignature::Base(base_type))
+    }
+
+    fn parse_reference_type(&mut self) -> Result<TypeSignature, ClassReaderError> {
+        if self.depth == MAX_NESTING_DEPTH {
+            return Err(self.error());
+        }
+        self.depth += 1;
+        let reference_type = self.parse_nested_reference_type();
+        self.depth -= 1;
+        reference_type
+    }
+
+    fn parse_nested_reference_type(&mut self) -> Result<TypeSignature, ClassReaderError> {
+        match self.peek() {
+            Some('L') => Ok(TypeSignature::Class(self.parse_class_type()?)),
+            Some('T') => self.parse_type_variable(),
+            Some('[') => {
+                let mut dimensions = 0;
+                while self.peek() == Some('[') {
+                    self.next()?;
+                    dimensions += 1;
+                }
+                if dimensions > MAX_ARRAY_DIMENSIONS {
+                    return Err(self.error());
+                }
+                let mut array_type = self.parse_java_type()?;
+                for _ in 0..dimensions {
+                    array_type = TypeSignature::Array(Box::new(array_type));
+                }
+                Ok(array_type)
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn parse_type_variable(&mut self) -> Result<TypeSignature, ClassReaderError> {
+        self.expect('T')?;
+        let name = self.parse_identifier()?;
+        self.expect(';')?;
+        Ok(TypeSignature::TypeVariable(name))
+    }
+
+    fn parse_class_type(&mut self) -> Result<ClassTypeSignature, ClassReaderError> {
+        self.expect('L')?;
+
+        // The package specifier is made of identifiers followed by slashes,
+        // so we can only recognize the class name when we find no more slashes
+        let mut package = String::new();
+        let mut name = self.parse_identifier()?;
+        while self.peek() == Some('/') {
+            self.next()?;
+            package.push_str(&name);
+            package.push('/');
+            name = self.parse_identifier()?;
+        }
+
+        let mut classes = vec![self.parse_simple_class_type(name)?];
+        while self.peek() == Some('.') {
+            self.next()?;
+            let name = self.parse_identifier()?;
+            classes.push(self.parse_simple_class_type(name)?);
+        }
+        self.expect(';')?;
+
+        Ok(ClassTypeSignature { package, classes })
+    }
+
+    fn parse_simple_class_type(
+        &mut self,
+        name: String,
+    ) -> Result<SimpleClassTypeSignature, ClassReaderError> {
+        let mut type_arguments = Vec::new();
+        if self.peek() == Some('<') {
+            self.next()?;
+            while self.peek() != Some('>') {
+                type_arguments.push(self.parse_type_argument()?);
+            }
+            self.expect('>')?;
+            if type_arguments.is_empty() {
+                return Err(self.error());
+            }
+        }
+        Ok(SimpleClassTypeSignature {
+            name,
+            type_arguments,
+        })
+    }
+
+    fn parse_type_argument(&mut self) -> Result<TypeArgument, ClassReaderError> {
+        match self.peek() {
+            Some('*') => {
+                self.next()?;
+                Ok(TypeArgument::Wildcard)
+            }
+            Some('+') => {
+                self.next()?;
+                Ok(TypeArgument::Extends(self.parse_reference_type()?))
+            }
+            Some('-') => {
+                self.next()?;
+                Ok(TypeArgument::Super(self.parse_reference_type()?))
+            }
+            _ => Ok(TypeArgument::Exact(self.parse_reference_type()?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        class_reader_error::ClassReaderError,
+        field_type::BaseType,
+        signature::{
+            ClassSignature, ClassTypeSignature, MethodSignature, SimpleClassTypeSignature,
+            TypeArgument, TypeParameter, TypeSignature,
+        },
+    };
+
+    fn class(package: &str, name: &str, type_arguments: Vec<TypeArgument>) -> ClassTypeSignature {
+        ClassTypeSignature {
+            package: package.to_string(),
+            classes: vec![SimpleClassTypeSignature {
+                name: name.to_string(),
+                type_arguments,
+            }],
+        }
+    }
+
+    fn type_variable(name: &str) -> TypeSignature {
+        TypeSignature::TypeVariable(name.to_string())
+    }
+
+    #[test]
+    fn can_parse_parameterized_field_signature() {
+        let signature = TypeSignature::parse_field_signature(
+            "Ljava/util/List<Ljava/util/Map<Ljava/lang/String;TT;>;>;",
+        )
+        .unwrap();
+        assert_eq!(
+            TypeSignature::Class(class(
+                "java/util/",
+                "List",
+                vec![TypeArgument::Exact(TypeSignature::Class(class(
+                    "java/util/",
+                    "Map",
+                    vec![
+                        TypeArgument::Exact(TypeSignature::Class(class(
+                            "java/lang/",
+                            "String",
+                            vec![]
+                        ))),
+                        TypeArgument::Exact(type_variable("T")),
+                    ]
+                )))]
+            )),
+            signature
+        );
+        assert_eq!(
+            "java.util.List<java.util.Map<java.lang.String, T>>",
+            signature.to_string()
+        );
+    }
+
+    #[test]
+    fn can_parse_wildcards_and_arrays() {
+        let signature =
+            TypeSignature::parse_field_signature("Ljava/util/Map<*+[TT;-Ljava/lang/Number;>;")
+                .unwrap();
+        assert_eq!(
+            "java.util.Map<?, ? extends T[], ? super java.lang.Number>",
+            signature.to_string()
+        );
+    }
+
+    #[test]
+    fn can_parse_nested_class_types() {
+        let signature =
+            TypeSignature::parse_field_signature("Ljava/util/Map<TK;TV;>.Entry<TK;TV;>;").unwrap();
+        assert_eq!("java.util.Map<K, V>.Entry<K, V>", signature.to_string());
+        match signature {
+            TypeSignature::Class(class) => assert_eq!("java/util/Map$Entry", class.binary_name()),
+            _ => panic!("should have parsed a class type"),
+        }
+    }
+
+    #[test]
+    fn display_uses_java_names() {
+        let signature = TypeSignature::parse_field_signature(
+            "Ljava/util/Map<TK;Ljava/util/List<+Ljava/lang/Comparable<-TV;>;>;>.Entry<*[[J>;",
+        )
+        .unwrap();
+        assert_eq!(
+            "java.util.Map<K, java.util.List<? extends java.lang.Comparable<? super V>>>\
+            .Entry<?, long[][]>",
+            signature.to_string()
+        );
+
+        let signature = MethodSignature::parse(
+            "<T:Ljava/lang/Object;>(Lrjvm/Outer<TT;>.Inner<Ljava/lang/String;>;)[TT;",
+        )
+        .unwrap();
+        assert_eq!(
+            "<T extends java.lang.Object> (rjvm.Outer<T>.Inner<java.lang.String>) -> T[]",
+            signature.to_string()
+        );
+    }
+
+    #[test]
+    fn can_parse_class_signature() {
+        let signature = ClassSignature::parse(
+            "<K::Ljava/lang/Comparable<TK;>;V:Ljava/lang/Object;>Ljava/lang/Object;Ljava/lang/Iterable<TV;>;",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                TypeParameter {
+                    name: "K".to_string(),
+                    class_bound: None,
+                    interface_bounds: vec![TypeSignature::Class(class(
+                        "java/lang/",
+                        "Comparable",
+                        vec![TypeArgument::Exact(type_variable("K"))]
+                    ))],
+                },
+                TypeParameter {
+                    name: "V".to_string(),
+                    class_bound: Some(TypeSignature::Class(class("java/lang/", "Object", vec![]))),
+                    interface_bounds: vec![],
+                },
+            ],
+            signature.type_parameters
+        );
+        assert_eq!(
+            "<K extends java.lang.Comparable<K>, V extends java.lang.Object> \
+            extends java.lang.Object implements java.lang.Iterable<V>",
+            signature.to_string()
+        );
+    }
+
+    #[test]
+    fn can_parse_method_signature() {
+        let signature = MethodSignature::parse(
+            "<E:Ljava/lang/Exception;>([ILjava/util/List<*>;)TE;^TE;^Ljava/io/IOException;",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                TypeSignature::Array(Box::new(TypeSignature::Base(BaseType::Int))),
+                TypeSignature::Class(class("java/util/", "List", vec![TypeArgument::Wildcard])),
+            ],
+            signature.parameters
+        );
+        assert_eq!(Some(type_variable("E")), signature.return_type);
+        assert_eq!(
+            vec![
+                type_variable("E"),
+                TypeSignature::Class(class("java/io/", "IOException", vec![])),
+            ],
+            signature.thrown_exceptions
+        );
+        assert_eq!(
+            "<E extends java.lang.Exception> (int[], java.util.List<?>) -> E \
+            throws E, java.io.IOException",
+            signature.to_string()
+        );
+    }
+
+    #[test]
+    fn can_parse_void_method_signature() {
+        let signature = MethodSignature::parse("(Ljava/util/List<Ljava/lang/String;>;)V").unwrap();
+        assert_eq!(None, signature.return_type);
+        assert_eq!(
+            "(java.util.List<java.lang.String>) -> void",
+            signature.to_string()
+        );
+    }
+
+    #[test]
+    fn encoding_is_the_inverse_of_parsing() {
+        for signature in [
+            "Ljava/util/List<Ljava/util/Map<Ljava/lang/String;TT;>;>;",
+            "Ljava/util/Map<*+[TT;-Ljava/lang/Number;>;",
+            "Ljava/util/Map<TK;TV;>.Entry<TK;TV;>;",
+            "[[TT;",
+        ] {
+            assert_eq!(
+                signature,
+                TypeSignature::parse_field_signature(signature)
+                    .unwrap()
+                    .encode()
+            );
+        }
+        let signature = "<K::Ljava/lang/Comparable<TK;>;V:Ljava/lang/Object;>Ljava/lang/Object;Ljava/lang/Iterable<TV;>;";
+        assert_eq!(
+            signature,
+            ClassSignature::parse(signature).unwrap().encode()
+        );
+        for signature in [
+            "<E:Ljava/lang/Exception;>([ILjava/util/List<*>;)TE;^TE;^Ljava/io/IOException;",
+            "(Ljava/util/List<Ljava/lang/String;>;)V",
+        ] {
+            assert_eq!(
+                signature,
+                MethodSignature::parse(signature).unwrap().encode()
+            );
+        }
+    }
+
+    #[test]
+    fn cannot_parse_invalid_signatures() {
+        for signature in [
+            "",
+            "I",
+            "Ljava/util/List<>;",
+            "Ljava/util/List<Ljava/lang/String;>",
+            "TT",
+            "Ljava/lang/Object;X",
+        ] {
+            assert!(matches!(
+                TypeSignature::parse_field_signature(signature),
+                Err(ClassReaderError::InvalidTypeDescriptor(s)) if s == signature
+            ));
+        }
+        for signature in ["<>Ljava/lang/Object;", "<T>Ljava/lang/Object;", ""] {
+            assert!(ClassSignature::parse(signature).is_err());
+        }
+        for signature in ["()", "(I)VV", "()V^I", "<T:>()V"] {
+            assert!(MethodSignature::parse(signature).is_err());
+        }
+    }
+
+    #[test]
+    fn cannot_parse_signatures_nested_too_deeply() {
+        let nested_lists = |depth: usize| {
+            format!(
+                "{}Ljava/lang/String;{}",
+                "Ljava/util/List<".repeat(depth),
+                ">;".repeat(depth)
+            )
+        };
+        assert!(TypeSignature::parse_field_signature(&nested_lists(63)).is_ok());
+        assert!(TypeSignature::parse_field_signature(&nested_lists(100_000)).is_err());
+
+        let array = |dimensions: usize| format!("{}I", "[".repeat(dimensions));
+        assert!(TypeSignature::parse_field_signature(&array(255)).is_ok());
+        assert!(TypeSignature::parse_field_signature(&array(100_000)).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use std::{fmt, fmt::Formatter};
+
+use itertools::Itertools;
+
+use crate::{
+    field_type::{BaseType, FieldType},
+    program_counter::ProgramCounter,
+};
+
+/// One frame of the `StackMapTable` attribute, which declares the types of the local
+/// variables and of the operand stack at a given instruction. Frames other than
+/// [StackMapFrameKind::Full] are expressed as a difference from the previous frame.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StackMapFrame {
+    /// The absolute offset in the bytecode, computed from the deltas stored in the class file
+    pub program_counter: ProgramCounter,
+    pub kind: StackMapFrameKind,
+}
+
+impl fmt::Display for StackMapFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.program_counter, self.kind)
+    }
+}
+
+/// Possible kinds of [StackMapFrame]. The extended forms are used when the offset delta from
+/// the previous frame does not fit in the compact encoding.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StackMapFrameKind {
+    /// Same locals as the previous frame and empty stack
+    Same,
+    SameExtended,
+    /// Same locals as the previous frame and a stack with exactly one item
+    SameLocals1StackItem(VerificationType),
+    SameLocals1StackItemExtended(VerificationType),
+    /// The previous frame without its last locals (between 1 and 3), and empty stack
+    Chop(u8),
+    /// The previous frame with some additional locals (between 1 and 3), and empty stack
+    Append(Vec<VerificationType>),
+    /// All the locals and the stack items
+    Full {
+        locals: Vec<VerificationType>,
+        stack: Vec<VerificationType>,
+    },
+}
+
+impl fmt::Display for StackMapFrameKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StackMapFrameKind::Same => f.write_str("same"),
+            StackMapFrameKind::SameExtended => f.write_str("same_frame_extended"),
+            StackMapFrameKind::SameLocals1StackItem(item) => {
+                write!(f, "same_locals_1_stack_item stack = [{item}]")
+            }
+            StackMapFrameKind::SameLocals1StackItemExtended(item) => {
+                write!(f, "same_locals_1_stack_item_extended stack = [{item}]")
+            }
+            StackMapFrameKind::Chop(count) => write!(f, "chop {count}"),
+            StackMapFrameKind::Append(locals) => {
+                write!(f, "append locals = [{}]", locals.iter().join(", "))
+            }
+            StackMapFrameKind::Full { locals, stack } => write!(
+                f,
+                "full locals = [{}] stack = [{}]",
+                locals.iter().join(", "),
+                stack.iter().join(", ")
+            ),
+        }
+    }
+}
+
+/// The type of a local variable or stack entry, as declared in a [StackMapFrame]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VerificationType {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    /// The `this` reference in a constructor, before the superclass constructor has been called
+    UninitializedThis,
+    /// An instance of the given class, or an array type in descriptor form
+    Object(String),
+    /// An object created by the `new` instruction at the given offset, whose constructor
+    /// has not been called yet
+    Uninitialized(ProgramCounter),
+    /// The address pushed by `jsr`, i.e. the offset of the instruction following it. It never
+    /// appears in a `StackMapTable`, and is only used to infer the types of old class files
+    /// that use subroutines.
+    ReturnAddress(ProgramCounter),
+}
+
+impl fmt::Display for VerificationType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationType::Top => f.write_str("top"),
+            VerificationType::Integer => f.write_str("int"),
+            VerificationType::Float => f.write_str("float"),
+            VerificationType::Double => f.write_str("double"),
+            VerificationType::Long => f.write_str("long"),
+            VerificationType::Null => f.write_str("null"),
+            VerificationType::UninitializedThis => f.write_str("uninitialized_this"),
+            VerificationType::Object(class_name) => f.write_str(class_name),
+            VerificationType::Uninitialized(pc) => write!(f, "uninitialized({pc})"),
+            VerificationType::ReturnAddress(pc) => write!(f, "return_address({pc})"),
+        }
+    }
+}
+
+impl From<&FieldType> for VerificationType {
+    /// Booleans, bytes, chars and shorts are integers, while arrays are objects whose
+    /// class name is the array descriptor
+    fn from(field_type: &FieldType) -> Self {
+        match field_type {
+            FieldType::Base(BaseType::Boolean)
+            | FieldType::Base(BaseType::Byte)
+            | FieldType::Base(BaseType::Char)
+            | FieldType::Base(BaseType::Short)
+            | FieldType::Base(BaseType::Int) => VerificationType::Integer,
+            FieldType::Base(BaseType::Float) => VerificationType::Float,
+            FieldType::Base(BaseType::Long) => VerificationType::Long,
+            FieldType::Base(BaseType::Double) => VerificationType::Double,
+            FieldType::Object(class_name) => VerificationType::Object(class_name.clone()),
+            FieldType::Array(_) => VerificationType::Object(field_type.descriptor()),
+        }
+    }
+}
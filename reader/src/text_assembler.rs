@@ -0,0 +1,1555 @@
+//! An assembler for a textual representation of classes, similar to the one of
+//! [Jasmin](https://jasmin.sourceforge.net/). It makes it easy to write bytecode by hand,
+//! including code that javac would never generate, for example to test the verifier.
+//!
+//! An example of the syntax:
+//!
+//! ```text
+//! .version 49
+//! .source Counter.java
+//! .class public super rjvm/Counter
+//! .super java/lang/Object
+//! .implements java/lang/Runnable
+//!
+//! .field private static count I = 0
+//!
+//! .method public static countDown(I)I
+//!     .limit stack 2
+//!     .limit locals 1
+//!     .line 3
+//! Loop:
+//!     iload_0
+//!     ifle End
+//!     iinc 0 -1
+//!     goto Loop
+//! End:
+//!     getstatic rjvm/Counter/count I
+//!     ireturn
+//! .end method
+//! ```
+//!
+//! Comments start with `;`. Fields are referred to as `owner/name descriptor`, and methods
+//! as `owner/name(parameters)return`; `invokestatic` and `invokespecial` can be followed by
+//! the keyword `interface` to refer to an interface method. Exception handlers are declared
+//! with `.catch <class or all> from <label> to <label> using <label>`, and switches list
+//! their targets on the following lines:
+//!
+//! ```text
+//!     tableswitch 1               lookupswitch
+//!         One                         -1 : MinusOne
+//!         Two                         10 : Ten
+//!         default : Other             default : Other
+//! ```
+//!
+//! Unless specified with `.version`, classes have version 49 (Java 5), so that they can
+//! use `jsr` and do not need a `StackMapTable`. Jumps and `ldc` use their wide form when
+//! needed, as do the instructions on local variables with an index above 255; `wide` can
+//! also be written explicitly. `.limit locals` defaults to the size of the parameters,
+//! while `.limit stack` is computed from the code when missing, which does not work for
+//! methods using subroutines.
+//!
+//! Besides numbers, strings and `class <name>`, `ldc` can load method types, written as
+//! `methodtype <descriptor>`, and method handles, written as `methodhandle <kind> <reference>`.
+//! The kind of a handle is the instruction that it behaves like, or `newinvokespecial` for
+//! constructors, and the reference uses the syntax of that instruction. `invokedynamic`
+//! takes the name and descriptor of the call site, followed by its bootstrap method, written
+//! as a method handle without the `methodhandle` keyword, and its static arguments:
+//!
+//! ```text
+//!     invokedynamic sum(II)I invokestatic rjvm/Sums/bootstrap(...)Ljava/lang/invoke/CallSite; 42
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    bootstrap_method::BootstrapMethod,
+    class_access_flags::ClassAccessFlags,
+    class_file::ClassFile,
+    class_file_field::{ClassFileField, FieldConstantValue},
+    class_file_method::{ClassFileMethod, ClassFileMethodCode},
+    class_file_version::ClassFileVersion,
+    code_assembler::{CodeAssembler, Jump, Label},
+    constant_pool::MethodHandleKind,
+    constant_pool_builder::ConstantPoolBuilder,
+    field_flags::FieldFlags,
+    field_type::{BaseType, FieldType},
+    frame_analysis::{analyze_method, UnknownClassHierarchy},
+    instruction::{Instruction, NewArrayType, WideInstruction},
+    line_number::LineNumber,
+    method_descriptor::MethodDescriptor,
+    method_flags::MethodFlags,
+    text_assembler_error::{Result, TextAssemblerError},
+};
+
+/// Assembles the given source into a class
+pub fn assemble(source: &str) -> Result<ClassFile> {
+    let mut lines = Vec::new();
+    for (index, text) in source.lines().enumerate() {
+        let tokens = tokenize(index + 1, text)?;
+        if !tokens.is_empty() {
+            lines.push(SourceLine {
+                number: index + 1,
+                tokens,
+            });
+        }
+    }
+
+    let assembler = TextAssembler {
+        lines: lines.into_iter(),
+        constants: ConstantPoolBuilder::new(),
+        class_file: ClassFile {
+            version: ClassFileVersion::Jdk1_5,
+            ..Default::default()
+        },
+        methods_without_max_stack: Vec::new(),
+    };
+    assembler.assemble()
+}
+
+/// A non-empty line of the source
+struct SourceLine {
+    number: usize,
+    tokens: Vec<String>,
+}
+
+struct TextAssembler {
+    lines: std::vec::IntoIter<SourceLine>,
+    constants: ConstantPoolBuilder,
+    class_file: ClassFile,
+    /// Index and line of the methods without `.limit stack`, whose `max_stack` is computed
+    /// once the constant pool is complete
+    methods_without_max_stack: Vec<(usize, usize)>,
+}
+
+/// The code of the method being assembled
+#[derive(Default)]
+struct MethodCode {
+    assembler: CodeAssembler,
+    labels: HashMap<String, LabelState>,
+    max_stack: Option<u16>,
+    max_locals: Option<u16>,
+    num_instructions: usize,
+}
+
+struct LabelState {
+    label: Label,
+    placed: bool,
+    /// The first line where the label appears, for error messages
+    line: usize,
+}
+
+impl TextAssembler {
+    fn assemble(mut self) -> Result<ClassFile> {
+        while let Some(SourceLine { number, tokens }) = self.lines.next() {
+            let operands = &tokens[1..];
+            match tokens[0].as_str() {
+                ".version" => {
+                    let (major, minor) = match operands {
+                        [major] => (major, "0"),
+                        [major, minor] => (major, minor.as_str()),
+                        _ => return Err(error(number, "expected .version <major> [<minor>]")),
+                    };
+                    let minor = parse_number(number, minor)?;
+                    self.class_file.version =
+                        ClassFileVersion::from(parse_number(number, major)?, minor)
+                            .map_err(|err| error(number, err.to_string()))?;
+                    self.class_file.minor_version = minor;
+                }
+                ".source" => {
+                    let [source_file] = expect_operands(number, operands)?;
+                    self.class_file.source_file = Some(source_file.to_string());
+                }
+                directive @ (".class" | ".interface") => {
+                    let (mut flags, operands) = parse_flags(operands, class_flag);
+                    if directive == ".interface" {
+                        flags |= ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT;
+                    }
+                    let [name] = expect_operands(number, operands)?;
+                    if !self.class_file.name.is_empty() {
+                        return Err(error(number, "the class has already been declared"));
+                    }
+                    self.class_file.flags = flags;
+                    self.class_file.name = name.to_string();
+                }
+                ".super" => {
+                    let [superclass] = expect_operands(number, operands)?;
+                    self.class_file.superclass = Some(superclass.to_string());
+                }
+                ".implements" => {
+                    let [interface] = expect_operands(number, operands)?;
+                    self.class_file.interfaces.push(interface.to_string());
+                }
+                ".field" => self.field(number, operands)?,
+                ".method" => self.method(number, operands)?,
+                other => return Err(error(number, format!("unexpected {other}"))),
+            }
+        }
+
+        if self.class_file.name.is_empty() {
+            return Err(error(1, "missing .class declaration"));
+        }
+        if self.class_file.superclass.is_none() && self.class_file.name != "java/lang/Object" {
+            self.class_file.superclass = Some("java/lang/Object".to_string());
+        }
+        self.class_file.constants = self.constants.build();
+
+        for (index, line) in self.methods_without_max_stack.iter() {
+            let method = &self.class_file.methods[*index];
+            let frames = analyze_method(&self.class_file, method, &mut UnknownClassHierarchy)
+                .map_err(|err| error(*line, format!("cannot compute .limit stack: {err}")))?;
+            if let Some(code) = self.class_file.methods[*index].code.as_mut() {
+                code.max_stack = frames.max_stack;
+            }
+        }
+        Ok(self.class_file)
+    }
+
+    fn field(&mut self, line: usize, tokens: &[String]) -> Result<()> {
+        let (flags, tokens) = parse_flags(tokens, field_flag);
+        let (declaration, value) = match tokens.iter().position(|token| token == "=") {
+            Some(position) => (&tokens[..position], Some(&tokens[position + 1..])),
+            None => (tokens, None),
+        };
+        let [name, descriptor] = expect_operands(line, declaration)?;
+        let type_descriptor =
+            FieldType::parse(descriptor).map_err(|err| error(line, err.to_string()))?;
+        let constant_value = match value {
+            Some(value) => {
+                let [value] = expect_operands(line, value)?;
+                Some(parse_constant_value(line, &type_descriptor, value)?)
+            }
+            None => None,
+        };
+
+        self.class_file.fields.push(ClassFileField {
+            flags,
+            name: name.to_string(),
+            type_descriptor,
+            constant_value,
+            deprecated: false,
+            signature: None,
+            annotations: Vec::new(),
+            type_annotations: Vec::new(),
+            custom_attributes: Vec::new(),
+            attributes: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Parses a method, consuming all the lines until `.end method`
+    fn method(&mut self, line: usize, tokens: &[String]) -> Result<()> {
+        let (flags, tokens) = parse_flags(tokens, method_flag);
+        let [declaration] = expect_operands(line, tokens)?;
+        let parenthesis = declaration
+            .find('(')
+            .ok_or_else(|| error(line, "expected a method name followed by its descriptor"))?;
+        let (name, type_descriptor) = declaration.split_at(parenthesis);
+        let parsed_type_descriptor =
+            MethodDescriptor::parse(type_descriptor).map_err(|err| error(line, err.to_string()))?;
+
+        let mut code = MethodCode::default();
+        let mut thrown_exceptions = Vec::new();
+        loop {
+            let SourceLine { number, tokens } = self
+                .lines
+                .next()
+                .ok_or_else(|| error(line, "missing .end method"))?;
+            let mut tokens = tokens.as_slice();
+            if tokens.len() >= 2 && tokens[1] == ":" {
+                code.place_label(number, &tokens[0])?;
+                tokens = &tokens[2..];
+                if tokens.is_empty() {
+                    continue;
+                }
+            }
+
+            let operands = &tokens[1..];
+            match tokens[0].as_str() {
+                ".end" => {
+                    let [what] = expect_operands(number, operands)?;
+                    if what != "method" {
+                        return Err(error(number, "expected .end method"));
+                    }
+                    break;
+                }
+                ".limit" => {
+                    let [what, value] = expect_operands(number, operands)?;
+                    match what {
+                        "stack" => code.max_stack = Some(parse_number(number, value)?),
+                        "locals" => code.max_locals = Some(parse_number(number, value)?),
+                        _ => return Err(error(number, "expected .limit stack or .limit locals")),
+                    }
+                }
+                ".throws" => {
+                    let [class] = expect_operands(number, operands)?;
+                    thrown_exceptions.push(class.to_string());
+                }
+                ".line" => {
+                    let [line_number] = expect_operands(number, operands)?;
+                    code.assembler
+                        .line_number(LineNumber(parse_number(number, line_number)?));
+                }
+                ".catch" => {
+                    let [class, from, start, to, end, using, handler] =
+                        expect_operands(number, operands)?;
+                    if from != "from" || to != "to" || using != "using" {
+                        return Err(error(
+                            number,
+                            "expected .catch <class> from <label> to <label> using <label>",
+                        ));
+                    }
+                    let catch_class = (class != "all").then(|| class.to_string());
+                    let start = code.label(number, start);
+                    let end = code.label(number, end);
+                    let handler = code.label(number, handler);
+                    code.assembler
+                        .exception_handler(start, end, handler, catch_class);
+                }
+                directive if directive.starts_with('.') => {
+                    return Err(error(number, format!("unexpected {directive} in method")))
+                }
+                mnemonic => self.instruction(&mut code, number, mnemonic, operands)?,
+            }
+        }
+
+        let has_code =
+            code.num_instructions > 0 || code.max_stack.is_some() || code.max_locals.is_some();
+        let code = if has_code {
+            code.check_labels()?;
+            let max_stack = code.max_stack.unwrap_or_else(|| {
+                self.methods_without_max_stack
+                    .push((self.class_file.methods.len(), line));
+                0
+            });
+            let max_locals = match code.max_locals {
+                Some(max_locals) => max_locals,
+                None => {
+                    let this_slot = if flags.contains(MethodFlags::STATIC) {
+                        0
+                    } else {
+                        1
+                    };
+                    (this_slot + parameters_size(&parsed_type_descriptor)) as u16
+                }
+            };
+            let assembled = code
+                .assembler
+                .assemble()
+                .map_err(|err| error(line, err.to_string()))?;
+            Some(ClassFileMethodCode {
+                max_stack,
+                max_locals,
+                code: assembled.code,
+                exception_table: assembled.exception_table,
+                line_number_table: assembled.line_number_table,
+                local_variable_table: None,
+                stack_map_table: Vec::new(),
+                type_annotations: Vec::new(),
+                custom_attributes: Vec::new(),
+                attributes: Vec::new(),
+            })
+        } else {
+            None
+        };
+
+        self.class_file.methods.push(ClassFileMethod {
+            flags,
+            name: name.to_string(),
+            type_descriptor: type_descriptor.to_string(),
+            parsed_type_descriptor,
+            attributes: Vec::new(),
+            code,
+            deprecated: false,
+            thrown_exceptions,
+            signature: None,
+            annotations: Vec::new(),
+            parameter_annotations: Vec::new(),
+            annotation_default: None,
+            parameters: Vec::new(),
+            type_annotations: Vec::new(),
+            custom_attributes: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn instruction(
+        &mut self,
+        code: &mut MethodCode,
+        line: usize,
+        mnemonic: &str,
+        operands: &[String],
+    ) -> Result<()> {
+        let (mnemonic, operands, force_wide) = match (mnemonic, operands.split_first()) {
+            ("wide", Some((mnemonic, operands))) => (mnemonic.as_str(), operands, true),
+            _ => (mnemonic, operands, false),
+        };
+
+        if let Some((short, wide)) = local_variable_instruction(mnemonic) {
+            let [index] = expect_operands(line, operands)?;
+            let index: u16 = parse_number(line, index)?;
+            code.emit(match u8::try_from(index) {
+                Ok(index) if !force_wide => short(index),
+                _ => Instruction::Wide(wide(index)),
+            });
+            return Ok(());
+        }
+        if mnemonic == "iinc" {
+            let [index, increment] = expect_operands(line, operands)?;
+            let index: u16 = parse_number(line, index)?;
+            let increment: i16 = parse_number(line, increment)?;
+            code.emit(match (u8::try_from(index), i8::try_from(increment)) {
+                (Ok(index), Ok(increment)) if !force_wide => Instruction::Iinc(index, increment),
+                _ => Instruction::Wide(WideInstruction::Iinc(index, increment)),
+            });
+            return Ok(());
+        }
+        if force_wide {
+            return Err(error(line, format!("{mnemonic} cannot be wide")));
+        }
+
+        if let Some(instruction) = simple_instruction(mnemonic) {
+            let [] = expect_operands(line, operands)?;
+            code.emit(instruction);
+            return Ok(());
+        }
+        if let Some(jump) = jump(mnemonic) {
+            let [target] = expect_operands(line, operands)?;
+            let target = code.label(line, target);
+            code.assembler.jump(jump, target);
+            code.num_instructions += 1;
+            return Ok(());
+        }
+
+        let instruction = match mnemonic {
+            "anewarray" => Instruction::Anewarray(self.class(line, operands)?),
+            "bipush" => {
+                let [value] = expect_operands(line, operands)?;
+                Instruction::Bipush(parse_number::<i8>(line, value)? as u8)
+            }
+            "checkcast" => Instruction::Checkcast(self.class(line, operands)?),
+            "getfield" => Instruction::Getfield(self.field_reference(line, operands)?),
+            "getstatic" => Instruction::Getstatic(self.field_reference(line, operands)?),
+            "instanceof" => Instruction::Instanceof(self.class(line, operands)?),
+            "invokedynamic" => Instruction::Invokedynamic(self.call_site(line, operands)?),
+            "invokeinterface" => {
+                let (reference, count) = match operands {
+                    [reference] => (reference, None),
+                    [reference, count] => (reference, Some(parse_number(line, count)?)),
+                    _ => return Err(error(line, "expected a method and an optional count")),
+                };
+                let (index, descriptor) =
+                    self.method_reference(line, std::slice::from_ref(reference), true)?;
+                let count = match count {
+                    Some(count) => count,
+                    None => (1 + parameters_size(&descriptor)) as u8,
+                };
+                Instruction::Invokeinterface(index, count)
+            }
+            "invokespecial" => {
+                Instruction::Invokespecial(self.method_reference(line, operands, false)?.0)
+            }
+            "invokestatic" => {
+                Instruction::Invokestatic(self.method_reference(line, operands, false)?.0)
+            }
+            "invokevirtual" => {
+                Instruction::Invokevirtual(self.method_reference(line, operands, false)?.0)
+            }
+            "ldc" => {
+                let index = self.constant(line, operands)?;
+                match u8::try_from(index) {
+                    Ok(index) => Instruction::Ldc(index),
+                    Err(_) => Instruction::Ldc_w(index),
+                }
+            }
+            "ldc_w" => Instruction::Ldc_w(self.constant(line, operands)?),
+            "ldc2_w" => {
+                let [value] = expect_operands(line, operands)?;
+                Instruction::Ldc2_w(self.wide_constant(line, value)?)
+            }
+            "lookupswitch" => {
+                let [] = expect_operands(line, operands)?;
+                return self.lookupswitch(code, line);
+            }
+            "multianewarray" => {
+                let [class, dimensions] = expect_operands(line, operands)?;
+                let dimensions = parse_number(line, dimensions)?;
+                Instruction::Multianewarray(self.constants.class(class), dimensions)
+            }
+            "new" => Instruction::New(self.class(line, operands)?),
+            "newarray" => {
+                let [array_type] = expect_operands(line, operands)?;
+                Instruction::Newarray(parse_new_array_type(line, array_type)?)
+            }
+            "putfield" => Instruction::Putfield(self.field_reference(line, operands)?),
+            "putstatic" => Instruction::Putstatic(self.field_reference(line, operands)?),
+            "sipush" => {
+                let [value] = expect_operands(line, operands)?;
+                Instruction::Sipush(parse_number(line, value)?)
+            }
+            "tableswitch" => {
+                let (low, high) = match operands {
+                    [low] => (parse_number(line, low)?, None),
+                    [low, high] => (parse_number(line, low)?, Some(parse_number(line, high)?)),
+                    _ => return Err(error(line, "expected tableswitch <low> [<high>]")),
+                };
+                return self.tableswitch(code, line, low, high);
+            }
+            _ => return Err(error(line, format!("unknown instruction {mnemonic}"))),
+        };
+        code.emit(instruction);
+        Ok(())
+    }
+
+    /// Parses the lines following a `tableswitch`
+    fn tableswitch(
+        &mut self,
+        code: &mut MethodCode,
+        line: usize,
+        low: i32,
+        high: Option<i32>,
+    ) -> Result<()> {
+        let mut jumps = Vec::new();
+        loop {
+            let SourceLine { number, tokens } = self
+                .lines
+                .next()
+                .ok_or_else(|| error(line, "missing default in tableswitch"))?;
+            match tokens.as_slice() {
+                [default, colon, target] if default == "default" && colon == ":" => {
+                    let default = code.label(number, target);
+                    if high.is_some_and(|high| high as i64 - low as i64 + 1 != jumps.len() as i64) {
+                        return Err(error(line, "the number of labels does not match the range"));
+                    }
+                    code.assembler.tableswitch(low, default, jumps);
+                    code.num_instructions += 1;
+                    return Ok(());
+                }
+                [target] => jumps.push(code.label(number, target)),
+                _ => {
+                    return Err(error(
+                        number,
+                        "expected a label, or default : <label> to end the tableswitch",
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Parses the lines following a `lookupswitch`
+    fn lookupswitch(&mut self, code: &mut MethodCode, line: usize) -> Result<()> {
+        let mut pairs: Vec<(i32, Label)> = Vec::new();
+        loop {
+            let SourceLine { number, tokens } = self
+                .lines
+                .next()
+                .ok_or_else(|| error(line, "missing default in lookupswitch"))?;
+            match tokens.as_slice() {
+                [default, colon, target] if default == "default" && colon == ":" => {
+                    let default = code.label(number, target);
+                    code.assembler.lookupswitch(default, pairs);
+                    code.num_instructions += 1;
+                    return Ok(());
+                }
+                [key, colon, target] if colon == ":" => {
+                    let key = parse_number(number, key)?;
+                    if pairs.iter().any(|(existing, _)| *existing == key) {
+                        return Err(error(number, format!("duplicate key {key}")));
+                    }
+                    pairs.push((key, code.label(number, target)));
+                }
+                _ => {
+                    return Err(error(
+                        number,
+                        "expected <key> : <label>, or default : <label> to end the lookupswitch",
+                    ))
+                }
+            }
+        }
+    }
+
+    fn class(&mut self, line: usize, operands: &[String]) -> Result<u16> {
+        let [class] = expect_operands(line, operands)?;
+        Ok(self.constants.class(class))
+    }
+
+    /// Parses a field reference, in the form `owner/name descriptor`
+    fn field_reference(&mut self, line: usize, operands: &[String]) -> Result<u16> {
+        let [reference, descriptor] = expect_operands(line, operands)?;
+        let (class, name) = split_member_reference(line, reference)?;
+        FieldType::parse(descriptor).map_err(|err| error(line, err.to_string()))?;
+        Ok(self.constants.field(class, name, descriptor))
+    }
+
+    /// Parses a method reference, in the form `[interface] owner/name(parameters)return`
+    fn method_reference(
+        &mut self,
+        line: usize,
+        operands: &[String],
+        is_interface: bool,
+    ) -> Result<(u16, MethodDescriptor)> {
+        let (is_interface, operands) = match operands.split_first() {
+            Some((keyword, operands)) if keyword == "interface" => (true, operands),
+            _ => (is_interface, operands),
+        };
+        let [reference] = expect_operands(line, operands)?;
+        let parenthesis = reference
+            .find('(')
+            .ok_or_else(|| error(line, "expected a method name followed by its descriptor"))?;
+        let (class, name) = split_member_reference(line, &reference[..parenthesis])?;
+        let descriptor = &reference[parenthesis..];
+        let parsed_descriptor =
+            MethodDescriptor::parse(descriptor).map_err(|err| error(line, err.to_string()))?;
+        let index = self.constants.method(class, name, descriptor, is_interface);
+        Ok((index, parsed_descriptor))
+    }
+
+    /// Parses the operand of `ldc` and `ldc_w`, see [TextAssembler::loadable_constant]
+    fn constant(&mut self, line: usize, operands: &[String]) -> Result<u16> {
+        match self.loadable_constant(line, operands)? {
+            (index, []) => Ok(index),
+            _ => Err(error(line, "expected a constant")),
+        }
+    }
+
+    /// Parses the constant at the start of the operands, returning its index and the
+    /// remaining operands. It can be an int, a float, a string, `class <name>`,
+    /// `methodtype <descriptor>` or `methodhandle <kind> <reference>`.
+    fn loadable_constant<'o>(
+        &mut self,
+        line: usize,
+        operands: &'o [String],
+    ) -> Result<(u16, &'o [String])> {
+        match operands {
+            [keyword, class, rest @ ..] if keyword == "class" => {
+                Ok((self.constants.class(class), rest))
+            }
+            [keyword, descriptor, rest @ ..] if keyword == "methodtype" => {
+                MethodDescriptor::parse(descriptor).map_err(|err| error(line, err.to_string()))?;
+                Ok((self.constants.method_type(descriptor), rest))
+            }
+            [keyword, rest @ ..] if keyword == "methodhandle" => self.method_handle(line, rest),
+            [value, rest @ ..] if value.starts_with('"') => {
+                Ok((self.constants.string(&parse_string(line, value)?), rest))
+            }
+            [value, rest @ ..] if is_floating_point(value) => Ok((
+                self.constants.float(parse_floating_point(line, value)?),
+                rest,
+            )),
+            [value, rest @ ..] => Ok((self.constants.integer(parse_number(line, value)?), rest)),
+            [] => Err(error(line, "expected a constant")),
+        }
+    }
+
+    /// Parses a method handle at the start of the operands, in the form `<kind> <reference>`.
+    /// The kind is the instruction that the handle behaves like, or `newinvokespecial` for
+    /// constructors, and the reference has the same syntax as in that instruction.
+    fn method_handle<'o>(
+        &mut self,
+        line: usize,
+        operands: &'o [String],
+    ) -> Result<(u16, &'o [String])> {
+        let (kind, operands) = operands
+            .split_first()
+            .ok_or_else(|| error(line, "expected the kind of the method handle"))?;
+        let kind = match kind.as_str() {
+            "getfield" => MethodHandleKind::GetField,
+            "getstatic" => MethodHandleKind::GetStatic,
+            "putfield" => MethodHandleKind::PutField,
+            "putstatic" => MethodHandleKind::PutStatic,
+            "invokevirtual" => MethodHandleKind::InvokeVirtual,
+            "invokestatic" => MethodHandleKind::InvokeStatic,
+            "invokespecial" => MethodHandleKind::InvokeSpecial,
+            "newinvokespecial" => MethodHandleKind::NewInvokeSpecial,
+            "invokeinterface" => MethodHandleKind::InvokeInterface,
+            other => return Err(error(line, format!("invalid method handle kind {other}"))),
+        };
+        let reference_length = match kind {
+            MethodHandleKind::GetField
+            | MethodHandleKind::GetStatic
+            | MethodHandleKind::PutField
+            | MethodHandleKind::PutStatic => 2,
+            _ if operands
+                .first()
+                .is_some_and(|keyword| keyword == "interface") =>
+            {
+                2
+            }
+            _ => 1,
+        };
+        if operands.len() < reference_length {
+            return Err(error(line, "expected the reference of the method handle"));
+        }
+        let (reference, rest) = operands.split_at(reference_length);
+        let reference_index = match kind {
+            MethodHandleKind::GetField
+            | MethodHandleKind::GetStatic
+            | MethodHandleKind::PutField
+            | MethodHandleKind::PutStatic => self.field_reference(line, reference)?,
+            _ => {
+                let is_interface = kind == MethodHandleKind::InvokeInterface;
+                self.method_reference(line, reference, is_interface)?.0
+            }
+        };
+        Ok((self.constants.method_handle(kind, reference_index), rest))
+    }
+
+    /// Parses the operands of `invokedynamic`, in the form `name(parameters)return`
+    /// followed by the bootstrap method, as a method handle, and its static arguments
+    fn call_site(&mut self, line: usize, operands: &[String]) -> Result<u16> {
+        let (reference, operands) = operands
+            .split_first()
+            .ok_or_else(|| error(line, "expected a method name followed by its descriptor"))?;
+        let (name, descriptor) = reference
+            .find('(')
+            .filter(|parenthesis| *parenthesis > 0)
+            .map(|parenthesis| reference.split_at(parenthesis))
+            .ok_or_else(|| error(line, "expected a method name followed by its descriptor"))?;
+        MethodDescriptor::parse(descriptor).map_err(|err| error(line, err.to_string()))?;
+
+        let (method_ref, mut operands) = self.method_handle(line, operands)?;
+        let mut arguments = Vec::new();
+        while !operands.is_empty() {
+            let (argument, rest) = self.loadable_constant(line, operands)?;
+            arguments.push(argument);
+            operands = rest;
+        }
+        let bootstrap_method = BootstrapMethod {
+            method_ref,
+            arguments,
+        };
+        let bootstrap_methods = &mut self.class_file.bootstrap_methods;
+        let bootstrap_method_index = match bootstrap_methods
+            .iter()
+            .position(|existing| *existing == bootstrap_method)
+        {
+            Some(index) => index,
+            None => {
+                bootstrap_methods.push(bootstrap_method);
+                bootstrap_methods.len() - 1
+            }
+        };
+        Ok(self
+            .constants
+            .invoke_dynamic(bootstrap_method_index as u16, name, descriptor))
+    }
+
+    /// Parses the operand of `ldc2_w`: a long or a double
+    fn wide_constant(&mut self, line: usize, value: &str) -> Result<u16> {
+        if is_floating_point(value) {
+            Ok(self.constants.double(parse_floating_point(line, value)?))
+        } else {
+            Ok(self.constants.long(parse_number(line, value)?))
+        }
+    }
+}
+
+impl MethodCode {
+    fn emit(&mut self, instruction: Instruction) {
+        self.assembler.emit(instruction);
+        self.num_instructions += 1;
+    }
+
+    /// Returns the label with the given name, creating it if it does not exist yet
+    fn label(&mut self, line: usize, name: &str) -> Label {
+        let assembler = &mut self.assembler;
+        self.labels
+            .entry(name.to_string())
+            .or_insert_with(|| LabelState {
+                label: assembler.new_label(),
+                placed: false,
+                line,
+            })
+            .label
+    }
+
+    fn place_label(&mut self, line: usize, name: &str) -> Result<()> {
+        let label = self.label(line, name);
+        let state = self.labels.get_mut(name).unwrap();
+        if state.placed {
+            return Err(error(line, format!("label {name} is already defined")));
+        }
+        state.placed = true;
+        self.assembler.place_label(label);
+        Ok(())
+    }
+
+    fn check_labels(&self) -> Result<()> {
+        match self
+            .labels
+            .iter()
+            .filter(|(_, state)| !state.placed)
+            .min_by_key(|(_, state)| state.line)
+        {
+            Some((name, state)) => Err(error(state.line, format!("label {name} is not defined"))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn error(line: usize, message: impl Into<String>) -> TextAssemblerError {
+    TextAssemblerError {
+        line,
+        message: message.into(),
+    }
+}
+
+/// Splits a line in tokens. String literals are kept quoted and escaped, and colons
+/// are tokens by themselves. A `;` at the start of a token begins a comment.
+fn tokenize(line: usize, text: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            ';' if token.is_empty() => break,
+            '"' if token.is_empty() => {
+                token.push(char);
+                loop {
+                    match chars.next() {
+                        None => return Err(error(line, "unterminated string")),
+                        Some('"') => break,
+                        Some('\\') => {
+                            token.push('\\');
+                            token.extend(chars.next());
+                        }
+                        Some(char) => token.push(char),
+                    }
+                }
+                token.push('"');
+                tokens.push(std::mem::take(&mut token));
+            }
+            ':' => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+                tokens.push(":".to_string());
+            }
+            char if char.is_whitespace() => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            char => token.push(char),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn expect_operands<const N: usize>(line: usize, operands: &[String]) -> Result<[&str; N]> {
+    if operands.len() != N {
+        return Err(error(
+            line,
+            format!("expected {N} operands, found {}", operands.len()),
+        ));
+    }
+    Ok(std::array::from_fn(|index| operands[index].as_str()))
+}
+
+/// Parses the leading flag keywords, and returns the remaining tokens
+fn parse_flags<F: Default + std::ops::BitOrAssign>(
+    tokens: &[String],
+    flag: fn(&str) -> Option<F>,
+) -> (F, &[String]) {
+    let mut flags = F::default();
+    let mut count = 0;
+    while let Some(parsed) = tokens.get(count).and_then(|token| flag(token)) {
+        flags |= parsed;
+        count += 1;
+    }
+    (flags, &tokens[count..])
+}
+
+fn class_flag(keyword: &str) -> Option<ClassAccessFlags> {
+    match keyword {
+        "public" => Some(ClassAccessFlags::PUBLIC),
+        "final" => Some(ClassAccessFlags::FINAL),
+        "super" => Some(ClassAccessFlags::SUPER),
+        "interface" => Some(ClassAccessFlags::INTERFACE),
+        "abstract" => Some(ClassAccessFlags::ABSTRACT),
+        "synthetic" => Some(ClassAccessFlags::SYNTHETIC),
+        "annotation" => Some(ClassAccessFlags::ANNOTATION),
+        "enum" => Some(ClassAccessFlags::ENUM),
+        _ => None,
+    }
+}
+
+fn field_flag(keyword: &str) -> Option<FieldFlags> {
+    match keyword {
+        "public" => Some(FieldFlags::PUBLIC),
+        "private" => Some(FieldFlags::PRIVATE),
+        "protected" => Some(FieldFlags::PROTECTED),
+        "static" => Some(FieldFlags::STATIC),
+        "final" => Some(FieldFlags::FINAL),
+        "volatile" => Some(FieldFlags::VOLATILE),
+        "transient" => Some(FieldFlags::TRANSIENT),
+        "synthetic" => Some(FieldFlags::SYNTHETIC),
+        "enum" => Some(FieldFlags::ENUM),
+        _ => None,
+    }
+}
+
+fn method_flag(keyword: &str) -> Option<MethodFlags> {
+    match keyword {
+        "public" => Some(MethodFlags::PUBLIC),
+        "private" => Some(MethodFlags::PRIVATE),
+        "protected" => Some(MethodFlags::PROTECTED),
+        "static" => Some(MethodFlags::STATIC),
+        "final" => Some(MethodFlags::FINAL),
+        "synchronized" => Some(MethodFlags::SYNCHRONIZED),
+        "bridge" => Some(MethodFlags::BRIDGE),
+        "varargs" => Some(MethodFlags::VARARGS),
+        "native" => Some(MethodFlags::NATIVE),
+        "abstract" => Some(MethodFlags::ABSTRACT),
+        "strict" => Some(MethodFlags::STRICT),
+        "synthetic" => Some(MethodFlags::SYNTHETIC),
+        _ => None,
+    }
+}
+
+/// Parses an integer, in decimal or hexadecimal (`0x`) notation. Long constants
+/// can have the suffix `L`.
+fn parse_number<T: TryFrom<i128>>(line: usize, value: &str) -> Result<T> {
+    let digits = value.trim_end_matches(['L', 'l']);
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, digits),
+    };
+    let parsed = match digits.strip_prefix("0x") {
+        Some(hex_digits) => i128::from_str_radix(hex_digits, 16),
+        None => digits.parse::<i128>(),
+    };
+    parsed
+        .ok()
+        .map(|parsed| if negative { -parsed } else { parsed })
+        .and_then(|parsed| T::try_from(parsed).ok())
+        .ok_or_else(|| error(line, format!("invalid number {value}")))
+}
+
+fn is_floating_point(value: &str) -> bool {
+    let digits = value.trim_start_matches(['-', '+']);
+    if digits.starts_with("0x") {
+        return false;
+    }
+    digits.contains(['.', 'e', 'E', 'f', 'F', 'd', 'D']) || digits == "NaN" || digits == "Infinity"
+}
+
+/// Parses a floating point number. The suffixes `f` and `d` are accepted, as are
+/// `NaN` and `Infinity`.
+fn parse_floating_point<T: std::str::FromStr>(line: usize, value: &str) -> Result<T> {
+    let digits = if value.ends_with("Infinity") {
+        value
+    } else {
+        value.trim_end_matches(['f', 'F', 'd', 'D'])
+    };
+    digits
+        .parse()
+        .map_err(|_| error(line, format!("invalid number {value}")))
+}
+
+/// Parses a quoted string literal, handling the escapes `\n`, `\r`, `\t`, `\"`, `\'`,
+/// `\\` and `\uXXXX`
+fn parse_string(line: usize, value: &str) -> Result<String> {
+    let content = &value[1..value.len() - 1];
+    let mut string = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            string.push(char);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some(char @ ('"' | '\'' | '\\')) => char,
+            Some('u') => {
+                let hex_digits: String = chars.by_ref().take(4).collect();
+                u32::from_str_radix(&hex_digits, 16)
+                    .ok()
+                    .filter(|_| hex_digits.len() == 4)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| error(line, format!("invalid escape \\u{hex_digits}")))?
+            }
+            _ => return Err(error(line, "invalid escape in string")),
+        };
+        string.push(escaped);
+    }
+    Ok(string)
+}
+
+fn parse_constant_value(
+    line: usize,
+    type_descriptor: &FieldType,
+    value: &str,
+) -> Result<FieldConstantValue> {
+    match type_descriptor {
+        FieldType::Base(BaseType::Long) => Ok(FieldConstantValue::Long(parse_number(line, value)?)),
+        FieldType::Base(BaseType::Float) => Ok(FieldConstantValue::Float(parse_floating_point(
+            line, value,
+        )?)),
+        FieldType::Base(BaseType::Double) => Ok(FieldConstantValue::Double(parse_floating_point(
+            line, value,
+        )?)),
+        FieldType::Base(_) => Ok(FieldConstantValue::Int(parse_number(line, value)?)),
+        FieldType::Object(class) if class == "java/lang/String" && value.starts_with('"') => {
+            Ok(FieldConstantValue::String(parse_string(line, value)?))
+        }
+        _ => Err(error(
+            line,
+            format!("invalid constant value {value} for a field of type {type_descriptor}"),
+        )),
+    }
+}
+
+fn parse_new_array_type(line: usize, array_type: &str) -> Result<NewArrayType> {
+    match array_type {
+        "boolean" => Ok(NewArrayType::Boolean),
+        "char" => Ok(NewArrayType::Char),
+        "float" => Ok(NewArrayType::Float),
+        "double" => Ok(NewArrayType::Double),
+        "byte" => Ok(NewArrayType::Byte),
+        "short" => Ok(NewArrayType::Short),
+        "int" => Ok(NewArrayType::Int),
+        "long" => Ok(NewArrayType::Long),
+        _ => Err(error(line, format!("invalid array type {array_type}"))),
+    }
+}
+
+/// Splits `owner/name` at the last slash
+fn split_member_reference(line: usize, reference: &str) -> Result<(&str, &str)> {
+    reference
+        .rsplit_once('/')
+        .filter(|(class, name)| !class.is_empty() && !name.is_empty())
+        .ok_or_else(|| error(line, format!("expected owner/name, found {reference}")))
+}
+
+/// Number of local variable slots taken by the parameters
+fn parameters_size(descriptor: &MethodDescriptor) -> usize {
+    descriptor
+        .parameters
+        .iter()
+        .map(|parameter| match parameter {
+            FieldType::Base(BaseType::Long) | FieldType::Base(BaseType::Double) => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+type LocalVariableInstruction = (fn(u8) -> Instruction, fn(u16) -> WideInstruction);
+
+/// Instructions that take the index of a local variable, and can be made wide
+fn local_variable_instruction(mnemonic: &str) -> Option<LocalVariableInstruction> {
+    match mnemonic {
+        "aload" => Some((Instruction::Aload, WideInstruction::Aload)),
+        "astore" => Some((Instruction::Astore, WideInstruction::Astore)),
+        "dload" => Some((Instruction::Dload, WideInstruction::Dload)),
+        "dstore" => Some((Instruction::Dstore, WideInstruction::Dstore)),
+        "fload" => Some((Instruction::Fload, WideInstruction::Fload)),
+        "fstore" => Some((Instruction::Fstore, WideInstruction::Fstore)),
+        "iload" => Some((Instruction::Iload, WideInstruction::Iload)),
+        "istore" => Some((Instruction::Istore, WideInstruction::Istore)),
+        "lload" => Some((Instruction::Lload, WideInstruction::Lload)),
+        "lstore" => Some((Instruction::Lstore, WideInstruction::Lstore)),
+        "ret" => Some((Instruction::Ret, WideInstruction::Ret)),
+        _ => None,
+    }
+}
+
+fn jump(mnemonic: &str) -> Option<Jump> {
+    match mnemonic {
+        "goto" => Some(Jump::Goto),
+        "jsr" => Some(Jump::Jsr),
+        "if_acmpeq" => Some(Jump::If_acmpeq),
+        "if_acmpne" => Some(Jump::If_acmpne),
+        "if_icmpeq" => Some(Jump::If_icmpeq),
+        "if_icmpne" => Some(Jump::If_icmpne),
+        "if_icmplt" => Some(Jump::If_icmplt),
+        "if_icmpge" => Some(Jump::If_icmpge),
+        "if_icmpgt" => Some(Jump::If_icmpgt),
+        "if_icmple" => Some(Jump::If_icmple),
+        "ifeq" => Some(Jump::Ifeq),
+        "ifne" => Some(Jump::Ifne),
+        "iflt" => Some(Jump::Iflt),
+        "ifge" => Some(Jump::Ifge),
+        "ifgt" => Some(Jump::Ifgt),
+        "ifle" => Some(Jump::Ifle),
+        "ifnonnull" => Some(Jump::Ifnonnull),
+        "ifnull" => Some(Jump::Ifnull),
+        _ => None,
+    }
+}
+
+/// Instructions without operands
+fn simple_instruction(mnemonic: &str) -> Option<Instruction> {
+    let instruction = match mnemonic {
+        "aaload" => Instruction::Aaload,
+        "aastore" => Instruction::Aastore,
+        "aconst_null" => Instruction::Aconst_null,
+        "aload_0" => Instruction::Aload_0,
+        "aload_1" => Instruction::Aload_1,
+        "aload_2" => Instruction::Aload_2,
+        "aload_3" => Instruction::Aload_3,
+        "areturn" => Instruction::Areturn,
+        "arraylength" => Instruction::Arraylength,
+        "astore_0" => Instruction::Astore_0,
+        "astore_1" => Instruction::Astore_1,
+        "astore_2" => Instruction::Astore_2,
+        "astore_3" => Instruction::Astore_3,
+        "athrow" => Instruction::Athrow,
+        "baload" => Instruction::Baload,
+        "bastore" => Instruction::Bastore,
+        "caload" => Instruction::Caload,
+        "castore" => Instruction::Castore,
+        "d2f" => Instruction::D2f,
+        "d2i" => Instruction::D2i,
+        "d2l" => Instruction::D2l,
+        "dadd" => Instruction::Dadd,
+        "daload" => Instruction::Daload,
+        "dastore" => Instruction::Dastore,
+        "dcmpg" => Instruction::Dcmpg,
+        "dcmpl" => Instruction::Dcmpl,
+        "dconst_0" => Instruction::Dconst_0,
+        "dconst_1" => Instruction::Dconst_1,
+        "ddiv" => Instruction::Ddiv,
+        "dload_0" => Instruction::Dload_0,
+        "dload_1" => Instruction::Dload_1,
+        "dload_2" => Instruction::Dload_2,
+        "dload_3" => Instruction::Dload_3,
+        "dmul" => Instruction::Dmul,
+        "dneg" => Instruction::Dneg,
+        "drem" => Instruction::Drem,
+        "dreturn" => Instruction::Dreturn,
+        "dstore_0" => Instruction::Dstore_0,
+        "dstore_1" => Instruction::Dstore_1,
+        "dstore_2" => Instruction::Dstore_2,
+        "dstore_3" => Instruction::Dstore_3,
+        "dsub" => Instruction::Dsub,
+        "dup" => Instruction::Dup,
+        "dup_x1" => Instruction::Dup_x1,
+        "dup_x2" => Instruction::Dup_x2,
+        "dup2" => Instruction::Dup2,
+        "dup2_x1" => Instruction::Dup2_x1,
+        "dup2_x2" => Instruction::Dup2_x2,
+        "f2d" => Instruction::F2d,
+        "f2i" => Instruction::F2i,
+        "f2l" => Instruction::F2l,
+        "fadd" => Instruction::Fadd,
+        "faload" => Instruction::Faload,
+        "fastore" => Instruction::Fastore,
+        "fcmpg" => Instruction::Fcmpg,
+        "fcmpl" => Instruction::Fcmpl,
+        "fconst_0" => Instruction::Fconst_0,
+        "fconst_1" => Instruction::Fconst_1,
+        "fconst_2" => Instruction::Fconst_2,
+        "fdiv" => Instruction::Fdiv,
+        "fload_0" => Instruction::Fload_0,
+        "fload_1" => Instruction::Fload_1,
+        "fload_2" => Instruction::Fload_2,
+        "fload_3" => Instruction::Fload_3,
+        "fmul" => Instruction::Fmul,
+        "fneg" => Instruction::Fneg,
+        "frem" => Instruction::Frem,
+        "freturn" => Instruction::Freturn,
+        "fstore_0" => Instruction::Fstore_0,
+        "fstore_1" => Instruction::Fstore_1,
+        "fstore_2" => Instruction::Fstore_2,
+        "fstore_3" => Instruction::Fstore_3,
+        "fsub" => Instruction::Fsub,
+        "i2b" => Instruction::I2b,
+        "i2c" => Instruction::I2c,
+        "i2d" => Instruction::I2d,
+        "i2f" => Instruction::I2f,
+        "i2l" => Instruction::I2l,
+        "i2s" => Instruction::I2s,
+        "iadd" => Instruction::Iadd,
+        "iaload" => Instruction::Iaload,
+        "iand" => Instruction::Iand,
+        "iastore" => Instruction::Iastore,
+        "iconst_m1" => Instruction::Iconst_m1,
+        "iconst_0" => Instruction::Iconst_0,
+        "iconst_1" => Instruction::Iconst_1,
+        "iconst_2" => Instruction::Iconst_2,
+        "iconst_3" => Instruction::Iconst_3,
+        "iconst_4" => Instruction::Iconst_4,
+        "iconst_5" => Instruction::Iconst_5,
+        "idiv" => Instruction::Idiv,
+        "iload_0" => Instruction::Iload_0,
+        "iload_1" => Instruction::Iload_1,
+        "iload_2" => Instruction::Iload_2,
+        "iload_3" => Instruction::Iload_3,
+        "imul" => Instruction::Imul,
+        "ineg" => Instruction::Ineg,
+        "ior" => Instruction::Ior,
+        "irem" => Instruction::Irem,
+        "ireturn" => Instruction::Ireturn,
+        "ishl" => Instruction::Ishl,
+        "ishr" => Instruction::Ishr,
+        "istore_0" => Instruction::Istore_0,
+        "istore_1" => Instruction::Istore_1,
+        "istore_2" => Instruction::Istore_2,
+        "istore_3" => Instruction::Istore_3,
+        "isub" => Instruction::Isub,
+        "iushr" => Instruction::Iushr,
+        "ixor" => Instruction::Ixor,
+        "l2d" => Instruction::L2d,
+        "l2f" => Instruction::L2f,
+        "l2i" => Instruction::L2i,
+        "ladd" => Instruction::Ladd,
+        "laload" => Instruction::Laload,
+        "land" => Instruction::Land,
+        "lastore" => Instruction::Lastore,
+        "lcmp" => Instruction::Lcmp,
+        "lconst_0" => Instruction::Lconst_0,
+        "lconst_1" => Instruction::Lconst_1,
+        "ldiv" => Instruction::Ldiv,
+        "lload_0" => Instruction::Lload_0,
+        "lload_1" => Instruction::Lload_1,
+        "lload_2" => Instruction::Lload_2,
+        "lload_3" => Instruction::Lload_3,
+        "lmul" => Instruction::Lmul,
+        "lneg" => Instruction::Lneg,
+        "lor" => Instruction::Lor,
+        "lrem" => Instruction::Lrem,
+        "lreturn" => Instruction::Lreturn,
+        "lshl" => Instruction::Lshl,
+        "lshr" => Instruction::Lshr,
+        "lstore_0" => Instruction::Lstore_0,
+        "lstore_1" => Instruction::Lstore_1,
+        "lstore_2" => Instruction::Lstore_2,
+        "lstore_3" => Instruction::Lstore_3,
+        "lsub" => Instruction::Lsub,
+        "lushr" => Instruction::Lushr,
+        "lxor" => Instruction::Lxor,
+        "monitorenter" => Instruction::Monitorenter,
+        "monitorexit" => Instruction::Monitorexit,
+        "nop" => Instruction::Nop,
+        "pop" => Instruction::Pop,
+        "pop2" => Instruction::Pop2,
+        "return" => Instruction::Return,
+        "saload" => Instruction::Saload,
+        "sastore" => Instruction::Sastore,
+        "swap" => Instruction::Swap,
+        _ => return None,
+    };
+    Some(instruction)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        class_access_flags::ClassAccessFlags,
+        class_file_field::FieldConstantValue,
+        exception_table::ExceptionTableEntry,
+        field_flags::FieldFlags,
+        instruction::{Instruction, LookupSwitch, TableSwitch, WideInstruction},
+        method_flags::MethodFlags,
+        program_counter::ProgramCounter,
+        text_assembler::assemble,
+        text_assembler_error::TextAssemblerError,
+    };
+
+    #[test]
+    fn can_assemble_class_declarations() {
+        let class = assemble(
+            r#"
+            .version 52
+            .source Foo.java
+            .interface public rjvm/Foo
+            .implements java/lang/Runnable
+            .field public static final NAME Ljava/lang/String; = "a \"name\"è"
+            .field private volatile count J = -0x10L
+            .method public abstract run()V
+            .end method
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(52, class.version.major());
+        assert_eq!(Some("Foo.java".to_string()), class.source_file);
+        assert_eq!(
+            ClassAccessFlags::PUBLIC | ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT,
+            class.flags
+        );
+        assert_eq!("rjvm/Foo", class.name);
+        assert_eq!(Some("java/lang/Object".to_string()), class.superclass);
+        assert_eq!(vec!["java/lang/Runnable".to_string()], class.interfaces);
+
+        assert_eq!(2, class.fields.len());
+        assert_eq!(
+            FieldFlags::PUBLIC | FieldFlags::STATIC | FieldFlags::FINAL,
+            class.fields[0].flags
+        );
+        assert_eq!(
+            Some(FieldConstantValue::String("a \"name\"è".to_string())),
+            class.fields[0].constant_value
+        );
+        assert_eq!(
+            Some(FieldConstantValue::Long(-16)),
+            class.fields[1].constant_value
+        );
+
+        assert_eq!(1, class.methods.len());
+        assert_eq!(
+            MethodFlags::PUBLIC | MethodFlags::ABSTRACT,
+            class.methods[0].flags
+        );
+        assert_eq!("()V", class.methods[0].type_descriptor);
+        assert!(class.methods[0].code.is_none());
+    }
+
+    #[test]
+    fn can_assemble_code() {
+        let class = assemble(
+            r#"
+            .class public super rjvm/Foo
+            .method public static test(IJ)Ljava/lang/Object; ; a comment
+                .limit stack 4
+                .throws java/lang/Exception
+                .line 10
+            Start: iload_0
+                tableswitch 1 2
+                    One
+                    Two
+                    default : Other
+            One:
+                ldc "one"
+                areturn
+            Two:
+                iload 300
+                lookupswitch
+                    10 : Other
+                    -1 : Start
+                    default : Other
+            Other:
+                wide lload 1
+                ldc2_w 1.5
+                getstatic rjvm/Foo/bar [I
+                invokeinterface java/util/List/get(I)Ljava/lang/Object;
+                invokestatic interface java/util/List/of()Ljava/util/List;
+                areturn
+            End:
+            Handler:
+                athrow
+                .catch all from Start to End using Handler
+            .end method
+            "#,
+        )
+        .unwrap();
+
+        let code = class.methods[0].code.as_ref().unwrap();
+        assert_eq!(4, code.max_stack);
+        assert_eq!(3, code.max_locals);
+        assert_eq!(
+            vec!["java/lang/Exception".to_string()],
+            class.methods[0].thrown_exceptions
+        );
+
+        let instructions = Instruction::parse_instructions(&code.code).unwrap();
+        assert_eq!(
+            vec![
+                (0, Instruction::Iload_0),
+                (
+                    1,
+                    Instruction::Tableswitch(TableSwitch {
+                        default: 56,
+                        low: 1,
+                        high: 2,
+                        jumps: vec![24, 27],
+                    })
+                ),
+                (24, Instruction::Ldc(2)),
+                (26, Instruction::Areturn),
+                (27, Instruction::Wide(WideInstruction::Iload(300))),
+                (
+                    31,
+                    Instruction::Lookupswitch(LookupSwitch {
+                        default: 56,
+                        pairs: vec![(-1, 0), (10, 56)],
+                    })
+                ),
+            ],
+            instructions[0..6]
+        );
+        assert_eq!(
+            Instruction::Wide(WideInstruction::Lload(1)),
+            instructions[6].1
+        );
+        let constants = &class.constants;
+        let text_of = |instruction: &Instruction| {
+            let index = match instruction {
+                Instruction::Ldc(index) => *index as u16,
+                Instruction::Ldc2_w(index)
+                | Instruction::Getstatic(index)
+                | Instruction::Invokeinterface(index, 2)
+                | Instruction::Invokestatic(index) => *index,
+                _ => panic!("unexpected instruction {instruction:?}"),
+            };
+            constants.text_of(index).unwrap()
+        };
+        assert_eq!("one", text_of(&instructions[2].1));
+        assert_eq!("1.5", text_of(&instructions[7].1));
+        assert_eq!("rjvm/Foo.bar: [I", text_of(&instructions[8].1));
+        assert_eq!(
+            "java/util/List.get: (I)Ljava/lang/Object;",
+            text_of(&instructions[9].1)
+        );
+        assert_eq!(
+            "java/util/List.of: ()Ljava/util/List;",
+            text_of(&instructions[10].1)
+        );
+
+        let end = instructions[12].0 as u16;
+        assert_eq!(
+            vec![ExceptionTableEntry {
+                range: ProgramCounter(0)..ProgramCounter(end),
+                handler_pc: ProgramCounter(end),
+                catch_class: None,
+            }],
+            code.exception_table.entries()
+        );
+        assert_eq!(
+            10,
+            code.line_number_table
+                .as_ref()
+                .unwrap()
+                .lookup_pc(ProgramCounter(5))
+                .0
+        );
+    }
+
+    #[test]
+    fn can_assemble_method_types_and_handles() {
+        let class = assemble(
+            r#"
+            .class public super rjvm/Foo
+            .method public static test()V
+                ldc methodtype (IJ)V
+                ldc methodhandle invokestatic interface java/util/List/of()Ljava/util/List;
+                ldc methodhandle getfield rjvm/Foo/bar I
+                ldc methodhandle newinvokespecial rjvm/Foo/<init>()V
+                return
+            .end method
+            "#,
+        )
+        .unwrap();
+
+        let code = class.methods[0].code.as_ref().unwrap();
+        let texts: Vec<String> = Instruction::parse_instructions(&code.code)
+            .unwrap()
+            .iter()
+            .filter_map(|(_, instruction)| match instruction {
+                Instruction::Ldc(index) => Some(class.constants.text_of(*index as u16).unwrap()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                "(IJ)V",
+                "REF_invokeStatic java/util/List.of: ()Ljava/util/List;",
+                "REF_getField rjvm/Foo.bar: I",
+                "REF_newInvokeSpecial rjvm/Foo.<init>: ()V",
+            ],
+            texts
+        );
+    }
+
+    #[test]
+    fn can_assemble_invokedynamic() {
+        let class = assemble(
+            r#"
+            .class public super rjvm/Foo
+            .method public static test()V
+                invokedynamic run()Ljava/lang/Runnable; invokestatic rjvm/Foo/bootstrap()V 1 "a"
+                invokedynamic get()Ljava/lang/Runnable; invokestatic rjvm/Foo/bootstrap()V 1 "a"
+                invokedynamic run()Ljava/lang/Runnable; invokestatic rjvm/Foo/bootstrap()V
+                return
+            .end method
+            "#,
+        )
+        .unwrap();
+
+        let code = class.methods[0].code.as_ref().unwrap();
+        let texts: Vec<String> = Instruction::parse_instructions(&code.code)
+            .unwrap()
+            .iter()
+            .filter_map(|(_, instruction)| match instruction {
+                Instruction::Invokedynamic(index) => Some(class.constants.text_of(*index).unwrap()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                "#0:run: ()Ljava/lang/Runnable;",
+                "#0:get: ()Ljava/lang/Runnable;",
+                "#1:run: ()Ljava/lang/Runnable;",
+            ],
+            texts
+        );
+        assert_eq!(2, class.bootstrap_methods.len());
+        assert_eq!(2, class.bootstrap_methods[0].arguments.len());
+        assert!(class.bootstrap_methods[1].arguments.is_empty());
+    }
+
+    #[test]
+    fn errors_report_the_line() {
+        let assert_error = |source: &str, line: usize, message: &str| {
+            assert_eq!(
+                Err(TextAssemblerError {
+                    line,
+                    message: message.to_string(),
+                }),
+                assemble(source).map(|_| ())
+            )
+        };
+
+        assert_error(".super java/lang/Object", 1, "missing .class declaration");
+        assert_error(
+            ".class rjvm/Foo\n.method static foo()V\n.limit stack 1\ngoto Nowhere\n.end method",
+            4,
+            "label Nowhere is not defined",
+        );
+        assert_error(
+            ".class rjvm/Foo\n.method static foo()V\n.limit stack 1\nA:\nA:\n.end method",
+            5,
+            "label A is already defined",
+        );
+        assert_error(
+            ".class rjvm/Foo\n.method static foo()V\npop\nreturn\n.end method",
+            2,
+            "cannot compute .limit stack: invalid class: pc 0: attempt to pop an empty stack",
+        );
+        assert_error(
+            ".class rjvm/Foo\n.method static foo()V\n  bipush 200\n",
+            3,
+            "invalid number 200",
+        );
+        assert_error(
+            ".class rjvm/Foo\n.method static foo()V\n  frobnicate\n",
+            3,
+            "unknown instruction frobnicate",
+        );
+        assert_error(
+            ".class rjvm/Foo\n.method static foo()V\n  wide nop\n",
+            3,
+            "nop cannot be wide",
+        );
+        assert_error(
+            ".class rjvm/Foo\n.method static foo()V\n  iadd 1\n",
+            3,
+            "expected 0 operands, found 1",
+        );
+        assert_error(
+            ".class rjvm/Foo\n.method static foo()V\n  ldc \"oops\n",
+            3,
+            "unterminated string",
+        );
+        assert_error(
+            ".class rjvm/Foo\n.method static foo()V\n  return\n",
+            2,
+            "missing .end method",
+        );
+    }
+}
@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+/// Models an error in the source given to the [crate::text_assembler]
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct TextAssemblerError {
+    /// The line of the source containing the error, starting from 1
+    pub line: usize,
+    pub message: String,
+}
+
+pub type Result<T> = std::result::Result<T, TextAssemblerError>;
@@ -0,0 +1,301 @@
+use std::{fmt, fmt::Formatter, ops::Range};
+
+use itertools::Itertools;
+
+use crate::{annotation::Annotation, program_counter::ProgramCounter};
+
+/// An annotation applied to a use of a type rather than to a declaration, as introduced
+/// in Java 8. Depending on where the type is used, it belongs to a class, field, method
+/// or to the code of a method.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeAnnotation {
+    /// The type, in a declaration or in an expression, that is annotated
+    pub target: TypeAnnotationTarget,
+    /// The part of the target type that is annotated, for example the element type of
+    /// an array. Empty if the annotation applies to the whole type.
+    pub type_path: Vec<TypePathElement>,
+    pub annotation: Annotation,
+}
+
+impl fmt::Display for TypeAnnotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.annotation, self.target)?;
+        if !self.type_path.is_empty() {
+            write!(f, ", location=[{}]", self.type_path.iter().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of type that is annotated, i.e. the `target_type` of the annotation,
+/// together with the `target_info` that identifies it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeAnnotationTarget {
+    ClassTypeParameter {
+        type_parameter_index: u8,
+    },
+    MethodTypeParameter {
+        type_parameter_index: u8,
+    },
+    /// A type in the `extends` or `implements` clause of a class. The index refers to
+    /// the interfaces of the class, and is missing for the superclass.
+    ClassExtends {
+        interface_index: Option<u16>,
+    },
+    ClassTypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    MethodTypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    Field,
+    /// The return type of a method, or the type of a newly constructed object
+    MethodReturn,
+    MethodReceiver,
+    MethodFormalParameter {
+        formal_parameter_index: u8,
+    },
+    /// A type in the `throws` clause of a method, given its index in the `Exceptions` attribute
+    Throws {
+        throws_type_index: u16,
+    },
+    LocalVariable(Vec<LocalVariableTarget>),
+    /// A variable declared in a try-with-resources statement
+    ResourceVariable(Vec<LocalVariableTarget>),
+    /// The type of a `catch` clause, given the index of its entry in the exception table
+    ExceptionParameter {
+        exception_table_index: u16,
+    },
+    InstanceOf {
+        offset: ProgramCounter,
+    },
+    New {
+        offset: ProgramCounter,
+    },
+    ConstructorReference {
+        offset: ProgramCounter,
+    },
+    MethodReference {
+        offset: ProgramCounter,
+    },
+    Cast {
+        offset: ProgramCounter,
+        type_argument_index: u8,
+    },
+    ConstructorInvocationTypeArgument {
+        offset: ProgramCounter,
+        type_argument_index: u8,
+    },
+    MethodInvocationTypeArgument {
+        offset: ProgramCounter,
+        type_argument_index: u8,
+    },
+    ConstructorReferenceTypeArgument {
+        offset: ProgramCounter,
+        type_argument_index: u8,
+    },
+    MethodReferenceTypeArgument {
+        offset: ProgramCounter,
+        type_argument_index: u8,
+    },
+}
+
+impl TypeAnnotationTarget {
+    /// The `target_type` value that identifies the kind of target in the class file
+    pub fn target_type(&self) -> u8 {
+        match self {
+            TypeAnnotationTarget::ClassTypeParameter { .. } => 0x00,
+            TypeAnnotationTarget::MethodTypeParameter { .. } => 0x01,
+            TypeAnnotationTarget::ClassExtends { .. } => 0x10,
+            TypeAnnotationTarget::ClassTypeParameterBound { .. } => 0x11,
+            TypeAnnotationTarget::MethodTypeParameterBound { .. } => 0x12,
+            TypeAnnotationTarget::Field => 0x13,
+            TypeAnnotationTarget::MethodReturn => 0x14,
+            TypeAnnotationTarget::MethodReceiver => 0x15,
+            TypeAnnotationTarget::MethodFormalParameter { .. } => 0x16,
+            TypeAnnotationTarget::Throws { .. } => 0x17,
+            TypeAnnotationTarget::LocalVariable(_) => 0x40,
+            TypeAnnotationTarget::ResourceVariable(_) => 0x41,
+            TypeAnnotationTarget::ExceptionParameter { .. } => 0x42,
+            TypeAnnotationTarget::InstanceOf { .. } => 0x43,
+            TypeAnnotationTarget::New { .. } => 0x44,
+            TypeAnnotationTarget::ConstructorReference { .. } => 0x45,
+            TypeAnnotationTarget::MethodReference { .. } => 0x46,
+            TypeAnnotationTarget::Cast { .. } => 0x47,
+            TypeAnnotationTarget::ConstructorInvocationTypeArgument { .. } => 0x48,
+            TypeAnnotationTarget::MethodInvocationTypeArgument { .. } => 0x49,
+            TypeAnnotationTarget::ConstructorReferenceTypeArgument { .. } => 0x4A,
+            TypeAnnotationTarget::MethodReferenceTypeArgument { .. } => 0x4B,
+        }
+    }
+}
+
+/// Uses the same names as javap
+impl fmt::Display for TypeAnnotationTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeAnnotationTarget::ClassTypeParameter {
+                type_parameter_index,
+            } => write!(
+                f,
+                "CLASS_TYPE_PARAMETER, param_index={type_parameter_index}"
+            ),
+            TypeAnnotationTarget::MethodTypeParameter {
+                type_parameter_index,
+            } => write!(
+                f,
+                "METHOD_TYPE_PARAMETER, param_index={type_parameter_index}"
+            ),
+            TypeAnnotationTarget::ClassExtends { interface_index } => write!(
+                f,
+                "CLASS_EXTENDS, type_index={}",
+                interface_index.unwrap_or(u16::MAX)
+            ),
+            TypeAnnotationTarget::ClassTypeParameterBound {
+                type_parameter_index,
+                bound_index,
+            } => write!(
+                f,
+                "CLASS_TYPE_PARAMETER_BOUND, param_index={type_parameter_index}, \
+                bound_index={bound_index}"
+            ),
+            TypeAnnotationTarget::MethodTypeParameterBound {
+                type_parameter_index,
+                bound_index,
+            } => write!(
+                f,
+                "METHOD_TYPE_PARAMETER_BOUND, param_index={type_parameter_index}, \
+                bound_index={bound_index}"
+            ),
+            TypeAnnotationTarget::Field => write!(f, "FIELD"),
+            TypeAnnotationTarget::MethodReturn => write!(f, "METHOD_RETURN"),
+            TypeAnnotationTarget::MethodReceiver => write!(f, "METHOD_RECEIVER"),
+            TypeAnnotationTarget::MethodFormalParameter {
+                formal_parameter_index,
+            } => write!(
+                f,
+                "METHOD_FORMAL_PARAMETER, param_index={formal_parameter_index}"
+            ),
+            TypeAnnotationTarget::Throws { throws_type_index } => {
+                write!(f, "THROWS, type_index={throws_type_index}")
+            }
+            TypeAnnotationTarget::LocalVariable(variables) => {
+                write!(f, "LOCAL_VARIABLE, {}", variables.iter().join(", "))
+            }
+            TypeAnnotationTarget::ResourceVariable(variables) => {
+                write!(f, "RESOURCE_VARIABLE, {}", variables.iter().join(", "))
+            }
+            TypeAnnotationTarget::ExceptionParameter {
+                exception_table_index,
+            } => write!(
+                f,
+                "EXCEPTION_PARAMETER, exception_index={exception_table_index}"
+            ),
+            TypeAnnotationTarget::InstanceOf { offset } => write!(f, "INSTANCEOF, offset={offset}"),
+            TypeAnnotationTarget::New { offset } => write!(f, "NEW, offset={offset}"),
+            TypeAnnotationTarget::ConstructorReference { offset } => {
+                write!(f, "CONSTRUCTOR_REFERENCE, offset={offset}")
+            }
+            TypeAnnotationTarget::MethodReference { offset } => {
+                write!(f, "METHOD_REFERENCE, offset={offset}")
+            }
+            TypeAnnotationTarget::Cast {
+                offset,
+                type_argument_index,
+            } => write!(f, "CAST, offset={offset}, type_index={type_argument_index}"),
+            TypeAnnotationTarget::ConstructorInvocationTypeArgument {
+                offset,
+                type_argument_index,
+            } => write!(
+                f,
+                "CONSTRUCTOR_INVOCATION_TYPE_ARGUMENT, offset={offset}, \
+                type_index={type_argument_index}"
+            ),
+            TypeAnnotationTarget::MethodInvocationTypeArgument {
+                offset,
+                type_argument_index,
+            } => write!(
+                f,
+                "METHOD_INVOCATION_TYPE_ARGUMENT, offset={offset}, \
+                type_index={type_argument_index}"
+            ),
+            TypeAnnotationTarget::ConstructorReferenceTypeArgument {
+                offset,
+                type_argument_index,
+            } => write!(
+                f,
+                "CONSTRUCTOR_REFERENCE_TYPE_ARGUMENT, offset={offset}, \
+                type_index={type_argument_index}"
+            ),
+            TypeAnnotationTarget::MethodReferenceTypeArgument {
+                offset,
+                type_argument_index,
+            } => write!(
+                f,
+                "METHOD_REFERENCE_TYPE_ARGUMENT, offset={offset}, \
+                type_index={type_argument_index}"
+            ),
+        }
+    }
+}
+
+/// A range of code in which a local variable, identified by its slot, is live
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocalVariableTarget {
+    pub range: Range<ProgramCounter>,
+    pub index: u16,
+}
+
+impl fmt::Display for LocalVariableTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{start_pc={}, length={}, index={}}}",
+            self.range.start,
+            self.range.end.0.saturating_sub(self.range.start.0),
+            self.index
+        )
+    }
+}
+
+/// A step of the path that leads from the target type to the part that is annotated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypePathElement {
+    /// Deeper in an array type, i.e. its element type
+    Array,
+    /// Deeper in a nested type, i.e. from the outer class to the inner one
+    InnerType,
+    /// On the bound of a wildcard type argument
+    Wildcard,
+    /// On the type argument of a parameterized type with the given index
+    TypeArgument(u8),
+}
+
+impl TypePathElement {
+    /// The `type_path_kind` value that identifies the element in the class file
+    pub fn kind(&self) -> u8 {
+        match self {
+            TypePathElement::Array => 0,
+            TypePathElement::InnerType => 1,
+            TypePathElement::Wildcard => 2,
+            TypePathElement::TypeArgument(_) => 3,
+        }
+    }
+}
+
+impl fmt::Display for TypePathElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TypePathElement::Array => write!(f, "ARRAY"),
+            TypePathElement::InnerType => write!(f, "INNER_TYPE"),
+            TypePathElement::Wildcard => write!(f, "WILDCARD"),
+            TypePathElement::TypeArgument(index) => write!(f, "TYPE_ARGUMENT({index})"),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::annotation::{Annotation, AnnotationElement, ElementValue};
+
+use crate::utils;
+use utils::read_class_from_bytes;
+
+fn marker(runtime_visible: bool) -> Annotation {
+    Annotation {
+        type_descriptor: "Lrjvm/Annotations$Marker;".to_string(),
+        runtime_visible,
+        elements: vec![],
+    }
+}
+
+fn deprecated() -> Annotation {
+    Annotation {
+        type_descriptor: "Ljava/lang/Deprecated;".to_string(),
+        runtime_visible: true,
+        elements: vec![],
+    }
+}
+
+#[test_log::test]
+fn can_read_class_annotations() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Annotations.class"));
+    assert_eq!(2, class.annotations.len());
+
+    let everything = &class.annotations[0];
+    assert_eq!("Lrjvm/Annotations$Everything;", everything.type_descriptor);
+    assert!(everything.runtime_visible);
+    let element = |name: &str| {
+        everything
+            .element(name)
+            .unwrap_or_else(|| panic!("should have element {name}"))
+            .clone()
+    };
+    assert_eq!(ElementValue::Byte(1), element("aByte"));
+    assert_eq!(ElementValue::Char('c' as u16), element("aChar"));
+    assert_eq!(ElementValue::Double(2.5), element("aDouble"));
+    assert_eq!(ElementValue::Float(3.5), element("aFloat"));
+    assert_eq!(ElementValue::Int(4), element("anInt"));
+    assert_eq!(ElementValue::Long(5), element("aLong"));
+    assert_eq!(ElementValue::Short(6), element("aShort"));
+    assert_eq!(ElementValue::Boolean(true), element("aBoolean"));
+    assert_eq!(
+        ElementValue::String("hello".to_string()),
+        element("aString")
+    );
+    assert_eq!(
+        ElementValue::Enum {
+            type_descriptor: "Ljava/lang/annotation/ElementType;".to_string(),
+            const_name: "FIELD".to_string(),
+        },
+        element("anEnum")
+    );
+    assert_eq!(
+        ElementValue::Class("Ljava/lang/String;".to_string()),
+        element("aClass")
+    );
+    assert_eq!(
+        ElementValue::Annotation(Box::new(marker(true))),
+        element("anAnnotation")
+    );
+    assert_eq!(
+        ElementValue::Array(vec![ElementValue::Int(7), ElementValue::Int(8)]),
+        element("anArray")
+    );
+    assert_eq!(
+        "@rjvm/Annotations$Everything(aByte = 1, aChar = 'c', aDouble = 2.5, aFloat = 3.5, \
+        anInt = 4, aLong = 5, aShort = 6, aBoolean = true, aString = \"hello\", \
+        anEnum = java/lang/annotation/ElementType.FIELD, aClass = java/lang/String.class, \
+        anAnnotation = @rjvm/Annotations$Marker, anArray = {7, 8})",
+        everything.to_string()
+    );
+
+    // Marker does not have runtime retention
+    assert_eq!(marker(false), class.annotations[1]);
+}
+
+#[test_log::test]
+fn can_read_field_and_method_annotations() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Annotations.class"));
+
+    let field = class.fields.iter().find(|f| f.name == "field").unwrap();
+    assert_eq!(vec![deprecated()], field.annotations);
+
+    let method = class.methods.iter().find(|m| m.name == "method").unwrap();
+    assert_eq!(vec![marker(false)], method.annotations);
+    assert_eq!(
+        vec![vec![deprecated()], vec![], vec![marker(false)]],
+        method.parameter_annotations
+    );
+    assert_eq!(None, method.annotation_default);
+}
+
+#[test_log::test]
+fn can_read_annotation_default() {
+    let class = read_class_from_bytes(include_bytes!(
+        "../resources/rjvm/Annotations$Everything.class"
+    ));
+
+    let method = class.methods.iter().find(|m| m.name == "anArray").unwrap();
+    assert_eq!(
+        Some(ElementValue::Array(vec![ElementValue::Int(42)])),
+        method.annotation_default
+    );
+    let method = class.methods.iter().find(|m| m.name == "anInt").unwrap();
+    assert_eq!(None, method.annotation_default);
+
+    assert_eq!(
+        vec![Annotation {
+            type_descriptor: "Ljava/lang/annotation/Retention;".to_string(),
+            runtime_visible: true,
+            elements: vec![AnnotationElement {
+                name: "value".to_string(),
+                value: ElementValue::Enum {
+                    type_descriptor: "Ljava/lang/annotation/RetentionPolicy;".to_string(),
+                    const_name: "RUNTIME".to_string(),
+                },
+            }],
+        }],
+        class.annotations
+    );
+}
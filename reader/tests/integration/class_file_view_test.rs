@@ -0,0 +1,147 @@
+extern crate rjvm_reader;
+
+use std::{borrow::Cow, fs};
+
+use rjvm_reader::{
+    class_file_view::{AttributeView, ClassFileView},
+    class_reader::read_buffer,
+    class_reader_error::{ClassReaderError, Result},
+    field_type::FieldType,
+};
+
+use crate::utils::test_class_files;
+
+fn attribute_names<'data>(
+    attributes: impl Iterator<Item = Result<AttributeView<'data>>>,
+) -> Vec<String> {
+    attributes
+        .map(|attribute| attribute.unwrap().name.into_owned())
+        .collect()
+}
+
+#[test_log::test]
+fn views_match_the_owned_model() {
+    for path in test_class_files() {
+        let bytes = fs::read(&path).unwrap();
+        let class = read_buffer(&bytes).unwrap();
+        let view = ClassFileView::parse(&bytes).unwrap();
+        let context = path.display();
+
+        assert_eq!(&class.version, view.version(), "{context}");
+        assert_eq!(class.minor_version, view.minor_version(), "{context}");
+        assert_eq!(class.flags, view.flags(), "{context}");
+        assert_eq!(class.name, view.name().unwrap(), "{context}");
+        assert_eq!(
+            class.superclass,
+            view.superclass().unwrap().map(Cow::into_owned),
+            "{context}"
+        );
+        assert_eq!(
+            class.interfaces,
+            view.interfaces().collect::<Result<Vec<_>>>().unwrap(),
+            "{context}"
+        );
+        // The owned model only keeps the attributes it does not parse
+        let class_attributes = attribute_names(view.attributes());
+        for attribute in class.attributes.iter() {
+            assert!(class_attributes.contains(&attribute.name), "{context}");
+        }
+
+        assert_eq!(class.fields.len(), view.fields().len(), "{context}");
+        for (field, field_view) in class.fields.iter().zip(view.fields()) {
+            assert_eq!(field.flags, field_view.flags(), "{context}");
+            assert_eq!(field.name, field_view.name().unwrap(), "{context}");
+            assert_eq!(
+                field.type_descriptor,
+                FieldType::parse(&field_view.descriptor().unwrap()).unwrap(),
+                "{context}"
+            );
+        }
+
+        assert_eq!(class.methods.len(), view.methods().len(), "{context}");
+        for (method, method_view) in class.methods.iter().zip(view.methods()) {
+            let context = format!("{context} {}", method.name);
+            assert_eq!(method.flags, method_view.flags(), "{context}");
+            assert_eq!(method.name, method_view.name().unwrap(), "{context}");
+            assert_eq!(
+                method.type_descriptor,
+                method_view.descriptor().unwrap(),
+                "{context}"
+            );
+            assert_eq!(
+                method
+                    .attributes
+                    .iter()
+                    .map(|attribute| attribute.name.clone())
+                    .collect::<Vec<_>>(),
+                attribute_names(method_view.attributes()),
+                "{context}"
+            );
+
+            let code_view = method_view.code().unwrap();
+            assert_eq!(method.code.is_some(), code_view.is_some(), "{context}");
+            let (Some(code), Some(code_view)) = (&method.code, code_view) else {
+                continue;
+            };
+            assert_eq!(code.max_stack, code_view.max_stack, "{context}");
+            assert_eq!(code.max_locals, code_view.max_locals, "{context}");
+            assert_eq!(code.code, code_view.code, "{context}");
+            assert_eq!(
+                code.exception_table,
+                code_view.exception_table().unwrap(),
+                "{context}"
+            );
+            assert!(code_view.instructions().is_ok(), "{context}");
+            assert_eq!(
+                code.attributes
+                    .iter()
+                    .map(|attribute| attribute.name.clone())
+                    .collect::<Vec<_>>(),
+                attribute_names(code_view.attributes()),
+                "{context}"
+            );
+        }
+    }
+}
+
+#[test_log::test]
+fn views_borrow_the_strings_of_the_class() {
+    let bytes = include_bytes!("../resources/rjvm/Complex.class");
+    let view = ClassFileView::parse(bytes).unwrap();
+
+    assert!(matches!(
+        view.name().unwrap(),
+        Cow::Borrowed("rjvm/Complex")
+    ));
+    let class_names = view
+        .constants()
+        .class_names()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    for class_name in [
+        "rjvm/Complex",
+        "java/lang/Object",
+        "java/lang/Cloneable",
+        "java/io/Serializable",
+        "java/lang/Math",
+    ] {
+        assert!(class_names.contains(&Cow::Borrowed(class_name)));
+    }
+}
+
+#[test_log::test]
+fn errors_report_the_offset_where_the_structure_starts() {
+    let location = |bytes: &[u8]| match ClassFileView::parse(bytes) {
+        Err(ClassReaderError::Located { offset, path, .. }) => (offset, path),
+        result => panic!("expected a located error, got {:?}", result.err()),
+    };
+
+    let mut bytes = include_bytes!("../resources/rjvm/Complex.class").to_vec();
+    bytes[8..10].copy_from_slice(&[0, 0]);
+    assert_eq!(
+        (8, vec!["constant_pool_count".to_string()]),
+        location(&bytes)
+    );
+    bytes[0] = 0;
+    assert_eq!((0, vec!["magic".to_string()]), location(&bytes));
+}
@@ -0,0 +1,64 @@
+extern crate rjvm_reader;
+
+use std::fs;
+
+use rjvm_reader::{class_reader::read_buffer, class_writer::write, instruction::Instruction};
+
+use crate::utils::{read_class_from_bytes, test_class_files};
+
+#[test_log::test]
+fn written_classes_can_be_read_back() {
+    for path in test_class_files() {
+        let class = read_buffer(&fs::read(&path).unwrap()).unwrap();
+        let bytes = write(&class).unwrap();
+        let written_class = read_buffer(&bytes)
+            .unwrap_or_else(|err| panic!("cannot read back {}: {err}", path.display()));
+        // The debug format includes everything, including the constant pool and
+        // the raw attributes, and handles NaN constants properly
+        assert_eq!(
+            format!("{class:?}"),
+            format!("{written_class:?}"),
+            "{} did not round trip",
+            path.display()
+        );
+        assert_eq!(bytes, write(&written_class).unwrap());
+    }
+}
+
+#[test_log::test]
+fn minor_version_is_written_back() {
+    let mut class = read_class_from_bytes(include_bytes!("../resources/rjvm/Complex.class"));
+    // Classes using preview features have the minor version set to 65535
+    class.minor_version = 65535;
+    let bytes = write(&class).unwrap();
+    assert_eq!([0xFF, 0xFF], bytes[4..6]);
+
+    let written_class = read_buffer(&bytes).unwrap();
+    assert_eq!(65535, written_class.minor_version);
+    assert_eq!(class.version, written_class.version);
+    assert_eq!(bytes, write(&written_class).unwrap());
+}
+
+#[test_log::test]
+fn instructions_can_be_encoded_back() {
+    for path in test_class_files() {
+        let class = read_buffer(&fs::read(&path).unwrap()).unwrap();
+        for method in class.methods.iter() {
+            let Some(code) = &method.code else {
+                continue;
+            };
+            let instructions: Vec<Instruction> = Instruction::parse_instructions(&code.code)
+                .unwrap()
+                .into_iter()
+                .map(|(_, instruction)| instruction)
+                .collect();
+            assert_eq!(
+                code.code,
+                Instruction::encode_instructions(&instructions).unwrap(),
+                "code of {}.{} did not round trip",
+                class.name,
+                method.name
+            );
+        }
+    }
+}
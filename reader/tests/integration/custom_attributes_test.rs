@@ -0,0 +1,147 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    attribute::Attribute,
+    class_file::ClassFile,
+    class_reader::{read_buffer, read_buffer_with_parsers},
+    class_reader_error::ClassReaderError,
+    class_writer::write,
+    constant_pool::{ConstantPool, ConstantPoolEntry},
+    custom_attribute::AttributeParsers,
+};
+
+use crate::utils::read_class_from_bytes;
+
+/// An in-house attribute recording which source file and build produced the class
+#[derive(Debug, PartialEq)]
+struct BuildStamp {
+    source: String,
+    build_number: u32,
+}
+
+fn parse_build_stamp(
+    bytes: &[u8],
+    constants: &ConstantPool,
+) -> Result<BuildStamp, ClassReaderError> {
+    let [source_index_high, source_index_low, build_number @ ..] = bytes else {
+        return Err(ClassReaderError::invalid_class_data(
+            "build stamp too short".to_string(),
+        ));
+    };
+    let build_number: [u8; 4] = build_number.try_into().map_err(|_| {
+        ClassReaderError::invalid_class_data("invalid build stamp number".to_string())
+    })?;
+    Ok(BuildStamp {
+        source: constants.text_of(u16::from_be_bytes([*source_index_high, *source_index_low]))?,
+        build_number: u32::from_be_bytes(build_number),
+    })
+}
+
+fn attribute_parsers() -> AttributeParsers {
+    let mut parsers = AttributeParsers::new();
+    parsers.register("SourceDebugExtension", |bytes, _| {
+        Ok(String::from_utf8_lossy(bytes).to_string())
+    });
+    parsers.register("BuildStamp", parse_build_stamp);
+    parsers
+}
+
+fn complex_class() -> ClassFile {
+    read_class_from_bytes(include_bytes!("../resources/rjvm/Complex.class"))
+}
+
+/// Returns a build stamp attribute, referring to the name of the source file of the class
+fn build_stamp(class: &ClassFile, build_number: u32) -> Attribute {
+    let (source_index, _) = class
+        .constants
+        .iter()
+        .find(|(_, entry)| **entry == ConstantPoolEntry::Utf8("Complex.java".to_string()))
+        .unwrap();
+    let mut bytes = source_index.to_be_bytes().to_vec();
+    bytes.extend_from_slice(&build_number.to_be_bytes());
+    Attribute {
+        name: "BuildStamp".to_string(),
+        bytes,
+    }
+}
+
+#[test_log::test]
+fn custom_attributes_are_parsed_by_the_registered_parsers() {
+    let mut class = complex_class();
+    class.attributes.push(Attribute {
+        name: "SourceDebugExtension".to_string(),
+        bytes: b"SMAP\nComplex.kt\nKotlin\n*E\n".to_vec(),
+    });
+    let field_stamp = build_stamp(&class, 1);
+    class.fields[0].attributes.push(field_stamp);
+    let method_stamp = build_stamp(&class, 2);
+    class.methods[0].attributes.push(method_stamp);
+    let code = class.methods[0].code.as_mut().unwrap();
+    code.attributes.push(Attribute {
+        name: "Unknown".to_string(),
+        bytes: vec![1, 2, 3],
+    });
+    let bytes = write(&class).unwrap();
+
+    let class = read_buffer_with_parsers(&bytes, &attribute_parsers()).unwrap();
+    assert_eq!(1, class.custom_attributes.len());
+    assert_eq!("SourceDebugExtension", class.custom_attributes[0].name);
+    assert_eq!(
+        Some(&"SMAP\nComplex.kt\nKotlin\n*E\n".to_string()),
+        class.custom_attributes[0].value::<String>()
+    );
+    assert_eq!(None, class.custom_attributes[0].value::<BuildStamp>());
+
+    assert_eq!(1, class.fields[0].custom_attributes.len());
+    assert_eq!(
+        Some(&BuildStamp {
+            source: "Complex.java".to_string(),
+            build_number: 1,
+        }),
+        class.fields[0].custom_attributes[0].value::<BuildStamp>()
+    );
+    assert_eq!(1, class.methods[0].custom_attributes.len());
+    assert_eq!(
+        Some(&BuildStamp {
+            source: "Complex.java".to_string(),
+            build_number: 2,
+        }),
+        class.methods[0].custom_attributes[0].value::<BuildStamp>()
+    );
+
+    // Attributes without a parser are only kept in their raw form, as are those
+    // with a parser, so that the class can be written back
+    let code = class.methods[0].code.as_ref().unwrap();
+    assert!(code.custom_attributes.is_empty());
+    assert!(code.attributes.iter().any(|attr| attr.name == "Unknown"));
+    assert!(class
+        .attributes
+        .iter()
+        .any(|attr| attr.name == "SourceDebugExtension"));
+    assert_eq!(bytes, write(&class).unwrap());
+
+    // Without parsers, nothing is parsed
+    let class = read_buffer(&bytes).unwrap();
+    assert!(class.custom_attributes.is_empty());
+    assert!(class.fields[0].custom_attributes.is_empty());
+}
+
+#[test_log::test]
+fn errors_of_custom_parsers_are_located() {
+    let mut class = complex_class();
+    class.fields[0].attributes.push(Attribute {
+        name: "BuildStamp".to_string(),
+        bytes: vec![0, 0],
+    });
+    let bytes = write(&class).unwrap();
+
+    let err = read_buffer_with_parsers(&bytes, &attribute_parsers()).unwrap_err();
+    assert_eq!(
+        &ClassReaderError::invalid_class_data("invalid build stamp number".to_string()),
+        err.without_location()
+    );
+    let ClassReaderError::Located { path, .. } = err else {
+        panic!("error should be located: {err}");
+    };
+    assert_eq!(vec!["fields[0] \"real\"", "BuildStamp"], path);
+}
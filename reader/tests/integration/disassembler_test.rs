@@ -0,0 +1,33 @@
+extern crate rjvm_reader;
+
+use std::{fs, path::Path};
+
+use rjvm_reader::disassembler::disassemble;
+
+use crate::utils::read_class_from_bytes;
+
+/// Compares the disassembly of a class in `tests/resources/rjvm` with the expected one,
+/// stored in `tests/resources/javap`
+fn assert_disassembles_to_golden_file(class_name: &str) {
+    let resources = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources");
+    let class = read_class_from_bytes(
+        &fs::read(resources.join(format!("rjvm/{class_name}.class"))).unwrap(),
+    );
+    let expected = fs::read_to_string(resources.join(format!("javap/{class_name}.txt"))).unwrap();
+    assert_eq!(expected, disassemble(&class), "disassembly of {class_name}");
+}
+
+#[test_log::test]
+fn can_disassemble_constants_of_all_types() {
+    assert_disassembles_to_golden_file("Constants");
+}
+
+#[test_log::test]
+fn can_disassemble_generic_signatures() {
+    assert_disassembles_to_golden_file("Generics");
+}
+
+#[test_log::test]
+fn can_disassemble_local_variable_tables() {
+    assert_disassembles_to_golden_file("LocalVariables");
+}
@@ -0,0 +1,66 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    class_file::ClassFile,
+    frame_analysis::{recompute_frames, UnknownClassHierarchy},
+    program_counter::ProgramCounter,
+    stack_map_table::{StackMapFrame, StackMapFrameKind, VerificationType},
+};
+
+use crate::utils::read_class_from_bytes;
+
+fn stack_map_table_of<'c>(class: &'c ClassFile, name: &str) -> &'c [StackMapFrame] {
+    &class
+        .methods
+        .iter()
+        .find(|m| m.name == name)
+        .unwrap_or_else(|| panic!("should find method {name}"))
+        .code
+        .as_ref()
+        .unwrap()
+        .stack_map_table
+}
+
+#[test_log::test]
+fn can_recompute_the_frames_generated_by_javac() {
+    let bytes = include_bytes!("../resources/rjvm/StackMaps.class");
+    let original = read_class_from_bytes(bytes);
+    let mut class = read_class_from_bytes(bytes);
+
+    recompute_frames(&mut class, &mut UnknownClassHierarchy).unwrap();
+
+    for (method, original_method) in class.methods.iter().zip(original.methods.iter()) {
+        let code = method.code.as_ref().unwrap();
+        let original_code = original_method.code.as_ref().unwrap();
+        assert_eq!(original_code.max_stack, code.max_stack, "{}", method.name);
+        assert_eq!(original_code.max_locals, code.max_locals, "{}", method.name);
+    }
+    for name in ["fullFrame", "extended"] {
+        assert_eq!(
+            stack_map_table_of(&original, name),
+            stack_map_table_of(&class, name)
+        );
+    }
+
+    // javac drops the locals that go out of scope, while the analysis keeps them
+    assert_eq!(
+        vec![
+            StackMapFrame {
+                program_counter: ProgramCounter(4),
+                kind: StackMapFrameKind::Append(vec![
+                    VerificationType::Integer,
+                    VerificationType::Integer
+                ]),
+            },
+            StackMapFrame {
+                program_counter: ProgramCounter(26),
+                kind: StackMapFrameKind::Append(vec![VerificationType::Long]),
+            },
+            StackMapFrame {
+                program_counter: ProgramCounter(32),
+                kind: StackMapFrameKind::Chop(1),
+            },
+        ],
+        stack_map_table_of(&class, "loops")
+    );
+}
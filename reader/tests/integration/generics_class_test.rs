@@ -0,0 +1,45 @@
+extern crate rjvm_reader;
+
+use crate::utils;
+use utils::read_class_from_bytes;
+
+#[test_log::test]
+fn can_read_generic_signatures() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Generics.class"));
+
+    assert_eq!(
+        "<T extends java.lang.Comparable<T>> extends java.lang.Object \
+        implements java.lang.Iterable<T>",
+        class
+            .signature
+            .expect("should have a signature")
+            .to_string()
+    );
+
+    let field = class
+        .fields
+        .iter()
+        .find(|field| field.name == "values")
+        .expect("should have the field");
+    assert_eq!(
+        "java.util.List<java.util.Map<java.lang.String, T>>",
+        field.signature.as_ref().unwrap().to_string()
+    );
+
+    let method = class
+        .methods
+        .iter()
+        .find(|method| method.name == "find")
+        .expect("should have the method");
+    assert_eq!(
+        "<E extends java.lang.Exception> (java.util.List<? super T>) -> T throws E",
+        method.signature.as_ref().unwrap().to_string()
+    );
+
+    let constructor = class
+        .methods
+        .iter()
+        .find(|method| method.name == "<init>")
+        .expect("should have the constructor");
+    assert_eq!(None, constructor.signature);
+}
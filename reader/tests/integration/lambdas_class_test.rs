@@ -0,0 +1,67 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    bootstrap_method::BootstrapMethod,
+    constant_pool::{ConstantPoolEntry, MethodHandleKind},
+};
+
+use crate::utils;
+use utils::read_class_from_bytes;
+
+#[test_log::test]
+fn can_read_class_with_dynamic_constants() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Lambdas.class"));
+    assert_eq!("rjvm/Lambdas", class.name);
+
+    assert_eq!(
+        &ConstantPoolEntry::InvokeDynamic(0, 8),
+        class.constants.get(7).unwrap()
+    );
+    assert_eq!(
+        "#0:get: ()Ljava/util/function/Supplier;",
+        class.constants.text_of(7).unwrap()
+    );
+
+    assert_eq!(
+        &ConstantPoolEntry::MethodHandle(MethodHandleKind::InvokeStatic, 68),
+        class.constants.get(67).unwrap()
+    );
+    assert_eq!(
+        "REF_invokeStatic rjvm/Lambdas.lambda$main$0: ()Ljava/lang/String;",
+        class.constants.text_of(67).unwrap()
+    );
+
+    assert_eq!(
+        &ConstantPoolEntry::MethodType(19),
+        class.constants.get(66).unwrap()
+    );
+    assert_eq!("()Ljava/lang/Object;", class.constants.text_of(66).unwrap());
+}
+
+#[test_log::test]
+fn can_read_bootstrap_methods() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Lambdas.class"));
+
+    assert_eq!(
+        vec![
+            BootstrapMethod {
+                method_ref: 59,
+                arguments: vec![66, 67, 70],
+            },
+            BootstrapMethod {
+                method_ref: 59,
+                arguments: vec![71, 72, 75],
+            },
+            BootstrapMethod {
+                method_ref: 76,
+                arguments: vec![82],
+            },
+        ],
+        class.bootstrap_methods
+    );
+    assert_eq!(
+        "REF_invokeStatic java/lang/invoke/StringConcatFactory.makeConcatWithConstants: \
+        (Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/String;[Ljava/lang/Object;)Ljava/lang/invoke/CallSite;",
+        class.constants.text_of(76).unwrap()
+    );
+}
@@ -0,0 +1,59 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    field_type::{BaseType, FieldType},
+    program_counter::ProgramCounter,
+};
+
+use crate::utils;
+use utils::read_class_from_bytes;
+
+#[test_log::test]
+fn can_read_local_variable_tables() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/LocalVariables.class"));
+    let method = class.methods.iter().find(|m| m.name == "repeat").unwrap();
+    let table = method
+        .code
+        .as_ref()
+        .unwrap()
+        .local_variable_table
+        .as_ref()
+        .expect("class was compiled with debug information");
+    assert_eq!(4, table.entries().len());
+
+    let value = table.lookup(ProgramCounter(0), 0).unwrap();
+    assert_eq!("value", value.name);
+    assert_eq!(
+        FieldType::Object("java/lang/String".to_string()),
+        value.type_descriptor
+    );
+    assert_eq!(None, value.signature);
+
+    let times = table.lookup(ProgramCounter(14), 1).unwrap();
+    assert_eq!("times", times.name);
+    assert_eq!(FieldType::Base(BaseType::Long), times.type_descriptor);
+    assert_eq!(None, table.lookup(ProgramCounter(14), 2));
+
+    let result = table.lookup(ProgramCounter(33), 3).unwrap();
+    assert_eq!("result", result.name);
+    assert_eq!(
+        "result: java.util.List<java.lang.String> (slot 3, pc 8..35)",
+        result.to_string()
+    );
+    assert_eq!(None, table.lookup(ProgramCounter(7), 3));
+
+    let count = table.lookup(ProgramCounter(27), 4).unwrap();
+    assert_eq!("count", count.name);
+    assert_eq!(FieldType::Base(BaseType::Int), count.type_descriptor);
+    assert_eq!(None, table.lookup(ProgramCounter(33), 4));
+}
+
+#[test_log::test]
+fn local_variable_table_is_missing_without_debug_information() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Complex.class"));
+    assert!(class
+        .methods
+        .iter()
+        .filter_map(|method| method.code.as_ref())
+        .all(|code| code.local_variable_table.is_none()));
+}
@@ -1,6 +1,23 @@
+mod annotations_class_test;
 mod assertions;
+mod class_file_view_test;
+mod class_writer_test;
 mod constants_class_test;
+mod custom_attributes_test;
 mod deprecated_class_test;
+mod disassembler_test;
 mod exceptions;
+mod frame_analysis_test;
+mod generics_class_test;
+mod lambdas_class_test;
+mod local_variables_class_test;
+mod malformed_classes_test;
+mod modern_classes_test;
+mod nested_classes_test;
 mod pojo_class_test;
+#[cfg(feature = "serde")]
+mod serde_test;
+mod stack_maps_class_test;
+mod text_assembler_test;
+mod type_annotations_class_test;
 mod utils;
@@ -0,0 +1,104 @@
+extern crate rjvm_reader;
+
+use std::{fs, path::Path};
+
+use rjvm_reader::{
+    class_file_view::ClassFileView, class_reader::read_buffer, disassembler::disassemble,
+    instruction::Instruction,
+};
+
+use crate::utils::test_class_files;
+
+/// Reads a class, the bytecode of its methods and its disassembly, as a user of the reader
+/// would. Returns whether the class is valid.
+fn read(bytes: &[u8]) -> bool {
+    match read_buffer(bytes) {
+        Ok(class) => {
+            for code in class
+                .methods
+                .iter()
+                .filter_map(|method| method.code.as_ref())
+            {
+                let _ = Instruction::parse_instructions(&code.code);
+            }
+            let _ = disassemble(&class);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Accesses everything that a view of the class decodes lazily
+fn scan(bytes: &[u8]) {
+    let Ok(view) = ClassFileView::parse(bytes) else {
+        return;
+    };
+    let _ = view.name();
+    let _ = view.superclass();
+    view.interfaces().for_each(drop);
+    view.constants().class_names().for_each(drop);
+    view.attributes().for_each(drop);
+    for field in view.fields() {
+        let _ = (field.name(), field.descriptor());
+        field.attributes().for_each(drop);
+    }
+    for method in view.methods() {
+        let _ = (method.name(), method.descriptor());
+        method.attributes().for_each(drop);
+        if let Ok(Some(code)) = method.code() {
+            let _ = (code.instructions(), code.exception_table());
+            code.attributes().for_each(drop);
+        }
+    }
+}
+
+/// The corpus contains the inputs that used to crash the reader, either found by the fuzz
+/// target or crafted by hand
+#[test_log::test]
+fn malformed_classes_in_the_corpus_are_rejected() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/resources/malformed");
+    let mut num_inputs = 0;
+    for entry in fs::read_dir(corpus).unwrap() {
+        let path = entry.unwrap().path();
+        let bytes = fs::read(&path).unwrap();
+        assert!(!read(&bytes), "{} should not be valid", path.display());
+        scan(&bytes);
+        num_inputs += 1;
+    }
+    assert!(num_inputs > 0);
+}
+
+#[test_log::test]
+fn truncated_classes_are_rejected() {
+    for path in test_class_files() {
+        let bytes = fs::read(&path).unwrap();
+        for len in 0..bytes.len() {
+            assert!(
+                !read(&bytes[..len]),
+                "{} truncated at {len} should not be valid",
+                path.display()
+            );
+            assert!(
+                ClassFileView::parse(&bytes[..len]).is_err(),
+                "{} truncated at {len} should not be viewable",
+                path.display()
+            );
+        }
+    }
+}
+
+#[test_log::test]
+fn corrupted_classes_do_not_panic() {
+    for path in test_class_files() {
+        let bytes = fs::read(&path).unwrap();
+        let mut corrupted = bytes.clone();
+        for position in 0..bytes.len() {
+            for value in [0x00, 0xFF, bytes[position].wrapping_add(1)] {
+                corrupted[position] = value;
+                read(&corrupted);
+                scan(&corrupted);
+            }
+            corrupted[position] = bytes[position];
+        }
+    }
+}
@@ -0,0 +1,127 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    annotation::Annotation,
+    class_access_flags::ClassAccessFlags,
+    field_type::{BaseType, FieldType},
+    module::{Module, ModulePackage, ModuleProvides, ModuleRequires},
+    module_flags::{ModuleFlags, ModulePackageFlags, ModuleRequiresFlags},
+    record_component::RecordComponent,
+};
+
+use crate::utils;
+use utils::read_class_from_bytes;
+
+fn requires(module: &str, flags: ModuleRequiresFlags) -> ModuleRequires {
+    ModuleRequires {
+        module: module.to_string(),
+        flags,
+        version: Some("17.0.15".to_string()),
+    }
+}
+
+fn package(package: &str, to: &[&str]) -> ModulePackage {
+    ModulePackage {
+        package: package.to_string(),
+        flags: ModulePackageFlags::empty(),
+        to: to.iter().map(|module| module.to_string()).collect(),
+    }
+}
+
+#[test_log::test]
+fn can_read_module() {
+    let class = read_class_from_bytes(include_bytes!("../resources/module/module-info.class"));
+    assert_eq!("module-info", class.name);
+    assert_eq!(ClassAccessFlags::MODULE, class.flags);
+    assert_eq!(
+        Some(Module {
+            name: "rjvm.module".to_string(),
+            flags: ModuleFlags::empty(),
+            version: Some("1.0".to_string()),
+            requires: vec![
+                requires("java.base", ModuleRequiresFlags::MANDATED),
+                requires("java.logging", ModuleRequiresFlags::empty()),
+                requires("java.sql", ModuleRequiresFlags::TRANSITIVE),
+                requires("java.compiler", ModuleRequiresFlags::STATIC_PHASE),
+            ],
+            exports: vec![
+                package("rjvm/module/api", &[]),
+                package("rjvm/module/impl", &["java.base"]),
+            ],
+            opens: vec![package("rjvm/module/impl", &[])],
+            uses: vec!["rjvm/module/api/Service".to_string()],
+            provides: vec![ModuleProvides {
+                service: "rjvm/module/api/Service".to_string(),
+                implementations: vec!["rjvm/module/impl/ServiceImpl".to_string()],
+            }],
+        }),
+        class.module
+    );
+    assert_eq!(
+        vec!["rjvm/module", "rjvm/module/api", "rjvm/module/impl"],
+        class.module_packages
+    );
+    assert_eq!(
+        Some("rjvm/module/Main".to_string()),
+        class.module_main_class
+    );
+}
+
+#[test_log::test]
+fn can_read_nests_and_permitted_subclasses() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Shapes.class"));
+    assert_eq!(None, class.nest_host);
+    assert_eq!(
+        vec!["rjvm/Shapes$Square", "rjvm/Shapes$Circle"],
+        class.nest_members
+    );
+    assert_eq!(
+        vec!["rjvm/Shapes$Circle", "rjvm/Shapes$Square"],
+        class.permitted_subclasses
+    );
+    assert_eq!(None, class.record_components);
+
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Shapes$Circle.class"));
+    assert_eq!(Some("rjvm/Shapes".to_string()), class.nest_host);
+    assert!(class.nest_members.is_empty());
+    assert!(class.permitted_subclasses.is_empty());
+}
+
+#[test_log::test]
+fn can_read_records() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Records$Point.class"));
+    assert_eq!(Some("rjvm/Records".to_string()), class.nest_host);
+    let int_component = |name: &str, annotations: Vec<Annotation>| RecordComponent {
+        name: name.to_string(),
+        type_descriptor: FieldType::Base(BaseType::Int),
+        signature: None,
+        annotations,
+        type_annotations: vec![],
+        attributes: vec![],
+    };
+    assert_eq!(
+        Some(vec![
+            int_component("x", vec![]),
+            int_component(
+                "y",
+                vec![Annotation {
+                    type_descriptor: "Lrjvm/Records$Marker;".to_string(),
+                    runtime_visible: true,
+                    elements: vec![],
+                }]
+            ),
+        ]),
+        class.record_components
+    );
+
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Records$Box.class"));
+    let components = class.record_components.unwrap();
+    assert_eq!(1, components.len());
+    assert_eq!(
+        "items: java/util/List signature: java.util.List<T>",
+        components[0].to_string()
+    );
+
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Records$Empty.class"));
+    assert_eq!(Some(vec![]), class.record_components);
+}
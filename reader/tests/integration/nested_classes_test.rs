@@ -0,0 +1,91 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    enclosing_method::EnclosingMethod, inner_class::InnerClass, inner_class_flags::InnerClassFlags,
+};
+
+use crate::utils;
+use utils::read_class_from_bytes;
+
+#[test_log::test]
+fn can_read_inner_classes_of_outer_class() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Nested.class"));
+    assert_eq!(None, class.enclosing_method);
+    assert_eq!(
+        vec![
+            InnerClass {
+                name: "rjvm/Nested$1Local".to_string(),
+                outer_class: None,
+                simple_name: Some("Local".to_string()),
+                flags: InnerClassFlags::empty(),
+            },
+            InnerClass {
+                name: "rjvm/Nested$1".to_string(),
+                outer_class: None,
+                simple_name: None,
+                flags: InnerClassFlags::empty(),
+            },
+            InnerClass {
+                name: "rjvm/Nested$NestedInterface".to_string(),
+                outer_class: Some("rjvm/Nested".to_string()),
+                simple_name: Some("NestedInterface".to_string()),
+                flags: InnerClassFlags::PROTECTED
+                    | InnerClassFlags::STATIC
+                    | InnerClassFlags::INTERFACE
+                    | InnerClassFlags::ABSTRACT,
+            },
+            InnerClass {
+                name: "rjvm/Nested$Inner".to_string(),
+                outer_class: Some("rjvm/Nested".to_string()),
+                simple_name: Some("Inner".to_string()),
+                flags: InnerClassFlags::PUBLIC,
+            },
+        ],
+        class.inner_classes
+    );
+}
+
+#[test_log::test]
+fn can_read_member_class() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Nested$Inner.class"));
+    assert_eq!(None, class.enclosing_method);
+    assert_eq!(
+        vec![InnerClass {
+            name: "rjvm/Nested$Inner".to_string(),
+            outer_class: Some("rjvm/Nested".to_string()),
+            simple_name: Some("Inner".to_string()),
+            flags: InnerClassFlags::PUBLIC,
+        }],
+        class.inner_classes
+    );
+}
+
+#[test_log::test]
+fn can_read_enclosing_method_of_local_class() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Nested$1Local.class"));
+    assert_eq!(
+        Some(EnclosingMethod {
+            class_name: "rjvm/Nested".to_string(),
+            method_name: Some("createLocal".to_string()),
+            method_type_descriptor: Some("()Ljava/lang/Object;".to_string()),
+        }),
+        class.enclosing_method
+    );
+}
+
+#[test_log::test]
+fn can_read_enclosing_method_of_anonymous_class() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Nested$1.class"));
+    assert_eq!(
+        Some(EnclosingMethod {
+            class_name: "rjvm/Nested".to_string(),
+            method_name: Some("createAnonymous".to_string()),
+            method_type_descriptor: Some("()Lrjvm/Nested$NestedInterface;".to_string()),
+        }),
+        class.enclosing_method
+    );
+    assert_eq!(
+        Some("rjvm/Nested.createAnonymous: ()Lrjvm/Nested$NestedInterface;".to_string()),
+        class.enclosing_method.map(|m| m.to_string())
+    );
+}
@@ -48,6 +48,11 @@ fn check_fields(class: &ClassFile) {
                 type_descriptor: FieldType::Base(BaseType::Double),
                 constant_value: None,
                 deprecated: false,
+                signature: None,
+                annotations: vec![],
+                type_annotations: vec![],
+                custom_attributes: vec![],
+                attributes: vec![],
             },
             ClassFileField {
                 flags: FieldFlags::PRIVATE | FieldFlags::FINAL,
@@ -55,6 +60,11 @@ fn check_fields(class: &ClassFile) {
                 type_descriptor: FieldType::Base(BaseType::Double),
                 constant_value: None,
                 deprecated: false,
+                signature: None,
+                annotations: vec![],
+                type_annotations: vec![],
+                custom_attributes: vec![],
+                attributes: vec![],
             }
         ),
         class.fields
@@ -0,0 +1,64 @@
+extern crate rjvm_reader;
+
+use std::fs;
+
+use serde_json::json;
+
+use rjvm_reader::{
+    class_file::ClassFile, class_reader::read_buffer, field_flags::FieldFlags,
+    instruction::Instruction,
+};
+
+use crate::utils::{read_class_from_bytes, test_class_files};
+
+#[test_log::test]
+fn classes_can_be_serialized_and_deserialized() {
+    for path in test_class_files() {
+        let class = read_buffer(&fs::read(&path).unwrap()).unwrap();
+        let json = serde_json::to_string(&class).unwrap();
+        let deserialized: ClassFile = serde_json::from_str(&json)
+            .unwrap_or_else(|err| panic!("cannot deserialize {}: {err}", path.display()));
+        assert_eq!(
+            format!("{class:?}"),
+            format!("{deserialized:?}"),
+            "{} did not round trip",
+            path.display()
+        );
+    }
+}
+
+#[test_log::test]
+fn json_is_readable() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/Complex.class"));
+    let json = serde_json::to_value(&class).unwrap();
+
+    assert_eq!(json!("Jdk6"), json["version"]);
+    assert_eq!(json!(["PUBLIC", "SUPER"]), json["flags"]);
+    assert_eq!(json!("rjvm/Complex"), json["name"]);
+    assert_eq!(json!(["PRIVATE", "FINAL"]), json["fields"][0]["flags"]);
+    assert_eq!(
+        json!({ "Base": "Double" }),
+        json["fields"][0]["type_descriptor"]
+    );
+    assert_eq!(
+        json!({ "MethodReference": [6, 23] }),
+        json["constants"]["1"]
+    );
+}
+
+#[test_log::test]
+fn flags_and_instructions_can_be_deserialized() {
+    assert_eq!(
+        FieldFlags::PUBLIC | FieldFlags::STATIC,
+        serde_json::from_value::<FieldFlags>(json!(["STATIC", "PUBLIC"])).unwrap()
+    );
+    assert!(serde_json::from_value::<FieldFlags>(json!(["NATIVE"])).is_err());
+
+    let instructions = vec![Instruction::Aload_0, Instruction::Invokevirtual(7)];
+    let json = serde_json::to_value(&instructions).unwrap();
+    assert_eq!(json!(["Aload_0", { "Invokevirtual": 7 }]), json);
+    assert_eq!(
+        instructions,
+        serde_json::from_value::<Vec<Instruction>>(json).unwrap()
+    );
+}
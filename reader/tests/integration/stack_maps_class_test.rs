@@ -0,0 +1,130 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    class_file::ClassFile,
+    program_counter::ProgramCounter,
+    stack_map_table::{StackMapFrame, StackMapFrameKind, VerificationType},
+};
+
+use crate::utils;
+use utils::read_class_from_bytes;
+
+fn stack_map_table_of<'c>(
+    class: &'c ClassFile,
+    name: &str,
+    type_descriptor: &str,
+) -> &'c [StackMapFrame] {
+    &class
+        .methods
+        .iter()
+        .find(|m| m.name == name && m.type_descriptor == type_descriptor)
+        .unwrap_or_else(|| panic!("should find method {name}"))
+        .code
+        .as_ref()
+        .unwrap()
+        .stack_map_table
+}
+
+fn frame(pc: u16, kind: StackMapFrameKind) -> StackMapFrame {
+    StackMapFrame {
+        program_counter: ProgramCounter(pc),
+        kind,
+    }
+}
+
+#[test_log::test]
+fn can_read_frames_with_uninitialized_this() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/StackMaps.class"));
+    assert_eq!(
+        vec![
+            frame(
+                10,
+                StackMapFrameKind::SameLocals1StackItem(VerificationType::UninitializedThis)
+            ),
+            frame(
+                11,
+                StackMapFrameKind::Full {
+                    locals: vec![
+                        VerificationType::UninitializedThis,
+                        VerificationType::Integer
+                    ],
+                    stack: vec![
+                        VerificationType::UninitializedThis,
+                        VerificationType::Object("java/lang/Object".to_string())
+                    ],
+                }
+            ),
+        ],
+        stack_map_table_of(&class, "<init>", "(Z)V")
+    );
+    assert!(stack_map_table_of(&class, "<init>", "(Ljava/lang/Object;)V").is_empty());
+}
+
+#[test_log::test]
+fn can_read_append_and_chop_frames() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/StackMaps.class"));
+    assert_eq!(
+        vec![
+            frame(
+                4,
+                StackMapFrameKind::Append(vec![
+                    VerificationType::Integer,
+                    VerificationType::Integer
+                ])
+            ),
+            frame(26, StackMapFrameKind::Same),
+            frame(32, StackMapFrameKind::Chop(1)),
+        ],
+        stack_map_table_of(&class, "loops", "(I)I")
+    );
+}
+
+#[test_log::test]
+fn can_read_frames_with_uninitialized_objects() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/StackMaps.class"));
+    let uninitialized = VerificationType::Uninitialized(ProgramCounter(0));
+    assert_eq!(
+        vec![
+            frame(
+                13,
+                StackMapFrameKind::Full {
+                    locals: vec![VerificationType::Integer],
+                    stack: vec![uninitialized.clone(), uninitialized.clone()],
+                }
+            ),
+            frame(
+                15,
+                StackMapFrameKind::Full {
+                    locals: vec![VerificationType::Integer],
+                    stack: vec![
+                        uninitialized.clone(),
+                        uninitialized,
+                        VerificationType::Object("java/lang/Object".to_string())
+                    ],
+                }
+            ),
+        ],
+        stack_map_table_of(&class, "create", "(Z)Lrjvm/StackMaps;")
+    );
+}
+
+#[test_log::test]
+fn can_read_extended_frames() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/StackMaps.class"));
+    let frames = stack_map_table_of(&class, "extended", "(I)I");
+    assert_eq!(
+        vec![
+            frame(64, StackMapFrameKind::SameExtended),
+            frame(72, StackMapFrameKind::Same),
+            frame(
+                143,
+                StackMapFrameKind::SameLocals1StackItemExtended(VerificationType::Integer)
+            ),
+        ],
+        frames
+    );
+    assert_eq!(
+        "143: same_locals_1_stack_item_extended stack = [int]",
+        frames[2].to_string()
+    );
+}
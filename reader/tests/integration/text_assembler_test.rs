@@ -0,0 +1,61 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    class_reader::read_buffer, class_writer::write, instruction::Instruction,
+    text_assembler::assemble,
+};
+
+#[test_log::test]
+fn assembled_classes_can_be_written_and_read_back() {
+    let class = assemble(
+        r#"
+        .version 49
+        .source Subroutines.java
+        .class public super rjvm/Subroutines
+
+        .field private static final LIMIT I = 10
+
+        .method public static run(I)I
+            .limit stack 2
+            .limit locals 2
+            .line 5
+            jsr Subroutine
+            iload_0
+            ifeq Zero
+            getstatic rjvm/Subroutines/LIMIT I
+            ireturn
+        Zero:
+            iconst_0
+            ireturn
+        Subroutine:
+            astore_1
+            ret 1
+        .end method
+        "#,
+    )
+    .unwrap();
+    let code = class.methods[0].code.as_ref().unwrap();
+
+    let read_class = read_buffer(&write(&class).unwrap()).unwrap();
+    assert_eq!("rjvm/Subroutines", read_class.name);
+    assert_eq!(Some("Subroutines.java".to_string()), read_class.source_file);
+    assert_eq!(class.fields, read_class.fields);
+    let read_code = read_class.methods[0].code.as_ref().unwrap();
+    assert_eq!(code.code, read_code.code);
+    assert_eq!(code.max_locals, read_code.max_locals);
+    assert_eq!(code.line_number_table, read_code.line_number_table);
+    assert_eq!(
+        vec![
+            (0, Instruction::Jsr(13)),
+            (3, Instruction::Iload_0),
+            (4, Instruction::Ifeq(11)),
+            (7, Instruction::Getstatic(6)),
+            (10, Instruction::Ireturn),
+            (11, Instruction::Iconst_0),
+            (12, Instruction::Ireturn),
+            (13, Instruction::Astore_1),
+            (14, Instruction::Ret(1)),
+        ],
+        Instruction::parse_instructions(&code.code).unwrap()
+    );
+}
@@ -0,0 +1,180 @@
+extern crate rjvm_reader;
+
+use rjvm_reader::{
+    annotation::{Annotation, AnnotationElement, ElementValue},
+    method_parameter::MethodParameter,
+    method_parameter_flags::MethodParameterFlags,
+    program_counter::ProgramCounter,
+    type_annotation::{LocalVariableTarget, TypeAnnotation, TypeAnnotationTarget, TypePathElement},
+};
+
+use crate::utils;
+use utils::read_class_from_bytes;
+
+fn tag(value: i32) -> Annotation {
+    Annotation {
+        type_descriptor: "Lrjvm/Tag;".to_string(),
+        runtime_visible: false,
+        elements: vec![AnnotationElement {
+            name: "value".to_string(),
+            value: ElementValue::Int(value),
+        }],
+    }
+}
+
+fn visible() -> Annotation {
+    Annotation {
+        type_descriptor: "Lrjvm/Visible;".to_string(),
+        runtime_visible: true,
+        elements: vec![],
+    }
+}
+
+fn type_annotation(
+    target: TypeAnnotationTarget,
+    type_path: Vec<TypePathElement>,
+    annotation: Annotation,
+) -> TypeAnnotation {
+    TypeAnnotation {
+        target,
+        type_path,
+        annotation,
+    }
+}
+
+#[test_log::test]
+fn can_read_method_parameters() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/TypeAnnotations.class"));
+
+    let method = class.methods.iter().find(|m| m.name == "method").unwrap();
+    assert_eq!(
+        vec![
+            MethodParameter {
+                name: Some("count".to_string()),
+                flags: MethodParameterFlags::FINAL,
+            },
+            MethodParameter {
+                name: Some("name".to_string()),
+                flags: MethodParameterFlags::empty(),
+            },
+        ],
+        method.parameters
+    );
+
+    let constructor = class.methods.iter().find(|m| m.name == "<init>").unwrap();
+    assert_eq!(Vec::<MethodParameter>::new(), constructor.parameters);
+}
+
+#[test_log::test]
+fn can_read_class_and_field_type_annotations() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/TypeAnnotations.class"));
+    assert_eq!(
+        vec![
+            type_annotation(
+                TypeAnnotationTarget::ClassExtends {
+                    interface_index: Some(0)
+                },
+                vec![],
+                visible()
+            ),
+            type_annotation(
+                TypeAnnotationTarget::ClassExtends {
+                    interface_index: Some(0)
+                },
+                vec![TypePathElement::TypeArgument(0)],
+                tag(2)
+            ),
+            type_annotation(
+                TypeAnnotationTarget::ClassTypeParameter {
+                    type_parameter_index: 0
+                },
+                vec![],
+                tag(1)
+            ),
+        ],
+        class.type_annotations
+    );
+
+    let array = class.fields.iter().find(|f| f.name == "array").unwrap();
+    assert_eq!(
+        vec![
+            type_annotation(TypeAnnotationTarget::Field, vec![], visible()),
+            type_annotation(
+                TypeAnnotationTarget::Field,
+                vec![TypePathElement::Array],
+                tag(3)
+            ),
+        ],
+        array.type_annotations
+    );
+
+    let map = class.fields.iter().find(|f| f.name == "map").unwrap();
+    assert_eq!(
+        "@rjvm/Visible: FIELD, location=[TYPE_ARGUMENT(1), TYPE_ARGUMENT(0)]",
+        map.type_annotations[0].to_string()
+    );
+}
+
+#[test_log::test]
+fn can_read_method_and_code_type_annotations() {
+    let class = read_class_from_bytes(include_bytes!("../resources/rjvm/TypeAnnotations.class"));
+    let method = class.methods.iter().find(|m| m.name == "method").unwrap();
+    assert_eq!(
+        vec![
+            type_annotation(TypeAnnotationTarget::MethodReturn, vec![], visible()),
+            type_annotation(
+                TypeAnnotationTarget::Throws {
+                    throws_type_index: 0
+                },
+                vec![],
+                tag(6)
+            ),
+            type_annotation(
+                TypeAnnotationTarget::MethodFormalParameter {
+                    formal_parameter_index: 0
+                },
+                vec![],
+                tag(5)
+            ),
+        ],
+        method.type_annotations
+    );
+
+    let code = method.code.as_ref().unwrap();
+    assert_eq!(
+        vec![
+            type_annotation(
+                TypeAnnotationTarget::InstanceOf {
+                    offset: ProgramCounter(3)
+                },
+                vec![],
+                tag(8)
+            ),
+            type_annotation(
+                TypeAnnotationTarget::Cast {
+                    offset: ProgramCounter(10),
+                    type_argument_index: 0
+                },
+                vec![],
+                tag(9)
+            ),
+            type_annotation(
+                TypeAnnotationTarget::New {
+                    offset: ProgramCounter(14)
+                },
+                vec![],
+                tag(10)
+            ),
+            type_annotation(
+                TypeAnnotationTarget::LocalVariable(vec![LocalVariableTarget {
+                    range: ProgramCounter(2)..ProgramCounter(29),
+                    index: 3,
+                }]),
+                vec![],
+                tag(7)
+            ),
+        ],
+        code.type_annotations
+    );
+    assert_eq!(0x40, code.type_annotations[3].target.target_type());
+}
@@ -1,3 +1,8 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
 use log::info;
 
 use rjvm_reader::{class_file::ClassFile, class_reader};
@@ -7,3 +12,33 @@ pub fn read_class_from_bytes(bytes: &[u8]) -> ClassFile {
     info!("read class file: {}", class);
     class
 }
+
+fn class_files_in(directory: &Path) -> Vec<PathBuf> {
+    let mut class_files = Vec::new();
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            class_files.append(&mut class_files_in(&path));
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "class")
+        {
+            class_files.push(path);
+        }
+    }
+    class_files
+}
+
+/// All the class files used by the tests of both the reader and the vm
+pub fn test_class_files() -> Vec<PathBuf> {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let class_files: Vec<_> = [
+        manifest_dir.join("tests/resources"),
+        manifest_dir.join("../vm/tests/resources"),
+    ]
+    .iter()
+    .flat_map(|directory| class_files_in(directory))
+    .collect();
+    assert!(!class_files.is_empty());
+    class_files
+}
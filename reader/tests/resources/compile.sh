@@ -1,2 +1,14 @@
 #!/usr/bin/env sh
-javac -source 6 -target 6 rjvm/*.java
+javac -source 6 -target 6 rjvm/Complex.java rjvm/Constants.java rjvm/DeprecatedClass.java rjvm/ExceptionsHandlers.java
+javac --release 11 rjvm/Lambdas.java
+javac --release 8 rjvm/Nested.java
+javac --release 8 rjvm/Generics.java
+javac --release 8 rjvm/Annotations.java
+javac -g --release 8 rjvm/LocalVariables.java
+javac --release 8 rjvm/StackMaps.java
+javac -parameters --release 8 rjvm/TypeAnnotations.java
+javac --release 17 rjvm/Records.java rjvm/Shapes.java
+# The jar tool adds the ModulePackages and ModuleMainClass attributes to module-info.class
+cd module && javac --release 17 -d . module-info.java rjvm/module/*.java rjvm/module/*/*.java \
+  && jar --create --file module.jar --main-class rjvm.module.Main --module-version 1.0 -C . . \
+  && unzip -o -q module.jar module-info.class && rm module.jar
@@ -0,0 +1,14 @@
+module rjvm.module {
+    requires java.logging;
+    requires transitive java.sql;
+    requires static java.compiler;
+
+    exports rjvm.module.api;
+    exports rjvm.module.impl to java.base;
+
+    opens rjvm.module.impl;
+
+    uses rjvm.module.api.Service;
+
+    provides rjvm.module.api.Service with rjvm.module.impl.ServiceImpl;
+}
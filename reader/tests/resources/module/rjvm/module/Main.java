@@ -0,0 +1,6 @@
+package rjvm.module;
+
+public class Main {
+    public static void main(String[] args) {
+    }
+}
@@ -0,0 +1,5 @@
+package rjvm.module.api;
+
+public interface Service {
+    String name();
+}
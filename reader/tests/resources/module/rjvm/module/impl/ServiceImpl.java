@@ -0,0 +1,9 @@
+package rjvm.module.impl;
+
+import rjvm.module.api.Service;
+
+public class ServiceImpl implements Service {
+    public String name() {
+        return "impl";
+    }
+}
@@ -0,0 +1,61 @@
+package rjvm;
+
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+
+@Annotations.Marker
+@Annotations.Everything(
+        aByte = 1,
+        aChar = 'c',
+        aDouble = 2.5,
+        aFloat = 3.5f,
+        anInt = 4,
+        aLong = 5L,
+        aShort = 6,
+        aBoolean = true,
+        aString = "hello",
+        anEnum = ElementType.FIELD,
+        aClass = String.class,
+        anAnnotation = @Annotations.Marker,
+        anArray = {7, 8})
+public class Annotations {
+    @Retention(RetentionPolicy.RUNTIME)
+    public @interface Everything {
+        byte aByte();
+
+        char aChar();
+
+        double aDouble();
+
+        float aFloat();
+
+        int anInt();
+
+        long aLong();
+
+        short aShort();
+
+        boolean aBoolean();
+
+        String aString();
+
+        ElementType anEnum();
+
+        Class<?> aClass();
+
+        Marker anAnnotation();
+
+        int[] anArray() default {42};
+    }
+
+    public @interface Marker {
+    }
+
+    @Deprecated
+    public int field;
+
+    @Marker
+    public void method(@Deprecated int first, int second, @Marker String third) {
+    }
+}
@@ -0,0 +1,10 @@
+package rjvm;
+
+import java.util.List;
+import java.util.Map;
+
+public abstract class Generics<T extends Comparable<T>> implements Iterable<T> {
+    private List<Map<String, T>> values;
+
+    public abstract <E extends Exception> T find(List<? super T> candidates) throws E;
+}
@@ -0,0 +1,12 @@
+package rjvm;
+
+import java.util.function.Function;
+import java.util.function.Supplier;
+
+public class Lambdas {
+    public static void main(String[] args) {
+        Supplier<String> hello = () -> "hello";
+        Function<Integer, Integer> twice = x -> x * 2;
+        String text = hello.get() + twice.apply(21);
+    }
+}
@@ -0,0 +1,14 @@
+package rjvm;
+
+import java.util.ArrayList;
+import java.util.List;
+
+public class LocalVariables {
+    public static List<String> repeat(String value, long times) {
+        List<String> result = new ArrayList<>();
+        for (int count = 0; count < times; ++count) {
+            result.add(value);
+        }
+        return result;
+    }
+}
@@ -0,0 +1,24 @@
+package rjvm;
+
+public class Nested {
+    public class Inner {
+    }
+
+    protected interface NestedInterface {
+        void run();
+    }
+
+    public Object createLocal() {
+        class Local {
+        }
+        return new Local();
+    }
+
+    public NestedInterface createAnonymous() {
+        return new NestedInterface() {
+            @Override
+            public void run() {
+            }
+        };
+    }
+}
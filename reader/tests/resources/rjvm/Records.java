@@ -0,0 +1,20 @@
+package rjvm;
+
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.util.List;
+
+public class Records {
+    @Retention(RetentionPolicy.RUNTIME)
+    public @interface Marker {
+    }
+
+    public record Point(int x, @Marker int y) {
+    }
+
+    public record Box<T>(List<T> items) {
+    }
+
+    public record Empty() {
+    }
+}
@@ -0,0 +1,9 @@
+package rjvm;
+
+public sealed interface Shapes permits Shapes.Circle, Shapes.Square {
+    final class Circle implements Shapes {
+    }
+
+    non-sealed class Square implements Shapes {
+    }
+}
@@ -0,0 +1,51 @@
+package rjvm;
+
+public class StackMaps {
+    private final Object value;
+
+    public StackMaps(boolean flag) {
+        this(flag ? "yes" : null);
+    }
+
+    private StackMaps(Object value) {
+        this.value = value;
+    }
+
+    public static int loops(int limit) {
+        int sum = 0;
+        for (int i = 0; i < limit; ++i) {
+            long squared = (long) i * i;
+            if (squared > 100) {
+                sum += 2;
+            }
+        }
+        return sum;
+    }
+
+    public static StackMaps create(boolean flag) {
+        return new StackMaps(flag ? "a" : "b");
+    }
+
+    public static int fullFrame(int value) {
+        if (value > 0) {
+            int a = value;
+            double b = a;
+            String c = "x";
+            int d = 0;
+            if (b > d) {
+                return c.length();
+            }
+        }
+        return value;
+    }
+
+    public static int extended(int value) {
+        if (value > 0) {
+            value = value * 1000 + value * 2000 + value * 3000 + value * 4000 + value * 5000
+                    + value * 6000 + value * 7000 + value * 8000 + value * 9000 + value * 10000;
+        }
+        return value < 0 ? 0 : value * 1000 + value * 2000 + value * 3000 + value * 4000
+                + value * 5000 + value * 6000 + value * 7000 + value * 8000 + value * 9000
+                + value * 10000 + value * 11000 + value * 12000;
+    }
+}
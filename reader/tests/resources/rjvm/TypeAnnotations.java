@@ -0,0 +1,36 @@
+package rjvm;
+
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.util.List;
+import java.util.Map;
+
+@Target({ElementType.TYPE_USE, ElementType.TYPE_PARAMETER})
+@interface Tag {
+    int value();
+}
+
+@Retention(RetentionPolicy.RUNTIME)
+@Target(ElementType.TYPE_USE)
+@interface Visible {
+}
+
+public class TypeAnnotations<@Tag(1) T> implements @Visible Comparable<@Tag(2) T> {
+    public @Tag(3) String @Visible [] array;
+
+    public Map<@Tag(4) String, List<@Visible ? extends Number>> map;
+
+    public @Visible String method(final @Tag(5) int count, String name) throws @Tag(6) IllegalStateException {
+        @Tag(7) Object local = name;
+        if (local instanceof @Tag(8) String) {
+            return (@Tag(9) String) local;
+        }
+        return new @Tag(10) StringBuilder().append(count).toString();
+    }
+
+    public int compareTo(T other) {
+        return 0;
+    }
+}
@@ -263,7 +263,10 @@ unsafe fn write_value(ptr: *mut u8, value: Value) {
         Value::Long(long) => std::ptr::write(ptr as *mut i64, long),
         Value::Float(float) => std::ptr::write(ptr as *mut f32, float),
         Value::Double(double) => std::ptr::write(ptr as *mut f64, double),
-        Value::Uninitialized | Value::Null => std::ptr::write(ptr as *mut u64, 0),
+        // Return addresses can never be stored in fields or arrays, so we treat them as uninitialized
+        Value::Uninitialized | Value::Null | Value::ReturnAddress(_) => {
+            std::ptr::write(ptr as *mut u64, 0)
+        }
         Value::Object(obj) => std::ptr::write(ptr as *mut AbstractObject, obj),
     }
 }
@@ -5,8 +5,9 @@ use rjvm_reader::{
     class_file_method::ClassFileMethod,
     constant_pool::ConstantPoolEntry,
     field_type::{BaseType, FieldType, FieldType::Base},
-    instruction::{Instruction, NewArrayType},
+    instruction::{Instruction, NewArrayType, WideInstruction},
     line_number::LineNumber,
+    method_descriptor::MethodDescriptor,
     program_counter::ProgramCounter,
     type_conversion::ToUsizeSafe,
 };
@@ -16,12 +17,17 @@ use crate::{
     array::Array,
     array_entry_type::ArrayEntryType,
     call_frame::InstructionCompleted::{ContinueMethodExecution, ReturnFromMethod},
+    call_site::{get_method_handle, CallSite},
     call_stack::CallStack,
-    class::Class,
+    class::{Class, ClassRef},
     class_and_method::ClassAndMethod,
     class_resolver_by_id::ClassByIdResolver,
     exceptions::{JavaException, MethodCallFailed},
     java_objects_creation::{new_java_lang_class_object, new_java_lang_string_object},
+    method_handle,
+    method_handle::{
+        invoke_exact, is_signature_polymorphic, new_method_handle_object, new_method_type_object,
+    },
     object::Object,
     stack_trace_element::StackTraceElement,
     value::{
@@ -39,10 +45,10 @@ use crate::{
 pub type MethodCallResult<'a> = Result<Option<Value<'a>>, MethodCallFailed<'a>>;
 
 #[derive(Debug)]
-struct MethodReference<'a> {
-    class_name: &'a str,
-    method_name: &'a str,
-    type_descriptor: &'a str,
+pub(crate) struct MethodReference<'a> {
+    pub class_name: &'a str,
+    pub method_name: &'a str,
+    pub type_descriptor: &'a str,
 }
 
 #[derive(Debug)]
@@ -294,6 +300,22 @@ impl<'a> CallFrame<'a> {
         None
     }
 
+    /// Returns the name of the local variable in the given slot, if the class was compiled
+    /// with debug information and the variable is in scope at the current instruction
+    fn get_local_variable_name(&self, index: usize) -> Option<&'a str> {
+        let local_variable_table = self
+            .class_and_method
+            .method
+            .code
+            .as_ref()?
+            .local_variable_table
+            .as_ref()?;
+        let index = u16::try_from(index).ok()?;
+        local_variable_table
+            .lookup(self.pc, index)
+            .map(|entry| entry.name.as_str())
+    }
+
     /// Executes the whole method
     pub fn execute(
         &mut self,
@@ -335,7 +357,9 @@ impl<'a> CallFrame<'a> {
                             return Err(MethodCallFailed::ExceptionThrown(exception));
                         }
                         Ok(Some(catch_handler_pc)) => {
-                            // Re-push exception on the stack and continue execution of this method from the catch handler
+                            // The catch handler starts with only the exception on the stack,
+                            // then continues execution of this method
+                            self.stack.truncate(0)?;
                             self.stack.push(Value::Object(exception.0))?;
                             self.pc = catch_handler_pc
                         }
@@ -474,6 +498,9 @@ impl<'a> CallFrame<'a> {
             Instruction::Bipush(byte_value) => self.push(Int(byte_value as i32))?,
             Instruction::Sipush(short_value) => self.push(Int(short_value as i32))?,
 
+            Instruction::Invokedynamic(constant_index) => {
+                self.execute_invokedynamic(vm, call_stack, constant_index)?
+            }
             Instruction::Invokespecial(constant_index) => {
                 self.invoke_method(vm, call_stack, constant_index, InvokeKind::Special)?
             }
@@ -545,9 +572,7 @@ impl<'a> CallFrame<'a> {
             })?,
 
             Instruction::Iinc(index, constant) => {
-                let index = index.into_usize_safe();
-                let local = self.get_local_int_as_int(vm, index)?;
-                self.locals[index] = Int(local + constant as i32);
+                self.execute_iinc(vm, index.into_usize_safe(), constant as i32)?
             }
 
             Instruction::Ladd => self.execute_long_math(|a, b| Ok(a + b))?,
@@ -621,7 +646,22 @@ impl<'a> CallFrame<'a> {
             Instruction::Fneg => self.execute_fneg()?,
             Instruction::Dneg => self.execute_dneg()?,
 
-            Instruction::Goto(jump_address) => self.goto(jump_address),
+            Instruction::Goto(jump_address) | Instruction::Goto_w(jump_address) => {
+                self.goto(jump_address)
+            }
+            Instruction::Jsr(jump_address) | Instruction::Jsr_w(jump_address) => {
+                self.execute_jsr(jump_address)?
+            }
+            Instruction::Ret(index) => self.execute_ret(index.into_usize_safe())?,
+
+            Instruction::Tableswitch(table_switch) => {
+                let value = self.pop_int()?;
+                self.goto(table_switch.jump_address(value))
+            }
+            Instruction::Lookupswitch(lookup_switch) => {
+                let value = self.pop_int()?;
+                self.goto(lookup_switch.jump_address(value))
+            }
 
             Instruction::Ifeq(jump_address) => self.execute_if(jump_address, |v| v == 0)?,
             Instruction::Ifne(jump_address) => self.execute_if(jump_address, |v| v != 0)?,
@@ -691,16 +731,25 @@ impl<'a> CallFrame<'a> {
 
             Instruction::Athrow => self.execute_athrow()?,
 
+            Instruction::Wide(wide_instruction) => match wide_instruction {
+                WideInstruction::Aload(index) => self.execute_aload(index.into_usize_safe())?,
+                WideInstruction::Astore(index) => self.execute_astore(index.into_usize_safe())?,
+                WideInstruction::Dload(index) => self.execute_dload(index.into_usize_safe())?,
+                WideInstruction::Dstore(index) => self.execute_dstore(index.into_usize_safe())?,
+                WideInstruction::Fload(index) => self.execute_fload(index.into_usize_safe())?,
+                WideInstruction::Fstore(index) => self.execute_fstore(index.into_usize_safe())?,
+                WideInstruction::Iinc(index, constant) => {
+                    self.execute_iinc(vm, index.into_usize_safe(), constant as i32)?
+                }
+                WideInstruction::Iload(index) => self.execute_iload(index.into_usize_safe())?,
+                WideInstruction::Istore(index) => self.execute_istore(index.into_usize_safe())?,
+                WideInstruction::Lload(index) => self.execute_lload(index.into_usize_safe())?,
+                WideInstruction::Lstore(index) => self.execute_lstore(index.into_usize_safe())?,
+                WideInstruction::Ret(index) => self.execute_ret(index.into_usize_safe())?,
+            },
+
             /* Unimplemented instructions:
-            Instruction::Goto_w => {}
-            Instruction::Invokedynamic(_) => {}
-            Instruction::Jsr(_) => {}
-            Instruction::Jsr_w => {}
-            Instruction::Lookupswitch => {}
             Instruction::Multianewarray(_, _) => {}
-            Instruction::Ret(_) => {}
-            Instruction::Tableswitch => {}
-            Instruction::Wide => {}
             */
             Instruction::Nop => {}
 
@@ -798,6 +847,10 @@ impl<'a> CallFrame<'a> {
             let clone = vm.clone_array(array)?;
             return self.push(clone);
         }
+        if is_signature_polymorphic(&method_reference) {
+            let type_descriptor = method_reference.type_descriptor.to_string();
+            return self.invoke_method_handle(vm, call_stack, &type_descriptor);
+        }
 
         let static_method_reference =
             self.get_method_to_invoke_statically(vm, call_stack, method_reference, kind)?;
@@ -821,6 +874,103 @@ impl<'a> CallFrame<'a> {
         Ok(())
     }
 
+    /// Invokes `MethodHandle::invokeExact` or `MethodHandle::invoke`, whose arguments
+    /// are described by the descriptor at the call site
+    fn invoke_method_handle(
+        &mut self,
+        vm: &mut Vm<'a>,
+        call_stack: &mut CallStack<'a>,
+        type_descriptor: &str,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let descriptor =
+            MethodDescriptor::parse(type_descriptor).map_err(|_| VmError::ValidationException)?;
+        let cur_stack_len = self.stack.len();
+        let num_params = descriptor.num_arguments();
+        if cur_stack_len < num_params + 1 {
+            return Err(MethodCallFailed::InternalError(
+                VmError::ValidationException,
+            ));
+        }
+        let new_stack_len = cur_stack_len - num_params - 1;
+        let handle = match &self.stack[new_stack_len] {
+            Value::Object(handle) => handle.clone(),
+            Null => {
+                return Err(MethodCallFailed::InternalError(
+                    VmError::NullPointerException,
+                ))
+            }
+            _ => {
+                return Err(MethodCallFailed::InternalError(
+                    VmError::ValidationException,
+                ))
+            }
+        };
+        let params = Vec::from(&self.stack[new_stack_len + 1..cur_stack_len]);
+        self.stack.truncate(new_stack_len)?;
+
+        let result = invoke_exact(vm, call_stack, &handle, type_descriptor, params)?;
+        Self::validate_type_opt(vm, descriptor.return_type, &result)?;
+        if let Some(value) = result {
+            self.push(value)?;
+        }
+        Ok(())
+    }
+
+    fn execute_invokedynamic(
+        &mut self,
+        vm: &mut Vm<'a>,
+        call_stack: &mut CallStack<'a>,
+        constant_index: u16,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        let call_site =
+            vm.get_or_link_call_site(call_stack, self.class_and_method.class, constant_index)?;
+        match call_site {
+            CallSite::Lambda(lambda_class) => self.new_lambda(vm, lambda_class),
+            CallSite::MethodHandle(target) => {
+                let cur_stack_len = self.stack.len();
+                let num_params = target.num_arguments();
+                if cur_stack_len < num_params {
+                    return Err(MethodCallFailed::InternalError(
+                        VmError::ValidationException,
+                    ));
+                }
+                let new_stack_len = cur_stack_len - num_params;
+                let params = Vec::from(&self.stack[new_stack_len..cur_stack_len]);
+                self.stack.truncate(new_stack_len)?;
+
+                let result = method_handle::invoke(vm, call_stack, target, params)?;
+                if let Some(value) = result {
+                    self.push(value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn new_lambda(
+        &mut self,
+        vm: &mut Vm<'a>,
+        lambda_class: ClassRef<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        // The captured arguments are on the stack, and each one is stored in a field.
+        // We allocate the object _before_ popping them, so that they are still
+        // reachable if the allocation triggers a garbage collection.
+        let num_captured_arguments = lambda_class.fields.len();
+        let cur_stack_len = self.stack.len();
+        if cur_stack_len < num_captured_arguments {
+            return Err(MethodCallFailed::InternalError(
+                VmError::ValidationException,
+            ));
+        }
+        let lambda = vm.new_object_of_class(lambda_class);
+        let new_stack_len = cur_stack_len - num_captured_arguments;
+        for (index, value) in self.stack[new_stack_len..cur_stack_len].iter().enumerate() {
+            lambda.set_field(lambda_class.first_field_index + index, value.clone());
+        }
+        self.stack.truncate(new_stack_len)?;
+        self.push(Value::Object(lambda))
+    }
+
     fn get_field(
         class: &'a Class,
         field_reference: FieldReference,
@@ -975,7 +1125,7 @@ impl<'a> CallFrame<'a> {
             ))
     }
 
-    fn get_method_checking_superclasses<'b>(
+    pub(crate) fn get_method_checking_superclasses<'b>(
         class: &'b Class<'a>,
         method_reference: MethodReference,
     ) -> Result<ClassAndMethod<'b>, MethodCallFailed<'a>> {
@@ -1005,7 +1155,7 @@ impl<'a> CallFrame<'a> {
         }
     }
 
-    fn resolve_virtual_method(
+    pub(crate) fn resolve_virtual_method(
         vm: &Vm<'a>,
         receiver: Option<AbstractObject>,
         class_and_method: ClassAndMethod,
@@ -1075,7 +1225,7 @@ impl<'a> CallFrame<'a> {
     // Since in our implementation we do not "split" the numbers in two 32-bits parts,
     // we can just add an empty slot in the variable after a long or a double. All the
     // bytecode instructions will refer to the "first" value anyway.
-    fn fix_long_and_double_params(params: &mut Vec<Value>) -> Result<(), VmError> {
+    pub(crate) fn fix_long_and_double_params(params: &mut Vec<Value>) -> Result<(), VmError> {
         let mut num_params = params.len();
         let mut i = 0usize;
         while i < num_params {
@@ -1159,6 +1309,12 @@ impl<'a> CallFrame<'a> {
         }
     }
 
+    fn execute_iinc(&mut self, vm: &Vm, index: usize, constant: i32) -> Result<(), VmError> {
+        let local = self.get_local_int_as_int(vm, index)?;
+        self.locals[index] = Int(local.wrapping_add(constant));
+        Ok(())
+    }
+
     generate_execute_math!(execute_int_math, pop_int, Int, i32);
     generate_execute_math!(execute_long_math, pop_long, Long, i64);
     generate_execute_math!(execute_float_math, pop_float, Float, f32);
@@ -1195,6 +1351,24 @@ impl<'a> CallFrame<'a> {
         self.pc = ProgramCounter(jump_address);
     }
 
+    fn execute_jsr(&mut self, jump_address: u16) -> Result<(), MethodCallFailed<'a>> {
+        // The pc has already been moved to the instruction following the jsr
+        self.push(Value::ReturnAddress(self.pc.0))?;
+        self.goto(jump_address);
+        Ok(())
+    }
+
+    fn execute_ret(&mut self, index: usize) -> Result<(), VmError> {
+        let local = self.locals.get(index).ok_or(VmError::ValidationException)?;
+        match local {
+            Value::ReturnAddress(return_address) => {
+                self.goto(*return_address);
+                Ok(())
+            }
+            _ => Err(VmError::ValidationException),
+        }
+    }
+
     fn execute_if<T>(
         &mut self,
         jump_address: u16,
@@ -1312,7 +1486,7 @@ impl<'a> CallFrame<'a> {
     fn execute_astore(&mut self, index: usize) -> Result<(), MethodCallFailed<'a>> {
         let value = self.pop()?;
         match value {
-            Value::Object(..) => {
+            Value::Object(..) | Value::ReturnAddress(_) => {
                 self.locals[index] = value;
                 Ok(())
             }
@@ -1337,35 +1511,52 @@ impl<'a> CallFrame<'a> {
         call_stack: &mut CallStack<'a>,
         index: u16,
     ) -> Result<(), MethodCallFailed<'a>> {
-        let constant_value = self.get_constant(index)?;
+        let value =
+            Self::resolve_loadable_constant(vm, call_stack, self.class_and_method.class, index)?;
+        self.push(value)
+    }
+
+    /// Resolves a constant of the given class that can be loaded with `ldc`, or passed
+    /// as a static argument to a bootstrap method
+    pub(crate) fn resolve_loadable_constant(
+        vm: &mut Vm<'a>,
+        call_stack: &mut CallStack<'a>,
+        class: ClassRef<'a>,
+        index: u16,
+    ) -> Result<Value<'a>, MethodCallFailed<'a>> {
+        let get_utf8 = |index: u16| match class.constants.get(index) {
+            Ok(ConstantPoolEntry::Utf8(string)) => Ok(string.as_str()),
+            _ => Err(VmError::ValidationException),
+        };
+        let constant_value = class
+            .constants
+            .get(index)
+            .map_err(|_| VmError::ValidationException)?;
         match constant_value {
-            ConstantPoolEntry::Integer(value) => self.push(Int(*value)),
-            ConstantPoolEntry::Float(value) => self.push(Float(*value)),
+            ConstantPoolEntry::Integer(value) => Ok(Int(*value)),
+            ConstantPoolEntry::Float(value) => Ok(Float(*value)),
+            ConstantPoolEntry::Long(value) => Ok(Long(*value)),
+            ConstantPoolEntry::Double(value) => Ok(Double(*value)),
             ConstantPoolEntry::StringReference(string_index) => {
-                let constant = self.get_constant(*string_index)?;
-                match constant {
-                    ConstantPoolEntry::Utf8(string) => {
-                        let string_object = new_java_lang_string_object(vm, call_stack, string)?;
-                        self.push(Value::Object(string_object))
-                    }
-                    _ => Err(MethodCallFailed::InternalError(
-                        VmError::ValidationException,
-                    )),
-                }
+                let string = get_utf8(*string_index)?;
+                let string_object = new_java_lang_string_object(vm, call_stack, string)?;
+                Ok(Value::Object(string_object))
             }
             ConstantPoolEntry::ClassReference(class_index) => {
-                let constant = self.get_constant(*class_index)?;
-                match constant {
-                    ConstantPoolEntry::Utf8(class_name) => {
-                        let class_object = new_java_lang_class_object(vm, call_stack, class_name)?;
-                        self.push(Value::Object(class_object))
-                    }
-                    _ => Err(MethodCallFailed::InternalError(
-                        VmError::ValidationException,
-                    )),
-                }
+                let class_name = get_utf8(*class_index)?;
+                let class_object = new_java_lang_class_object(vm, call_stack, class_name)?;
+                Ok(Value::Object(class_object))
+            }
+            ConstantPoolEntry::MethodType(descriptor_index) => {
+                let descriptor = get_utf8(*descriptor_index)?;
+                let method_type = new_method_type_object(vm, call_stack, descriptor)?;
+                Ok(Value::Object(method_type))
+            }
+            ConstantPoolEntry::MethodHandle(_, _) => {
+                let reference = get_method_handle(&class.constants, index)?;
+                let method_handle = new_method_handle_object(vm, call_stack, &reference)?;
+                Ok(Value::Object(method_handle))
             }
-            // TODO: method type or method handle
             _ => Err(MethodCallFailed::InternalError(
                 VmError::ValidationException,
             )),
@@ -1770,8 +1961,11 @@ impl<'a> CallFrame<'a> {
             debug!("  - {:?}", stack_entry);
         }
         debug!("  locals:");
-        for local_variable in self.locals.iter() {
-            debug!("  - {:?}", local_variable);
+        for (index, local_variable) in self.locals.iter().enumerate() {
+            match self.get_local_variable_name(index) {
+                Some(name) => debug!("  - {} = {:?}", name, local_variable),
+                None => debug!("  - {:?}", local_variable),
+            }
         }
         debug!("  next instruction: {:?}", instruction)
     }
@@ -0,0 +1,347 @@
+use log::debug;
+
+use rjvm_reader::{
+    bootstrap_method::BootstrapMethod,
+    constant_pool::{ConstantPool, ConstantPoolEntry, MethodHandleKind},
+    field_type::FieldType,
+    method_descriptor::MethodDescriptor,
+};
+
+use crate::{
+    call_frame::CallFrame,
+    call_stack::CallStack,
+    class::ClassRef,
+    exceptions::{JavaException, MethodCallFailed},
+    java_objects_creation::new_java_lang_string_object,
+    lambda_metafactory::{spin_lambda_class, LambdaClassSpec},
+    method_handle::{
+        invoke_with_arguments, new_method_handle_object, new_method_type_object, MethodHandle,
+    },
+    value::Value,
+    vm::Vm,
+    vm_error::VmError,
+};
+
+/// A method handle constant, resolved from the constant pool
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MethodHandleReference<'c> {
+    pub kind: MethodHandleKind,
+    pub class_name: &'c str,
+    pub method_name: &'c str,
+    pub type_descriptor: &'c str,
+    /// Whether the method is declared in an interface
+    pub is_interface: bool,
+}
+
+/// A linked call site of an `invokedynamic` instruction.
+///
+/// Call sites are linked by invoking their bootstrap method, which returns a
+/// `java.lang.invoke.CallSite` whose target is invoked by every execution.
+/// The bootstrap methods of `LambdaMetafactory`, generated by javac for lambdas and
+/// method references, rely on the `java.lang.invoke` machinery of the JDK that we
+/// cannot execute. Thus, we link them natively, generating a class implementing the
+/// functional interface just like the real JVM does.
+#[derive(Debug, Clone)]
+pub(crate) enum CallSite<'a> {
+    /// The class implementing the functional interface. Every execution of the call site
+    /// creates a new instance, storing the captured arguments in its fields.
+    Lambda(ClassRef<'a>),
+
+    /// The target of the `CallSite` returned by the bootstrap method
+    MethodHandle(MethodHandle<'a>),
+}
+
+const LAMBDA_METAFACTORY: &str = "java/lang/invoke/LambdaMetafactory";
+
+// Flags of LambdaMetafactory::altMetafactory
+const FLAG_SERIALIZABLE: i32 = 1 << 0;
+const FLAG_MARKERS: i32 = 1 << 1;
+const FLAG_BRIDGES: i32 = 1 << 2;
+
+/// Links the call site of an `invokedynamic` instruction, given the index of
+/// its constant in the pool of `class`
+pub(crate) fn link_call_site<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    class: ClassRef<'a>,
+    constant_index: u16,
+) -> Result<CallSite<'a>, MethodCallFailed<'a>> {
+    let constants = &class.constants;
+    let (bootstrap_method_index, name_and_type_index) =
+        match get_constant(constants, constant_index)? {
+            ConstantPoolEntry::InvokeDynamic(bootstrap_method_index, name_and_type_index) => {
+                (*bootstrap_method_index, *name_and_type_index)
+            }
+            _ => {
+                return Err(MethodCallFailed::InternalError(
+                    VmError::ValidationException,
+                ))
+            }
+        };
+    let (method_name, call_site_descriptor) = get_name_and_type(constants, name_and_type_index)?;
+
+    let bootstrap_method = class
+        .bootstrap_methods
+        .get(bootstrap_method_index as usize)
+        .ok_or(VmError::ValidationException)?;
+    let bootstrap_method_handle = get_method_handle(constants, bootstrap_method.method_ref)?;
+    let is_alt_metafactory = match (
+        bootstrap_method_handle.class_name,
+        bootstrap_method_handle.method_name,
+    ) {
+        (LAMBDA_METAFACTORY, "metafactory") => false,
+        (LAMBDA_METAFACTORY, "altMetafactory") => true,
+        _ => {
+            return invoke_bootstrap_method(
+                vm,
+                call_stack,
+                class,
+                bootstrap_method,
+                method_name,
+                call_site_descriptor,
+            )
+        }
+    };
+    let call_site_descriptor =
+        MethodDescriptor::parse(call_site_descriptor).map_err(|_| VmError::ValidationException)?;
+
+    // The static arguments of both metafactories start with: the erased descriptor
+    // of the interface method, the implementation method, and the instantiated descriptor
+    let arguments = &bootstrap_method.arguments;
+    if arguments.len() < 3 {
+        return Err(MethodCallFailed::InternalError(
+            VmError::ValidationException,
+        ));
+    }
+    let interface_method_descriptor = get_method_type(constants, arguments[0])?;
+    let implementation = get_method_handle(constants, arguments[1])?;
+
+    let functional_interface = match call_site_descriptor.return_type {
+        Some(FieldType::Object(class_name)) => class_name,
+        _ => {
+            return Err(MethodCallFailed::InternalError(
+                VmError::ValidationException,
+            ))
+        }
+    };
+    let mut interfaces = vec![functional_interface];
+    let mut method_descriptors = vec![interface_method_descriptor];
+    if is_alt_metafactory {
+        parse_alt_metafactory_arguments(
+            constants,
+            &arguments[3..],
+            &mut interfaces,
+            &mut method_descriptors,
+        )?;
+    }
+
+    let spec = LambdaClassSpec {
+        class_name: format!("{}$$Lambda${}", class.name, constant_index),
+        interfaces,
+        method_name,
+        method_descriptors,
+        captured_types: call_site_descriptor.parameters,
+        implementation,
+    };
+    debug!(
+        "linking call site {constant_index} of {}: {spec:?}",
+        class.name
+    );
+    let class_file = spin_lambda_class(&spec)?;
+    let lambda_class = vm.define_class(call_stack, class_file)?;
+    Ok(CallSite::Lambda(lambda_class))
+}
+
+/// Invokes the bootstrap method with a lookup, the name and the method type of the call
+/// site, and the static arguments. Then, links the call site to the target of the
+/// returned `java.lang.invoke.CallSite`.
+fn invoke_bootstrap_method<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    class: ClassRef<'a>,
+    bootstrap_method: &BootstrapMethod,
+    method_name: &str,
+    call_site_descriptor: &str,
+) -> Result<CallSite<'a>, MethodCallFailed<'a>> {
+    let bootstrap_method_handle = new_method_handle_object(
+        vm,
+        call_stack,
+        &get_method_handle(&class.constants, bootstrap_method.method_ref)?,
+    )?;
+
+    // We do not implement access checks, so the lookup does not need any state
+    let lookup = vm.new_object(call_stack, "java/lang/invoke/MethodHandles$Lookup")?;
+    let name = new_java_lang_string_object(vm, call_stack, method_name)?;
+    let method_type = new_method_type_object(vm, call_stack, call_site_descriptor)?;
+    let mut args = vec![
+        Value::Object(lookup),
+        Value::Object(name),
+        Value::Object(method_type),
+    ];
+    for argument in bootstrap_method.arguments.iter() {
+        args.push(CallFrame::resolve_loadable_constant(
+            vm, call_stack, class, *argument,
+        )?);
+    }
+    let call_site = match invoke_with_arguments(vm, call_stack, &bootstrap_method_handle, args)? {
+        Some(Value::Object(call_site)) => call_site,
+        _ => {
+            return Err(bootstrap_method_error(
+                vm,
+                call_stack,
+                "the bootstrap method did not return a call site",
+            ))
+        }
+    };
+
+    let get_target = vm.resolve_class_method(
+        call_stack,
+        "java/lang/invoke/CallSite",
+        "getTarget",
+        "()Ljava/lang/invoke/MethodHandle;",
+    )?;
+    let get_target = CallFrame::resolve_virtual_method(vm, Some(call_site.clone()), get_target)?;
+    let target = match vm.invoke(call_stack, get_target, Some(call_site), Vec::new())? {
+        Some(Value::Object(target)) => vm
+            .get_method_handle_associated_with_object(&target)
+            .cloned()
+            .ok_or(VmError::ValidationException)?,
+        _ => {
+            return Err(MethodCallFailed::InternalError(
+                VmError::NullPointerException,
+            ))
+        }
+    };
+
+    let target_descriptor = target.type_descriptor();
+    if target_descriptor != call_site_descriptor {
+        return Err(bootstrap_method_error(
+            vm,
+            call_stack,
+            &format!(
+                "the type {target_descriptor} of the target does not match the call site type {call_site_descriptor}"
+            ),
+        ));
+    }
+    debug!(
+        "linked call site {method_name}{call_site_descriptor} of {} to {}::{}",
+        class.name, target.class_and_method.class.name, target.class_and_method.method.name
+    );
+    Ok(CallSite::MethodHandle(target))
+}
+
+fn bootstrap_method_error<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    message: &str,
+) -> MethodCallFailed<'a> {
+    match vm.new_exception(call_stack, "java/lang/BootstrapMethodError", message) {
+        Ok(exception) => MethodCallFailed::ExceptionThrown(JavaException(exception)),
+        Err(err) => err,
+    }
+}
+
+/// The additional arguments of `altMetafactory` are: an int with the flags,
+/// then optionally the marker interfaces and the bridges, each preceded by their count
+fn parse_alt_metafactory_arguments<'c>(
+    constants: &'c ConstantPool,
+    arguments: &[u16],
+    interfaces: &mut Vec<String>,
+    method_descriptors: &mut Vec<&'c str>,
+) -> Result<(), VmError> {
+    let mut arguments = arguments.iter();
+    let mut next_argument = || {
+        arguments
+            .next()
+            .copied()
+            .ok_or(VmError::ValidationException)
+    };
+
+    let flags = get_int(constants, next_argument()?)?;
+    if flags & FLAG_SERIALIZABLE != 0 {
+        interfaces.push("java/io/Serializable".to_string());
+    }
+    if flags & FLAG_MARKERS != 0 {
+        let count = get_int(constants, next_argument()?)?;
+        for _ in 0..count {
+            interfaces.push(get_class_name(constants, next_argument()?)?.to_string());
+        }
+    }
+    if flags & FLAG_BRIDGES != 0 {
+        let count = get_int(constants, next_argument()?)?;
+        for _ in 0..count {
+            method_descriptors.push(get_method_type(constants, next_argument()?)?);
+        }
+    }
+    Ok(())
+}
+
+fn get_constant(constants: &ConstantPool, index: u16) -> Result<&ConstantPoolEntry, VmError> {
+    constants
+        .get(index)
+        .map_err(|_| VmError::ValidationException)
+}
+
+fn get_utf8(constants: &ConstantPool, index: u16) -> Result<&str, VmError> {
+    match get_constant(constants, index)? {
+        ConstantPoolEntry::Utf8(string) => Ok(string),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+fn get_int(constants: &ConstantPool, index: u16) -> Result<i32, VmError> {
+    match get_constant(constants, index)? {
+        ConstantPoolEntry::Integer(value) => Ok(*value),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+fn get_class_name(constants: &ConstantPool, index: u16) -> Result<&str, VmError> {
+    match get_constant(constants, index)? {
+        ConstantPoolEntry::ClassReference(name_index) => get_utf8(constants, *name_index),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+fn get_name_and_type(constants: &ConstantPool, index: u16) -> Result<(&str, &str), VmError> {
+    match get_constant(constants, index)? {
+        ConstantPoolEntry::NameAndTypeDescriptor(name_index, type_descriptor_index) => Ok((
+            get_utf8(constants, *name_index)?,
+            get_utf8(constants, *type_descriptor_index)?,
+        )),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+fn get_method_type(constants: &ConstantPool, index: u16) -> Result<&str, VmError> {
+    match get_constant(constants, index)? {
+        ConstantPoolEntry::MethodType(descriptor_index) => get_utf8(constants, *descriptor_index),
+        _ => Err(VmError::ValidationException),
+    }
+}
+
+pub(crate) fn get_method_handle(
+    constants: &ConstantPool,
+    index: u16,
+) -> Result<MethodHandleReference<'_>, VmError> {
+    let (kind, reference_index) = match get_constant(constants, index)? {
+        ConstantPoolEntry::MethodHandle(kind, reference_index) => (*kind, *reference_index),
+        _ => return Err(VmError::ValidationException),
+    };
+    let (class_index, name_and_type_index, is_interface) =
+        match get_constant(constants, reference_index)? {
+            ConstantPoolEntry::MethodReference(c, n) => (*c, *n, false),
+            ConstantPoolEntry::InterfaceMethodReference(c, n) => (*c, *n, true),
+            ConstantPoolEntry::FieldReference(c, n) => (*c, *n, false),
+            _ => return Err(VmError::ValidationException),
+        };
+    let class_name = get_class_name(constants, class_index)?;
+    let (method_name, type_descriptor) = get_name_and_type(constants, name_and_type_index)?;
+    Ok(MethodHandleReference {
+        kind,
+        class_name,
+        method_name,
+        type_descriptor,
+        is_interface,
+    })
+}
@@ -1,8 +1,9 @@
 use std::{fmt, fmt::Formatter};
 
 use rjvm_reader::{
-    class_access_flags::ClassAccessFlags, class_file_field::ClassFileField,
-    class_file_method::ClassFileMethod, constant_pool::ConstantPool,
+    bootstrap_method::BootstrapMethod, class_access_flags::ClassAccessFlags,
+    class_file_field::ClassFileField, class_file_method::ClassFileMethod,
+    constant_pool::ConstantPool,
 };
 
 /// In various data structures, we store the class id of the object, i..e. a progressive
@@ -43,6 +44,8 @@ pub struct Class<'a> {
     pub interfaces: Vec<ClassRef<'a>>,
     pub fields: Vec<ClassFileField>,
     pub methods: Vec<ClassFileMethod>,
+    /// Used to link the call sites of `invokedynamic`
+    pub bootstrap_methods: Vec<BootstrapMethod>,
     // Base classes field have the same index they have in the base class, and our own
     // field come after. This is the index of the first "owned" field.
     // Note that this will include the static fields, as required by the bytecode specs.
@@ -4,13 +4,19 @@ use indexmap::IndexMap;
 use log::debug;
 use typed_arena::Arena;
 
-use rjvm_reader::{class_file::ClassFile, class_reader};
+use rjvm_reader::{
+    class_access_flags::ClassAccessFlags,
+    class_file::ClassFile,
+    class_reader,
+    frame_analysis::{ClassHierarchy, ClassHierarchyEntry},
+};
 
 use crate::{
     class::{Class, ClassId, ClassRef},
     class_loader::ClassLoader,
     class_path::{ClassPath, ClassPathParseError},
     class_resolver_by_id::ClassByIdResolver,
+    verifier,
     vm_error::VmError,
 };
 
@@ -26,6 +32,9 @@ pub(crate) struct ClassManager<'a> {
     /// Used to generate ClassId
     next_id: u32,
 
+    /// Hierarchy of the classes that the verifier needed, but that are not loaded
+    hierarchy_of_unloaded_classes: HashMap<String, ClassHierarchyEntry>,
+
     /// In a real implementation, we would have a current class loader for each thread,
     /// in a hierarchy. Currently, we only have exactly ONE global class loader.
     current_class_loader: ClassLoader<'a>,
@@ -39,6 +48,7 @@ impl<'a> Default for ClassManager<'a> {
             classes_by_name: Default::default(),
             arena: Arena::with_capacity(100),
             next_id: 1,
+            hierarchy_of_unloaded_classes: Default::default(),
             current_class_loader: Default::default(),
         }
     }
@@ -82,6 +92,33 @@ impl<'a> ClassByIdResolver<'a> for ClassManager<'a> {
     }
 }
 
+impl<'a> ClassHierarchy for ClassManager<'a> {
+    type Error = VmError;
+
+    fn lookup_class(&mut self, class_name: &str) -> Result<ClassHierarchyEntry, VmError> {
+        if let Some(class) = self.find_class_by_name(class_name) {
+            return Ok(ClassHierarchyEntry {
+                superclass: class.superclass.map(|superclass| superclass.name.clone()),
+                is_interface: class.flags.contains(ClassAccessFlags::INTERFACE),
+            });
+        }
+        if let Some(entry) = self.hierarchy_of_unloaded_classes.get(class_name) {
+            return Ok(entry.clone());
+        }
+
+        // We only need the superclass and the flags, so we just parse the class: loading it
+        // would also resolve and verify its whole hierarchy
+        let class_file = self.read_class_file(class_name)?;
+        let entry = ClassHierarchyEntry {
+            superclass: class_file.superclass,
+            is_interface: class_file.flags.contains(ClassAccessFlags::INTERFACE),
+        };
+        self.hierarchy_of_unloaded_classes
+            .insert(class_name.to_string(), entry.clone());
+        Ok(entry)
+    }
+}
+
 impl<'a> ClassManager<'a> {
     pub fn append_class_path(&mut self, class_path: &str) -> Result<(), ClassPathParseError> {
         self.class_path.push(class_path)
@@ -104,18 +141,44 @@ impl<'a> ClassManager<'a> {
         &mut self,
         class_name: &str,
     ) -> Result<ClassesToInitialize<'a>, VmError> {
+        let class_file = self.read_class_file(class_name)?;
+        let verify = !verifier::is_trusted_class(&class_file.name);
+        self.load_class(class_file, verify)
+    }
+
+    fn read_class_file(&self, class_name: &str) -> Result<ClassFile, VmError> {
         let class_file_bytes = self
             .class_path
             .resolve(class_name)
             .map_err(|err| VmError::ClassLoadingError(err.to_string()))?
             .ok_or(VmError::ClassNotFoundException(class_name.to_string()))?;
-        let class_file = class_reader::read_buffer(&class_file_bytes)
-            .map_err(|err| VmError::ClassLoadingError(err.to_string()))?;
-        self.load_class(class_file)
+        class_reader::read_buffer(&class_file_bytes)
+            .map_err(|err| VmError::ClassLoadingError(err.to_string()))
+    }
+
+    /// Loads a class that was not read from the class path, but rather generated at runtime
+    pub fn define_class(&mut self, class_file: ClassFile) -> Result<ResolvedClass<'a>, VmError> {
+        if self.find_class_by_name(&class_file.name).is_some() {
+            return Err(VmError::ClassLoadingError(format!(
+                "class {} is already loaded",
+                class_file.name
+            )));
+        }
+        // Classes generated by the vm itself are not verified. Note that lambda classes
+        // invoke the private methods of their host class, which the verifier would reject.
+        self.load_class(class_file, false)
+            .map(ResolvedClass::NewClass)
     }
 
-    fn load_class(&mut self, class_file: ClassFile) -> Result<ClassesToInitialize<'a>, VmError> {
+    fn load_class(
+        &mut self,
+        class_file: ClassFile,
+        verify: bool,
+    ) -> Result<ClassesToInitialize<'a>, VmError> {
         let referenced_classes = self.resolve_super_and_interfaces(&class_file)?;
+        if verify {
+            verifier::verify_class(&class_file, self)?;
+        }
         let loaded_class = self.allocate(class_file, referenced_classes)?;
         self.register_loaded_class(loaded_class.resolved_class);
         Ok(loaded_class)
@@ -224,6 +287,7 @@ impl<'a> ClassManager<'a> {
             interfaces,
             fields: class_file.fields,
             methods: class_file.methods,
+            bootstrap_methods: class_file.bootstrap_methods,
             num_total_fields: num_superclass_fields + num_this_class_fields,
             first_field_index: num_superclass_fields,
         })
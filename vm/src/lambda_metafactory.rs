@@ -0,0 +1,573 @@
+use rjvm_reader::{
+    class_access_flags::ClassAccessFlags,
+    class_file::ClassFile,
+    class_file_field::ClassFileField,
+    class_file_method::{ClassFileMethod, ClassFileMethodCode},
+    class_file_version::ClassFileVersion,
+    constant_pool::MethodHandleKind,
+    constant_pool_builder::ConstantPoolBuilder,
+    field_flags::FieldFlags,
+    field_type::{BaseType, FieldType},
+    instruction::{Instruction, WideInstruction},
+    method_descriptor::MethodDescriptor,
+    method_flags::MethodFlags,
+};
+
+use crate::{call_site::MethodHandleReference, vm_error::VmError};
+
+/// Describes the class that implements a lambda or a method reference, as requested
+/// by the arguments of the `LambdaMetafactory` bootstrap methods.
+#[derive(Debug)]
+pub(crate) struct LambdaClassSpec<'c> {
+    pub class_name: String,
+    /// The functional interface, followed by any marker interface
+    pub interfaces: Vec<String>,
+    /// The name of the functional interface's method
+    pub method_name: &'c str,
+    /// The erased descriptor of the functional interface's method, followed by
+    /// the descriptors of the bridges that should be generated
+    pub method_descriptors: Vec<&'c str>,
+    /// Types of the arguments captured at the call site
+    pub captured_types: Vec<FieldType>,
+    /// The method that implements the lambda
+    pub implementation: MethodHandleReference<'c>,
+}
+
+/// Generates the class that implements a functional interface, just like the real
+/// `LambdaMetafactory` does. The class stores each captured argument in a field, and
+/// implements the interface method by loading the captured arguments and the method
+/// parameters, adapting their types, and then invoking the implementation method.
+pub(crate) fn spin_lambda_class(spec: &LambdaClassSpec) -> Result<ClassFile, VmError> {
+    let mut constants = ConstantPoolBuilder::default();
+
+    let fields: Vec<ClassFileField> = spec
+        .captured_types
+        .iter()
+        .enumerate()
+        .map(|(index, captured_type)| ClassFileField {
+            flags: FieldFlags::PRIVATE | FieldFlags::FINAL,
+            name: captured_field_name(index),
+            type_descriptor: captured_type.clone(),
+            constant_value: None,
+            deprecated: false,
+            signature: None,
+            annotations: Vec::new(),
+            type_annotations: Vec::new(),
+            custom_attributes: Vec::new(),
+            attributes: Vec::new(),
+        })
+        .collect();
+
+    let methods = spec
+        .method_descriptors
+        .iter()
+        .enumerate()
+        .map(|(index, descriptor)| {
+            let flags = if index == 0 {
+                MethodFlags::PUBLIC
+            } else {
+                MethodFlags::PUBLIC | MethodFlags::BRIDGE | MethodFlags::SYNTHETIC
+            };
+            generate_method(spec, &mut constants, flags, descriptor)
+        })
+        .collect::<Result<Vec<ClassFileMethod>, VmError>>()?;
+
+    Ok(ClassFile {
+        version: ClassFileVersion::Jdk8,
+        constants: constants.build(),
+        flags: ClassAccessFlags::FINAL | ClassAccessFlags::SUPER | ClassAccessFlags::SYNTHETIC,
+        name: spec.class_name.clone(),
+        superclass: Some("java/lang/Object".to_string()),
+        interfaces: spec.interfaces.clone(),
+        fields,
+        methods,
+        ..Default::default()
+    })
+}
+
+fn captured_field_name(index: usize) -> String {
+    format!("arg${}", index + 1)
+}
+
+fn generate_method(
+    spec: &LambdaClassSpec,
+    constants: &mut ConstantPoolBuilder,
+    flags: MethodFlags,
+    descriptor: &str,
+) -> Result<ClassFileMethod, VmError> {
+    let parsed_descriptor =
+        MethodDescriptor::parse(descriptor).map_err(|_| VmError::ValidationException)?;
+    let implementation = &spec.implementation;
+    let implementation_descriptor = MethodDescriptor::parse(implementation.type_descriptor)
+        .map_err(|_| VmError::ValidationException)?;
+
+    // The receiver of instance methods is passed as the first argument
+    let mut target_types: Vec<FieldType> = Vec::new();
+    if matches!(
+        implementation.kind,
+        MethodHandleKind::InvokeVirtual
+            | MethodHandleKind::InvokeInterface
+            | MethodHandleKind::InvokeSpecial
+    ) {
+        target_types.push(FieldType::Object(implementation.class_name.to_string()));
+    }
+    target_types.extend(implementation_descriptor.parameters.iter().cloned());
+
+    let source_types: Vec<&FieldType> = spec
+        .captured_types
+        .iter()
+        .chain(parsed_descriptor.parameters.iter())
+        .collect();
+    if source_types.len() != target_types.len() {
+        return Err(VmError::ValidationException);
+    }
+
+    let mut code = CodeBuilder::new(constants);
+    if implementation.kind == MethodHandleKind::NewInvokeSpecial {
+        code.emit_new(implementation.class_name);
+    }
+
+    let this_class_name = spec.class_name.as_str();
+    for (index, captured_type) in spec.captured_types.iter().enumerate() {
+        code.emit_get_captured_field(this_class_name, &captured_field_name(index), captured_type);
+        code.emit_conversion(captured_type, &target_types[index])?;
+    }
+
+    let mut local_index = 1;
+    for (index, parameter_type) in parsed_descriptor.parameters.iter().enumerate() {
+        code.emit_load(parameter_type, local_index);
+        local_index += slots_of(parameter_type);
+        code.emit_conversion(
+            parameter_type,
+            &target_types[spec.captured_types.len() + index],
+        )?;
+    }
+
+    code.emit_invoke(implementation, &implementation_descriptor)?;
+
+    let implementation_return_type = match implementation.kind {
+        MethodHandleKind::NewInvokeSpecial => {
+            Some(FieldType::Object(implementation.class_name.to_string()))
+        }
+        _ => implementation_descriptor.return_type,
+    };
+    match (&implementation_return_type, &parsed_descriptor.return_type) {
+        (None, None) => code.emit(Instruction::Return),
+        (Some(implementation_return_type), None) => {
+            code.emit_pop(implementation_return_type);
+            code.emit(Instruction::Return);
+        }
+        (Some(implementation_return_type), Some(return_type)) => {
+            code.emit_conversion(implementation_return_type, return_type)?;
+            code.emit_return(return_type);
+        }
+        (None, Some(_)) => return Err(VmError::ValidationException),
+    }
+
+    // A conservative estimate: each argument is at most two slots, both before and
+    // after the conversion, plus the room for `new` and `dup` and for the conversions
+    let max_stack = 4 + source_types
+        .iter()
+        .zip(target_types.iter())
+        .map(|(source, target)| slots_of(source).max(slots_of(target)))
+        .sum::<usize>();
+
+    Ok(ClassFileMethod {
+        flags,
+        name: spec.method_name.to_string(),
+        type_descriptor: descriptor.to_string(),
+        parsed_type_descriptor: parsed_descriptor,
+        attributes: Vec::new(),
+        code: Some(ClassFileMethodCode {
+            max_stack: max_stack as u16,
+            max_locals: local_index as u16,
+            code: code.build()?,
+            ..Default::default()
+        }),
+        deprecated: false,
+        thrown_exceptions: Vec::new(),
+        signature: None,
+        annotations: Vec::new(),
+        parameter_annotations: Vec::new(),
+        annotation_default: None,
+        parameters: Vec::new(),
+        type_annotations: Vec::new(),
+        custom_attributes: Vec::new(),
+    })
+}
+
+fn slots_of(field_type: &FieldType) -> usize {
+    match field_type {
+        FieldType::Base(BaseType::Long) | FieldType::Base(BaseType::Double) => 2,
+        _ => 1,
+    }
+}
+
+fn is_int_like(base_type: &BaseType) -> bool {
+    matches!(
+        base_type,
+        BaseType::Int | BaseType::Short | BaseType::Char | BaseType::Byte | BaseType::Boolean
+    )
+}
+
+/// Returns the wrapper class of a primitive type and the name of its unboxing method
+fn wrapper_of(base_type: &BaseType) -> (&'static str, &'static str) {
+    match base_type {
+        BaseType::Byte => ("java/lang/Byte", "byteValue"),
+        BaseType::Char => ("java/lang/Character", "charValue"),
+        BaseType::Double => ("java/lang/Double", "doubleValue"),
+        BaseType::Float => ("java/lang/Float", "floatValue"),
+        BaseType::Int => ("java/lang/Integer", "intValue"),
+        BaseType::Long => ("java/lang/Long", "longValue"),
+        BaseType::Short => ("java/lang/Short", "shortValue"),
+        BaseType::Boolean => ("java/lang/Boolean", "booleanValue"),
+    }
+}
+
+fn unwrapped_type_of(class_name: &str) -> Option<BaseType> {
+    match class_name {
+        "java/lang/Byte" => Some(BaseType::Byte),
+        "java/lang/Character" => Some(BaseType::Char),
+        "java/lang/Double" => Some(BaseType::Double),
+        "java/lang/Float" => Some(BaseType::Float),
+        "java/lang/Integer" => Some(BaseType::Int),
+        "java/lang/Long" => Some(BaseType::Long),
+        "java/lang/Short" => Some(BaseType::Short),
+        "java/lang/Boolean" => Some(BaseType::Boolean),
+        _ => None,
+    }
+}
+
+/// The short and the wide form of an instruction loading a local variable
+type LoadInstruction = (fn(u8) -> Instruction, fn(u16) -> WideInstruction);
+
+/// Emits the instructions of a method of the generated class
+struct CodeBuilder<'b> {
+    constants: &'b mut ConstantPoolBuilder,
+    instructions: Vec<Instruction>,
+}
+
+impl<'b> CodeBuilder<'b> {
+    fn new(constants: &'b mut ConstantPoolBuilder) -> Self {
+        Self {
+            constants,
+            instructions: Vec::new(),
+        }
+    }
+
+    fn emit(&mut self, instruction: Instruction) {
+        self.instructions.push(instruction);
+    }
+
+    /// Encodes the instructions into bytecode
+    fn build(self) -> Result<Vec<u8>, VmError> {
+        Instruction::encode_instructions(&self.instructions)
+            .map_err(|_| VmError::ValidationException)
+    }
+
+    fn emit_new(&mut self, class_name: &str) {
+        let class_index = self.constants.class(class_name);
+        self.emit(Instruction::New(class_index));
+        self.emit(Instruction::Dup);
+    }
+
+    fn emit_get_captured_field(&mut self, class_name: &str, name: &str, field_type: &FieldType) {
+        let field_index = self
+            .constants
+            .field(class_name, name, &field_type.descriptor());
+        self.emit(Instruction::Aload_0);
+        self.emit(Instruction::Getfield(field_index));
+    }
+
+    fn emit_load(&mut self, field_type: &FieldType, local_index: usize) {
+        let (short, wide): LoadInstruction = match field_type {
+            FieldType::Base(BaseType::Long) => (Instruction::Lload, WideInstruction::Lload),
+            FieldType::Base(BaseType::Float) => (Instruction::Fload, WideInstruction::Fload),
+            FieldType::Base(BaseType::Double) => (Instruction::Dload, WideInstruction::Dload),
+            FieldType::Base(_) => (Instruction::Iload, WideInstruction::Iload),
+            FieldType::Object(_) | FieldType::Array(_) => {
+                (Instruction::Aload, WideInstruction::Aload)
+            }
+        };
+        self.emit(match u8::try_from(local_index) {
+            Ok(local_index) => short(local_index),
+            Err(_) => Instruction::Wide(wide(local_index as u16)),
+        });
+    }
+
+    fn emit_pop(&mut self, field_type: &FieldType) {
+        self.emit(if slots_of(field_type) == 2 {
+            Instruction::Pop2
+        } else {
+            Instruction::Pop
+        });
+    }
+
+    fn emit_return(&mut self, field_type: &FieldType) {
+        self.emit(match field_type {
+            FieldType::Base(BaseType::Long) => Instruction::Lreturn,
+            FieldType::Base(BaseType::Float) => Instruction::Freturn,
+            FieldType::Base(BaseType::Double) => Instruction::Dreturn,
+            FieldType::Base(_) => Instruction::Ireturn,
+            FieldType::Object(_) | FieldType::Array(_) => Instruction::Areturn,
+        });
+    }
+
+    fn emit_invoke(
+        &mut self,
+        implementation: &MethodHandleReference,
+        implementation_descriptor: &MethodDescriptor,
+    ) -> Result<(), VmError> {
+        let method_name = match implementation.kind {
+            MethodHandleKind::NewInvokeSpecial => "<init>",
+            _ => implementation.method_name,
+        };
+        let method_index = self.constants.method(
+            implementation.class_name,
+            method_name,
+            implementation.type_descriptor,
+            implementation.is_interface,
+        );
+        let instruction = match implementation.kind {
+            MethodHandleKind::InvokeStatic => Instruction::Invokestatic(method_index),
+            MethodHandleKind::InvokeVirtual => Instruction::Invokevirtual(method_index),
+            MethodHandleKind::InvokeSpecial | MethodHandleKind::NewInvokeSpecial => {
+                Instruction::Invokespecial(method_index)
+            }
+            MethodHandleKind::InvokeInterface => {
+                let count = 1 + implementation_descriptor
+                    .parameters
+                    .iter()
+                    .map(slots_of)
+                    .sum::<usize>();
+                Instruction::Invokeinterface(method_index, count as u8)
+            }
+            // Field accessors cannot implement a lambda
+            MethodHandleKind::GetField
+            | MethodHandleKind::GetStatic
+            | MethodHandleKind::PutField
+            | MethodHandleKind::PutStatic => return Err(VmError::ValidationException),
+        };
+        self.emit(instruction);
+        Ok(())
+    }
+
+    /// Adapts the value on top of the stack from one type to another, applying widening,
+    /// boxing, unboxing, and casts where needed
+    fn emit_conversion(&mut self, from: &FieldType, to: &FieldType) -> Result<(), VmError> {
+        if from == to {
+            return Ok(());
+        }
+        match (from, to) {
+            (FieldType::Base(from), FieldType::Base(to)) => self.emit_widening(from, to),
+            (FieldType::Base(from), _) => {
+                self.emit_boxing(from);
+                Ok(())
+            }
+            (_, FieldType::Base(to)) => self.emit_unboxing(from, to),
+            (_, FieldType::Object(class_name)) => {
+                if class_name != "java/lang/Object" {
+                    let class_index = self.constants.class(class_name);
+                    self.emit(Instruction::Checkcast(class_index));
+                }
+                Ok(())
+            }
+            // TODO: our checkcast does not support arrays, so we trust the caller
+            (_, FieldType::Array(_)) => Ok(()),
+        }
+    }
+
+    fn emit_widening(&mut self, from: &BaseType, to: &BaseType) -> Result<(), VmError> {
+        let instruction = match (from, to) {
+            (from, to) if from == to => return Ok(()),
+            (from, to) if is_int_like(from) && is_int_like(to) => return Ok(()),
+            (from, BaseType::Long) if is_int_like(from) => Instruction::I2l,
+            (from, BaseType::Float) if is_int_like(from) => Instruction::I2f,
+            (from, BaseType::Double) if is_int_like(from) => Instruction::I2d,
+            (BaseType::Long, BaseType::Float) => Instruction::L2f,
+            (BaseType::Long, BaseType::Double) => Instruction::L2d,
+            (BaseType::Float, BaseType::Double) => Instruction::F2d,
+            _ => return Err(VmError::ValidationException),
+        };
+        self.emit(instruction);
+        Ok(())
+    }
+
+    fn emit_boxing(&mut self, base_type: &BaseType) {
+        let (wrapper_class, _) = wrapper_of(base_type);
+        let primitive_descriptor = FieldType::Base(base_type.clone()).descriptor();
+        let method_index = self.constants.method(
+            wrapper_class,
+            "valueOf",
+            &format!("({primitive_descriptor})L{wrapper_class};"),
+            false,
+        );
+        self.emit(Instruction::Invokestatic(method_index));
+    }
+
+    fn emit_unboxing(&mut self, from: &FieldType, to: &BaseType) -> Result<(), VmError> {
+        // If the source is a wrapper we unbox it and then widen the primitive,
+        // otherwise we need to cast it to the wrapper of the target type
+        let unboxed_type = match from {
+            FieldType::Object(class_name) => unwrapped_type_of(class_name),
+            _ => None,
+        };
+        let unboxed_type = match unboxed_type {
+            Some(unboxed_type) => unboxed_type,
+            None => {
+                let (wrapper_class, _) = wrapper_of(to);
+                let class_index = self.constants.class(wrapper_class);
+                self.emit(Instruction::Checkcast(class_index));
+                to.clone()
+            }
+        };
+
+        let (wrapper_class, unboxing_method) = wrapper_of(&unboxed_type);
+        let primitive_descriptor = FieldType::Base(unboxed_type.clone()).descriptor();
+        let method_index = self.constants.method(
+            wrapper_class,
+            unboxing_method,
+            &format!("(){primitive_descriptor}"),
+            false,
+        );
+        self.emit(Instruction::Invokevirtual(method_index));
+        self.emit_widening(&unboxed_type, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rjvm_reader::{
+        constant_pool::MethodHandleKind,
+        field_type::{BaseType, FieldType},
+        instruction::Instruction,
+    };
+
+    use crate::{
+        call_site::MethodHandleReference,
+        lambda_metafactory::{spin_lambda_class, LambdaClassSpec},
+    };
+
+    #[test]
+    fn can_spin_class_for_capturing_lambda() {
+        let spec = LambdaClassSpec {
+            class_name: "rjvm/Test$$Lambda$1".to_string(),
+            interfaces: vec!["rjvm/Test$Transformer".to_string()],
+            method_name: "transform",
+            method_descriptors: vec!["(Ljava/lang/Object;)Ljava/lang/Object;"],
+            captured_types: vec![FieldType::Base(BaseType::Long)],
+            implementation: MethodHandleReference {
+                kind: MethodHandleKind::InvokeStatic,
+                class_name: "rjvm/Test",
+                method_name: "lambda$main$0",
+                type_descriptor: "(JLjava/lang/Integer;)I",
+                is_interface: false,
+            },
+        };
+
+        let class_file = spin_lambda_class(&spec).unwrap();
+        assert_eq!("rjvm/Test$$Lambda$1", class_file.name);
+        assert_eq!(
+            vec!["rjvm/Test$Transformer".to_string()],
+            class_file.interfaces
+        );
+        assert_eq!(1, class_file.fields.len());
+        assert_eq!("arg$1", class_file.fields[0].name);
+        assert_eq!(1, class_file.methods.len());
+
+        let method = &class_file.methods[0];
+        assert_eq!("transform", method.name);
+        let code = method.code.as_ref().unwrap();
+        assert_eq!(2, code.max_locals);
+
+        let instructions: Vec<Instruction> = Instruction::parse_instructions(&code.code)
+            .unwrap()
+            .into_iter()
+            .map(|(_, instruction)| instruction)
+            .collect();
+        assert_eq!(
+            vec![
+                Instruction::Aload_0,
+                Instruction::Getfield(6),
+                Instruction::Aload(1),
+                Instruction::Checkcast(8),
+                Instruction::Invokestatic(14),
+                Instruction::Invokestatic(18),
+                Instruction::Areturn,
+            ],
+            instructions
+        );
+        assert_eq!(
+            "rjvm/Test$$Lambda$1.arg$1: J",
+            class_file.constants.text_of(6).unwrap()
+        );
+        assert_eq!(
+            "java/lang/Integer",
+            class_file.constants.text_of(8).unwrap()
+        );
+        assert_eq!(
+            "rjvm/Test.lambda$main$0: (JLjava/lang/Integer;)I",
+            class_file.constants.text_of(14).unwrap()
+        );
+        assert_eq!(
+            "java/lang/Integer.valueOf: (I)Ljava/lang/Integer;",
+            class_file.constants.text_of(18).unwrap()
+        );
+    }
+
+    #[test]
+    fn can_spin_class_for_constructor_reference() {
+        let spec = LambdaClassSpec {
+            class_name: "rjvm/Test$$Lambda$2".to_string(),
+            interfaces: vec!["java/lang/Runnable".to_string()],
+            method_name: "run",
+            method_descriptors: vec!["()V"],
+            captured_types: vec![],
+            implementation: MethodHandleReference {
+                kind: MethodHandleKind::NewInvokeSpecial,
+                class_name: "rjvm/Test",
+                method_name: "<init>",
+                type_descriptor: "()V",
+                is_interface: false,
+            },
+        };
+
+        let class_file = spin_lambda_class(&spec).unwrap();
+        let code = class_file.methods[0].code.as_ref().unwrap();
+        let instructions: Vec<Instruction> = Instruction::parse_instructions(&code.code)
+            .unwrap()
+            .into_iter()
+            .map(|(_, instruction)| instruction)
+            .collect();
+        assert_eq!(
+            vec![
+                Instruction::New(2),
+                Instruction::Dup,
+                Instruction::Invokespecial(6),
+                Instruction::Pop,
+                Instruction::Return,
+            ],
+            instructions
+        );
+    }
+
+    #[test]
+    fn cannot_spin_class_if_arguments_do_not_match() {
+        let spec = LambdaClassSpec {
+            class_name: "rjvm/Test$$Lambda$3".to_string(),
+            interfaces: vec!["java/lang/Runnable".to_string()],
+            method_name: "run",
+            method_descriptors: vec!["()V"],
+            captured_types: vec![],
+            implementation: MethodHandleReference {
+                kind: MethodHandleKind::InvokeStatic,
+                class_name: "rjvm/Test",
+                method_name: "lambda$main$0",
+                type_descriptor: "(I)V",
+                is_interface: false,
+            },
+        };
+
+        assert!(spin_lambda_class(&spec).is_err());
+    }
+}
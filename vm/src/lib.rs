@@ -3,12 +3,13 @@ pub mod alloc_entry;
 pub mod array;
 pub mod array_entry_type;
 mod call_frame;
+mod call_site;
 pub mod call_stack;
 pub mod class;
 pub mod class_and_method;
 mod class_loader;
 mod class_manager;
-mod class_path;
+pub mod class_path;
 mod class_path_entry;
 mod class_resolver_by_id;
 pub mod exceptions;
@@ -16,6 +17,8 @@ mod file_system_class_path_entry;
 mod gc;
 mod jar_file_class_path_entry;
 pub mod java_objects_creation;
+mod lambda_metafactory;
+mod method_handle;
 mod native_methods_impl;
 pub mod native_methods_registry;
 pub mod object;
@@ -23,5 +26,6 @@ pub mod stack_trace_element;
 mod time;
 pub mod value;
 mod value_stack;
+pub mod verifier;
 pub mod vm;
 pub mod vm_error;
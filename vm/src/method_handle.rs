@@ -0,0 +1,287 @@
+use log::warn;
+
+use rjvm_reader::{
+    constant_pool::MethodHandleKind,
+    field_type::{BaseType, FieldType},
+    method_descriptor::MethodDescriptor,
+};
+
+use crate::{
+    abstract_object::AbstractObject,
+    array::Array,
+    array_entry_type::ArrayEntryType,
+    call_frame::{CallFrame, MethodCallResult, MethodReference},
+    call_site::MethodHandleReference,
+    call_stack::CallStack,
+    class_and_method::ClassAndMethod,
+    exceptions::{JavaException, MethodCallFailed},
+    java_objects_creation::new_java_lang_class_object,
+    object::Object,
+    value::Value,
+    vm::Vm,
+    vm_error::VmError,
+};
+
+/// The target of an instance of `java.lang.invoke.MethodHandle`.
+///
+/// The JDK implements method handles with a lot of java code that relies on the internals
+/// of HotSpot, and that we cannot execute. Thus, we model them natively: the java objects
+/// are empty shells, except for their type, and the vm keeps track of what they refer to.
+/// Only handles to methods and constructors are supported; handles to fields are not.
+#[derive(Debug, Clone)]
+pub(crate) struct MethodHandle<'a> {
+    pub kind: MethodHandleKind,
+    pub class_and_method: ClassAndMethod<'a>,
+}
+
+impl<'a> MethodHandle<'a> {
+    /// The descriptor of the method type of the handle, which includes the receiver
+    /// for instance methods and returns the new object for constructors
+    pub fn type_descriptor(&self) -> String {
+        let class_name = &self.class_and_method.class.name;
+        let descriptor = &self.class_and_method.method.type_descriptor;
+        match self.kind {
+            MethodHandleKind::InvokeVirtual
+            | MethodHandleKind::InvokeSpecial
+            | MethodHandleKind::InvokeInterface => {
+                format!("(L{class_name};{}", &descriptor[1..])
+            }
+            MethodHandleKind::NewInvokeSpecial => {
+                let parameters_end = descriptor.find(')').unwrap_or(descriptor.len() - 1);
+                format!("{}L{class_name};", &descriptor[..=parameters_end])
+            }
+            _ => descriptor.clone(),
+        }
+    }
+
+    /// The number of parameters of the method type of the handle
+    pub fn num_arguments(&self) -> usize {
+        match self.kind {
+            MethodHandleKind::InvokeVirtual
+            | MethodHandleKind::InvokeSpecial
+            | MethodHandleKind::InvokeInterface => self.class_and_method.num_arguments() + 1,
+            _ => self.class_and_method.num_arguments(),
+        }
+    }
+}
+
+/// The classes of `java.lang.invoke` have static initializers that set up the JDK
+/// implementation of method handles. Since we replace it, we do not execute them.
+pub(crate) fn skips_static_initializer(class_name: &str) -> bool {
+    class_name.starts_with("java/lang/invoke/")
+}
+
+/// Returns whether the method is one of the signature polymorphic methods of
+/// `MethodHandle`, whose arguments are given by the descriptor at the call site
+/// rather than by the declaration
+pub(crate) fn is_signature_polymorphic(method_reference: &MethodReference) -> bool {
+    method_reference.class_name == "java/lang/invoke/MethodHandle"
+        && matches!(method_reference.method_name, "invoke" | "invokeExact")
+}
+
+/// Creates a new instance of `java.lang.invoke.MethodType` with the given descriptor.
+/// Like in the JDK, it stores the return type in the field `rtype` and the parameter
+/// types in the field `ptypes`.
+pub(crate) fn new_method_type_object<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    type_descriptor: &str,
+) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+    let descriptor =
+        MethodDescriptor::parse(type_descriptor).map_err(|_| VmError::ValidationException)?;
+
+    let return_type = match &descriptor.return_type {
+        Some(return_type) => java_class_name(return_type),
+        None => "void".to_string(),
+    };
+    let return_type = new_java_lang_class_object(vm, call_stack, &return_type)?;
+    let class_class = vm.get_or_resolve_class(call_stack, "java/lang/Class")?;
+    let parameter_types = vm.new_array(
+        ArrayEntryType::Object(class_class.id),
+        descriptor.parameters.len(),
+    );
+    for (index, parameter) in descriptor.parameters.iter().enumerate() {
+        let parameter_type =
+            new_java_lang_class_object(vm, call_stack, &java_class_name(parameter))?;
+        parameter_types.set_element(index, Value::Object(parameter_type))?;
+    }
+
+    let method_type = vm.new_object(call_stack, "java/lang/invoke/MethodType")?;
+    set_field_by_name(vm, &method_type, "rtype", Value::Object(return_type))?;
+    set_field_by_name(vm, &method_type, "ptypes", Value::Object(parameter_types))?;
+    Ok(method_type)
+}
+
+/// Creates a new instance of `java.lang.invoke.MethodHandle` that refers to the given
+/// method or constructor. Its field `type` contains its method type, as in the JDK.
+pub(crate) fn new_method_handle_object<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    reference: &MethodHandleReference,
+) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+    match reference.kind {
+        MethodHandleKind::InvokeVirtual
+        | MethodHandleKind::InvokeStatic
+        | MethodHandleKind::InvokeSpecial
+        | MethodHandleKind::NewInvokeSpecial
+        | MethodHandleKind::InvokeInterface => {}
+        _ => {
+            warn!(
+                "unsupported method handle {} to {}::{}",
+                reference.kind, reference.class_name, reference.method_name
+            );
+            return Err(MethodCallFailed::InternalError(VmError::NotImplemented));
+        }
+    }
+    let class = vm.get_or_resolve_class(call_stack, reference.class_name)?;
+    let class_and_method = CallFrame::get_method_checking_superclasses(
+        class,
+        MethodReference {
+            class_name: reference.class_name,
+            method_name: reference.method_name,
+            type_descriptor: reference.type_descriptor,
+        },
+    )?;
+    let method_handle = MethodHandle {
+        kind: reference.kind,
+        class_and_method,
+    };
+
+    let method_type = new_method_type_object(vm, call_stack, &method_handle.type_descriptor())?;
+    let object = vm.new_object(call_stack, "java/lang/invoke/MethodHandle")?;
+    set_field_by_name(vm, &object, "type", Value::Object(method_type))?;
+    vm.associate_method_handle_with_object(object.clone(), method_handle);
+    Ok(object)
+}
+
+/// Implements `MethodHandle::invokeExact`, given the descriptor at the call site and
+/// one argument for each of its parameters. We do not implement the conversions of
+/// `MethodHandle::invoke`, so it is also required to match the type exactly.
+pub(crate) fn invoke_exact<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    handle: &AbstractObject<'a>,
+    type_descriptor: &str,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let method_handle = vm
+        .get_method_handle_associated_with_object(handle)
+        .cloned()
+        .ok_or(VmError::ValidationException)?;
+    let handle_type_descriptor = method_handle.type_descriptor();
+    if handle_type_descriptor != type_descriptor {
+        let exception = vm.new_exception(
+            call_stack,
+            "java/lang/invoke/WrongMethodTypeException",
+            &format!("expected {handle_type_descriptor} but found {type_descriptor}"),
+        )?;
+        return Err(MethodCallFailed::ExceptionThrown(JavaException(exception)));
+    }
+    invoke(vm, call_stack, method_handle, args)
+}
+
+/// Implements `MethodHandle::invokeWithArguments`, which we use to call bootstrap methods.
+/// As for `invokeExact`, we do not implement conversions of the arguments.
+pub(crate) fn invoke_with_arguments<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    handle: &AbstractObject<'a>,
+    args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let method_handle = vm
+        .get_method_handle_associated_with_object(handle)
+        .cloned()
+        .ok_or(VmError::ValidationException)?;
+    if method_handle.num_arguments() != args.len() {
+        let exception = vm.new_exception(
+            call_stack,
+            "java/lang/invoke/WrongMethodTypeException",
+            &format!(
+                "cannot invoke {} with {} arguments",
+                method_handle.type_descriptor(),
+                args.len()
+            ),
+        )?;
+        return Err(MethodCallFailed::ExceptionThrown(JavaException(exception)));
+    }
+    invoke(vm, call_stack, method_handle, args)
+}
+
+/// Invokes the target of a method handle, with one argument for each parameter of its type
+pub(crate) fn invoke<'a>(
+    vm: &mut Vm<'a>,
+    call_stack: &mut CallStack<'a>,
+    method_handle: MethodHandle<'a>,
+    mut args: Vec<Value<'a>>,
+) -> MethodCallResult<'a> {
+    let receiver = match method_handle.kind {
+        MethodHandleKind::InvokeStatic => None,
+        MethodHandleKind::NewInvokeSpecial => {
+            Some(vm.new_object_of_class(method_handle.class_and_method.class))
+        }
+        _ => {
+            if args.is_empty() {
+                return Err(MethodCallFailed::InternalError(
+                    VmError::ValidationException,
+                ));
+            }
+            match args.remove(0) {
+                Value::Object(receiver) => Some(receiver),
+                Value::Null => {
+                    return Err(MethodCallFailed::InternalError(
+                        VmError::NullPointerException,
+                    ))
+                }
+                _ => {
+                    return Err(MethodCallFailed::InternalError(
+                        VmError::ValidationException,
+                    ))
+                }
+            }
+        }
+    };
+    let class_and_method = match method_handle.kind {
+        MethodHandleKind::InvokeVirtual | MethodHandleKind::InvokeInterface => {
+            CallFrame::resolve_virtual_method(vm, receiver.clone(), method_handle.class_and_method)?
+        }
+        _ => method_handle.class_and_method,
+    };
+
+    CallFrame::fix_long_and_double_params(&mut args)?;
+    let result = vm.invoke(call_stack, class_and_method, receiver.clone(), args)?;
+    match method_handle.kind {
+        MethodHandleKind::NewInvokeSpecial => Ok(receiver.map(Value::Object)),
+        _ => Ok(result),
+    }
+}
+
+/// The name stored in the class object of a type: primitive types use their keyword,
+/// while classes use the same internal name as `ldc`
+fn java_class_name(field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Base(BaseType::Boolean) => "boolean".to_string(),
+        FieldType::Base(BaseType::Byte) => "byte".to_string(),
+        FieldType::Base(BaseType::Char) => "char".to_string(),
+        FieldType::Base(BaseType::Short) => "short".to_string(),
+        FieldType::Base(BaseType::Int) => "int".to_string(),
+        FieldType::Base(BaseType::Long) => "long".to_string(),
+        FieldType::Base(BaseType::Float) => "float".to_string(),
+        FieldType::Base(BaseType::Double) => "double".to_string(),
+        FieldType::Object(class_name) => class_name.clone(),
+        FieldType::Array(_) => field_type.descriptor(),
+    }
+}
+
+fn set_field_by_name<'a>(
+    vm: &Vm<'a>,
+    object: &AbstractObject<'a>,
+    field_name: &str,
+    value: Value<'a>,
+) -> Result<(), VmError> {
+    let class = vm.get_class_by_id(object.class_id())?;
+    let (index, _) = class.find_field(field_name).ok_or_else(|| {
+        VmError::FieldNotFoundException(class.name.clone(), field_name.to_string())
+    })?;
+    object.set_field(index, value);
+    Ok(())
+}
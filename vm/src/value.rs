@@ -37,7 +37,10 @@ pub enum Value<'a> {
 
     /// Models a null object
     Null,
-    // TODO: the JVM spec says we need to add return address, which are used to implement `finally`
+
+    /// Models a return address, pushed by `jsr` and consumed by `ret`.
+    /// Used by old compilers to implement `finally` blocks.
+    ReturnAddress(u16),
 }
 
 impl<'a> Value<'a> {
@@ -54,7 +57,7 @@ impl<'a> Value<'a> {
         ResByName: FnOnce(&str) -> Option<ClassRef<'b>>,
     {
         match self {
-            Value::Uninitialized => false,
+            Value::Uninitialized | Value::ReturnAddress(_) => false,
             Value::Int(_) => match expected_type {
                 FieldType::Base(base_type) => matches!(
                     base_type,
@@ -0,0 +1,892 @@
+//! Bytecode verifier, modelled after the one described in the JVM specs:
+//! https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html#jvms-4.10
+//!
+//! Methods of class files with version 50 (Java 6) or newer are type-checked against their
+//! `StackMapTable`, while for older class files the types are inferred by a data flow
+//! analysis, like the old HotSpot verifier did. As in HotSpot, version 50 class files that
+//! fail type checking are verified again with type inference.
+//!
+//! The effect of each instruction is simulated by the [FrameInterpreter] of the reader, the
+//! same engine used to compute the frames of the `StackMapTable`, with all its checks enabled.
+//!
+//! Methods using subroutines (`jsr` and `ret`) are verified by inferring the types separately
+//! for each chain of subroutine calls reaching an instruction, so that each subroutine is
+//! verified once for every call site, and `ret` always knows where it returns to.
+//!
+//! Some limitations, compared to a real JVM:
+//! - access to protected members is not checked;
+//! - all class loading is delegated to a [ClassHierarchy], so classes referenced by
+//!   the verified code must be reachable from the class path.
+
+use std::collections::{HashMap, VecDeque};
+
+use log::debug;
+
+use rjvm_reader::{
+    class_file::ClassFile,
+    class_file_method::{ClassFileMethod, ClassFileMethodCode},
+    class_file_version::ClassFileVersion,
+    control_flow_graph::ControlFlowGraph,
+    dataflow::{self, DataflowAnalysis},
+    frame_analysis::{
+        exception_frame, is_subroutine_instruction, ClassHierarchy, Frame, FrameChecks,
+        FrameInterpreter,
+    },
+    instruction::{Instruction, WideInstruction},
+    method_flags::MethodFlags,
+    program_counter::ProgramCounter,
+    stack_map_table::{StackMapFrameKind, VerificationType},
+};
+
+use crate::vm_error::VmError;
+
+/// Classes of the runtime library are not verified, just like HotSpot does by default
+/// for the classes loaded by the bootstrap class loader
+pub fn is_trusted_class(class_name: &str) -> bool {
+    ["java/", "javax/", "jdk/", "sun/", "com/sun/"]
+        .iter()
+        .any(|package| class_name.starts_with(package))
+}
+
+/// Verifies the code of all the methods of the given class, returning a
+/// [VmError::VerifyError] if any of them is invalid
+pub fn verify_class(
+    class_file: &ClassFile,
+    hierarchy: &mut dyn ClassHierarchy<Error = VmError>,
+) -> Result<(), VmError> {
+    debug!("verifying class {}", class_file.name);
+    for method in class_file.methods.iter() {
+        verify_method(class_file, method, hierarchy).map_err(|err| match err {
+            VmError::VerifyError(message) => VmError::VerifyError(format!(
+                "{}.{}{}: {}",
+                class_file.name, method.name, method.type_descriptor, message
+            )),
+            err => err,
+        })?;
+    }
+    Ok(())
+}
+
+fn verify_method(
+    class_file: &ClassFile,
+    method: &ClassFileMethod,
+    hierarchy: &mut dyn ClassHierarchy<Error = VmError>,
+) -> Result<(), VmError> {
+    let code = match &method.code {
+        Some(code) => code,
+        None if method
+            .flags
+            .intersects(MethodFlags::ABSTRACT | MethodFlags::NATIVE) =>
+        {
+            return Ok(())
+        }
+        None => return verify_error("non abstract and non native method without code"),
+    };
+    if method
+        .flags
+        .intersects(MethodFlags::ABSTRACT | MethodFlags::NATIVE)
+    {
+        return verify_error("abstract or native method with code");
+    }
+
+    let mut verifier = MethodVerifier::new(class_file, method, code, hierarchy)?;
+    if class_file.version >= ClassFileVersion::Jdk7 {
+        verifier.type_check()
+    } else if class_file.version == ClassFileVersion::Jdk6 {
+        verifier.type_check().or_else(|err| {
+            debug!("type checking failed with {err}, falling back to type inference");
+            verifier.infer_types()
+        })
+    } else {
+        verifier.infer_types()
+    }
+}
+
+fn verify_error<T>(message: impl Into<String>) -> Result<T, VmError> {
+    Err(VmError::VerifyError(message.into()))
+}
+
+/// Enables all the checks of the [FrameInterpreter], and enforces `max_stack`
+/// and `max_locals`
+struct VerifierChecks<'h> {
+    hierarchy: &'h mut dyn ClassHierarchy<Error = VmError>,
+    max_stack: usize,
+    max_locals: usize,
+}
+
+impl FrameChecks for VerifierChecks<'_> {
+    type Error = VmError;
+
+    fn invalid(&self, message: String) -> VmError {
+        VmError::VerifyError(message)
+    }
+
+    fn hierarchy(&mut self) -> &mut dyn ClassHierarchy<Error = VmError> {
+        self.hierarchy
+    }
+
+    fn checks_operand_types(&self) -> bool {
+        true
+    }
+
+    fn check_size(&mut self, frame: &Frame) -> Result<(), VmError> {
+        if frame.stack_size() > self.max_stack {
+            return verify_error("stack size exceeds max_stack");
+        }
+        if frame.locals.len() > self.max_locals {
+            return verify_error(format!(
+                "invalid local variable index {}",
+                frame.locals.len() - 1
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The subroutine calls being executed, as pairs of subroutine address and return address,
+/// with the innermost call last
+type SubroutineCalls = Vec<(usize, usize)>;
+
+/// The address of an instruction, along with the subroutine calls through which it
+/// is reached
+type Location = (usize, SubroutineCalls);
+
+struct MethodVerifier<'c, 'h> {
+    code: &'c ClassFileMethodCode,
+    interpreter: FrameInterpreter<'c, VerifierChecks<'h>>,
+}
+
+impl<'c, 'h> MethodVerifier<'c, 'h> {
+    fn new(
+        class_file: &'c ClassFile,
+        method: &'c ClassFileMethod,
+        code: &'c ClassFileMethodCode,
+        hierarchy: &'h mut dyn ClassHierarchy<Error = VmError>,
+    ) -> Result<Self, VmError> {
+        let checks = VerifierChecks {
+            hierarchy,
+            max_stack: code.max_stack as usize,
+            max_locals: code.max_locals as usize,
+        };
+        Ok(Self {
+            code,
+            interpreter: FrameInterpreter::new(class_file, method, checks)?,
+        })
+    }
+
+    fn uses_subroutines(&self) -> bool {
+        self.interpreter
+            .instructions()
+            .iter()
+            .any(|(_, instruction)| is_subroutine_instruction(instruction))
+    }
+
+    /// Creates a frame from a list of locals where `long` and `double` are a single entry,
+    /// padding the locals with `top` up to `max_locals`
+    fn expand_frame(
+        &self,
+        compact_locals: &[VerificationType],
+        stack: Vec<VerificationType>,
+    ) -> Result<Frame, VmError> {
+        let mut frame = Frame::from_compact_locals(compact_locals, stack);
+        if frame.locals.len() > self.code.max_locals as usize {
+            return verify_error(format!(
+                "{} locals are needed, but max_locals is {}",
+                frame.locals.len(),
+                self.code.max_locals
+            ));
+        }
+        frame
+            .locals
+            .resize(self.code.max_locals as usize, VerificationType::Top);
+        Ok(frame)
+    }
+
+    fn initial_frame(&self) -> Result<Frame, VmError> {
+        self.expand_frame(&self.interpreter.parameters_types(), Vec::new())
+    }
+
+    /// Computes the full frames declared in the `StackMapTable`, indexed by pc
+    fn declared_frames(&self) -> Result<HashMap<usize, Frame>, VmError> {
+        let mut frames = HashMap::new();
+        let mut compact_locals = self.interpreter.parameters_types();
+        for declared in self.code.stack_map_table.iter() {
+            let pc = declared.program_counter.0 as usize;
+            let stack = match &declared.kind {
+                StackMapFrameKind::Same | StackMapFrameKind::SameExtended => Vec::new(),
+                StackMapFrameKind::SameLocals1StackItem(item)
+                | StackMapFrameKind::SameLocals1StackItemExtended(item) => vec![item.clone()],
+                StackMapFrameKind::Chop(count) => {
+                    let count = *count as usize;
+                    if count > compact_locals.len() {
+                        return verify_error(format!(
+                            "cannot chop {count} locals in stack map frame at pc {pc}"
+                        ));
+                    }
+                    compact_locals.truncate(compact_locals.len() - count);
+                    Vec::new()
+                }
+                StackMapFrameKind::Append(locals) => {
+                    compact_locals.extend(locals.iter().cloned());
+                    Vec::new()
+                }
+                StackMapFrameKind::Full { locals, stack } => {
+                    compact_locals = locals.clone();
+                    stack.clone()
+                }
+            };
+
+            let frame = self.expand_frame(&compact_locals, stack)?;
+            if frame.stack_size() > self.code.max_stack as usize {
+                return verify_error(format!("stack map frame at pc {pc} exceeds max_stack"));
+            }
+            self.interpreter.instruction_index(pc)?;
+            frames.insert(pc, frame);
+        }
+        Ok(frames)
+    }
+
+    /// Verifies the method by checking each instruction against the frames declared in
+    /// the `StackMapTable`, in a single linear pass
+    fn type_check(&mut self) -> Result<(), VmError> {
+        if self.uses_subroutines() {
+            return verify_error("jsr and ret are not allowed in class files with stack maps");
+        }
+        let declared_frames = self.declared_frames()?;
+        self.check_exception_table()?;
+
+        let mut current = Some(self.initial_frame()?);
+        for index in 0..self.interpreter.instructions().len() {
+            let (pc, instruction) = self.interpreter.instructions()[index].clone();
+
+            if let Some(declared) = declared_frames.get(&pc) {
+                if let Some(current) = &current {
+                    self.check_frame_assignable(current, declared, pc)?;
+                }
+                current = Some(declared.clone());
+            }
+            let Some(frame) = current.take() else {
+                return verify_error(format!("expected a stack map frame at pc {pc}"));
+            };
+            current = self
+                .type_check_instruction(&declared_frames, frame, pc, &instruction)
+                .map_err(|err| with_pc(err, pc))?;
+        }
+
+        if current.is_some() {
+            verify_error("execution can fall off the end of the code")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks one instruction, as well as its exception handlers and its jump targets.
+    /// Returns the frame of the next instruction, if the execution can fall through.
+    fn type_check_instruction(
+        &mut self,
+        declared_frames: &HashMap<usize, Frame>,
+        frame: Frame,
+        pc: usize,
+        instruction: &Instruction,
+    ) -> Result<Option<Frame>, VmError> {
+        for (handler_pc, exception_frame) in self.exception_handlers(pc, &frame)? {
+            let Some(declared) = declared_frames.get(&handler_pc) else {
+                return verify_error(format!(
+                    "expected a stack map frame at exception handler pc {handler_pc}"
+                ));
+            };
+            self.check_frame_assignable(&exception_frame, declared, handler_pc)?;
+        }
+
+        let mut next = frame;
+        let successors = self.interpreter.execute(&mut next, pc, instruction)?;
+        for target in successors.jumps {
+            let target = target as usize;
+            self.interpreter.instruction_index(target)?;
+            let Some(declared) = declared_frames.get(&target) else {
+                return verify_error(format!(
+                    "expected a stack map frame at jump target pc {target}"
+                ));
+            };
+            self.check_frame_assignable(&next, declared, target)?;
+        }
+        Ok(successors.falls_through.then_some(next))
+    }
+
+    /// Verifies the method by inferring the types of each instruction with a data flow
+    /// analysis, merging the frames where different paths join
+    fn infer_types(&mut self) -> Result<(), VmError> {
+        self.check_exception_table()?;
+        if self.uses_subroutines() {
+            return self.infer_types_with_subroutines();
+        }
+
+        let graph = match ControlFlowGraph::build(self.code) {
+            Ok(graph) => graph,
+            Err(err) => return verify_error(err.to_string()),
+        };
+        let initial_frame = self.initial_frame()?;
+        dataflow::analyze(&graph, initial_frame, self)?;
+        Ok(())
+    }
+
+    /// Infers the types of a method using subroutines, as described in JVMS 4.10.2.4.
+    /// Instructions are analyzed separately for each chain of subroutine calls that reaches
+    /// them, so the frame at a `ret` only comes from the call it returns to.
+    fn infer_types_with_subroutines(&mut self) -> Result<(), VmError> {
+        let mut frames: HashMap<Location, Frame> = HashMap::new();
+        let mut worklist = VecDeque::new();
+        let entry = (0, SubroutineCalls::new());
+        frames.insert(entry.clone(), self.initial_frame()?);
+        worklist.push_back(entry);
+
+        while let Some(key) = worklist.pop_front() {
+            let (pc, calls) = &key;
+            let frame = frames[&key].clone();
+            let successors = self
+                .subroutine_successors(frame, *pc, calls)
+                .map_err(|err| with_pc(err, *pc))?;
+            for (target, frame) in successors {
+                let merged = match frames.get(&target) {
+                    None => frame,
+                    Some(old_frame) => {
+                        let merged = self
+                            .interpreter
+                            .merge_frames(old_frame, &frame)
+                            .map_err(|err| with_pc(err, target.0))?;
+                        if &merged == old_frame {
+                            continue;
+                        }
+                        merged
+                    }
+                };
+                frames.insert(target.clone(), merged);
+                if !worklist.contains(&target) {
+                    worklist.push_back(target);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes the instruction at the given pc, reached with the given subroutine calls.
+    /// Returns the frames with which its successors are reached.
+    fn subroutine_successors(
+        &mut self,
+        frame: Frame,
+        pc: usize,
+        calls: &SubroutineCalls,
+    ) -> Result<Vec<(Location, Frame)>, VmError> {
+        let mut successors: Vec<_> = self
+            .exception_handlers(pc, &frame)?
+            .into_iter()
+            .map(|(handler_pc, frame)| ((handler_pc, calls.clone()), frame))
+            .collect();
+
+        let index = self.interpreter.instruction_index(pc)?;
+        let instruction = self.interpreter.instructions()[index].1.clone();
+        let mut next = frame;
+        let executed = self.interpreter.execute(&mut next, pc, &instruction)?;
+        match instruction {
+            Instruction::Jsr(target) | Instruction::Jsr_w(target) => {
+                let target = target as usize;
+                if calls.iter().any(|(subroutine, _)| *subroutine == target) {
+                    return verify_error(format!(
+                        "recursive call to the subroutine at pc {target}"
+                    ));
+                }
+                let mut nested_calls = calls.clone();
+                nested_calls.push((target, self.interpreter.next_pc(pc)?));
+                successors.push(((target, nested_calls), next));
+            }
+            Instruction::Ret(index) => {
+                successors.push(self.return_from_subroutine(next, index as usize, calls)?)
+            }
+            Instruction::Wide(WideInstruction::Ret(index)) => {
+                successors.push(self.return_from_subroutine(next, index as usize, calls)?)
+            }
+            _ => {
+                if executed.falls_through {
+                    let next_pc = self.interpreter.next_pc(pc)?;
+                    if next_pc == self.code.code.len() {
+                        return verify_error("execution can fall off the end of the code");
+                    }
+                    successors.push(((next_pc, calls.clone()), next.clone()));
+                }
+                for target in executed.jumps {
+                    successors.push(((target as usize, calls.clone()), next.clone()));
+                }
+            }
+        }
+
+        for ((target, _), _) in successors.iter() {
+            self.interpreter.instruction_index(*target)?;
+        }
+        Ok(successors)
+    }
+
+    /// Returns from the subroutine whose return address is in the given local variable.
+    /// As the specs allow, this can also return from some of the enclosing subroutines.
+    fn return_from_subroutine(
+        &self,
+        frame: Frame,
+        index: usize,
+        calls: &SubroutineCalls,
+    ) -> Result<(Location, Frame), VmError> {
+        let Some(VerificationType::ReturnAddress(return_address)) = frame.locals.get(index) else {
+            return verify_error(format!(
+                "expected a return address in local variable {index}"
+            ));
+        };
+        let return_address = return_address.0 as usize;
+        let Some(call) = calls
+            .iter()
+            .rposition(|(_, caller_return_address)| *caller_return_address == return_address)
+        else {
+            return verify_error(format!(
+                "return address {return_address} does not belong to a subroutine being executed"
+            ));
+        };
+        Ok(((return_address, calls[..call].to_vec()), frame))
+    }
+
+    fn check_exception_table(&mut self) -> Result<(), VmError> {
+        let code_length = self.code.code.len();
+        for entry in self.code.exception_table.entries() {
+            let (start, end) = (entry.range.start.0 as usize, entry.range.end.0 as usize);
+            if start >= end {
+                return verify_error(format!("invalid exception table range {start}..{end}"));
+            }
+            self.interpreter.instruction_index(start)?;
+            if end != code_length {
+                self.interpreter.instruction_index(end)?;
+            }
+            self.interpreter
+                .instruction_index(entry.handler_pc.0 as usize)?;
+            if let Some(catch_class) = &entry.catch_class {
+                self.interpreter.check_assignable(
+                    &VerificationType::Object(catch_class.clone()),
+                    &VerificationType::Object("java/lang/Throwable".to_string()),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the frames with which the exception handlers covering the given pc
+    /// are reached, along with the handlers pc
+    fn exception_handlers(&self, pc: usize, frame: &Frame) -> Result<Vec<(usize, Frame)>, VmError> {
+        let Ok(pc) = u16::try_from(pc) else {
+            return verify_error(format!("invalid pc {pc}"));
+        };
+        Ok(self
+            .code
+            .exception_table
+            .lookup(ProgramCounter(pc))
+            .into_iter()
+            .map(|entry| {
+                (
+                    entry.handler_pc.0 as usize,
+                    exception_frame(frame, entry.catch_class.as_deref()),
+                )
+            })
+            .collect())
+    }
+
+    fn check_frame_assignable(
+        &mut self,
+        from: &Frame,
+        to: &Frame,
+        pc: usize,
+    ) -> Result<(), VmError> {
+        let assignable = from.stack.len() == to.stack.len()
+            && (!from.this_uninitialized || to.this_uninitialized)
+            && self.all_assignable(&from.locals, &to.locals)?
+            && self.all_assignable(&from.stack, &to.stack)?;
+        if assignable {
+            Ok(())
+        } else {
+            verify_error(format!(
+                "frame at pc {pc} is not assignable to the stack map frame: \
+                found locals {:?} and stack {:?}, expected locals {:?} and stack {:?}",
+                from.locals, from.stack, to.locals, to.stack
+            ))
+        }
+    }
+
+    fn all_assignable(
+        &mut self,
+        from: &[VerificationType],
+        to: &[VerificationType],
+    ) -> Result<bool, VmError> {
+        for (from, to) in from.iter().zip(to.iter()) {
+            if !self.interpreter.is_assignable(from, to)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<'c, 'h> DataflowAnalysis for MethodVerifier<'c, 'h> {
+    type State = Frame;
+    type Error = VmError;
+
+    fn transfer(
+        &mut self,
+        frame: &mut Frame,
+        pc: usize,
+        instruction: &Instruction,
+    ) -> Result<(), VmError> {
+        // The successors are already known from the control flow graph
+        self.interpreter
+            .execute(frame, pc, instruction)
+            .map_err(|err| with_pc(err, pc))?;
+        Ok(())
+    }
+
+    fn exception_state(
+        &mut self,
+        frame: &Frame,
+        catch_class: Option<&str>,
+    ) -> Result<Frame, VmError> {
+        Ok(exception_frame(frame, catch_class))
+    }
+
+    fn join(&mut self, pc: usize, first: &Frame, second: &Frame) -> Result<Frame, VmError> {
+        self.interpreter
+            .merge_frames(first, second)
+            .map_err(|err| with_pc(err, pc))
+    }
+}
+
+fn with_pc(err: VmError, pc: usize) -> VmError {
+    match err {
+        VmError::VerifyError(message) => VmError::VerifyError(format!("pc {pc}: {message}")),
+        err => err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rjvm_reader::{
+        class_file::ClassFile,
+        class_file_method::{ClassFileMethod, ClassFileMethodCode},
+        class_file_version::ClassFileVersion,
+        constant_pool::{ConstantPool, ConstantPoolEntry},
+        frame_analysis::{ClassHierarchy, ClassHierarchyEntry},
+        method_descriptor::MethodDescriptor,
+        method_flags::MethodFlags,
+        program_counter::ProgramCounter,
+        stack_map_table::{StackMapFrame, StackMapFrameKind, VerificationType},
+        text_assembler::assemble,
+    };
+
+    use crate::{verifier::verify_class, vm_error::VmError};
+
+    struct TestHierarchy(HashMap<&'static str, Option<&'static str>>);
+
+    impl ClassHierarchy for TestHierarchy {
+        type Error = VmError;
+
+        fn lookup_class(&mut self, class_name: &str) -> Result<ClassHierarchyEntry, VmError> {
+            match self.0.get(class_name) {
+                Some(superclass) => Ok(ClassHierarchyEntry {
+                    superclass: superclass.map(str::to_string),
+                    is_interface: false,
+                }),
+                None => Err(VmError::ClassNotFoundException(class_name.to_string())),
+            }
+        }
+    }
+
+    fn method(name: &str, descriptor: &str, code: Vec<u8>) -> ClassFileMethod {
+        ClassFileMethod {
+            flags: if name == "<init>" {
+                MethodFlags::PUBLIC
+            } else {
+                MethodFlags::PUBLIC | MethodFlags::STATIC
+            },
+            name: name.to_string(),
+            type_descriptor: descriptor.to_string(),
+            parsed_type_descriptor: MethodDescriptor::parse(descriptor).unwrap(),
+            attributes: Vec::new(),
+            code: Some(ClassFileMethodCode {
+                max_stack: 2,
+                max_locals: 2,
+                code,
+                ..Default::default()
+            }),
+            deprecated: false,
+            thrown_exceptions: Vec::new(),
+            signature: None,
+            annotations: Vec::new(),
+            parameter_annotations: Vec::new(),
+            annotation_default: None,
+            parameters: Vec::new(),
+            type_annotations: Vec::new(),
+            custom_attributes: Vec::new(),
+        }
+    }
+
+    fn with_stack_map_table(
+        mut method: ClassFileMethod,
+        frames: Vec<(u16, StackMapFrameKind)>,
+    ) -> ClassFileMethod {
+        method.code.as_mut().unwrap().stack_map_table = frames
+            .into_iter()
+            .map(|(pc, kind)| StackMapFrame {
+                program_counter: ProgramCounter(pc),
+                kind,
+            })
+            .collect();
+        method
+    }
+
+    fn verify(version: ClassFileVersion, method: ClassFileMethod) -> Result<(), VmError> {
+        let mut constants = ConstantPool::new();
+        constants.add(ConstantPoolEntry::Utf8("java/lang/Object".to_string()));
+        constants.add(ConstantPoolEntry::ClassReference(1));
+        constants.add(ConstantPoolEntry::Utf8("<init>".to_string()));
+        constants.add(ConstantPoolEntry::Utf8("()V".to_string()));
+        constants.add(ConstantPoolEntry::NameAndTypeDescriptor(3, 4));
+        constants.add(ConstantPoolEntry::MethodReference(2, 5));
+        let class_file = ClassFile {
+            version,
+            constants,
+            name: "rjvm/Test".to_string(),
+            superclass: Some("java/lang/Object".to_string()),
+            methods: vec![method],
+            ..Default::default()
+        };
+
+        let mut hierarchy = TestHierarchy(HashMap::from([
+            ("java/lang/Object", None),
+            ("java/lang/String", Some("java/lang/Object")),
+        ]));
+        verify_class(&class_file, &mut hierarchy)
+    }
+
+    fn assert_verify_error(result: Result<(), VmError>, expected_message: &str) {
+        match result {
+            Err(VmError::VerifyError(message)) => assert!(
+                message.contains(expected_message),
+                "unexpected message: {message}"
+            ),
+            result => panic!("expected a verify error, but got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_code() {
+        // iconst_1; ireturn
+        let method = method("one", "()I", vec![0x04, 0xac]);
+        assert_eq!(Ok(()), verify(ClassFileVersion::Jdk8, method));
+    }
+
+    #[test]
+    fn rejects_wrong_operand_types() {
+        // fconst_1; ireturn
+        let method = method("one", "()I", vec![0x0c, 0xac]);
+        assert_verify_error(
+            verify(ClassFileVersion::Jdk8, method),
+            "rjvm/Test.one()I: pc 1: expected int but found float",
+        );
+    }
+
+    #[test]
+    fn rejects_stack_underflow_and_overflow() {
+        // ireturn
+        let method_underflow = method("underflow", "()I", vec![0xac]);
+        assert_verify_error(
+            verify(ClassFileVersion::Jdk8, method_underflow),
+            "attempt to pop an empty stack",
+        );
+
+        // iconst_1; iconst_1; iconst_1; ireturn
+        let method_overflow = method("overflow", "()I", vec![0x04, 0x04, 0x04, 0xac]);
+        assert_verify_error(
+            verify(ClassFileVersion::Jdk8, method_overflow),
+            "pc 2: stack size exceeds max_stack",
+        );
+    }
+
+    #[test]
+    fn rejects_falling_off_the_end_of_the_code() {
+        // nop
+        let method = method("nop", "()V", vec![0x00]);
+        assert_verify_error(
+            verify(ClassFileVersion::Jdk8, method),
+            "execution can fall off the end of the code",
+        );
+    }
+
+    #[test]
+    fn branch_targets_must_have_a_stack_map_frame() {
+        // 0: iload_0; 1: ifeq 6; 4: iconst_1; 5: ireturn; 6: iconst_0; 7: ireturn
+        let code = vec![0x1a, 0x99, 0x00, 0x05, 0x04, 0xac, 0x03, 0xac];
+        let without_frames = method("choose", "(I)I", code.clone());
+        assert_verify_error(
+            verify(ClassFileVersion::Jdk8, without_frames),
+            "pc 1: expected a stack map frame at jump target pc 6",
+        );
+
+        let with_frames = with_stack_map_table(
+            method("choose", "(I)I", code.clone()),
+            vec![(6, StackMapFrameKind::Same)],
+        );
+        assert_eq!(Ok(()), verify(ClassFileVersion::Jdk8, with_frames));
+
+        let with_wrong_frames = with_stack_map_table(
+            method("choose", "(I)I", code),
+            vec![(
+                6,
+                StackMapFrameKind::SameLocals1StackItem(VerificationType::Integer),
+            )],
+        );
+        assert_verify_error(
+            verify(ClassFileVersion::Jdk8, with_wrong_frames),
+            "frame at pc 6 is not assignable to the stack map frame",
+        );
+    }
+
+    #[test]
+    fn old_class_files_are_verified_with_type_inference() {
+        // 0: iinc 0 -1; 3: iload_0; 4: ifne 0; 7: return
+        let code = vec![0x84, 0x00, 0xff, 0x1a, 0x9a, 0xff, 0xfc, 0xb1];
+        assert_eq!(
+            Ok(()),
+            verify(ClassFileVersion::Jdk1_4, method("loop", "(I)V", code))
+        );
+
+        // 0: iload_0; 1: ifeq 5; 4: iconst_1; 5: iconst_0; 6: ireturn
+        let code = vec![0x1a, 0x99, 0x00, 0x04, 0x04, 0x03, 0xac];
+        assert_verify_error(
+            verify(ClassFileVersion::Jdk1_4, method("merge", "(I)I", code)),
+            "pc 5: inconsistent stack height",
+        );
+    }
+
+    #[test]
+    fn constructors_must_invoke_the_superclass_constructor() {
+        // aload_0; invokespecial java/lang/Object.<init>()V; return
+        let code = vec![0x2a, 0xb7, 0x00, 0x06, 0xb1];
+        assert_eq!(
+            Ok(()),
+            verify(ClassFileVersion::Jdk8, method("<init>", "()V", code))
+        );
+
+        // return
+        assert_verify_error(
+            verify(ClassFileVersion::Jdk8, method("<init>", "()V", vec![0xb1])),
+            "constructor must call super() or this()",
+        );
+    }
+
+    #[test]
+    fn checks_assignability_with_the_class_hierarchy() {
+        // aload_0; areturn
+        let code = vec![0x2a, 0xb0];
+        assert_eq!(
+            Ok(()),
+            verify(
+                ClassFileVersion::Jdk8,
+                method(
+                    "widen",
+                    "(Ljava/lang/String;)Ljava/lang/Object;",
+                    code.clone()
+                )
+            )
+        );
+        assert_verify_error(
+            verify(
+                ClassFileVersion::Jdk8,
+                method("narrow", "(Ljava/lang/Object;)Ljava/lang/String;", code),
+            ),
+            "expected java/lang/String but found java/lang/Object",
+        );
+    }
+
+    #[test]
+    fn verifies_assembled_classes() {
+        let class_file = assemble(
+            r#"
+            .class public super rjvm/Assembled
+            .method public static max(II)I
+                .limit stack 2
+                iload_0
+                iload_1
+                if_icmpge First
+                iload_1
+                ireturn
+            First:
+                iload_0
+                ireturn
+            .end method
+            .method public static unbalanced(I)I
+                .limit stack 2
+                iload_0
+                ifeq Join
+                iconst_1
+            Join:
+                iconst_0
+                ireturn
+            .end method
+            "#,
+        )
+        .unwrap();
+
+        let mut hierarchy = TestHierarchy(HashMap::from([("java/lang/Object", None)]));
+        assert_verify_error(
+            verify_class(&class_file, &mut hierarchy),
+            "rjvm/Assembled.unbalanced(I)I: pc 5: inconsistent stack height",
+        );
+    }
+
+    #[test]
+    fn verifies_subroutines() {
+        let class_file = assemble(
+            r#"
+            .class public super rjvm/Subroutines
+            .method public static valid(I)I
+                .limit stack 1
+                .limit locals 3
+                iload_0
+                ifeq Zero
+                jsr Finally
+                iload_0
+                ireturn
+            Zero:
+                jsr Finally
+                iconst_1
+                ireturn
+            Finally:
+                astore_2
+                iinc 0 1
+                ret 2
+            .end method
+            .method public static clobbered()I
+                .limit stack 1
+                .limit locals 2
+                jsr Subroutine
+                iload_1
+                ireturn
+            Subroutine:
+                astore_0
+                fconst_1
+                fstore_1
+                ret 0
+            .end method
+            "#,
+        )
+        .unwrap();
+
+        let mut hierarchy = TestHierarchy(HashMap::from([("java/lang/Object", None)]));
+        assert_verify_error(
+            verify_class(&class_file, &mut hierarchy),
+            "rjvm/Subroutines.clobbered()I: pc 3: expected int in local variable 1 but found float",
+        );
+    }
+}
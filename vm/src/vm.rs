@@ -3,21 +3,24 @@ use std::{collections::HashMap, string::ToString};
 use log::{debug, error, info};
 use typed_arena::Arena;
 
-use rjvm_reader::type_conversion::ToUsizeSafe;
+use rjvm_reader::{class_file::ClassFile, type_conversion::ToUsizeSafe};
 
 use crate::{
     abstract_object::{AbstractObject, ObjectKind},
     array::Array,
     array_entry_type::ArrayEntryType,
     call_frame::MethodCallResult,
+    call_site::{link_call_site, CallSite},
     call_stack::CallStack,
     class::{ClassId, ClassRef},
     class_and_method::ClassAndMethod,
     class_manager::{ClassManager, ResolvedClass},
     class_path::ClassPathParseError,
     class_resolver_by_id::ClassByIdResolver,
-    exceptions::MethodCallFailed,
+    exceptions::{JavaException, MethodCallFailed},
     gc::ObjectAllocator,
+    java_objects_creation::new_java_lang_string_object,
+    method_handle::{skips_static_initializer, MethodHandle},
     native_methods_impl::array_copy,
     native_methods_registry::NativeMethodsRegistry,
     stack_trace_element::StackTraceElement,
@@ -53,6 +56,15 @@ pub struct Vm<'a> {
     /// clarity.
     throwable_call_stacks: HashMap<i32, Vec<StackTraceElement<'a>>>,
 
+    /// The linked call sites of `invokedynamic`, identified by the class and
+    /// the index of the constant used by the instruction
+    call_sites: HashMap<(ClassId, u16), CallSite<'a>>,
+
+    /// Stores the targets of the instances of `java.lang.invoke.MethodHandle`, which
+    /// we implement natively. Like for the stack traces of throwables, we have no
+    /// place to store them inside the actual objects.
+    method_handles: HashMap<i32, MethodHandle<'a>>,
+
     /// Since we do not have I/O, we have a fake native method that does a println.
     /// To check in the tests what the java bytecode printed, we store it here.
     pub printed: Vec<Value<'a>>,
@@ -79,6 +91,8 @@ impl<'a> Vm<'a> {
             statics: Default::default(),
             native_methods_registry: Default::default(),
             throwable_call_stacks: Default::default(),
+            call_sites: Default::default(),
+            method_handles: Default::default(),
             printed: Vec::new(),
         };
         crate::native_methods_impl::register_natives(&mut result.native_methods_registry);
@@ -98,13 +112,70 @@ impl<'a> Vm<'a> {
         stack: &mut CallStack<'a>,
         class_name: &str,
     ) -> Result<ClassRef<'a>, MethodCallFailed<'a>> {
-        let class = self.class_manager.get_or_resolve_class(class_name)?;
-        if let ResolvedClass::NewClass(classes_to_init) = &class {
+        let class = match self.class_manager.get_or_resolve_class(class_name) {
+            Ok(class) => class,
+            Err(err) => return Err(self.linkage_failure(stack, err)),
+        };
+        self.init_new_classes(stack, &class)?;
+        Ok(class.get_class())
+    }
+
+    /// Loads and initializes a class generated at runtime
+    pub(crate) fn define_class(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        class_file: ClassFile,
+    ) -> Result<ClassRef<'a>, MethodCallFailed<'a>> {
+        let class = self.class_manager.define_class(class_file)?;
+        self.init_new_classes(stack, &class)?;
+        Ok(class.get_class())
+    }
+
+    /// Classes that fail verification cause a `java.lang.VerifyError` to be thrown,
+    /// while other errors are internal errors of the vm
+    fn linkage_failure(&mut self, stack: &mut CallStack<'a>, err: VmError) -> MethodCallFailed<'a> {
+        match err {
+            VmError::VerifyError(message) => {
+                match self.new_exception(stack, "java/lang/VerifyError", &message) {
+                    Ok(exception) => MethodCallFailed::ExceptionThrown(JavaException(exception)),
+                    Err(err) => err,
+                }
+            }
+            err => MethodCallFailed::InternalError(err),
+        }
+    }
+
+    /// Creates an instance of the given exception class, with the given message
+    pub(crate) fn new_exception(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        class_name: &str,
+        message: &str,
+    ) -> Result<AbstractObject<'a>, MethodCallFailed<'a>> {
+        let exception = self.new_object(stack, class_name)?;
+        let message = new_java_lang_string_object(self, stack, message)?;
+        let constructor =
+            self.resolve_class_method(stack, class_name, "<init>", "(Ljava/lang/String;)V")?;
+        self.invoke(
+            stack,
+            constructor,
+            Some(exception.clone()),
+            vec![Value::Object(message)],
+        )?;
+        Ok(exception)
+    }
+
+    fn init_new_classes(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        class: &ResolvedClass<'a>,
+    ) -> Result<(), MethodCallFailed<'a>> {
+        if let ResolvedClass::NewClass(classes_to_init) = class {
             for class_to_init in classes_to_init.to_initialize.iter() {
                 self.init_class(stack, class_to_init)?;
             }
         }
-        Ok(class.get_class())
+        Ok(())
     }
 
     fn init_class(
@@ -115,6 +186,10 @@ impl<'a> Vm<'a> {
         debug!("creating static instance of {}", class_to_init.name);
         let static_instance = self.new_object_of_class(class_to_init);
         self.statics.insert(class_to_init.id, static_instance);
+        if skips_static_initializer(&class_to_init.name) {
+            debug!("skipping {}::<clinit>()", class_to_init.name);
+            return Ok(());
+        }
         if let Some(clinit_method) = class_to_init.find_method("<clinit>", "()V") {
             debug!("invoking {}::<clinit>()", class_to_init.name);
             self.invoke(
@@ -130,6 +205,22 @@ impl<'a> Vm<'a> {
         Ok(())
     }
 
+    /// Returns the call site of an `invokedynamic` instruction, linking it the first time
+    pub(crate) fn get_or_link_call_site(
+        &mut self,
+        stack: &mut CallStack<'a>,
+        class: ClassRef<'a>,
+        constant_index: u16,
+    ) -> Result<CallSite<'a>, MethodCallFailed<'a>> {
+        if let Some(call_site) = self.call_sites.get(&(class.id, constant_index)) {
+            return Ok(call_site.clone());
+        }
+        let call_site = link_call_site(self, stack, class, constant_index)?;
+        self.call_sites
+            .insert((class.id, constant_index), call_site.clone());
+        Ok(call_site)
+    }
+
     pub fn get_class_by_id(&self, class_id: ClassId) -> Result<ClassRef<'a>, VmError> {
         self.find_class_by_id(class_id)
             .ok_or(VmError::ValidationException)
@@ -290,6 +381,22 @@ impl<'a> Vm<'a> {
             .get(&throwable.identity_hash_code())
     }
 
+    pub(crate) fn associate_method_handle_with_object(
+        &mut self,
+        object: AbstractObject<'a>,
+        method_handle: MethodHandle<'a>,
+    ) {
+        self.method_handles
+            .insert(object.identity_hash_code(), method_handle);
+    }
+
+    pub(crate) fn get_method_handle_associated_with_object(
+        &self,
+        object: &AbstractObject<'a>,
+    ) -> Option<&MethodHandle<'a>> {
+        self.method_handles.get(&object.identity_hash_code())
+    }
+
     pub fn debug_stats(&self) {
         debug!(
             "VM classes={:?} allocator={:?}",
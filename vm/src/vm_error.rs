@@ -26,10 +26,15 @@ pub enum VmError {
 
     /// This is an overly generic error, abused to mean "something unexpected happened".
     /// It includes mostly errors that should be checked during the linking phase of the class file
-    /// (which we have not implemented).
+    /// but that the verifier does not cover.
     #[error("validation exception - invalid class file")]
     ValidationException,
 
+    /// The bytecode of a class failed verification. It is thrown to the java code
+    /// as a `java.lang.VerifyError`.
+    #[error("verify error: {0}")]
+    VerifyError(String),
+
     /// TODO: this should become throwing a real `java.lang.ArithmeticException`
     #[error("arithmetic exception")]
     ArithmeticException,
@@ -0,0 +1,401 @@
+use std::fs;
+
+use rjvm_reader::{class_writer::write, text_assembler::assemble};
+use rjvm_vm::{
+    exceptions::MethodCallFailed,
+    object::Object,
+    value::Value,
+    vm::{Vm, DEFAULT_MAX_MEMORY},
+};
+
+// This file tests classes assembled with the text assembler, for bytecode that javac
+// does not generate anymore
+
+/// Assembles the given sources into a directory of the temporary folder of the tests,
+/// and creates a vm whose class path includes it
+fn create_vm_with_classes(directory: &str, sources: &[&str]) -> Vm<'static> {
+    let classes_dir = format!("{}/{directory}", env!("CARGO_TARGET_TMPDIR"));
+    for source in sources {
+        let class = assemble(source).expect("source should be valid");
+        let path = format!("{classes_dir}/{}.class", class.name);
+        let parent = path.rsplit_once('/').expect("path has a directory").0;
+        fs::create_dir_all(parent).expect("should be able to create the classes directory");
+        fs::write(&path, write(&class).expect("class should be writable"))
+            .expect("should be able to write the class");
+    }
+
+    let mut vm = Vm::new(DEFAULT_MAX_MEMORY);
+    let src_dir = env!("CARGO_MANIFEST_DIR");
+    vm.append_class_path(&format!("{src_dir}/rt.jar:{classes_dir}"))
+        .expect("should be able to add entries to the classpath");
+    vm
+}
+
+fn invoke_static<'a>(
+    vm: &mut Vm<'a>,
+    class_name: &str,
+    method_name: &str,
+    descriptor: &str,
+    args: Vec<Value<'a>>,
+) -> Result<Option<Value<'a>>, MethodCallFailed<'a>> {
+    let call_stack = vm.allocate_call_stack();
+    let method = vm
+        .resolve_class_method(call_stack, class_name, method_name, descriptor)
+        .expect("should find method");
+    vm.invoke(call_stack, method, None, args)
+}
+
+/// A try/finally compiled the way javac did before Java 6, with the finally block in a
+/// subroutine. The subroutine is placed after enough padding that the jumps to it and
+/// over it need `jsr_w` and `goto_w`.
+fn subroutines_source() -> String {
+    format!(
+        r#"
+.version 49
+.class public super rjvm/Subroutines
+.super java/lang/Object
+
+.field private static finallyRuns I
+
+.method public static compute(I)I
+    .limit stack 2
+    .limit locals 4
+    .catch java/lang/IllegalArgumentException from Start to End using Catch
+    .catch all from Start to End using Any
+    iconst_0
+    istore_1
+Start:
+    iload_0
+    ifge Positive
+    new java/lang/IllegalArgumentException
+    dup
+    invokespecial java/lang/IllegalArgumentException/<init>()V
+    athrow
+Positive:
+    iload_0
+    iconst_2
+    imul
+    istore_1
+End:
+    jsr Finally
+    goto Done
+Catch:
+    pop
+    iconst_m1
+    istore_1
+    jsr Finally
+    goto Done
+Any:
+    astore_2
+    jsr Finally
+    aload_2
+    athrow
+{padding}
+Finally:
+    astore_3
+    iinc 1 100
+    getstatic rjvm/Subroutines/finallyRuns I
+    iconst_1
+    iadd
+    putstatic rjvm/Subroutines/finallyRuns I
+    ret 3
+Done:
+    iload_1
+    ireturn
+.end method
+
+.method public static finallyRuns()I
+    .limit stack 1
+    getstatic rjvm/Subroutines/finallyRuns I
+    ireturn
+.end method
+
+.method public static wideRet(I)I
+    .limit stack 1
+    .limit locals 301
+    jsr Subroutine
+    iload_0
+    ireturn
+Subroutine:
+    astore 300
+    iinc 0 1
+    ret 300
+.end method
+"#,
+        padding = "    nop\n".repeat(40_000)
+    )
+}
+
+#[test_log::test]
+fn subroutines_run_on_the_normal_and_exception_paths() {
+    let mut vm = create_vm_with_classes("subroutines", &[&subroutines_source()]);
+
+    let normal = invoke_static(
+        &mut vm,
+        "rjvm/Subroutines",
+        "compute",
+        "(I)I",
+        vec![Value::Int(5)],
+    );
+    assert_eq!(Ok(Some(Value::Int(110))), normal);
+    let exceptional = invoke_static(
+        &mut vm,
+        "rjvm/Subroutines",
+        "compute",
+        "(I)I",
+        vec![Value::Int(-1)],
+    );
+    assert_eq!(Ok(Some(Value::Int(99))), exceptional);
+    let finally_runs = invoke_static(&mut vm, "rjvm/Subroutines", "finallyRuns", "()I", vec![]);
+    assert_eq!(Ok(Some(Value::Int(2))), finally_runs);
+}
+
+#[test_log::test]
+fn wide_ret_returns_from_a_subroutine() {
+    let mut vm = create_vm_with_classes("subroutines_wide_ret", &[&subroutines_source()]);
+
+    let result = invoke_static(
+        &mut vm,
+        "rjvm/Subroutines",
+        "wideRet",
+        "(I)I",
+        vec![Value::Int(41)],
+    );
+    assert_eq!(Ok(Some(Value::Int(42))), result);
+}
+
+/// A class whose only method stores a float into an int field
+const BROKEN_SOURCE: &str = r#"
+.class public super rjvm/Broken
+.super java/lang/Object
+
+.field public value I
+
+.method public static set(Lrjvm/Broken;)V
+    .limit stack 2
+    aload_0
+    fconst_1
+    putfield rjvm/Broken/value I
+    return
+.end method
+"#;
+
+const MAIN_SOURCE: &str = r#"
+.class public super rjvm/Main
+.super java/lang/Object
+
+.method public static callBroken()I
+    .limit stack 1
+    .catch java/lang/VerifyError from Start to End using Handler
+Start:
+    aconst_null
+    invokestatic rjvm/Broken/set(Lrjvm/Broken;)V
+End:
+    iconst_0
+    ireturn
+Handler:
+    pop
+    iconst_1
+    ireturn
+.end method
+"#;
+
+#[test_log::test]
+fn classes_failing_verification_throw_verify_error_when_linked() {
+    let mut vm = create_vm_with_classes("verify_error", &[BROKEN_SOURCE, MAIN_SOURCE]);
+
+    let call_stack = vm.allocate_call_stack();
+    let exception = match vm.get_or_resolve_class(call_stack, "rjvm/Broken") {
+        Err(MethodCallFailed::ExceptionThrown(exception)) => exception,
+        result => panic!("expected a VerifyError, but got {:?}", result.err()),
+    };
+    let exception_class = vm
+        .get_class_by_id(exception.0.class_id())
+        .expect("exception class should be loaded");
+    assert_eq!("java/lang/VerifyError", exception_class.name);
+
+    let caught = invoke_static(&mut vm, "rjvm/Main", "callBroken", "()I", vec![]);
+    assert_eq!(Ok(Some(Value::Int(1))), caught);
+}
+
+const UNWIND_SOURCE: &str = r#"
+.class public super rjvm/Unwind
+.super java/lang/Object
+
+.method public static fail()V
+    .limit stack 2
+    new java/lang/RuntimeException
+    dup
+    invokespecial java/lang/RuntimeException/<init>()V
+    athrow
+.end method
+
+.method public static catchWithPendingOperands()I
+    .limit stack 2
+    .catch java/lang/RuntimeException from Start to End using Handler
+Start:
+    iconst_1
+    iconst_2
+    invokestatic rjvm/Unwind/fail()V
+End:
+    iadd
+    ireturn
+Handler:
+    pop
+    bipush 42
+    ireturn
+.end method
+"#;
+
+#[test_log::test]
+fn catch_handlers_start_with_only_the_exception_on_the_stack() {
+    let mut vm = create_vm_with_classes("unwind", &[UNWIND_SOURCE]);
+
+    let result = invoke_static(
+        &mut vm,
+        "rjvm/Unwind",
+        "catchWithPendingOperands",
+        "()I",
+        vec![],
+    );
+    assert_eq!(Ok(Some(Value::Int(42))), result);
+}
+
+const HANDLES_SOURCE: &str = r#"
+.class public super rjvm/Handles
+.super java/lang/Object
+
+.field private value I
+
+.method public <init>(I)V
+    aload_0
+    invokespecial java/lang/Object/<init>()V
+    aload_0
+    iload_1
+    putfield rjvm/Handles/value I
+    return
+.end method
+
+.method public getValue()I
+    aload_0
+    getfield rjvm/Handles/value I
+    ireturn
+.end method
+
+.method public static add(II)I
+    iload_0
+    iload_1
+    iadd
+    ireturn
+.end method
+
+.method public static parameterCount()I
+    ldc methodtype (IJLjava/lang/String;)V
+    invokevirtual java/lang/invoke/MethodType/parameterCount()I
+    ireturn
+.end method
+
+.method public static invokeStatic()I
+    ldc methodhandle invokestatic rjvm/Handles/add(II)I
+    iconst_1
+    iconst_2
+    invokevirtual java/lang/invoke/MethodHandle/invokeExact(II)I
+    ireturn
+.end method
+
+.method public static invokeConstructorAndVirtual()I
+    .limit locals 1
+    ldc methodhandle newinvokespecial rjvm/Handles/<init>(I)V
+    bipush 42
+    invokevirtual java/lang/invoke/MethodHandle/invokeExact(I)Lrjvm/Handles;
+    astore_0
+    ldc methodhandle invokevirtual rjvm/Handles/getValue()I
+    aload_0
+    invokevirtual java/lang/invoke/MethodHandle/invokeExact(Lrjvm/Handles;)I
+    ireturn
+.end method
+
+.method public static invokeWithWrongType()I
+    .catch java/lang/invoke/WrongMethodTypeException from Start to End using Handler
+Start:
+    ldc methodhandle invokestatic rjvm/Handles/add(II)I
+    lconst_1
+    lconst_1
+    invokevirtual java/lang/invoke/MethodHandle/invokeExact(JJ)J
+    l2i
+End:
+    ireturn
+Handler:
+    pop
+    iconst_m1
+    ireturn
+.end method
+"#;
+
+#[test_log::test]
+fn method_types_and_handles_can_be_loaded_and_invoked() {
+    let mut vm = create_vm_with_classes("method_handles", &[HANDLES_SOURCE]);
+
+    let mut invoke =
+        |method_name| invoke_static(&mut vm, "rjvm/Handles", method_name, "()I", vec![]);
+    assert_eq!(Ok(Some(Value::Int(3))), invoke("parameterCount"));
+    assert_eq!(Ok(Some(Value::Int(3))), invoke("invokeStatic"));
+    assert_eq!(
+        Ok(Some(Value::Int(42))),
+        invoke("invokeConstructorAndVirtual")
+    );
+    assert_eq!(Ok(Some(Value::Int(-1))), invoke("invokeWithWrongType"));
+}
+
+const DYNAMIC_SOURCE: &str = r#"
+.class public super rjvm/Dynamic
+.super java/lang/Object
+
+.method public static bootstrap(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;)Ljava/lang/invoke/CallSite;
+    .limit locals 4
+    new java/lang/invoke/ConstantCallSite
+    dup
+    aload_3
+    invokespecial java/lang/invoke/ConstantCallSite/<init>(Ljava/lang/invoke/MethodHandle;)V
+    areturn
+.end method
+
+.method public static add(II)I
+    iload_0
+    iload_1
+    iadd
+    ireturn
+.end method
+
+.method public static sum()I
+    bipush 40
+    iconst_2
+    invokedynamic sum(II)I invokestatic rjvm/Dynamic/bootstrap(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;)Ljava/lang/invoke/CallSite; methodhandle invokestatic rjvm/Dynamic/add(II)I
+    ireturn
+.end method
+
+.method public static sumWithWrongType()I
+    .catch java/lang/BootstrapMethodError from Start to End using Handler
+Start:
+    lconst_1
+    lconst_1
+    invokedynamic sum(JJ)J invokestatic rjvm/Dynamic/bootstrap(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;)Ljava/lang/invoke/CallSite; methodhandle invokestatic rjvm/Dynamic/add(II)I
+    l2i
+End:
+    ireturn
+Handler:
+    pop
+    iconst_m1
+    ireturn
+.end method
+"#;
+
+#[test_log::test]
+fn invokedynamic_invokes_the_bootstrap_method_and_its_target() {
+    let mut vm = create_vm_with_classes("invokedynamic", &[DYNAMIC_SOURCE]);
+
+    let mut invoke =
+        |method_name| invoke_static(&mut vm, "rjvm/Dynamic", method_name, "()I", vec![]);
+    assert_eq!(Ok(Some(Value::Int(42))), invoke("sum"));
+    assert_eq!(Ok(Some(Value::Int(-1))), invoke("sumWithWrongType"));
+}
@@ -1 +1,2 @@
+mod assembled_code_tests;
 mod real_code_tests;
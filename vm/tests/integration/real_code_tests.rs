@@ -93,6 +93,48 @@ fn control_flow() {
     );
 }
 
+#[test_log::test]
+fn switches() {
+    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(&mut vm, "rjvm/Switches", "main", "([Ljava/lang/String;)V");
+    assert_eq!(Ok(None), main_result);
+
+    assert_eq!(
+        vec![
+            // tableswitch
+            Value::Int(-1),
+            Value::Int(10),
+            Value::Int(20),
+            Value::Int(30),
+            Value::Int(-1),
+            // lookupswitch
+            Value::Int(1),
+            Value::Int(2),
+            Value::Int(3),
+            Value::Int(0),
+            // switch on strings
+            Value::Int(2),
+            Value::Int(1),
+            Value::Int(3),
+        ],
+        vm.printed
+    );
+}
+
+#[test_log::test]
+fn wide_instructions() {
+    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(
+        &mut vm,
+        "rjvm/WideInstructions",
+        "main",
+        "([Ljava/lang/String;)V",
+    );
+    assert_eq!(Ok(None), main_result);
+
+    assert_eq!(vec![Value::Int(-28999), Value::Long(274)], vm.printed);
+}
+
 #[test_log::test]
 fn numeric_types() {
     let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
@@ -376,3 +418,24 @@ fn generic() {
     let main_result = invoke(&mut vm, "rjvm/Generic", "main", "([Ljava/lang/String;)V");
     assert_eq!(Ok(None), main_result);
 }
+
+#[test_log::test]
+fn lambdas() {
+    let mut vm = create_base_vm(DEFAULT_MAX_MEMORY);
+    let main_result = invoke(&mut vm, "rjvm/Lambdas", "main", "([Ljava/lang/String;)V");
+    assert_eq!(Ok(None), main_result);
+
+    assert_eq!(
+        vec![
+            Value::Int(42),
+            Value::Int(15),
+            Value::Int(-7),
+            Value::Int(101),
+            Value::Int(42),
+            Value::Int(5),
+            Value::Int(3),
+            Value::Int(100),
+        ],
+        vm.printed
+    );
+}
@@ -1,2 +1,3 @@
 #!/usr/bin/env sh
-javac -source 6 -target 6 rjvm/*.java
+javac -source 7 -target 7 $(ls rjvm/*.java | grep -v Lambdas.java)
+javac --release 8 rjvm/Lambdas.java
@@ -0,0 +1,62 @@
+package rjvm;
+
+public class Lambdas {
+    interface IntOperation {
+        int apply(int value);
+    }
+
+    interface Transformer<T, R> {
+        R transform(T value);
+    }
+
+    interface Action {
+        void run();
+    }
+
+    private final int base;
+
+    private Lambdas(int base) {
+        this.base = base;
+    }
+
+    public static void main(String[] args) {
+        IntOperation doubler = x -> x * 2;
+        tempPrint(doubler.apply(21));
+
+        int offset = 10;
+        IntOperation adder = x -> x + offset;
+        tempPrint(adder.apply(5));
+
+        IntOperation negate = Lambdas::negate;
+        tempPrint(negate.apply(7));
+
+        Lambdas lambdas = new Lambdas(100);
+        IntOperation bound = lambdas::addBase;
+        tempPrint(bound.apply(1));
+
+        Transformer<Integer, Integer> boxed = x -> x + 1;
+        tempPrint(boxed.transform(41));
+
+        Transformer<String, Integer> length = String::length;
+        tempPrint(length.transform("hello"));
+
+        Transformer<Integer, Lambdas> constructor = Lambdas::new;
+        tempPrint(constructor.transform(3).base);
+
+        lambdas.printBase().run();
+    }
+
+    private static int negate(int value) {
+        return -value;
+    }
+
+    private int addBase(int value) {
+        return base + value;
+    }
+
+    private Action printBase() {
+        return () -> tempPrint(base);
+    }
+
+    private static native void tempPrint(int value);
+}
@@ -0,0 +1,55 @@
+package rjvm;
+
+public class Switches {
+    public static void main(String[] args) {
+        for (int i = 0; i < 5; ++i) {
+            tempPrint(denseSwitch(i));
+        }
+        tempPrint(sparseSwitch(-100));
+        tempPrint(sparseSwitch(7));
+        tempPrint(sparseSwitch(100000));
+        tempPrint(sparseSwitch(8));
+        tempPrint(stringSwitch("rust"));
+        tempPrint(stringSwitch("java"));
+        tempPrint(stringSwitch("kotlin"));
+    }
+
+    private static int denseSwitch(int value) {
+        switch (value) {
+            case 1:
+                return 10;
+            case 2:
+                return 20;
+            case 3:
+                return 30;
+            default:
+                return -1;
+        }
+    }
+
+    private static int sparseSwitch(int value) {
+        switch (value) {
+            case -100:
+                return 1;
+            case 7:
+                return 2;
+            case 100000:
+                return 3;
+            default:
+                return 0;
+        }
+    }
+
+    private static int stringSwitch(String value) {
+        switch (value) {
+            case "java":
+                return 1;
+            case "rust":
+                return 2;
+            default:
+                return 3;
+        }
+    }
+
+    private static native void tempPrint(int value);
+}
@@ -1,10 +1,16 @@
-use clap::Parser;
+use std::{fs, path::Path};
 
+use clap::{Parser, Subcommand};
+
+use rjvm_reader::{
+    class_file::ClassFile, class_reader, disassembler::disassemble, instruction::Instruction,
+};
 use rjvm_vm::{
     array::Array,
     array_entry_type::ArrayEntryType,
     call_stack::CallStack,
     class_and_method::ClassAndMethod,
+    class_path::ClassPath,
     exceptions::MethodCallFailed,
     java_objects_creation::new_java_lang_string_object,
     value::Value,
@@ -13,7 +19,42 @@ use rjvm_vm::{
 };
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    args: Option<Args>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Disassembles a class, printing its content like `javap -v`, or as JSON
+    Javap(JavapArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct JavapArgs {
+    /// Class path, used to look up the class by name. Use colon (:) as separator for entries
+    #[arg(short, long)]
+    classpath: Option<String>,
+
+    /// Prints the class as JSON, including the instructions of each method
+    #[arg(long)]
+    json: bool,
+
+    /// Path of a .class file, or name of the class to look up in the class path
+    class: String,
+}
+
+#[derive(clap::Args, Debug)]
 struct Args {
     /// Class path. Use colon (:) as separator for entries
     #[arg(short, long)]
@@ -31,12 +72,17 @@ struct Args {
 }
 
 fn main() {
-    let args = Args::parse();
+    let cli = Cli::parse();
     env_logger::init_from_env(
         env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
     );
 
-    let result = run(args);
+    let result = match cli.command {
+        Some(Command::Javap(javap_args)) => javap(javap_args),
+        None => run(cli
+            .args
+            .expect("clap requires the arguments without a subcommand")),
+    };
     match result {
         Ok(exit_code) => std::process::exit(exit_code),
         Err(err) => {
@@ -121,3 +167,47 @@ fn allocate_java_args<'a>(
     }
     Ok(Value::Object(array))
 }
+
+fn javap(args: JavapArgs) -> Result<i32, String> {
+    let path = Path::new(&args.class);
+    let bytes = if path.is_file() {
+        fs::read(path).map_err(|err| format!("cannot read {}: {err}", path.display()))?
+    } else {
+        let mut class_path = ClassPath::default();
+        if let Some(classpath) = &args.classpath {
+            class_path.push(classpath).map_err(|err| err.to_string())?;
+        }
+        let class_name = args.class.replace('.', "/");
+        class_path
+            .resolve(&class_name)
+            .map_err(|err| err.to_string())?
+            .ok_or_else(|| format!("class not found: {}", args.class))?
+    };
+
+    let class_file = class_reader::read_buffer(&bytes).map_err(|err| err.to_string())?;
+    if args.json {
+        println!("{}", to_json(&class_file)?);
+    } else {
+        print!("{}", disassemble(&class_file));
+    }
+    Ok(0)
+}
+
+/// Serializes the class, adding the decoded instructions next to the bytecode of each method
+fn to_json(class_file: &ClassFile) -> Result<String, String> {
+    let mut json = serde_json::to_value(class_file).map_err(|err| err.to_string())?;
+    for (index, method) in class_file.methods.iter().enumerate() {
+        let Some(code) = &method.code else {
+            continue;
+        };
+        let instructions = Instruction::parse_instructions(&code.code)
+            .map_err(|err| format!("invalid code of method {}: {err}", method.name))?;
+        json["methods"][index]["code"]["instructions"] = instructions
+            .into_iter()
+            .map(|(address, instruction)| {
+                serde_json::json!({ "address": address, "instruction": instruction })
+            })
+            .collect();
+    }
+    serde_json::to_string_pretty(&json).map_err(|err| err.to_string())
+}
@@ -0,0 +1,75 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+fn reader_resources() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../reader/tests/resources")
+}
+
+fn run_cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rjvm_vm_cli"))
+        .args(args)
+        .output()
+        .expect("should be able to run the cli")
+}
+
+fn stdout_of(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "cli failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn javap_disassembles_a_class_file() {
+    let path = reader_resources().join("rjvm/Complex.class");
+    let output = run_cli(&["javap", path.to_str().unwrap()]);
+
+    let stdout = stdout_of(&output);
+    assert!(stdout.starts_with("public class rjvm.Complex extends java.lang.Object"));
+    assert!(stdout.contains("  major version: 50\n"));
+    assert!(stdout.contains("  public double getReal();\n"));
+    assert!(stdout.contains("SourceFile: \"Complex.java\""));
+}
+
+#[test]
+fn javap_looks_up_classes_in_the_class_path() {
+    let class_path = reader_resources();
+    let output = run_cli(&[
+        "javap",
+        "--classpath",
+        class_path.to_str().unwrap(),
+        "rjvm.Complex",
+    ]);
+    assert!(stdout_of(&output).starts_with("public class rjvm.Complex"));
+
+    let output = run_cli(&[
+        "javap",
+        "--classpath",
+        class_path.to_str().unwrap(),
+        "rjvm.Missing",
+    ]);
+    assert!(!output.status.success());
+    assert_eq!(
+        "class not found: rjvm.Missing\n",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn javap_can_print_json() {
+    let path = reader_resources().join("rjvm/Complex.class");
+    let output = run_cli(&["javap", "--json", path.to_str().unwrap()]);
+
+    let json: serde_json::Value = serde_json::from_str(&stdout_of(&output)).unwrap();
+    assert_eq!("rjvm/Complex", json["name"]);
+    let constructor = &json["methods"][0];
+    assert_eq!("<init>", constructor["name"]);
+    assert_eq!(
+        serde_json::json!({ "address": 0, "instruction": "Aload_0" }),
+        constructor["code"]["instructions"][0]
+    );
+}
@@ -0,0 +1 @@
+mod javap_tests;